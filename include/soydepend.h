@@ -0,0 +1,45 @@
+/* C API of soydepend, built with
+   `cargo rustc --release --features capi --crate-type cdylib` */
+
+#ifndef SOYDEPEND_H
+#define SOYDEPEND_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct SoydepGraph SoydepGraph;
+
+#define SOYDEP_OK 0
+#define SOYDEP_EINVAL (-1)
+#define SOYDEP_ECYCLE (-2)
+#define SOYDEP_ESELF (-3)
+#define SOYDEP_ENOEDGE (-4)
+#define SOYDEP_ENONODE (-5)
+#define SOYDEP_EOTHER (-6)
+#define SOYDEP_EDEPENDED (-7)
+
+SoydepGraph *soydep_graph_new(void);
+void soydep_graph_free(SoydepGraph *graph);
+
+int soydep_depend(SoydepGraph *graph, const char *dependent, const char *dependency);
+int soydep_undepend(SoydepGraph *graph, const char *dependent, const char *dependency);
+int soydep_remove(SoydepGraph *graph, const char *node);
+
+/* Returns 1 if dependent depends on dependency in some way, 0 if not */
+int soydep_depends_on(const SoydepGraph *graph, const char *dependent, const char *dependency);
+
+/* Returns sorted deep dependencies, to be freed with soydep_strings_free */
+char **soydep_dependencies(const SoydepGraph *graph, const char *node, size_t *len);
+char **soydep_dependents(const SoydepGraph *graph, const char *node, size_t *len);
+void soydep_strings_free(char **strings, size_t len);
+
+const char *soydep_strerror(int code);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
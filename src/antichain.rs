@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns a largest set of mutually independent nodes, none of which
+    /// depends on another in any way. Its size is the most nodes
+    /// that can ever be processed in parallel.
+    pub fn max_parallelism(&self) -> HashSet<T> {
+        let nodes: Vec<&T> = self.nodes.iter().collect();
+        let index: HashMap<&T, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let reach: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|node| self.dependencies(node).iter().map(|n| index[n]).collect())
+            .collect();
+
+        // By Dilworth's theorem, the width is the number of nodes minus
+        // a maximum matching between dependents and their deep dependencies
+        let mut matched: Vec<Option<usize>> = vec![None; nodes.len()];
+        let mut matches: Vec<Option<usize>> = vec![None; nodes.len()];
+        for dependent in 0..nodes.len() {
+            let mut seen = vec![false; nodes.len()];
+            augment(dependent, &reach, &mut seen, &mut matched, &mut matches);
+        }
+
+        // By König's theorem, nodes reachable from unmatched dependents along
+        // alternating paths yield a minimum vertex cover, whose complement
+        // on both sides is the antichain
+        let mut left = vec![false; nodes.len()];
+        let mut right = vec![false; nodes.len()];
+        let mut q: Vec<usize> = (0..nodes.len()).filter(|i| matches[*i].is_none()).collect();
+        q.iter().for_each(|i| left[*i] = true);
+
+        while let Some(dependent) = q.pop() {
+            for dependency in &reach[dependent] {
+                if right[*dependency] {
+                    continue;
+                }
+
+                right[*dependency] = true;
+                if let Some(other) = matched[*dependency] {
+                    if !left[other] {
+                        left[other] = true;
+                        q.push(other);
+                    }
+                }
+            }
+        }
+
+        (0..nodes.len())
+            .filter(|i| left[*i] && !right[*i])
+            .map(|i| nodes[i].clone())
+            .collect()
+    }
+}
+
+/// Kuhn's augmenting path search, matching dependent to a deep dependency.
+/// matched maps dependencies to dependents, and matches the other way.
+fn augment(
+    dependent: usize,
+    reach: &[Vec<usize>],
+    seen: &mut [bool],
+    matched: &mut [Option<usize>],
+    matches: &mut [Option<usize>],
+) -> bool {
+    for dependency in &reach[dependent] {
+        if seen[*dependency] {
+            continue;
+        }
+
+        seen[*dependency] = true;
+        let free = match matched[*dependency] {
+            None => true,
+            Some(other) => augment(other, reach, seen, matched, matches),
+        };
+
+        if free {
+            matched[*dependency] = Some(dependent);
+            matches[dependent] = Some(*dependency);
+            return true;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_antichain(g: &Graph<&'static str>, antichain: &HashSet<&'static str>) {
+        for a in antichain {
+            for b in antichain {
+                assert!(!g.depends_on(a, b), "{a} depends on {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_parallelism() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "db").unwrap();
+        g.depend("app", "log").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("db", "tls").unwrap();
+
+        let antichain = g.max_parallelism();
+        assert_eq!(antichain.len(), 3);
+        assert!(antichain.contains("log"));
+        assert_antichain(&g, &antichain);
+
+        // A chain can only run one at a time
+        let mut chain = Graph::new();
+        chain.depend("c", "b").unwrap();
+        chain.depend("b", "a").unwrap();
+        assert_eq!(chain.max_parallelism().len(), 1);
+
+        let mut g = Graph::new();
+        g.depend("a", "b").unwrap();
+        g.depend("b", "c").unwrap();
+        g.depend("d", "c").unwrap();
+        g.depend("x", "y").unwrap();
+        g.depend("y", "z").unwrap();
+        let antichain = g.max_parallelism();
+        assert_eq!(antichain.len(), 3);
+        assert_antichain(&g, &antichain);
+
+        assert!(Graph::<&str>::new().max_parallelism().is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::history::Recorder;
+use crate::{Change, Error, Graph, Listener};
+
+/// Recorded mutation, see [`AuditedGraph`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry<T> {
+    pub time: SystemTime,
+    /// Who made the change, if given
+    pub actor: Option<String>,
+    /// Why the change was made, if given
+    pub reason: Option<String>,
+    pub change: Change<T>,
+}
+
+/// Graph wrapper keeping a trail of every mutation, with when it happened
+/// and, optionally, who made it and why.
+///
+/// Changes made by one call share their time, actor and reason.
+pub struct AuditedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    graph: Graph<T>,
+    recorder: Arc<Recorder<T>>,
+    entries: Vec<AuditEntry<T>>,
+}
+
+impl<T> AuditedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + 'static,
+{
+    pub fn new(mut graph: Graph<T>) -> Self {
+        let recorder = Arc::new(Recorder::new());
+        graph.subscribe(recorder.clone());
+
+        Self {
+            graph,
+            recorder,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the graph, dropping its audit trail
+    pub fn into_inner(mut self) -> Graph<T> {
+        let recorder: Arc<dyn Listener<T>> = self.recorder.clone();
+        self.graph.unsubscribe(&recorder);
+
+        self.graph
+    }
+
+    /// Applies f to the graph, recording its changes on behalf of actor for reason
+    pub fn edit_as<R, F>(&mut self, actor: Option<&str>, reason: Option<&str>, f: F) -> R
+    where
+        F: FnOnce(&mut Graph<T>) -> R,
+    {
+        let result = f(&mut self.graph);
+
+        let time = SystemTime::now();
+        self.entries
+            .extend(self.recorder.take().into_iter().map(|change| AuditEntry {
+                time,
+                actor: actor.map(str::to_string),
+                reason: reason.map(str::to_string),
+                change,
+            }));
+
+        result
+    }
+
+    /// Applies f to the graph, recording its changes without actor or reason
+    pub fn edit<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Graph<T>) -> R,
+    {
+        self.edit_as(None, None, f)
+    }
+
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
+        self.edit(|g| g.depend(dependent, dependency))
+    }
+
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
+        self.edit(|g| g.undepend(dependent, dependency))
+    }
+
+    pub fn remove(&mut self, target: &T) -> Result<(), Error<T>> {
+        self.edit(|g| g.remove(target))
+    }
+
+    /// Returns every recorded change, oldest first
+    pub fn entries(&self) -> &[AuditEntry<T>] {
+        &self.entries
+    }
+
+    /// Returns additions and removals of the edge, oldest first
+    pub fn edge_history<'a>(
+        &'a self,
+        dependent: &'a T,
+        dependency: &'a T,
+    ) -> impl Iterator<Item = &'a AuditEntry<T>> {
+        self.entries
+            .iter()
+            .filter(move |entry| match &entry.change {
+                Change::EdgeAdded(a, b) | Change::EdgeRemoved(a, b) => {
+                    a == dependent && b == dependency
+                }
+                _ => false,
+            })
+    }
+
+    /// Returns additions and removals of node, oldest first
+    pub fn node_history<'a>(&'a self, node: &'a T) -> impl Iterator<Item = &'a AuditEntry<T>> {
+        self.entries
+            .iter()
+            .filter(move |entry| match &entry.change {
+                Change::NodeAdded(n) | Change::NodeRemoved(n) => n == node,
+                _ => false,
+            })
+    }
+
+    /// Returns the latest addition of the edge, telling who added it and when
+    pub fn who_added(&self, dependent: &T, dependency: &T) -> Option<&AuditEntry<T>> {
+        self.entries.iter().rev().find(|entry| {
+            matches!(&entry.change, Change::EdgeAdded(a, b) if a == dependent && b == dependency)
+        })
+    }
+}
+
+impl<T> std::ops::Deref for AuditedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    type Target = Graph<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit() {
+        let mut g = AuditedGraph::new(Graph::new());
+        g.depend("app", "http").unwrap();
+
+        let before = SystemTime::now();
+        g.edit_as(Some("alice"), Some("JIRA-1"), |g| {
+            g.depend("app", "legacy-lib")
+        })
+        .unwrap();
+
+        g.edit_as(Some("bob"), None, |g| {
+            g.undepend(&"app", &"legacy-lib").unwrap();
+            g.remove(&"legacy-lib").unwrap();
+        });
+
+        let added = g.who_added(&"app", &"legacy-lib").unwrap();
+        assert_eq!(added.actor.as_deref(), Some("alice"));
+        assert_eq!(added.reason.as_deref(), Some("JIRA-1"));
+        assert!(added.time >= before);
+
+        let history: Vec<_> = g.edge_history(&"app", &"legacy-lib").collect();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].change, Change::EdgeRemoved("app", "legacy-lib"));
+        assert_eq!(history[1].actor.as_deref(), Some("bob"));
+
+        let nodes: Vec<_> = g.node_history(&"legacy-lib").collect();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[1].change, Change::NodeRemoved("legacy-lib"));
+
+        assert_eq!(g.entries().len(), 7);
+        assert!(g.entries()[0].actor.is_none());
+        assert!(g.who_added(&"app", &"xml").is_none());
+
+        let mut inner = g.into_inner();
+        inner.depend("app", "xml").unwrap();
+    }
+}
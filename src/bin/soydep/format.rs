@@ -0,0 +1,349 @@
+//! Output formats shared by every subcommand
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::io::IsTerminal;
+
+use soydepend::{Diff, Graph};
+
+use crate::json;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Plain,
+    Json,
+    Dot,
+    Mermaid,
+}
+
+/// Results of subcommands, along with the part of the graph they cover
+pub enum Output {
+    /// Nodes, one per line
+    Nodes(Vec<String>, Graph<String>),
+    /// A chain of nodes, on one line
+    Chain(Vec<String>, Graph<String>),
+    /// A rendered tree in plain format, nodes otherwise
+    Tree(String, Vec<String>, Graph<String>),
+}
+
+impl Output {
+    pub fn render(&self, format: Format) -> String {
+        let (Self::Nodes(nodes, g) | Self::Chain(nodes, g) | Self::Tree(_, nodes, g)) = self;
+
+        match (format, self) {
+            (Format::Plain, Self::Tree(tree, ..)) => tree.clone(),
+            (Format::Plain, Self::Nodes(..)) => nodes.iter().map(|n| format!("{n}\n")).collect(),
+            (Format::Plain, Self::Chain(..)) => format!("{}\n", nodes.join(" -> ")),
+            (Format::Json, _) => {
+                format!("{}\n", json::array(nodes.iter().map(|n| json::string(n))))
+            }
+            (Format::Dot, _) => g.to_dot(),
+            (Format::Mermaid, _) => g.to_mermaid(),
+        }
+    }
+}
+
+/// Removes `--format F` or `--format=F` from args, returning F
+pub fn take_format(args: &mut Vec<String>) -> Result<Format, String> {
+    let Some(name) = take_flag(args, "--format")? else {
+        return Ok(Format::default());
+    };
+
+    match name.as_str() {
+        "plain" => Ok(Format::Plain),
+        "json" => Ok(Format::Json),
+        "dot" => Ok(Format::Dot),
+        "mermaid" => Ok(Format::Mermaid),
+        _ => Err(format!("unknown format: {name}")),
+    }
+}
+
+/// Removes `--color WHEN` or `--color=WHEN` from args, returning whether
+/// to color output. WHEN is auto (default), always or never, where auto
+/// colors terminals unless the `NO_COLOR` environment variable is set.
+pub fn take_color(args: &mut Vec<String>) -> Result<bool, String> {
+    match take_flag(args, "--color")?.as_deref() {
+        None | Some("auto") => {
+            Ok(std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none())
+        }
+        Some("always") => Ok(true),
+        Some("never") => Ok(false),
+        Some(when) => Err(format!("unknown color mode: {when}")),
+    }
+}
+
+/// Removes `NAME VALUE` or `NAME=VALUE` from args, returning VALUE
+fn take_flag(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let Some(i) = args.iter().position(|arg| arg.starts_with(name)) else {
+        return Ok(None);
+    };
+
+    let flag = args.remove(i);
+    match flag.strip_prefix(name) {
+        Some("") if i < args.len() => Ok(Some(args.remove(i))),
+        Some(value) if value.starts_with('=') => Ok(Some(value[1..].to_string())),
+        _ => Err(format!("invalid flag: {flag}")),
+    }
+}
+
+/// Returns nodes and the edges of g between them
+pub fn induced(g: &Graph<String>, nodes: &HashSet<String>) -> Graph<String> {
+    let mut sub = Graph::new();
+    for dependent in nodes {
+        sub.add_node(dependent.clone());
+        for dependency in nodes {
+            if g.depends_on_directly(dependent, dependency) {
+                sub.depend(dependent.clone(), dependency.clone())
+                    .expect("subgraphs of acyclic graphs are acyclic");
+            }
+        }
+    }
+
+    sub
+}
+
+/// Renders diff, where dot and mermaid show both graphs
+/// with added parts in green and removed parts in red
+pub fn render_diff(
+    diff: &Diff<String>,
+    old: &Graph<String>,
+    new: &Graph<String>,
+    format: Format,
+) -> String {
+    match format {
+        Format::Plain => plain_diff(diff),
+        Format::Json => json_diff(diff),
+        Format::Dot => dot_diff(diff, old, new),
+        Format::Mermaid => mermaid_diff(diff, old, new),
+    }
+}
+
+fn plain_diff(diff: &Diff<String>) -> String {
+    let mut lines = Vec::new();
+    lines.extend(sorted(diff.removed_nodes.iter().map(|n| format!("- {n}"))));
+    lines.extend(sorted(diff.added_nodes.iter().map(|n| format!("+ {n}"))));
+    lines.extend(sorted(
+        diff.removed_edges
+            .iter()
+            .map(|(a, b)| format!("- {a} -> {b}")),
+    ));
+    lines.extend(sorted(
+        diff.added_edges
+            .iter()
+            .map(|(a, b)| format!("+ {a} -> {b}")),
+    ));
+
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+fn json_diff(diff: &Diff<String>) -> String {
+    let nodes =
+        |nodes: &HashSet<String>| json::array(sorted(nodes.iter().map(|n| json::string(n))));
+    let edges = |edges: &HashSet<(String, String)>| {
+        json::array(sorted(
+            edges
+                .iter()
+                .map(|(a, b)| json::array([json::string(a), json::string(b)])),
+        ))
+    };
+
+    format!(
+        "{{\"added_nodes\":{},\"removed_nodes\":{},\"added_edges\":{},\"removed_edges\":{}}}\n",
+        nodes(&diff.added_nodes),
+        nodes(&diff.removed_nodes),
+        edges(&diff.added_edges),
+        edges(&diff.removed_edges),
+    )
+}
+
+/// Nodes and edges of both graphs, sorted, with their colors if changed
+struct Union {
+    nodes: Vec<(String, Option<&'static str>)>,
+    edges: Vec<(String, String, Option<&'static str>)>,
+}
+
+const ADDED: &str = "green";
+const REMOVED: &str = "red";
+
+fn union(diff: &Diff<String>, old: &Graph<String>, new: &Graph<String>) -> Union {
+    let names = sorted(old.nodes().chain(&diff.added_nodes).cloned());
+
+    let nodes = names
+        .into_iter()
+        .map(|n| {
+            let color = match (
+                diff.added_nodes.contains(&n),
+                diff.removed_nodes.contains(&n),
+            ) {
+                (true, _) => Some(ADDED),
+                (_, true) => Some(REMOVED),
+                _ => None,
+            };
+
+            (n, color)
+        })
+        .collect();
+
+    let mut edges: Vec<(String, String, Option<&'static str>)> = new
+        .edges()
+        .map(|(a, b)| {
+            let edge = (a.clone(), b.clone());
+            let color = diff.added_edges.contains(&edge).then_some(ADDED);
+            (edge.0, edge.1, color)
+        })
+        .chain(
+            diff.removed_edges
+                .iter()
+                .map(|(a, b)| (a.clone(), b.clone(), Some(REMOVED))),
+        )
+        .collect();
+    edges.sort();
+
+    Union { nodes, edges }
+}
+
+fn dot_diff(diff: &Diff<String>, old: &Graph<String>, new: &Graph<String>) -> String {
+    let union = union(diff, old, new);
+    let attrs = |color: Option<&str>| match color {
+        Some(color) => format!(" [color={color}, fontcolor={color}]"),
+        None => String::new(),
+    };
+
+    let mut dot =
+        String::from("digraph diff {\n    rankdir=LR;\n    node [shape=box, style=rounded];\n");
+    for (node, color) in &union.nodes {
+        let _ = writeln!(dot, "    {}{};", json::string(node), attrs(*color));
+    }
+
+    for (dependent, dependency, color) in &union.edges {
+        let _ = writeln!(
+            dot,
+            "    {} -> {}{};",
+            json::string(dependent),
+            json::string(dependency),
+            attrs(*color)
+        );
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn mermaid_diff(diff: &Diff<String>, old: &Graph<String>, new: &Graph<String>) -> String {
+    let union = union(diff, old, new);
+    let ids: std::collections::HashMap<&str, usize> = union
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (n, _))| (n.as_str(), i))
+        .collect();
+
+    let mut mermaid = String::from("graph LR\n");
+    for (i, (node, color)) in union.nodes.iter().enumerate() {
+        let _ = writeln!(mermaid, "    n{i}[\"{}\"]", node.replace('"', "#quot;"));
+        if let Some(color) = color {
+            let _ = writeln!(mermaid, "    style n{i} stroke:{color},color:{color}");
+        }
+    }
+
+    for (i, (dependent, dependency, color)) in union.edges.iter().enumerate() {
+        let _ = writeln!(
+            mermaid,
+            "    n{} --> n{}",
+            ids[dependent.as_str()],
+            ids[dependency.as_str()]
+        );
+        if let Some(color) = color {
+            let _ = writeln!(mermaid, "    linkStyle {i} stroke:{color}");
+        }
+    }
+
+    mermaid
+}
+
+pub fn sorted<I: IntoIterator<Item = String>>(nodes: I) -> Vec<String> {
+    let mut nodes: Vec<String> = nodes.into_iter().collect();
+    nodes.sort();
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphs() -> (Graph<String>, Graph<String>) {
+        let mut old = Graph::new();
+        old.depend("app".to_string(), "xml".to_string()).unwrap();
+        old.depend("app".to_string(), "http".to_string()).unwrap();
+
+        let mut new = Graph::new();
+        new.depend("app".to_string(), "http".to_string()).unwrap();
+        new.depend("app".to_string(), "json".to_string()).unwrap();
+
+        (old, new)
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let (old, new) = graphs();
+        let diff = old.diff(&new);
+
+        assert_eq!(
+            render_diff(&diff, &old, &new, Format::Dot),
+            "\
+digraph diff {
+    rankdir=LR;
+    node [shape=box, style=rounded];
+    \"app\";
+    \"http\";
+    \"json\" [color=green, fontcolor=green];
+    \"xml\" [color=red, fontcolor=red];
+    \"app\" -> \"http\";
+    \"app\" -> \"json\" [color=green, fontcolor=green];
+    \"app\" -> \"xml\" [color=red, fontcolor=red];
+}
+"
+        );
+
+        assert_eq!(
+            render_diff(&diff, &old, &new, Format::Mermaid),
+            "\
+graph LR
+    n0[\"app\"]
+    n1[\"http\"]
+    n2[\"json\"]
+    style n2 stroke:green,color:green
+    n3[\"xml\"]
+    style n3 stroke:red,color:red
+    n0 --> n1
+    n0 --> n2
+    linkStyle 1 stroke:green
+    n0 --> n3
+    linkStyle 2 stroke:red
+"
+        );
+    }
+
+    #[test]
+    fn test_take_format() {
+        let mut args = vec!["a".to_string(), "--format".to_string(), "dot".to_string()];
+        assert_eq!(take_format(&mut args).unwrap(), Format::Dot);
+        assert_eq!(args, vec!["a"]);
+
+        let mut args = vec!["--format=mermaid".to_string()];
+        assert_eq!(take_format(&mut args).unwrap(), Format::Mermaid);
+        assert!(args.is_empty());
+
+        assert_eq!(take_format(&mut vec![]).unwrap(), Format::Plain);
+        take_format(&mut vec!["--format".to_string()]).expect_err("missing format");
+        take_format(&mut vec!["--format=xml".to_string()]).expect_err("unknown format");
+        take_format(&mut vec!["--formats".to_string()]).expect_err("unknown flag");
+
+        let mut args = vec!["--color".to_string(), "always".to_string(), "a".to_string()];
+        assert!(take_color(&mut args).unwrap());
+        assert_eq!(args, vec!["a"]);
+        assert!(!take_color(&mut vec!["--color=never".to_string()]).unwrap());
+        take_color(&mut vec!["--color=rainbow".to_string()]).expect_err("unknown mode");
+    }
+}
@@ -0,0 +1,42 @@
+//! JSON output
+
+use std::fmt::Write;
+
+/// Returns s as a JSON string literal
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Returns a JSON array of already encoded items
+pub fn array<I: IntoIterator<Item = String>>(items: I) -> String {
+    let items: Vec<String> = items.into_iter().collect();
+    format!("[{}]", items.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string() {
+        let s = "quote \" slash \\ tab \t bell \u{7} é";
+        assert_eq!(string(s), r#""quote \" slash \\ tab \t bell \u0007 é""#);
+        assert_eq!(array([string("a"), string("b")]), r#"["a","b"]"#);
+    }
+}
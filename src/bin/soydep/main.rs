@@ -0,0 +1,454 @@
+//! Command-line interface for interrogating dependency graphs stored in files.
+//!
+//! Graph files list one node per line, followed by its direct dependencies,
+//! separated by whitespace. Blank lines and lines starting with `#` are ignored.
+//! Files ending in `.json` instead hold an object mapping nodes to arrays
+//! of their direct dependencies, and files ending in `.toml`, `.yaml` or `.yml`
+//! follow the schema of [`Graph::from_toml_str`] and [`Graph::from_yaml_str`].
+//!
+//! `soydep tsort` instead works like GNU tsort, reading whitespace-separated
+//! `dependent dependency` pairs from a file or stdin.
+
+mod format;
+mod json;
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+use soydepend::Graph;
+
+use format::{induced, sorted, take_color, take_format, Output};
+
+const USAGE: &str = "\
+usage: soydep FILE COMMAND [--format FORMAT] [--color WHEN]
+       soydep tsort [FILE] [--format FORMAT]
+       soydep diff OLD NEW [--format FORMAT]
+
+commands:
+    deps NODE       deep dependencies of NODE
+    rdeps NODE      deep dependents of NODE
+    order           all nodes, each after its dependencies
+    why NODE DEP    a shortest chain from NODE to DEP
+    tree NODE       dependency tree of NODE, like cargo tree
+    query EXPR      nodes selected by EXPR, e.g. 'deps(app) & !deps(db)'
+    find PATTERN    nodes matching glob PATTERN, e.g. 'libfoo-*'
+
+tsort reads dependent-dependency pairs from FILE, or stdin if FILE is
+missing or -, and prints dependents before their dependencies
+
+diff prints nodes and edges added and removed going from OLD to NEW,
+and exits with 1 if there are any
+
+FORMAT is one of plain (default), json, dot or mermaid, where dot and
+mermaid draw the part of the graph covered by the output
+
+WHEN colors trees, and is one of auto (default), always or never";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut stdout = std::io::stdout().lock();
+
+    let result = match args.first().map(String::as_str) {
+        Some("tsort") => tsort(&args[1..], std::io::stdin().lock(), &mut stdout),
+        Some("diff") => match diff(&args[1..], &mut stdout) {
+            Ok(true) => return ExitCode::from(1),
+            result => result.map(drop),
+        },
+        _ => run(&args, &mut stdout),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("soydep: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run<W: Write>(args: &[String], out: &mut W) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let format = take_format(&mut args)?;
+    let color = take_color(&mut args)?;
+    let [file, command, rest @ ..] = args.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+
+    let g = load(file)?;
+
+    // Deep dependencies or dependents, drawn along with node
+    let reach = |node: String, mut nodes: HashSet<String>| {
+        let list = sorted(nodes.iter().cloned());
+        nodes.insert(node);
+        Output::Nodes(list, induced(&g, &nodes))
+    };
+
+    let output = match (command.as_str(), rest) {
+        ("deps", [node]) => {
+            let node = known(&g, node)?;
+            reach(node.clone(), g.dependencies(&node))
+        }
+        ("rdeps", [node]) => {
+            let node = known(&g, node)?;
+            reach(node.clone(), g.dependents(&node))
+        }
+        ("order", []) => {
+            Output::Nodes(g.layers().into_iter().flat_map(sorted).collect(), g.clone())
+        }
+        ("why", [node, dependency]) => {
+            let chain = g
+                .why(&known(&g, node)?, &known(&g, dependency)?)
+                .ok_or_else(|| format!("{node} does not depend on {dependency}"))?;
+
+            let mut drawn = Graph::new();
+            for pair in chain.windows(2) {
+                drawn
+                    .depend(pair[0].clone(), pair[1].clone())
+                    .expect("chains are acyclic");
+            }
+
+            Output::Chain(chain, drawn)
+        }
+        ("tree", [node]) => {
+            let node = known(&g, node)?;
+            let tree = match color {
+                true => g.tree_string_colored(&node),
+                false => g.tree_string(&node),
+            }
+            .map_err(|err| err.to_string())?;
+
+            let mut nodes = g.dependencies(&node);
+            nodes.insert(node);
+
+            Output::Tree(tree, sorted(nodes.iter().cloned()), induced(&g, &nodes))
+        }
+        ("query", [query]) => {
+            let nodes = g.query(query).map_err(|err| err.to_string())?;
+            Output::Nodes(sorted(nodes.iter().cloned()), induced(&g, &nodes))
+        }
+        ("find", [pattern]) => {
+            let nodes = g.find_nodes(pattern);
+            Output::Nodes(sorted(nodes.iter().cloned()), induced(&g, &nodes))
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+
+    out.write_all(output.render(format).as_bytes())
+        .map_err(|err| err.to_string())
+}
+
+/// Prints changes from graph file OLD to NEW, returning whether there are any
+fn diff<W: Write>(args: &[String], out: &mut W) -> Result<bool, String> {
+    let mut args = args.to_vec();
+    let format = take_format(&mut args)?;
+    let [old, new] = args.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+
+    let (old, new) = (load(old)?, load(new)?);
+    let diff = old.diff(&new);
+
+    out.write_all(format::render_diff(&diff, &old, &new, format).as_bytes())
+        .map_err(|err| err.to_string())?;
+
+    Ok(!diff.is_empty())
+}
+
+/// Prints nodes of pairs read from FILE or input with dependents first,
+/// like GNU tsort. Pairs closing a loop are reported and left out of the order.
+fn tsort<R: Read, W: Write>(args: &[String], mut input: R, out: &mut W) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let format = take_format(&mut args)?;
+    let mut source = String::new();
+    let name = match args.as_slice() {
+        [] => "-",
+        [file] => file.as_str(),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    match name {
+        "-" => input.read_to_string(&mut source),
+        file => std::fs::File::open(file).and_then(|mut f| f.read_to_string(&mut source)),
+    }
+    .map_err(|err| format!("{name}: {err}"))?;
+
+    let words: Vec<&str> = source.split_whitespace().collect();
+    if !words.len().is_multiple_of(2) {
+        return Err(format!("{name}: input contains an odd number of tokens"));
+    }
+
+    let mut g = Graph::new();
+    let mut loops = Vec::new();
+    for pair in words.chunks(2) {
+        let (dependent, dependency) = (pair[0].to_string(), pair[1].to_string());
+        if dependent == dependency {
+            g.add_node(dependent);
+            continue;
+        }
+
+        if let Some(mut chain) = g.why(&dependency, &dependent) {
+            chain.insert(0, dependent);
+            loops.push(chain.join(" -> "));
+            continue;
+        }
+
+        g.depend(dependent, dependency)
+            .map_err(|err| format!("{name}: {err}"))?;
+    }
+
+    let order = g.layers().into_iter().rev().flat_map(sorted).collect();
+    out.write_all(Output::Nodes(order, g).render(format).as_bytes())
+        .map_err(|err| err.to_string())?;
+
+    match loops.is_empty() {
+        true => Ok(()),
+        false => Err(format!(
+            "{name}: input contains a loop: {}",
+            loops.join(&format!("\nsoydep: {name}: input contains a loop: "))
+        )),
+    }
+}
+
+/// Reads graph file, in the format given by its extension
+fn load(file: &str) -> Result<Graph<String>, String> {
+    let source = std::fs::read_to_string(file).map_err(|err| format!("{file}: {err}"))?;
+    let extension = std::path::Path::new(file)
+        .extension()
+        .and_then(|e| e.to_str());
+
+    match extension {
+        Some("json") => Graph::from_json_str(&source).map_err(|err| format!("{file}: {err}")),
+        Some("toml") => Graph::from_toml_str(&source).map_err(|err| format!("{file}: {err}")),
+        Some("yaml" | "yml") => {
+            Graph::from_yaml_str(&source).map_err(|err| format!("{file}: {err}"))
+        }
+        _ => parse(&source).map_err(|err| format!("{file}:{err}")),
+    }
+}
+
+/// Parses graph files, failing with the line number and the graph error
+fn parse(source: &str) -> Result<Graph<String>, String> {
+    let mut g = Graph::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let Some(node) = words.next().filter(|word| !word.starts_with('#')) else {
+            continue;
+        };
+
+        g.add_node(node.to_string());
+        for dependency in words {
+            g.depend(node.to_string(), dependency.to_string())
+                .map_err(|err| format!("{}: {node} -> {dependency}: {err}", i + 1))?;
+        }
+    }
+
+    Ok(g)
+}
+
+fn known(g: &Graph<String>, node: &str) -> Result<String, String> {
+    let node = node.to_string();
+    match g.contains(&node) {
+        true => Ok(node),
+        false => Err(format!("no such node: {node}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAPH: &str = "\
+# services
+app http db
+http tls
+db tls libc
+tls libc
+scratch
+";
+
+    fn soydep(args: &[&str]) -> Result<String, String> {
+        let path = std::env::temp_dir().join(format!("soydep-{}.txt", std::process::id()));
+        std::fs::write(&path, GRAPH).unwrap();
+
+        let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.insert(0, path.to_string_lossy().into_owned());
+
+        let mut out = Vec::new();
+        let result = run(&args, &mut out);
+        std::fs::remove_file(&path).unwrap();
+
+        result.map(|()| String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(soydep(&["deps", "http"]).unwrap(), "libc\ntls\n");
+        assert_eq!(soydep(&["rdeps", "tls"]).unwrap(), "app\ndb\nhttp\n");
+        assert_eq!(
+            soydep(&["order"]).unwrap(),
+            "libc\nscratch\ntls\ndb\nhttp\napp\n"
+        );
+        assert_eq!(
+            soydep(&["why", "app", "tls"])
+                .unwrap()
+                .matches(" -> ")
+                .count(),
+            2
+        );
+        assert_eq!(soydep(&["why", "db", "libc"]).unwrap(), "db -> libc\n");
+
+        assert_eq!(
+            soydep(&["query", "deps(app) & !deps(db) | roots()"]).unwrap(),
+            "app\ndb\nhttp\nscratch\n"
+        );
+        assert_eq!(
+            soydep(&["query", "deps(nope)"]).unwrap_err(),
+            "no such node nope at byte 5"
+        );
+
+        assert_eq!(soydep(&["find", "*t*"]).unwrap(), "http\nscratch\ntls\n");
+
+        soydep(&["why", "libc", "app"]).expect_err("libc does not depend on app");
+        soydep(&["deps", "nope"]).expect_err("no such node");
+        soydep(&["deps"]).expect_err("missing node");
+        soydep(&["frobnicate"]).expect_err("unknown command");
+
+        assert_eq!(
+            soydep(&["deps", "http", "--format", "json"]).unwrap(),
+            "[\"libc\",\"tls\"]\n"
+        );
+        assert_eq!(
+            soydep(&["--format=json", "why", "db", "libc"]).unwrap(),
+            "[\"db\",\"libc\"]\n"
+        );
+
+        let dot = soydep(&["deps", "http", "--format=dot"]).unwrap();
+        assert!(dot.contains("\"http\" -> \"tls\";"));
+        assert!(dot.contains("\"tls\" -> \"libc\";"));
+        assert!(!dot.contains("app"));
+
+        let mermaid = soydep(&["rdeps", "http", "--format=mermaid"]).unwrap();
+        assert_eq!(
+            mermaid,
+            "graph LR\n    n0[\"app\"]\n    n1[\"http\"]\n    n0 --> n1\n"
+        );
+
+        assert_eq!(
+            soydep(&["tree", "db", "--color=never"]).unwrap(),
+            "db\n├── libc\n└── tls\n    └── libc\n"
+        );
+        assert!(soydep(&["tree", "db", "--color", "always"])
+            .unwrap()
+            .contains("\x1b[32mlibc\x1b[0m"));
+        assert_eq!(
+            soydep(&["tree", "db", "--format=json"]).unwrap(),
+            "[\"db\",\"libc\",\"tls\"]\n"
+        );
+
+        let mermaid = soydep(&["order", "--format=mermaid"]).unwrap();
+        assert_eq!(mermaid.matches("-->").count(), 6);
+    }
+
+    fn tsort_stdin(input: &str) -> (String, Result<(), String>) {
+        let mut out = Vec::new();
+        let result = tsort(&[], input.as_bytes(), &mut out);
+
+        (String::from_utf8(out).unwrap(), result)
+    }
+
+    #[test]
+    fn test_tsort() {
+        let (out, result) = tsort_stdin("app http\napp db http tls\n db tls\tx x");
+        result.unwrap();
+        assert_eq!(out, "app\ndb\nhttp\ntls\nx\n");
+
+        // Loops are reported, and everything else is still ordered
+        let (out, result) = tsort_stdin("a b b c c a c d");
+        assert_eq!(out, "a\nb\nc\nd\n");
+        assert_eq!(
+            result.unwrap_err(),
+            "-: input contains a loop: c -> a -> b -> c"
+        );
+
+        let mut out = Vec::new();
+        tsort(
+            &["--format=json".to_string()],
+            "b c a b".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(out, b"[\"a\",\"b\",\"c\"]\n");
+
+        let (_, result) = tsort_stdin("a b c");
+        assert_eq!(
+            result.unwrap_err(),
+            "-: input contains an odd number of tokens"
+        );
+
+        let mut out = Vec::new();
+        tsort(&["/nonexistent".to_string()], "".as_bytes(), &mut out).expect_err("no such file");
+    }
+
+    #[test]
+    fn test_diff() {
+        let dir = std::env::temp_dir().join(format!("soydep-diff-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let old = dir.join("old.json");
+        let new = dir.join("new.toml");
+        std::fs::write(&old, r#"{"app": ["http", "xml"], "http": ["tls"]}"#).unwrap();
+        std::fs::write(
+            &new,
+            "[app]\ndeps = [\"http\", \"json\"]\n[http]\ndeps = [\"tls\"]\n",
+        )
+        .unwrap();
+
+        let diff_files = |a: &std::path::Path, b: &std::path::Path, flags: &[&str]| {
+            let mut args = vec![
+                a.to_string_lossy().into_owned(),
+                b.to_string_lossy().into_owned(),
+            ];
+            args.extend(flags.iter().map(|flag| flag.to_string()));
+
+            let mut out = Vec::new();
+            let changed = diff(&args, &mut out).unwrap();
+            (changed, String::from_utf8(out).unwrap())
+        };
+
+        let (changed, plain) = diff_files(&old, &new, &[]);
+        assert!(changed);
+        assert_eq!(plain, "- xml\n+ json\n- app -> xml\n+ app -> json\n");
+
+        let (_, json) = diff_files(&old, &new, &["--format", "json"]);
+        assert_eq!(
+            json,
+            r#"{"added_nodes":["json"],"removed_nodes":["xml"],"added_edges":[["app","json"]],"removed_edges":[["app","xml"]]}"#
+                .to_string()
+                + "\n"
+        );
+
+        let (changed, plain) = diff_files(&new, &new, &["--format=plain"]);
+        assert!(!changed);
+        assert!(plain.is_empty());
+
+        let args = [
+            old.to_string_lossy().into_owned(),
+            new.to_string_lossy().into_owned(),
+            "--format=xml".to_string(),
+        ];
+        diff(&args, &mut Vec::new()).expect_err("unknown format");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = parse("a b\n\n  # comment\nb c\nd\n").unwrap();
+        assert!(g.depends_on(&"a".to_string(), &"c".to_string()));
+        assert!(g.contains(&"d".to_string()));
+
+        let err = parse("a b\nb a\n").unwrap_err();
+        assert!(err.starts_with("2: b -> a: "), "{err}");
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{Error, Graph};
+
+/// Size limits of a [`BoundedGraph`], where None means unlimited
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quotas {
+    pub max_nodes: Option<usize>,
+    pub max_edges: Option<usize>,
+}
+
+/// Graph wrapper rejecting mutations that would grow the graph past its
+/// quotas with [`Error::QuotaExceeded`], e.g. for graphs supplied by tenants
+#[derive(Clone, Debug)]
+pub struct BoundedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    graph: Graph<T>,
+    quotas: Quotas,
+}
+
+impl<T> BoundedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Wraps graph, failing if it is already over quotas
+    pub fn new(graph: Graph<T>, quotas: Quotas) -> Result<Self, Error<T>> {
+        let bounded = Self { graph, quotas };
+        if !bounded.fits(0, 0) {
+            return Err(Error::QuotaExceeded);
+        }
+
+        Ok(bounded)
+    }
+
+    pub fn quotas(&self) -> Quotas {
+        self.quotas
+    }
+
+    /// Changes quotas, failing without changing them if the graph is over the new ones
+    pub fn set_quotas(&mut self, quotas: Quotas) -> Result<(), Error<T>> {
+        let previous = std::mem::replace(&mut self.quotas, quotas);
+        if !self.fits(0, 0) {
+            self.quotas = previous;
+            return Err(Error::QuotaExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the graph, dropping its quotas
+    pub fn into_inner(self) -> Graph<T> {
+        self.graph
+    }
+
+    /// Like [`Graph::add_node`], but fails if the node would exceed quotas
+    pub fn add_node(&mut self, node: T) -> Result<(), Error<T>> {
+        if !self.fits(usize::from(!self.graph.contains(&node)), 0) {
+            return Err(Error::QuotaExceeded);
+        }
+
+        self.graph.add_node(node);
+
+        Ok(())
+    }
+
+    /// Like [`Graph::depend`], but fails if the edge or its new nodes would exceed quotas
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
+        let nodes = [&dependent, &dependency]
+            .into_iter()
+            .filter(|node| !self.graph.contains(node))
+            .count();
+        let edges = usize::from(!self.graph.depends_on_directly(&dependent, &dependency));
+
+        if dependent != dependency && !self.fits(nodes, edges) {
+            return Err(Error::QuotaExceeded);
+        }
+
+        self.graph.depend(dependent, dependency)
+    }
+
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
+        self.graph.undepend(dependent, dependency)
+    }
+
+    pub fn remove(&mut self, target: &T) -> Result<(), Error<T>> {
+        self.graph.remove(target)
+    }
+
+    pub fn remove_force(&mut self, target: &T) {
+        self.graph.remove_force(target)
+    }
+
+    pub fn remove_autoremove(&mut self, target: &T) {
+        self.graph.remove_autoremove(target)
+    }
+
+    /// Returns whether the graph stays within quotas after adding nodes and edges
+    fn fits(&self, nodes: usize, edges: usize) -> bool {
+        let within = |count: usize, max: Option<usize>| max.is_none_or(|max| count <= max);
+
+        within(self.graph.node_count() + nodes, self.quotas.max_nodes)
+            && within(self.graph.edge_count() + edges, self.quotas.max_edges)
+    }
+}
+
+impl<T> std::ops::Deref for BoundedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    type Target = Graph<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotas() {
+        let quotas = Quotas {
+            max_nodes: Some(3),
+            max_edges: Some(2),
+        };
+        let mut g = BoundedGraph::new(Graph::new(), quotas).unwrap();
+
+        g.depend("app", "http").unwrap();
+        g.add_node("log").unwrap();
+        assert!(matches!(g.add_node("tls"), Err(Error::QuotaExceeded)));
+        assert!(matches!(g.depend("http", "tls"), Err(Error::QuotaExceeded)));
+        assert!(!g.contains(&"tls"));
+
+        // Existing nodes and edges count against nothing
+        g.add_node("log").unwrap();
+        g.depend("app", "log").unwrap();
+        g.depend("app", "log").unwrap();
+        assert!(matches!(g.depend("http", "log"), Err(Error::QuotaExceeded)));
+        assert_eq!((g.node_count(), g.edge_count()), (3, 2));
+
+        // Other errors still come first for invalid edges
+        assert!(matches!(g.depend("app", "app"), Err(Error::DependsOnSelf)));
+
+        g.undepend(&"app", &"log").unwrap();
+        g.depend("http", "log").unwrap();
+
+        assert!(matches!(
+            g.set_quotas(Quotas {
+                max_nodes: Some(2),
+                max_edges: None,
+            }),
+            Err(Error::QuotaExceeded)
+        ));
+        assert_eq!(g.quotas(), quotas);
+
+        g.set_quotas(Quotas::default()).unwrap();
+        g.depend("http", "tls").unwrap();
+
+        let graph = g.into_inner();
+        assert!(matches!(
+            BoundedGraph::new(graph, quotas),
+            Err(Error::QuotaExceeded)
+        ));
+    }
+}
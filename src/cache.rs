@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::{Direction, Graph};
+
+/// Least recently used results of [`Graph::dependencies`] and [`Graph::dependents`],
+/// filled through shared references by queries
+#[derive(Debug)]
+pub(crate) struct QueryCache<T> {
+    /// Only changed through `&mut Graph`, so that a disabled cache is checked without locking
+    capacity: usize,
+    lru: Mutex<Lru<T>>,
+}
+
+#[derive(Clone, Debug)]
+struct Lru<T> {
+    tick: u64,
+    /// Cached results with the tick of their last use
+    entries: HashMap<(Direction, T), (HashSet<T>, u64)>,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Caches the results of up to capacity calls to [`Graph::dependencies`]
+    /// and [`Graph::dependents`], evicting the least recently used ones.
+    /// Zero, the default, disables the cache.
+    ///
+    /// Mutations only evict the results they change, i.e. an edge evicts
+    /// the dependencies of nodes reaching its dependent and
+    /// the dependents of nodes reached from its dependency.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.cache.capacity = capacity;
+        let lru = self.cache.lru.get_mut().unwrap();
+        while lru.entries.len() > capacity {
+            lru.evict();
+        }
+    }
+
+    pub fn cache_capacity(&self) -> usize {
+        self.cache.capacity
+    }
+}
+
+impl<T> QueryCache<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub(crate) fn get(&self, direction: Direction, node: &T) -> Option<HashSet<T>> {
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let mut lru = self.lru.lock().unwrap();
+
+        lru.tick += 1;
+        let tick = lru.tick;
+        let (result, used) = lru.entries.get_mut(&(direction, node.clone()))?;
+        *used = tick;
+
+        Some(result.clone())
+    }
+
+    pub(crate) fn insert(&self, direction: Direction, node: &T, result: &HashSet<T>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut lru = self.lru.lock().unwrap();
+        if lru.entries.len() >= self.capacity {
+            lru.evict();
+        }
+
+        lru.tick += 1;
+        let tick = lru.tick;
+        lru.entries
+            .insert((direction, node.clone()), (result.clone(), tick));
+    }
+
+    /// Evicts results changed by adding or removing the edge
+    pub(crate) fn edge_changed(&mut self, dependent: &T, dependency: &T) {
+        let lru = self.lru.get_mut().unwrap();
+        if lru.entries.is_empty() {
+            return;
+        }
+
+        lru.entries.retain(|(direction, node), (result, _)| {
+            let end = match direction {
+                Direction::Dependencies => dependent,
+                Direction::Dependents => dependency,
+            };
+
+            node != end && !result.contains(end)
+        });
+    }
+
+    /// Evicts the results of a removed node
+    pub(crate) fn node_removed(&mut self, node: &T) {
+        let lru = self.lru.get_mut().unwrap();
+        for direction in [Direction::Dependencies, Direction::Dependents] {
+            lru.entries.remove(&(direction, node.clone()));
+        }
+    }
+}
+
+impl<T> Lru<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn evict(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(key, _)| key.clone());
+
+        if let Some(oldest) = oldest {
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+impl<T> Default for QueryCache<T> {
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            lru: Mutex::new(Lru {
+                tick: 0,
+                entries: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl<T> Clone for QueryCache<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            capacity: self.capacity,
+            lru: Mutex::new(self.lru.lock().unwrap().clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_cache() {
+        let mut g = Graph::new();
+        g.depend("c", "b").unwrap();
+        g.depend("b", "a").unwrap();
+        g.depend("x", "y").unwrap();
+        g.set_cache_capacity(4);
+
+        let hits = |g: &Graph<&str>| g.stats().cache_hits;
+        g.dependencies(&"c");
+        g.dependencies(&"x");
+        g.dependents(&"a");
+        assert_eq!(g.dependencies(&"c").len(), 2);
+        assert_eq!(hits(&g), 1);
+
+        // Only results reaching the new edge are evicted
+        g.depend("y", "z").unwrap();
+        assert_eq!(g.dependencies(&"c").len(), 2);
+        assert_eq!(g.dependents(&"a").len(), 2);
+        assert_eq!(hits(&g), 3);
+        assert_eq!(g.dependencies(&"x"), HashSet::from(["y", "z"]));
+        assert_eq!(hits(&g), 3);
+
+        g.undepend(&"b", &"a").unwrap();
+        assert_eq!(g.dependencies(&"c"), HashSet::from(["b"]));
+        assert!(g.dependents(&"a").is_empty());
+        g.dependencies(&"x");
+        assert_eq!(hits(&g), 4);
+
+        g.remove_force(&"b");
+        assert!(g.dependencies(&"c").is_empty());
+
+        // Least recently used results go first
+        g.set_cache_capacity(2);
+        g.dependencies(&"x");
+        g.dependencies(&"c");
+        g.dependencies(&"y");
+        let before = hits(&g);
+        g.dependencies(&"y");
+        g.dependencies(&"x");
+        assert_eq!(hits(&g), before + 1);
+
+        g.set_cache_capacity(0);
+        g.dependencies(&"y");
+        assert_eq!(hits(&g), before + 1);
+        assert_eq!(g.stats().queries, 17);
+    }
+}
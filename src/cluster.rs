@@ -0,0 +1,321 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
+use crate::{Error, Graph};
+
+/// Hierarchy of clusters over nodes of a graph, e.g. teams owning services,
+/// for rolling edges up to the cluster level or drilling down into them.
+///
+/// Each node belongs to at most one cluster, and each cluster
+/// is nested in at most one parent cluster.
+#[derive(Clone, Debug)]
+pub struct Clusters<T, C> {
+    clusters: HashMap<T, C>,
+    parents: HashMap<C, C>,
+}
+
+impl<T, C> Clusters<T, C>
+where
+    T: Clone + Eq + Hash,
+    C: Clone + Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            clusters: HashMap::new(),
+            parents: HashMap::new(),
+        }
+    }
+
+    /// Puts node in cluster, moving it out of its previous cluster
+    pub fn assign(&mut self, node: T, cluster: C) {
+        self.clusters.insert(node, cluster);
+    }
+
+    /// Takes node out of its cluster, returning the cluster
+    pub fn unassign(&mut self, node: &T) -> Option<C> {
+        self.clusters.remove(node)
+    }
+
+    /// Nests child in parent, moving it out of its previous parent.
+    /// Fails with [`Error::CircularDependency`] if parent is nested in child.
+    pub fn nest(&mut self, child: C, parent: C) -> Result<(), Error<C>> {
+        if child == parent || self.ancestors(&parent).contains(&child) {
+            return Err(Error::CircularDependency);
+        }
+
+        self.parents.insert(child, parent);
+        Ok(())
+    }
+
+    /// Returns the innermost cluster of node
+    pub fn cluster_of(&self, node: &T) -> Option<&C> {
+        self.clusters.get(node)
+    }
+
+    pub fn parent(&self, cluster: &C) -> Option<&C> {
+        self.parents.get(cluster)
+    }
+
+    /// Returns clusters nested directly in cluster
+    pub fn children(&self, cluster: &C) -> HashSet<C> {
+        self.parents
+            .iter()
+            .filter(|(_, parent)| *parent == cluster)
+            .map(|(child, _)| child.clone())
+            .collect()
+    }
+
+    /// Returns enclosing clusters of cluster, from its parent outward
+    pub fn ancestors(&self, cluster: &C) -> Vec<C> {
+        let mut ancestors = Vec::new();
+        let mut current = cluster;
+        while let Some(parent) = self.parents.get(current) {
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+
+        ancestors
+    }
+
+    /// Returns nodes in cluster or any cluster nested in it
+    pub fn members(&self, cluster: &C) -> HashSet<T> {
+        self.clusters
+            .iter()
+            .filter(|(_, c)| *c == cluster || self.ancestors(c).contains(cluster))
+            .map(|(node, _)| node.clone())
+            .collect()
+    }
+
+    /// Returns the cluster of node at level, where the outermost clusters are
+    /// at level 0. Nodes in clusters nested less deeply get their innermost cluster.
+    pub fn cluster_at(&self, node: &T, level: usize) -> Option<C> {
+        let innermost = self.clusters.get(node)?;
+        let mut path = self.ancestors(innermost);
+        path.reverse();
+        path.push(innermost.clone());
+
+        let level = level.min(path.len() - 1);
+        Some(path.swap_remove(level))
+    }
+
+    /// Returns edges between clusters at level, see [`Clusters::cluster_at`],
+    /// with the number of node edges rolled into each.
+    ///
+    /// Edges within a cluster and edges of unclustered nodes are left out.
+    /// Cluster edges may form cycles even though node edges do not.
+    pub fn roll_up(&self, graph: &Graph<T>, level: usize) -> HashMap<(C, C), usize> {
+        let mut rolled = HashMap::new();
+        for (dependent, dependency) in graph.edges() {
+            let (Some(from), Some(to)) = (
+                self.cluster_at(dependent, level),
+                self.cluster_at(dependency, level),
+            ) else {
+                continue;
+            };
+
+            if from != to {
+                *rolled.entry((from, to)).or_default() += 1;
+            }
+        }
+
+        rolled
+    }
+
+    /// Returns node edges from members of cluster dependent to members of
+    /// cluster dependency, i.e. those behind an edge from [`Clusters::roll_up`]
+    pub fn edges_between(&self, graph: &Graph<T>, dependent: &C, dependency: &C) -> Vec<(T, T)> {
+        let (from, to) = (self.members(dependent), self.members(dependency));
+        graph
+            .edges()
+            .filter(|(a, b)| from.contains(*a) && to.contains(*b))
+            .map(|(a, b)| (a.clone(), b.clone()))
+            .collect()
+    }
+}
+
+impl<T, C> Clusters<T, C>
+where
+    T: Clone + Eq + Hash + Display,
+    C: Clone + Eq + Hash + Display,
+{
+    /// Like [`Graph::to_dot`], but drawing clusters as nested boxes around their nodes
+    pub fn to_dot(&self, graph: &Graph<T>) -> String {
+        let dot = graph.to_dot();
+        let (header, body) = dot.split_at(dot.find("\n    \"").map_or(dot.len() - 2, |i| i + 1));
+
+        // Sorted by display form, like nodes of to_dot
+        let mut roots: Vec<&C> = self
+            .clusters
+            .values()
+            .chain(self.parents.keys())
+            .chain(self.parents.values())
+            .filter(|c| !self.parents.contains_key(*c))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        roots.sort_by_key(|c| c.to_string());
+
+        let mut out = header.to_string();
+        let mut id = 0;
+        for root in roots {
+            self.write_cluster(&mut out, graph, root, 1, &mut id);
+        }
+        out.push_str(body);
+
+        out
+    }
+
+    fn write_cluster(
+        &self,
+        out: &mut String,
+        graph: &Graph<T>,
+        cluster: &C,
+        depth: usize,
+        id: &mut usize,
+    ) {
+        let indent = "    ".repeat(depth);
+        let _ = writeln!(out, "{indent}subgraph cluster_{id} {{");
+        let _ = writeln!(
+            out,
+            "{indent}    label={};",
+            crate::dot::quote(&cluster.to_string())
+        );
+        *id += 1;
+
+        let mut children: Vec<C> = self.children(cluster).into_iter().collect();
+        children.sort_by_key(|c| c.to_string());
+        for child in &children {
+            self.write_cluster(out, graph, child, depth + 1, id);
+        }
+
+        let mut nodes: Vec<String> = self
+            .clusters
+            .iter()
+            .filter(|(node, c)| *c == cluster && graph.contains(node))
+            .map(|(node, _)| node.to_string())
+            .collect();
+        nodes.sort();
+        for node in nodes {
+            let _ = writeln!(out, "{indent}    {};", crate::dot::quote(&node));
+        }
+
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+impl<T, C> Default for Clusters<T, C>
+where
+    T: Clone + Eq + Hash,
+    C: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (Graph<&'static str>, Clusters<&'static str, &'static str>) {
+        let mut g = Graph::new();
+        g.depend("checkout", "payments").unwrap();
+        g.depend("checkout", "cart").unwrap();
+        g.depend("cart", "catalog").unwrap();
+        g.depend("payments", "ledger").unwrap();
+        g.depend("search", "catalog").unwrap();
+        g.add_node("legacy");
+
+        let mut clusters = Clusters::new();
+        clusters.assign("checkout", "web");
+        clusters.assign("cart", "web");
+        clusters.assign("search", "web");
+        clusters.assign("payments", "money");
+        clusters.assign("ledger", "money");
+        clusters.assign("catalog", "inventory");
+        clusters.nest("web", "storefront").unwrap();
+        clusters.nest("inventory", "storefront").unwrap();
+
+        (g, clusters)
+    }
+
+    #[test]
+    fn test_clusters() {
+        let (g, mut clusters) = setup();
+
+        assert_eq!(clusters.cluster_of(&"cart"), Some(&"web"));
+        assert_eq!(clusters.cluster_of(&"legacy"), None);
+        assert_eq!(clusters.ancestors(&"web"), ["storefront"]);
+        assert_eq!(
+            clusters.children(&"storefront"),
+            HashSet::from(["web", "inventory"])
+        );
+        assert_eq!(
+            clusters.members(&"storefront"),
+            HashSet::from(["checkout", "cart", "search", "catalog"])
+        );
+        assert_eq!(clusters.cluster_at(&"cart", 0), Some("storefront"));
+        assert_eq!(clusters.cluster_at(&"cart", 5), Some("web"));
+        assert_eq!(clusters.cluster_at(&"ledger", 1), Some("money"));
+
+        // Team level, then drilled down
+        assert_eq!(
+            clusters.roll_up(&g, 1),
+            HashMap::from([(("web", "money"), 1), (("web", "inventory"), 2)])
+        );
+        assert_eq!(
+            clusters.roll_up(&g, 0),
+            HashMap::from([(("storefront", "money"), 1)])
+        );
+
+        let mut behind = clusters.edges_between(&g, &"web", &"inventory");
+        behind.sort();
+        assert_eq!(behind, [("cart", "catalog"), ("search", "catalog")]);
+
+        assert!(matches!(
+            clusters.nest("storefront", "web"),
+            Err(Error::CircularDependency)
+        ));
+        assert_eq!(clusters.unassign(&"search"), Some("web"));
+        assert_eq!(clusters.roll_up(&g, 1)[&("web", "inventory")], 1);
+    }
+
+    #[test]
+    fn test_clusters_to_dot() {
+        let (g, clusters) = setup();
+        let dot = clusters.to_dot(&g);
+
+        assert!(dot.starts_with(
+            "\
+digraph dependencies {
+    rankdir=LR;
+    node [shape=box, style=rounded, fontname=\"Helvetica\"];
+    subgraph cluster_0 {
+        label=\"money\";
+        \"ledger\";
+        \"payments\";
+    }
+    subgraph cluster_1 {
+        label=\"storefront\";
+        subgraph cluster_2 {
+            label=\"inventory\";
+            \"catalog\";
+        }
+        subgraph cluster_3 {
+            label=\"web\";
+            \"cart\";
+            \"checkout\";
+            \"search\";
+        }
+    }
+    \"cart\";
+"
+        ));
+        assert!(dot.ends_with("    \"search\" -> \"catalog\";\n}\n"));
+        assert_eq!(
+            Clusters::<&str, &str>::new().to_dot(&Graph::new()),
+            Graph::<&str>::new().to_dot()
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Splits the graph into weakly connected components, i.e. disjoint
+    /// sets of nodes connected by edges in either direction.
+    ///
+    /// Components are in no particular order.
+    pub fn components(&self) -> Vec<HashSet<T>> {
+        let mut seen: HashSet<&T> = HashSet::new();
+        let mut components = Vec::new();
+
+        for node in &self.nodes {
+            if !seen.insert(node) {
+                continue;
+            }
+
+            let mut component = HashSet::from([node.clone()]);
+            let mut q = vec![node];
+
+            while let Some(current) = q.pop() {
+                let dependencies = self.dependencies.get(current).into_iter().flatten();
+                let dependents = self.dependents.get(current).into_iter().flatten();
+
+                for other in dependencies.chain(dependents) {
+                    if seen.insert(other) {
+                        component.insert(other.clone());
+                        q.push(other);
+                    }
+                }
+            }
+
+            components.push(component);
+        }
+
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components() {
+        let mut g = Graph::new();
+        g.depend("web", "ui-kit").unwrap();
+        g.depend("admin", "ui-kit").unwrap();
+        g.depend("ui-kit", "icons").unwrap();
+        g.depend("worker", "queue").unwrap();
+        g.depend("docs", "mdbook").unwrap();
+
+        let mut components = g.components();
+        components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+
+        assert_eq!(components.len(), 3);
+        assert_eq!(
+            components[0],
+            HashSet::from(["web", "admin", "ui-kit", "icons"])
+        );
+        assert!(components[1..].contains(&HashSet::from(["worker", "queue"])));
+        assert!(components[1..].contains(&HashSet::from(["docs", "mdbook"])));
+
+        g.depend("worker", "icons").unwrap();
+        assert_eq!(g.components().len(), 2);
+
+        assert!(Graph::<&str>::new().components().is_empty());
+    }
+}
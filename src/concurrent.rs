@@ -0,0 +1,509 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+
+use crate::shard::{self, SHARDS};
+use crate::{Edges, Error, Graph};
+
+/// Graph for sharing between threads, with read-heavy workloads in mind.
+///
+/// Nodes and edges are spread over shards, each behind its own `RwLock`,
+/// so queries only briefly lock the shards they visit. Writers are serialized,
+/// so the cycle check and the insertion of an edge happen atomically
+/// with respect to other writers.
+///
+/// Queries running alongside a writer may observe its mutation partially applied.
+#[derive(Debug)]
+pub struct ConcurrentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    writer: Mutex<()>,
+    nodes: Vec<RwLock<HashSet<T>>>,
+    dependents: Vec<RwLock<Edges<T>>>,
+    dependencies: Vec<RwLock<Edges<T>>>,
+}
+
+impl<T> ConcurrentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            writer: Mutex::new(()),
+            nodes: (0..SHARDS).map(|_| RwLock::default()).collect(),
+            dependents: (0..SHARDS).map(|_| RwLock::default()).collect(),
+            dependencies: (0..SHARDS).map(|_| RwLock::default()).collect(),
+        }
+    }
+
+    /// Adds dependency edges to the graph,
+    /// returning whether the edge is new rather than already in the graph
+    pub fn depend(&self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
+        if dependent == dependency {
+            return Err(Error::DependsOnSelf);
+        }
+
+        let _writer = self.writer.lock().unwrap();
+
+        if self.depends_on(&dependency, &dependent) {
+            return Err(Error::CircularDependency);
+        }
+
+        insert_edge(&self.dependents, &dependency, &dependent);
+        let new = insert_edge(&self.dependencies, &dependent, &dependency);
+        shard_of(&self.nodes, &dependent)
+            .write()
+            .unwrap()
+            .insert(dependent);
+        shard_of(&self.nodes, &dependency)
+            .write()
+            .unwrap()
+            .insert(dependency);
+
+        Ok(new)
+    }
+
+    /// Removes dependency edges from the graph
+    pub fn undepend(&self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
+        let _writer = self.writer.lock().unwrap();
+
+        if !self.depends_on_directly(dependent, dependency) {
+            return Err(Error::NoSuchDirectDependency);
+        }
+
+        remove_edge(&self.dependencies, dependent, dependency);
+        remove_edge(&self.dependents, dependency, dependent);
+
+        Ok(())
+    }
+
+    /// Removes undepended target node
+    pub fn remove(&self, target: &T) -> Result<(), Error<T>> {
+        let _writer = self.writer.lock().unwrap();
+
+        if !self.contains(target) {
+            return Err(Error::NoSuchNode);
+        }
+
+        if self.is_dependend(target) {
+            return Err(Error::DependencyExists);
+        }
+
+        let dependencies = shard_of(&self.dependencies, target)
+            .write()
+            .unwrap()
+            .remove(target);
+
+        for dependency in dependencies.iter().flatten() {
+            remove_edge(&self.dependents, dependency, target);
+        }
+
+        shard_of(&self.nodes, target)
+            .write()
+            .unwrap()
+            .remove(target);
+
+        Ok(())
+    }
+
+    pub fn contains(&self, node: &T) -> bool {
+        shard_of(&self.nodes, node).read().unwrap().contains(node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.iter().map(|s| s.read().unwrap().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether dependent depends directly on dependency
+    pub fn depends_on_directly(&self, dependent: &T, dependency: &T) -> bool {
+        shard_of(&self.dependencies, dependent)
+            .read()
+            .unwrap()
+            .get(dependent)
+            .is_some_and(|deps| deps.contains(dependency))
+    }
+
+    /// Returns deep dependencies of node
+    pub fn dependencies(&self, node: &T) -> HashSet<T> {
+        dig_deep(&self.dependencies, node)
+    }
+
+    /// Returns deep dependents of node
+    pub fn dependents(&self, node: &T) -> HashSet<T> {
+        dig_deep(&self.dependents, node)
+    }
+
+    /// Returns whether dependent depends on dependency in some way
+    pub fn depends_on(&self, dependent: &T, dependency: &T) -> bool {
+        self.dependencies(dependent).contains(dependency)
+    }
+
+    /// Returns whether the node is depended on by other
+    pub fn is_dependend(&self, node: &T) -> bool {
+        shard_of(&self.dependents, node)
+            .read()
+            .unwrap()
+            .get(node)
+            .is_some_and(|deps| !deps.is_empty())
+    }
+
+    /// Copies the graph into a [`Graph`], blocking writers meanwhile
+    pub fn to_graph(&self) -> Graph<T> {
+        let _writer = self.writer.lock().unwrap();
+        let mut g = Graph::new();
+
+        for shard in &self.nodes {
+            shard.read().unwrap().iter().for_each(|n| g.insert_node(n));
+        }
+
+        for shard in &self.dependencies {
+            for (dependent, dependencies) in shard.read().unwrap().iter() {
+                for dependency in dependencies {
+                    g.depend(dependent.clone(), dependency.clone())
+                        .unwrap_or_else(|err| {
+                            panic!("concurrent graph should be acyclic: {}", err.map(drop))
+                        });
+                }
+            }
+        }
+
+        g
+    }
+}
+
+impl<T> ConcurrentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + Sync,
+{
+    /// Builds a graph from many edges at once, loading shards on parallel threads.
+    ///
+    /// Cycles are checked once after loading instead of on every edge,
+    /// which is much cheaper for large graphs.
+    pub fn from_edges<I>(edges: I) -> Result<Self, Error<T>>
+    where
+        I: IntoIterator<Item = (T, T)>,
+    {
+        let mut by_dependent: Vec<Vec<(T, T)>> = (0..SHARDS).map(|_| Vec::new()).collect();
+        let mut by_dependency: Vec<Vec<(T, T)>> = (0..SHARDS).map(|_| Vec::new()).collect();
+
+        for (dependent, dependency) in edges {
+            if dependent == dependency {
+                return Err(Error::DependsOnSelf);
+            }
+
+            let edge = (dependent.clone(), dependency.clone());
+            by_dependent[shard::index(&dependent, SHARDS)].push(edge);
+            by_dependency[shard::index(&dependency, SHARDS)].push((dependent, dependency));
+        }
+
+        let g = Self::new();
+
+        // Every thread owns its shards, so loading never contends on locks
+        std::thread::scope(|scope| {
+            let threads = parallelism().min(SHARDS);
+            for t in 0..threads {
+                let (g, by_dependent, by_dependency) = (&g, &by_dependent, &by_dependency);
+
+                scope.spawn(move || {
+                    for i in (t..SHARDS).step_by(threads) {
+                        let mut nodes = g.nodes[i].write().unwrap();
+                        let mut dependencies = g.dependencies[i].write().unwrap();
+                        let mut dependents = g.dependents[i].write().unwrap();
+
+                        for (dependent, dependency) in &by_dependent[i] {
+                            dependencies
+                                .entry(dependent.clone())
+                                .or_default()
+                                .insert(dependency.clone());
+                            nodes.insert(dependent.clone());
+                        }
+
+                        for (dependent, dependency) in &by_dependency[i] {
+                            dependents
+                                .entry(dependency.clone())
+                                .or_default()
+                                .insert(dependent.clone());
+                            nodes.insert(dependency.clone());
+                        }
+                    }
+                });
+            }
+        });
+
+        if !g.is_acyclic() {
+            return Err(Error::CircularDependency);
+        }
+
+        Ok(g)
+    }
+
+    /// Like [`ConcurrentGraph::dependencies`], but expands each level
+    /// of the search on the rayon thread pool with the `rayon` feature
+    pub fn dependencies_parallel(&self, node: &T) -> HashSet<T> {
+        dig_deep_parallel(&self.dependencies, node)
+    }
+
+    /// Like [`ConcurrentGraph::dependents`], but expands each level
+    /// of the search on the rayon thread pool with the `rayon` feature
+    pub fn dependents_parallel(&self, node: &T) -> HashSet<T> {
+        dig_deep_parallel(&self.dependents, node)
+    }
+
+    /// Kahn's algorithm over all shards
+    fn is_acyclic(&self) -> bool {
+        let mut pending: HashMap<T, usize> = HashMap::new();
+        let mut ready = Vec::new();
+
+        for shard in &self.nodes {
+            for node in shard.read().unwrap().iter() {
+                let count = shard_of(&self.dependencies, node)
+                    .read()
+                    .unwrap()
+                    .get(node)
+                    .map_or(0, |deps| deps.len());
+
+                match count {
+                    0 => ready.push(node.clone()),
+                    _ => {
+                        pending.insert(node.clone(), count);
+                    }
+                }
+            }
+        }
+
+        while let Some(node) = ready.pop() {
+            let shard = shard_of(&self.dependents, &node).read().unwrap();
+            for dependent in shard.get(&node).into_iter().flatten() {
+                let remaining = pending.get_mut(dependent).unwrap();
+                *remaining -= 1;
+
+                if *remaining == 0 {
+                    pending.remove(dependent);
+                    ready.push(dependent.clone());
+                }
+            }
+        }
+
+        pending.is_empty()
+    }
+}
+
+impl<T> Default for ConcurrentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<&Graph<T>> for ConcurrentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn from(graph: &Graph<T>) -> Self {
+        let g = Self::new();
+        for node in &graph.nodes {
+            shard_of(&g.nodes, node)
+                .write()
+                .unwrap()
+                .insert(node.clone());
+        }
+
+        for (dependent, dependencies) in &graph.dependencies {
+            for dependency in dependencies {
+                insert_edge(&g.dependents, dependency, dependent);
+                insert_edge(&g.dependencies, dependent, dependency);
+            }
+        }
+
+        g
+    }
+}
+
+fn shard_of<'a, T, S>(shards: &'a [RwLock<S>], key: &T) -> &'a RwLock<S>
+where
+    T: std::hash::Hash,
+{
+    &shards[shard::index(key, shards.len())]
+}
+
+/// Inserts value to the edges of key, returning whether it was new
+fn insert_edge<T>(edges: &[RwLock<Edges<T>>], key: &T, value: &T) -> bool
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    shard_of(edges, key)
+        .write()
+        .unwrap()
+        .entry(key.clone())
+        .or_default()
+        .insert(value.clone())
+}
+
+fn remove_edge<T>(edges: &[RwLock<Edges<T>>], key: &T, value: &T)
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    let mut shard = shard_of(edges, key).write().unwrap();
+    let Some(set) = shard.get_mut(key) else {
+        return;
+    };
+
+    set.remove(value);
+    if set.is_empty() {
+        shard.remove(key);
+    }
+}
+
+fn dig_deep<T>(edges: &[RwLock<Edges<T>>], node: &T) -> HashSet<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    let mut result = HashSet::new();
+    let mut q = vec![node.clone()];
+
+    while let Some(current) = q.pop() {
+        let shard = shard_of(edges, &current).read().unwrap();
+        for next in shard.get(&current).into_iter().flatten() {
+            if result.insert(next.clone()) {
+                q.push(next.clone());
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(feature = "rayon")]
+fn dig_deep_parallel<T>(edges: &[RwLock<Edges<T>>], node: &T) -> HashSet<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + Sync,
+{
+    use rayon::prelude::*;
+
+    let mut result = HashSet::new();
+    let mut frontier = vec![node.clone()];
+
+    while !frontier.is_empty() {
+        let discovered: Vec<T> = frontier
+            .par_iter()
+            .with_min_len(crate::parallel::MIN_CHUNK)
+            .flat_map_iter(|current| {
+                let shard = shard_of(edges, current).read().unwrap();
+                let next: Vec<T> = shard.get(current).into_iter().flatten().cloned().collect();
+                next
+            })
+            .collect();
+
+        frontier = discovered
+            .into_iter()
+            .filter(|n| result.insert(n.clone()))
+            .collect();
+    }
+
+    result
+}
+
+#[cfg(not(feature = "rayon"))]
+fn dig_deep_parallel<T>(edges: &[RwLock<Edges<T>>], node: &T) -> HashSet<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    dig_deep(edges, node)
+}
+
+fn parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::assert_no_dangling;
+
+    #[test]
+    fn test_concurrent_graph() {
+        let g = Arc::new(ConcurrentGraph::new());
+        assert!(g.depend(0, 1).unwrap());
+        assert!(!g.depend(0, 1).unwrap());
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let g = g.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..200 {
+                        assert!(g.depends_on(&0, &1));
+                        assert!(!g.depends_on(&1, &0));
+                    }
+                })
+            })
+            .collect();
+
+        // Writers racing to close a cycle, only one of each pair may win
+        let writers: Vec<_> = (0..4)
+            .map(|i| {
+                let g = g.clone();
+                std::thread::spawn(move || {
+                    for j in 1..50 {
+                        let (a, b) = (j * 10 + i, j * 10 + i + 100);
+                        let forward = g.depend(a, b).is_ok();
+                        let backward = g.depend(b, a).is_ok();
+                        assert!(forward ^ backward);
+                    }
+                })
+            })
+            .collect();
+
+        readers.into_iter().for_each(|r| r.join().unwrap());
+        writers.into_iter().for_each(|w| w.join().unwrap());
+
+        let snapshot = g.to_graph();
+        assert_no_dangling(&snapshot);
+        assert_eq!(snapshot.nodes.len(), g.len());
+
+        g.remove(&1).expect_err("1 is depended on by 0");
+        g.undepend(&0, &1).unwrap();
+        g.remove(&1).unwrap();
+        assert!(!g.contains(&1));
+        assert!(g.contains(&0));
+
+        let copied = ConcurrentGraph::from(&snapshot);
+        assert!(copied.depends_on(&0, &1));
+    }
+
+    #[test]
+    fn test_from_edges() {
+        // Binary tree of 1023 nodes, every node depending on its children
+        let edges = (1..512).flat_map(|n| [(n, n * 2), (n, n * 2 + 1)]);
+        let g = ConcurrentGraph::from_edges(edges).unwrap();
+
+        assert_eq!(g.len(), 1023);
+        assert!(g.depends_on(&1, &1023));
+        assert_eq!(g.dependencies_parallel(&1).len(), 1022);
+        assert_eq!(g.dependencies_parallel(&2), g.dependencies(&2));
+        assert_eq!(
+            g.dependents_parallel(&1023),
+            HashSet::from([511, 255, 127, 63, 31, 15, 7, 3, 1])
+        );
+        assert_no_dangling(&g.to_graph());
+
+        let cycle = [(1, 2), (2, 3), (3, 1)];
+        assert!(matches!(
+            ConcurrentGraph::from_edges(cycle),
+            Err(Error::CircularDependency)
+        ));
+        assert!(matches!(
+            ConcurrentGraph::from_edges([(1, 1)]),
+            Err(Error::DependsOnSelf)
+        ));
+    }
+}
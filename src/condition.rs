@@ -0,0 +1,206 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Error, Graph};
+
+/// Guard of a conditional edge, evaluated against enabled features
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Feature(String),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+/// Conditions of edges, keyed by dependent then dependency
+pub(crate) type Conditions<T> = HashMap<T, HashMap<T, Condition>>;
+
+impl Condition {
+    pub fn feature(name: &str) -> Self {
+        Self::Feature(name.to_string())
+    }
+
+    pub fn holds(&self, features: &HashSet<String>) -> bool {
+        match self {
+            Self::Feature(name) => features.contains(name),
+            Self::All(conditions) => conditions.iter().all(|c| c.holds(features)),
+            Self::Any(conditions) => conditions.iter().any(|c| c.holds(features)),
+            Self::Not(condition) => !condition.holds(features),
+        }
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Adds an edge that only exists in graphs resolved with features
+    /// satisfying condition, see [`Graph::resolve`].
+    ///
+    /// Conditional edges still take part in cycle detection. A conditional edge
+    /// added again with another condition exists if either holds, and
+    /// [`Graph::depend`] makes an existing conditional edge unconditional.
+    pub fn depend_if(
+        &mut self,
+        dependent: T,
+        dependency: T,
+        condition: Condition,
+    ) -> Result<(), Error<T>> {
+        let previous = self.condition(&dependent, &dependency).cloned();
+        let new = self.depend(dependent.clone(), dependency.clone())?;
+
+        let condition = match (new, previous) {
+            (true, _) => condition,
+            (false, Some(previous)) => Condition::Any(vec![previous, condition]),
+            (false, None) => return Ok(()),
+        };
+
+        self.conditions
+            .entry(dependent)
+            .or_default()
+            .insert(dependency, condition);
+
+        Ok(())
+    }
+
+    /// Returns condition of the edge, or None if it is unconditional or missing
+    pub fn condition(&self, dependent: &T, dependency: &T) -> Option<&Condition> {
+        self.conditions
+            .get(dependent)
+            .and_then(|conditions| conditions.get(dependency))
+    }
+
+    /// Materializes the concrete graph for enabled features.
+    ///
+    /// Conditional edges whose conditions do not hold are left out, along with
+    /// nodes that were only depended on through such edges.
+    pub fn resolve(&self, features: &HashSet<String>) -> Graph<T> {
+        let mut resolved = self.clone();
+        // Orphans are collected below, where roots are known to be kept
+        resolved.set_gc(false);
+
+        let conditions = std::mem::take(&mut resolved.conditions);
+        let mut orphans = Vec::new();
+
+        for (dependent, conditions) in &conditions {
+            for (dependency, condition) in conditions {
+                if !condition.holds(features) {
+                    resolved
+                        .undepend(dependent, dependency)
+                        .unwrap_or_else(|err| {
+                            panic!("conditional edges should exist: {}", err.map(drop))
+                        });
+                    orphans.push(dependency.clone());
+                }
+            }
+        }
+
+        while let Some(node) = orphans.pop() {
+            if !resolved.contains(&node) || resolved.dependents.contains_key(&node) {
+                continue;
+            }
+
+            // Still wanted if it was a root to begin with
+            if !self.dependents.contains_key(&node) {
+                continue;
+            }
+
+            orphans.extend(
+                resolved
+                    .dependencies
+                    .get(&node)
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            );
+            resolved.delete(&node);
+        }
+
+        resolved.set_gc(self.gc);
+        resolved
+    }
+
+    /// Drops condition of a removed edge
+    pub(crate) fn forget_condition(&mut self, dependent: &T, dependency: &T) {
+        let Some(conditions) = self.conditions.get_mut(dependent) else {
+            return;
+        };
+
+        conditions.remove(dependency);
+        if conditions.is_empty() {
+            self.conditions.remove(dependent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_no_dangling;
+
+    fn features(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_conditional_edges() {
+        let mut g = Graph::new();
+        g.depend("app", "core").unwrap();
+        g.depend_if("app", "tls", Condition::feature("https"))
+            .unwrap();
+        g.depend("tls", "crypto").unwrap();
+        g.depend("sign", "crypto").unwrap();
+        g.depend_if(
+            "app",
+            "sign",
+            Condition::All(vec![
+                Condition::feature("https"),
+                Condition::Not(Box::new(Condition::feature("minimal"))),
+            ]),
+        )
+        .unwrap();
+
+        let minimal = g.resolve(&features(&[]));
+        assert_eq!(minimal.nodes, HashSet::from(["app", "core"]));
+        assert!(minimal.conditions.is_empty());
+        assert_no_dangling(&minimal);
+
+        let https = g.resolve(&features(&["https"]));
+        assert_eq!(https.nodes.len(), 5);
+        assert!(https.depends_on(&"app", &"crypto"));
+
+        let https = g.resolve(&features(&["https", "minimal"]));
+        assert_eq!(https.nodes, HashSet::from(["app", "core", "tls", "crypto"]));
+
+        // Either condition enables the edge
+        g.depend_if("app", "tls", Condition::feature("tls"))
+            .unwrap();
+        assert!(g.resolve(&features(&["tls"])).contains(&"tls"));
+
+        // Unconditional edges stay unconditional
+        g.depend_if("app", "core", Condition::feature("never"))
+            .unwrap();
+        assert!(g.condition(&"app", &"core").is_none());
+        g.depend("app", "tls").unwrap();
+        assert!(g.condition(&"app", &"tls").is_none());
+
+        g.undepend(&"app", &"sign").unwrap();
+        assert!(g.conditions.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_with_gc() {
+        let mut g = Graph::new();
+        g.depend("app", "core").unwrap();
+        g.depend_if("app", "x", Condition::feature("f")).unwrap();
+        g.depend_if("x", "y", Condition::feature("g")).unwrap();
+        g.set_gc(true);
+
+        let resolved = g.resolve(&features(&[]));
+        assert_eq!(resolved.nodes, HashSet::from(["app", "core"]));
+        assert!(resolved.gc());
+        assert_no_dangling(&resolved);
+
+        let resolved = g.resolve(&features(&["f"]));
+        assert_eq!(resolved.nodes, HashSet::from(["app", "core", "x"]));
+    }
+}
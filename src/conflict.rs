@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+
+use crate::{edges_contain, insert_to_deps, rm_from_deps, Error, Graph};
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Declares that a and b must never be present together,
+    /// making plans containing both fail with [`Error::Conflict`]
+    pub fn conflict(&mut self, a: T, b: T) -> Result<(), Error<T>> {
+        if a == b {
+            return Err(Error::DependsOnSelf);
+        }
+
+        self.insert_node(&a);
+        self.insert_node(&b);
+        self.touch_pair(&a, &b);
+        self.touch_pair(&b, &a);
+        insert_to_deps(&mut self.conflicts, a.clone(), b.clone());
+        insert_to_deps(&mut self.conflicts, b, a);
+
+        Ok(())
+    }
+
+    /// Removes the conflict declaration between a and b
+    pub fn unconflict(&mut self, a: &T, b: &T) -> Result<(), Error<T>> {
+        if !self.conflicts_with(a, b) {
+            return Err(Error::NoSuchDirectDependency);
+        }
+
+        self.touch_pair(a, b);
+        self.touch_pair(b, a);
+        rm_from_deps(&mut self.conflicts, a, b);
+        rm_from_deps(&mut self.conflicts, b, a);
+
+        Ok(())
+    }
+
+    pub fn conflicts_with(&self, a: &T, b: &T) -> bool {
+        edges_contain(&self.conflicts, a, b)
+    }
+
+    /// Returns nodes declared to conflict with node
+    pub fn conflicts(&self, node: &T) -> HashSet<T> {
+        self.conflicts.get(node).cloned().unwrap_or_default()
+    }
+
+    /// Returns a pair of conflicting nodes both present in nodes, if any
+    pub fn find_conflict<'a, I>(&self, nodes: I) -> Option<(T, T)>
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        if self.conflicts.is_empty() {
+            return None;
+        }
+
+        let nodes: HashSet<&T> = nodes.into_iter().collect();
+        nodes.iter().find_map(|node| {
+            self.conflicts
+                .get(*node)
+                .into_iter()
+                .flatten()
+                .find(|other| nodes.contains(other))
+                .map(|other| ((*node).clone(), other.clone()))
+        })
+    }
+
+    /// Drops conflicts declared with a removed node
+    pub(crate) fn forget_conflicts(&mut self, node: &T) {
+        if self.touched.is_some() {
+            for other in self.conflicts(node) {
+                self.touch_pair(node, &other);
+                self.touch_pair(&other, node);
+            }
+        }
+
+        for other in self.conflicts.remove(node).into_iter().flatten() {
+            rm_from_deps(&mut self.conflicts, &other, node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    fn test_conflicts() {
+        let mut g = Graph::new();
+        g.depend("mutt", "postfix").unwrap();
+        g.depend("cron", "exim").unwrap();
+        g.depend("server", "mutt").unwrap();
+        g.depend("server", "cron").unwrap();
+        g.conflict("postfix", "exim").unwrap();
+        g.conflict("exim", "exim").expect_err("conflicts with self");
+
+        assert!(g.conflicts_with(&"exim", &"postfix"));
+        assert_eq!(g.conflicts(&"postfix"), HashSet::from(["exim"]));
+
+        assert!(g.install_plan(&"mutt", Level::Depends).is_ok());
+        match g.install_plan(&"server", Level::Depends) {
+            Err(err @ Error::Conflict { a, b }) => {
+                assert!((a, b) == ("postfix", "exim") || (a, b) == ("exim", "postfix"));
+                assert_eq!(
+                    err.to_string(),
+                    format!("conflicting nodes {a:?} and {b:?}")
+                );
+            }
+            other => panic!("expected a conflict, got {other:?}"),
+        }
+
+        let conflict = g.find_conflict(&["server", "postfix", "exim"]).unwrap();
+        assert!(conflict == ("postfix", "exim") || conflict == ("exim", "postfix"));
+        assert_eq!(g.find_conflict(&["server", "postfix"]), None);
+
+        g.unconflict(&"exim", &"postfix").unwrap();
+        g.unconflict(&"exim", &"postfix")
+            .expect_err("no longer conflicting");
+        assert!(g.install_plan(&"server", Level::Depends).is_ok());
+
+        // Conflicts go away with their nodes
+        g.conflict("postfix", "exim").unwrap();
+        g.remove_force(&"exim");
+        assert!(g.conflicts.is_empty());
+    }
+}
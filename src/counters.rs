@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Graph;
+
+/// Operation counts of a graph, see [`Graph::stats`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Calls to [`Graph::dependencies`], [`Graph::dependents`] and [`Graph::depends_on`]
+    pub queries: u64,
+    /// Queries answered from cache instead of walking the graph
+    pub cache_hits: u64,
+    pub edges_added: u64,
+    /// Removed edges, including edges removed with their nodes
+    pub edges_removed: u64,
+    /// Nodes reached by queries and by cycle checks of new edges
+    pub nodes_visited: u64,
+}
+
+/// Counters behind [`Stats`], updated through shared references by queries
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    queries: AtomicU64,
+    cache_hits: AtomicU64,
+    edges_added: AtomicU64,
+    edges_removed: AtomicU64,
+    nodes_visited: AtomicU64,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns counts of operations since the graph was created,
+    /// cloned, or last reset with [`Graph::reset_stats`]
+    pub fn stats(&self) -> Stats {
+        self.counters.snapshot()
+    }
+
+    pub fn reset_stats(&self) {
+        self.counters.set(Stats::default());
+    }
+}
+
+impl Counters {
+    /// Counts a query that walked the graph, reaching visited nodes
+    pub(crate) fn query(&self, visited: usize) {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        self.visit(visited);
+    }
+
+    /// Counts a query answered from cache
+    pub(crate) fn cache_hit(&self) {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn visit(&self, visited: usize) {
+        self.nodes_visited
+            .fetch_add(visited as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn edge_added(&self) {
+        self.edges_added.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn edge_removed(&self) {
+        self.edges_removed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Stats {
+        Stats {
+            queries: self.queries.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            edges_added: self.edges_added.load(Ordering::Relaxed),
+            edges_removed: self.edges_removed.load(Ordering::Relaxed),
+            nodes_visited: self.nodes_visited.load(Ordering::Relaxed),
+        }
+    }
+
+    fn set(&self, stats: Stats) {
+        self.queries.store(stats.queries, Ordering::Relaxed);
+        self.cache_hits.store(stats.cache_hits, Ordering::Relaxed);
+        self.edges_added.store(stats.edges_added, Ordering::Relaxed);
+        self.edges_removed
+            .store(stats.edges_removed, Ordering::Relaxed);
+        self.nodes_visited
+            .store(stats.nodes_visited, Ordering::Relaxed);
+    }
+}
+
+impl Clone for Counters {
+    fn clone(&self) -> Self {
+        let counters = Self::default();
+        counters.set(self.snapshot());
+        counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("c", "b").unwrap();
+
+        // Cycle checks visit nodes without being queries, even for existing edges
+        let stats = g.stats();
+        assert_eq!(stats.queries, 0);
+        assert_eq!(stats.edges_added, 2);
+        assert_eq!(stats.nodes_visited, 2);
+
+        assert_eq!(g.dependencies(&"c").len(), 2);
+        assert!(g.depends_on(&"b", &"a"));
+        g.dependents(&"missing");
+
+        let stats = g.stats();
+        assert_eq!(stats.queries, 3);
+        assert_eq!(stats.nodes_visited, 5);
+
+        g.undepend(&"c", &"b").unwrap();
+        g.remove(&"b").unwrap();
+        assert_eq!(g.stats().edges_removed, 2);
+        assert_eq!(g.stats().cache_hits, 0);
+
+        let copy = g.clone();
+        g.reset_stats();
+        assert_eq!(g.stats(), Stats::default());
+        assert_eq!(copy.stats().queries, 3);
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns nodes whose removal would disconnect some dependent
+    /// from one of its deep dependencies, i.e. nodes that every path
+    /// between the two goes through
+    pub fn critical_nodes(&self) -> HashSet<T> {
+        self.nodes
+            .iter()
+            .filter(|node| self.is_critical(node))
+            .cloned()
+            .collect()
+    }
+
+    fn is_critical(&self, node: &T) -> bool {
+        let below = self.dependencies(node);
+        if below.is_empty() {
+            return false;
+        }
+
+        self.dependents(node).iter().any(|dependent| {
+            let reached = self.dependencies_avoiding(dependent, node);
+            below.iter().any(|dependency| !reached.contains(dependency))
+        })
+    }
+
+    /// Returns deep dependencies of node reachable without going through avoided
+    fn dependencies_avoiding(&self, node: &T, avoided: &T) -> HashSet<&T> {
+        let mut result = HashSet::new();
+        let mut q = vec![node];
+
+        while let Some(current) = q.pop() {
+            for dependency in self.dependencies.get(current).into_iter().flatten() {
+                if dependency != avoided && result.insert(dependency) {
+                    q.push(dependency);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_critical_nodes() {
+        let mut g = Graph::new();
+        g.depend("web", "gateway").unwrap();
+        g.depend("gateway", "auth").unwrap();
+        g.depend("gateway", "cache").unwrap();
+        g.depend("auth", "db").unwrap();
+        g.depend("cache", "db").unwrap();
+
+        // db stays reachable through either auth or cache
+        assert_eq!(g.critical_nodes(), HashSet::from(["gateway"]));
+
+        g.depend("web", "db").unwrap();
+        assert_eq!(g.critical_nodes(), HashSet::from(["gateway"]));
+
+        g.depend("db", "disk").unwrap();
+        assert_eq!(g.critical_nodes(), HashSet::from(["gateway", "db"]));
+
+        g.depend("web", "auth").unwrap();
+        g.depend("web", "cache").unwrap();
+        assert_eq!(g.critical_nodes(), HashSet::from(["db"]));
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use crate::{Edges, Graph};
+
+/// Summary of node depths, see [`Graph::depth_stats`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DepthStats {
+    pub max: usize,
+    pub mean: f64,
+    pub median: usize,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Limits the number of edges in any dependency chain,
+    /// making [`Graph::depend`] reject edges that would create longer chains
+    /// with [`Error::TooDeep`](crate::Error::TooDeep), which carries the offending chain.
+    /// Existing chains are not checked.
+    pub fn set_max_depth(&mut self, max: Option<usize>) {
+        self.max_depth = max;
+    }
+
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Returns the longest chain that would pass through the edge, from the
+    /// topmost dependent down to the deepest dependency, whether or not the edge exists
+    pub fn chain_through(&self, dependent: &T, dependency: &T) -> Vec<T> {
+        let mut chain = longest_chain(&self.dependents, dependent);
+        chain.reverse();
+        chain.extend(longest_chain(&self.dependencies, dependency));
+
+        chain
+    }
+
+    /// Returns how many nodes sit at each depth, where leaves are at depth 0
+    /// and other nodes sit right above their deepest dependency,
+    /// like in [`Graph::layers`]
+    pub fn depth_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for depth in self.depths().into_values() {
+            if histogram.len() <= depth {
+                histogram.resize(depth + 1, 0);
+            }
+
+            histogram[depth] += 1;
+        }
+
+        histogram
+    }
+
+    /// Returns summary statistics of node depths, see [`Graph::depth_histogram`]
+    pub fn depth_stats(&self) -> DepthStats {
+        let histogram = self.depth_histogram();
+        let count: usize = histogram.iter().sum();
+        if count == 0 {
+            return DepthStats::default();
+        }
+
+        let total: usize = histogram.iter().enumerate().map(|(d, n)| d * n).sum();
+        let mut seen = 0;
+        let median = histogram
+            .iter()
+            .position(|n| {
+                seen += n;
+                seen * 2 >= count
+            })
+            .unwrap_or_default();
+
+        DepthStats {
+            max: histogram.len() - 1,
+            mean: total as f64 / count as f64,
+            median,
+        }
+    }
+
+    /// Returns a longest dependency chain in the graph,
+    /// from its topmost dependent down to a leaf
+    pub fn deepest_chain(&self) -> Vec<T> {
+        let depths = self.depths();
+        let Some(mut current) = depths.iter().max_by_key(|(_, d)| **d).map(|(n, _)| *n) else {
+            return Vec::new();
+        };
+
+        let mut chain = vec![current.clone()];
+        while let Some(next) = self
+            .dependencies
+            .get(current)
+            .into_iter()
+            .flatten()
+            .find(|dependency| depths[dependency] + 1 == depths[current])
+        {
+            chain.push(next.clone());
+            current = next;
+        }
+
+        chain
+    }
+
+    /// Returns depth of every node, visiting nodes after all of their dependencies
+    fn depths(&self) -> HashMap<&T, usize> {
+        let mut pending: HashMap<&T, usize> = self
+            .nodes
+            .iter()
+            .map(|node| (node, self.dependency_count(node)))
+            .collect();
+
+        let mut ready: Vec<&T> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(node, _)| *node)
+            .collect();
+
+        let mut depths: HashMap<&T, usize> = ready.iter().map(|node| (*node, 0)).collect();
+        while let Some(node) = ready.pop() {
+            let depth = depths[node] + 1;
+
+            for dependent in self.dependents.get(node).into_iter().flatten() {
+                let current = depths.entry(dependent).or_default();
+                *current = depth.max(*current);
+
+                let count = pending.get_mut(dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+
+        depths
+    }
+
+    /// Returns the chain through the edge if it is longer than max depth
+    pub(crate) fn too_deep(&self, dependent: &T, dependency: &T) -> Option<Vec<T>> {
+        let max = self.max_depth?;
+
+        // Chains of n nodes have n - 1 edges
+        let chain = self.chain_through(dependent, dependency);
+        (chain.len() > max + 1).then_some(chain)
+    }
+}
+
+/// Returns the longest chain starting at node and following edges
+fn longest_chain<T>(edges: &Edges<T>, node: &T) -> Vec<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    // Length of the longest chain from each node, and the next node on it
+    let mut longest: HashMap<&T, (usize, Option<&T>)> = HashMap::new();
+    let mut stack = vec![(node, false)];
+
+    while let Some((current, expanded)) = stack.pop() {
+        if longest.contains_key(current) {
+            continue;
+        }
+
+        let next = edges.get(current).into_iter().flatten();
+        if !expanded {
+            stack.push((current, true));
+            stack.extend(
+                next.filter(|n| !longest.contains_key(*n))
+                    .map(|n| (n, false)),
+            );
+            continue;
+        }
+
+        let best = next
+            .map(|n| (longest[n].0 + 1, Some(n)))
+            .max_by_key(|(len, _)| *len)
+            .unwrap_or((1, None));
+
+        longest.insert(current, best);
+    }
+
+    let mut chain = vec![node.clone()];
+    let mut current = node;
+    while let Some((_, Some(next))) = longest.get(current) {
+        chain.push((*next).clone());
+        current = next;
+    }
+
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_max_depth() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("y", "x").unwrap();
+
+        g.set_max_depth(Some(3));
+        assert_eq!(g.max_depth(), Some(3));
+
+        // x -> y brings y up to 3 edges from a
+        assert_eq!(g.chain_through(&"x", &"c"), vec!["y", "x", "c", "b", "a"]);
+        let Err(Error::TooDeep { chain }) = g.depend("x", "c") else {
+            panic!("x -> c should be too deep");
+        };
+        assert_eq!(chain, vec!["y", "x", "c", "b", "a"]);
+        assert_eq!(
+            g.depend("x", "c").unwrap_err().to_string(),
+            "dependency chain of 4 edges too deep"
+        );
+        assert_eq!(
+            format!("{:?}", g.depend("x", "c").unwrap_err()),
+            r#"TooDeep { chain: ["y", "x", "c", "b", "a"] }"#
+        );
+        assert!(!g.depends_on(&"x", &"c"));
+
+        g.depend("x", "b").unwrap();
+        assert_eq!(g.chain_through(&"x", &"b"), vec!["y", "x", "b", "a"]);
+        assert!(matches!(
+            g.depend("a", "z"),
+            Err(Error::TooDeep { chain }) if chain.len() == 5
+        ));
+
+        // Existing edges are fine
+        g.depend("x", "b").unwrap();
+
+        g.set_max_depth(None);
+        g.depend("a", "z").unwrap();
+        assert_eq!(g.chain_through(&"y", &"x").len(), 5);
+    }
+
+    #[test]
+    fn test_depth_histogram() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "libc").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("tls", "libc").unwrap();
+        g.depend("cli", "libc").unwrap();
+
+        assert_eq!(g.depth_histogram(), vec![1, 2, 1, 1]);
+        assert_eq!(
+            g.depth_histogram(),
+            g.layers().iter().map(|l| l.len()).collect::<Vec<_>>()
+        );
+
+        assert_eq!(g.deepest_chain(), vec!["app", "http", "tls", "libc"]);
+
+        let stats = g.depth_stats();
+        assert_eq!(stats.max, 3);
+        assert_eq!(stats.median, 1);
+        assert_eq!(stats.mean, 7.0 / 5.0);
+
+        let empty = Graph::<&str>::new();
+        assert!(empty.depth_histogram().is_empty());
+        assert_eq!(empty.depth_stats(), DepthStats::default());
+        assert!(empty.deepest_chain().is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns (top, left, right, bottom) for every pair of distinct direct
+    /// dependencies of top whose paths reconverge at bottom.
+    ///
+    /// Only the first nodes where paths meet are reported, not everything below them.
+    /// Pairs where one side depends on the other are triangles, not diamonds,
+    /// and are skipped. Each pair is reported once, in no particular order.
+    pub fn find_diamonds(&self) -> Vec<(T, T, T, T)> {
+        let mut diamonds = Vec::new();
+
+        for (top, dependencies) in &self.dependencies {
+            let dependencies: Vec<&T> = dependencies.iter().collect();
+
+            for (i, left) in dependencies.iter().enumerate() {
+                let below_left = self.dependencies(left);
+
+                for right in &dependencies[i + 1..] {
+                    if below_left.contains(*right) || self.depends_on(right, left) {
+                        continue;
+                    }
+
+                    let common: HashSet<T> = self
+                        .dependencies(right)
+                        .into_iter()
+                        .filter(|node| below_left.contains(node))
+                        .collect();
+
+                    for bottom in &common {
+                        if common.iter().any(|other| self.depends_on(other, bottom)) {
+                            continue;
+                        }
+
+                        diamonds.push((
+                            top.clone(),
+                            (*left).clone(),
+                            (*right).clone(),
+                            bottom.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        diamonds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_diamonds() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "db").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("db", "tls").unwrap();
+        g.depend("tls", "libc").unwrap();
+
+        // Triangle, not a diamond
+        g.depend("app", "log").unwrap();
+        g.depend("log", "db").unwrap();
+
+        let diamonds = g.find_diamonds();
+        assert_eq!(diamonds.len(), 2);
+        for (top, left, right, bottom) in diamonds {
+            assert_eq!(top, "app");
+            assert_eq!(bottom, "tls");
+            assert!(["http", "db", "log"].contains(&left));
+            assert!(["http", "db", "log"].contains(&right));
+            assert!(left == "http" || right == "http");
+        }
+
+        g.undepend(&"db", &"tls").unwrap();
+        assert!(g.find_diamonds().is_empty());
+    }
+}
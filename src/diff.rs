@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+
+use crate::{Error, Graph};
+
+/// Changes between two graphs, see [`Graph::diff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diff<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    pub added_nodes: HashSet<T>,
+    pub removed_nodes: HashSet<T>,
+    /// Added edges, as (dependent, dependency)
+    pub added_edges: HashSet<(T, T)>,
+    /// Removed edges, as (dependent, dependency)
+    pub removed_edges: HashSet<(T, T)>,
+}
+
+impl<T> Diff<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns nodes and edges added and removed going from self to newer
+    pub fn diff(&self, newer: &Graph<T>) -> Diff<T> {
+        let edges = |g: &Graph<T>| -> HashSet<(T, T)> {
+            g.dependencies
+                .iter()
+                .flat_map(|(dependent, dependencies)| {
+                    dependencies
+                        .iter()
+                        .map(move |dependency| (dependent.clone(), dependency.clone()))
+                })
+                .collect()
+        };
+
+        let (old_edges, new_edges) = (edges(self), edges(newer));
+
+        Diff {
+            added_nodes: newer.nodes.difference(&self.nodes).cloned().collect(),
+            removed_nodes: self.nodes.difference(&newer.nodes).cloned().collect(),
+            added_edges: new_edges.difference(&old_edges).cloned().collect(),
+            removed_edges: old_edges.difference(&new_edges).cloned().collect(),
+        }
+    }
+
+    /// Applies diff to the graph all at once, or not at all if any of its
+    /// changes fail, e.g. if it removes a node that still has dependents
+    pub fn apply_diff(&mut self, diff: &Diff<T>) -> Result<(), Error<T>> {
+        self.transaction(|tx| {
+            for (dependent, dependency) in &diff.removed_edges {
+                tx.undepend(dependent, dependency)?;
+            }
+
+            // Removing edges may have collected orphans already
+            for node in &diff.removed_nodes {
+                if tx.contains(node) {
+                    tx.remove(node)?;
+                }
+            }
+
+            for node in &diff.added_nodes {
+                tx.add_node(node.clone());
+            }
+
+            for (dependent, dependency) in &diff.added_edges {
+                tx.depend(dependent.clone(), dependency.clone())?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let mut old = Graph::new();
+        old.depend("app", "http").unwrap();
+        old.depend("http", "tls").unwrap();
+        old.depend("app", "xml").unwrap();
+
+        let mut new = old.clone();
+        new.undepend(&"app", &"xml").unwrap();
+        new.remove(&"xml").unwrap();
+        new.depend("app", "json").unwrap();
+        new.depend("app", "tls").unwrap();
+        new.add_node("app");
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_nodes, HashSet::from(["json"]));
+        assert_eq!(diff.removed_nodes, HashSet::from(["xml"]));
+        assert_eq!(
+            diff.added_edges,
+            HashSet::from([("app", "json"), ("app", "tls")])
+        );
+        assert_eq!(diff.removed_edges, HashSet::from([("app", "xml")]));
+        assert!(!diff.is_empty());
+
+        let reverse = new.diff(&old);
+        assert_eq!(reverse.added_nodes, diff.removed_nodes);
+        assert_eq!(reverse.removed_edges, diff.added_edges);
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn test_apply_diff() {
+        let mut old = Graph::new();
+        old.depend("app", "http").unwrap();
+        old.depend("app", "xml").unwrap();
+
+        let mut new = old.clone();
+        new.undepend(&"app", &"xml").unwrap();
+        new.remove(&"xml").unwrap();
+        new.depend("http", "tls").unwrap();
+
+        let mut g = old.clone();
+        g.apply_diff(&old.diff(&new)).unwrap();
+        assert!(g.diff(&new).is_empty());
+
+        // Failing diffs change nothing
+        let mut bad = new.diff(&old);
+        bad.removed_nodes.insert("http");
+        g.apply_diff(&bad).unwrap_err();
+        assert!(g.diff(&new).is_empty());
+    }
+}
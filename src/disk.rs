@@ -0,0 +1,430 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use crate::Graph;
+
+const MAGIC: &[u8; 8] = b"SOYDEP01";
+const HEADER_LEN: u64 = 16;
+const ENTRY_LEN: u64 = 36;
+
+/// Read-only graph of string nodes, queried directly from its bytes
+/// without loading it into memory, e.g. from a file memory-mapped
+/// with `DiskGraph::open` of the `mmap` feature.
+///
+/// The format is written with [`DiskGraph::write`], and consists of a header,
+/// a table of nodes sorted by name, node names, and adjacency lists of node ids.
+/// Lookups binary search the node table, so every query only touches
+/// the pages of storage it visits. Offsets, lengths and ids are checked
+/// against the storage before use, and corrupted storage fails queries
+/// with [`std::io::ErrorKind::InvalidData`].
+#[derive(Debug)]
+pub struct DiskGraph<S> {
+    storage: S,
+    len: u64,
+}
+
+/// Entry in the node table
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    name_offset: u64,
+    name_len: u32,
+    dependencies_offset: u64,
+    dependencies_len: u32,
+    dependents_offset: u64,
+    dependents_len: u32,
+}
+
+#[cfg(feature = "mmap")]
+impl DiskGraph<memmap2::Mmap> {
+    /// Memory-maps the graph file at path.
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn open<P>(path: P) -> std::io::Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only, and callers keep the file unmodified
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+
+        Self::new(map)
+    }
+}
+
+impl DiskGraph<Vec<u8>> {
+    /// Writes graph in the format read by [`DiskGraph::new`]
+    pub fn write<T, W>(graph: &Graph<T>, mut w: W) -> std::io::Result<()>
+    where
+        T: Clone + Eq + std::hash::Hash + AsRef<str>,
+        W: Write,
+    {
+        let mut nodes: Vec<&T> = graph.nodes.iter().collect();
+        nodes.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+
+        let ids: HashMap<&T, u32> = nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| (*node, id as u32))
+            .collect();
+
+        let adjacency = |edges: &crate::Edges<T>, node: &T| -> Vec<u32> {
+            let mut list: Vec<u32> = edges
+                .get(node)
+                .into_iter()
+                .flatten()
+                .map(|n| ids[n])
+                .collect();
+
+            list.sort_unstable();
+            list
+        };
+
+        let names_start = HEADER_LEN + ENTRY_LEN * nodes.len() as u64;
+        let names_len: u64 = nodes.iter().map(|n| n.as_ref().len() as u64).sum();
+        let mut name_offset = names_start;
+        let mut list_offset = names_start + names_len;
+
+        let mut table = Vec::new();
+        let mut lists = Vec::new();
+
+        for node in &nodes {
+            let dependencies = adjacency(&graph.dependencies, node);
+            let dependents = adjacency(&graph.dependents, node);
+
+            let entry = Entry {
+                name_offset,
+                name_len: node.as_ref().len() as u32,
+                dependencies_offset: list_offset,
+                dependencies_len: dependencies.len() as u32,
+                dependents_offset: list_offset + 4 * dependencies.len() as u64,
+                dependents_len: dependents.len() as u32,
+            };
+
+            name_offset += entry.name_len as u64;
+            list_offset = entry.dependents_offset + 4 * dependents.len() as u64;
+
+            entry.write(&mut table);
+            dependencies
+                .iter()
+                .chain(&dependents)
+                .for_each(|id| lists.extend(id.to_le_bytes()));
+        }
+
+        w.write_all(MAGIC)?;
+        w.write_all(&(nodes.len() as u64).to_le_bytes())?;
+        w.write_all(&table)?;
+        for node in &nodes {
+            w.write_all(node.as_ref().as_bytes())?;
+        }
+
+        w.write_all(&lists)
+    }
+}
+
+impl<S> DiskGraph<S>
+where
+    S: AsRef<[u8]>,
+{
+    pub fn new(storage: S) -> std::io::Result<Self> {
+        let bytes = storage.as_ref();
+        if bytes.get(..8) != Some(MAGIC) {
+            return Err(invalid("bad magic"));
+        }
+
+        let header = slice(bytes, 8, 8, "truncated header")?;
+        let len = u64::from_le_bytes(header.try_into().unwrap());
+        let table_len = len
+            .checked_mul(ENTRY_LEN)
+            .ok_or_else(|| invalid("node table out of bounds"))?;
+        slice(bytes, HEADER_LEN, table_len, "node table out of bounds")?;
+
+        Ok(Self { storage, len })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, node: &str) -> std::io::Result<bool> {
+        Ok(self.find(node)?.is_some())
+    }
+
+    /// Returns whether dependent depends directly on dependency
+    pub fn depends_on_directly(&self, dependent: &str, dependency: &str) -> std::io::Result<bool> {
+        let (Some(dependent), Some(dependency)) = (self.find(dependent)?, self.find(dependency)?)
+        else {
+            return Ok(false);
+        };
+
+        let entry = self.entry(dependent)?;
+        let list = self.list(entry.dependencies_offset, entry.dependencies_len)?;
+
+        Ok(list.binary_search(&dependency).is_ok())
+    }
+
+    /// Returns deep dependencies of node
+    pub fn dependencies(&self, node: &str) -> std::io::Result<HashSet<String>> {
+        self.names(self.dig_deep(node, |e| (e.dependencies_offset, e.dependencies_len))?)
+    }
+
+    /// Returns deep dependents of node
+    pub fn dependents(&self, node: &str) -> std::io::Result<HashSet<String>> {
+        self.names(self.dig_deep(node, |e| (e.dependents_offset, e.dependents_len))?)
+    }
+
+    /// Returns whether dependent depends on dependency in some way
+    pub fn depends_on(&self, dependent: &str, dependency: &str) -> std::io::Result<bool> {
+        let Some(dependency) = self.find(dependency)? else {
+            return Ok(false);
+        };
+
+        let found = self.dig_deep(dependent, |e| (e.dependencies_offset, e.dependencies_len))?;
+        Ok(found.contains(&dependency))
+    }
+
+    fn dig_deep<F>(&self, node: &str, list: F) -> std::io::Result<HashSet<u32>>
+    where
+        F: Fn(&Entry) -> (u64, u32),
+    {
+        let mut result = HashSet::new();
+        let Some(id) = self.find(node)? else {
+            return Ok(result);
+        };
+
+        let mut q = vec![id];
+        while let Some(current) = q.pop() {
+            let (offset, len) = list(&self.entry(current)?);
+            for next in self.list(offset, len)? {
+                if result.insert(next) {
+                    q.push(next);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Binary searches the node table for node
+    fn find(&self, node: &str) -> std::io::Result<Option<u32>> {
+        let (mut low, mut high) = (0, self.len);
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let name = self.name(&self.entry(mid as u32)?)?;
+
+            match name.as_str().cmp(node) {
+                std::cmp::Ordering::Equal => return Ok(Some(mid as u32)),
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn names(&self, ids: HashSet<u32>) -> std::io::Result<HashSet<String>> {
+        ids.into_iter()
+            .map(|id| self.name(&self.entry(id)?))
+            .collect()
+    }
+
+    fn entry(&self, id: u32) -> std::io::Result<Entry> {
+        if id as u64 >= self.len {
+            return Err(invalid("node id out of bounds"));
+        }
+
+        let offset = HEADER_LEN + ENTRY_LEN * id as u64;
+        let buf = slice(
+            self.storage.as_ref(),
+            offset,
+            ENTRY_LEN,
+            "node table out of bounds",
+        )?;
+
+        Ok(Entry::read(buf.try_into().unwrap()))
+    }
+
+    fn name(&self, entry: &Entry) -> std::io::Result<String> {
+        let len = entry.name_len as u64;
+        let buf = slice(
+            self.storage.as_ref(),
+            entry.name_offset,
+            len,
+            "node name out of bounds",
+        )?;
+
+        std::str::from_utf8(buf)
+            .map(String::from)
+            .map_err(|_| invalid("node name is not utf-8"))
+    }
+
+    fn list(&self, offset: u64, len: u32) -> std::io::Result<Vec<u32>> {
+        let buf = slice(
+            self.storage.as_ref(),
+            offset,
+            4 * len as u64,
+            "adjacency list out of bounds",
+        )?;
+
+        buf.chunks_exact(4)
+            .map(|id| u32::from_le_bytes(id.try_into().unwrap()))
+            .map(|id| match (id as u64) < self.len {
+                true => Ok(id),
+                false => Err(invalid("node id out of bounds")),
+            })
+            .collect()
+    }
+}
+
+impl Entry {
+    fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend(self.name_offset.to_le_bytes());
+        buf.extend(self.name_len.to_le_bytes());
+        buf.extend(self.dependencies_offset.to_le_bytes());
+        buf.extend(self.dependencies_len.to_le_bytes());
+        buf.extend(self.dependents_offset.to_le_bytes());
+        buf.extend(self.dependents_len.to_le_bytes());
+    }
+
+    fn read(buf: &[u8; ENTRY_LEN as usize]) -> Self {
+        let u64_at = |i: usize| u64::from_le_bytes(buf[i..i + 8].try_into().unwrap());
+        let u32_at = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+
+        Self {
+            name_offset: u64_at(0),
+            name_len: u32_at(8),
+            dependencies_offset: u64_at(12),
+            dependencies_len: u32_at(20),
+            dependents_offset: u64_at(24),
+            dependents_len: u32_at(32),
+        }
+    }
+}
+
+/// Returns len bytes of storage starting at offset, failing with msg
+/// unless they lie within storage
+fn slice<'a>(storage: &'a [u8], offset: u64, len: u64, msg: &str) -> std::io::Result<&'a [u8]> {
+    let end = offset.checked_add(len).ok_or_else(|| invalid(msg))?;
+    let range = (usize::try_from(offset).ok(), usize::try_from(end).ok());
+    match range {
+        (Some(start), Some(end)) => storage.get(start..end).ok_or_else(|| invalid(msg)),
+        _ => Err(invalid(msg)),
+    }
+}
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_graph() {
+        let mut g = Graph::new();
+        g.depend("stardust", "bigbang").unwrap();
+        g.depend("star", "stardust").unwrap();
+        g.depend("planet", "star").unwrap();
+        g.depend("moon", "planet").unwrap();
+        g.depend("life", "planet").unwrap();
+        g.depend("life", "star").unwrap();
+
+        let mut buf = Vec::new();
+        DiskGraph::write(&g, &mut buf).unwrap();
+
+        let disk = DiskGraph::new(buf).unwrap();
+        assert_eq!(disk.len(), 6);
+        assert!(disk.contains("moon").unwrap());
+        assert!(!disk.contains("sun").unwrap());
+
+        assert!(disk.depends_on_directly("life", "star").unwrap());
+        assert!(!disk.depends_on_directly("life", "bigbang").unwrap());
+        assert!(disk.depends_on("life", "bigbang").unwrap());
+        assert!(!disk.depends_on("bigbang", "life").unwrap());
+        assert!(!disk.depends_on("sun", "life").unwrap());
+
+        for node in ["bigbang", "star", "planet", "life"] {
+            assert_eq!(disk.dependencies(node).unwrap(), {
+                g.dependencies(&node)
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<HashSet<_>>()
+            });
+            assert_eq!(disk.dependents(node).unwrap(), {
+                g.dependents(&node)
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<HashSet<_>>()
+            });
+        }
+
+        DiskGraph::new(b"NOTSOYDEP".repeat(2)).expect_err("bad magic");
+        DiskGraph::new(&MAGIC[..]).expect_err("truncated header");
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_disk_graph_file() {
+        let path = std::env::temp_dir().join(format!("soydepend-{}.graph", std::process::id()));
+
+        let mut g = Graph::new();
+        g.depend("b".to_string(), "a".to_string()).unwrap();
+        DiskGraph::write(&g, std::fs::File::create(&path).unwrap()).unwrap();
+
+        let disk = DiskGraph::open(&path).unwrap();
+        assert!(disk.depends_on("b", "a").unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_disk_graph_corrupted() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+
+        let mut valid = Vec::new();
+        DiskGraph::write(&g, &mut valid).unwrap();
+
+        let corrupt = |at: usize, bytes: &[u8]| {
+            let mut buf = valid.clone();
+            buf[at..at + bytes.len()].copy_from_slice(bytes);
+            DiskGraph::new(buf)
+        };
+        let kind = |result: std::io::Result<bool>| result.unwrap_err().kind();
+        let entry = |id: usize, field: usize| (HEADER_LEN + ENTRY_LEN * id as u64) as usize + field;
+
+        // Node count larger than the table
+        let err = corrupt(8, &u64::MAX.to_le_bytes()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let err = corrupt(8, &3u64.to_le_bytes()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // Huge name length of a
+        let disk = corrupt(entry(0, 8), &u32::MAX.to_le_bytes()).unwrap();
+        assert_eq!(kind(disk.contains("a")), std::io::ErrorKind::InvalidData);
+
+        // Name offset of a past the end
+        let disk = corrupt(entry(0, 0), &u64::MAX.to_le_bytes()).unwrap();
+        assert_eq!(kind(disk.contains("a")), std::io::ErrorKind::InvalidData);
+
+        // Huge dependency list of b
+        let disk = corrupt(entry(1, 20), &u32::MAX.to_le_bytes()).unwrap();
+        assert_eq!(
+            kind(disk.depends_on("b", "a")),
+            std::io::ErrorKind::InvalidData
+        );
+
+        // Dependency of b pointing at a missing node
+        let list = valid.len() - 4;
+        let disk = corrupt(list, &7u32.to_le_bytes()).unwrap();
+        assert_eq!(
+            kind(disk.depends_on("b", "a")),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+}
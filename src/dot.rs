@@ -0,0 +1,139 @@
+use std::fmt::{Display, Write};
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Display,
+{
+    /// Returns the graph in Graphviz DOT format, with edges pointing
+    /// from dependents to dependencies. Nodes and edges are sorted
+    /// by their display form, so equal graphs give equal output.
+    pub fn to_dot(&self) -> String {
+        let mut nodes: Vec<String> = self.nodes.iter().map(|n| n.to_string()).collect();
+        nodes.sort();
+
+        let mut edges: Vec<(String, String)> = self
+            .dependencies
+            .iter()
+            .flat_map(|(dependent, dependencies)| {
+                dependencies
+                    .iter()
+                    .map(move |dependency| (dependent.to_string(), dependency.to_string()))
+            })
+            .collect();
+        edges.sort();
+
+        let mut dot = String::from("digraph dependencies {\n");
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=box, style=rounded, fontname=\"Helvetica\"];\n");
+
+        for node in &nodes {
+            let _ = writeln!(dot, "    {};", quote(node));
+        }
+
+        for (dependent, dependency) in &edges {
+            let _ = writeln!(dot, "    {} -> {};", quote(dependent), quote(dependency));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph to a PNG image at path with Graphviz `dot`,
+    /// which must be on PATH
+    #[cfg(feature = "graphviz")]
+    pub fn render_png<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        render(&self.to_dot(), "png", path.as_ref())
+    }
+
+    /// Renders the graph to an SVG image at path with Graphviz `dot`,
+    /// which must be on PATH
+    #[cfg(feature = "graphviz")]
+    pub fn render_svg<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        render(&self.to_dot(), "svg", path.as_ref())
+    }
+}
+
+pub(crate) fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Pipes dot source to `dot`, failing with its stderr if it exits unsuccessfully
+#[cfg(feature = "graphviz")]
+fn render(dot: &str, format: &str, path: &std::path::Path) -> std::io::Result<()> {
+    use std::io::Write as _;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("dot")
+        .arg(format!("-T{format}"))
+        .arg("-o")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(dot.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "dot exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_dot() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "say \"hi\"").unwrap();
+
+        assert_eq!(
+            g.to_dot(),
+            "\
+digraph dependencies {
+    rankdir=LR;
+    node [shape=box, style=rounded, fontname=\"Helvetica\"];
+    \"app\";
+    \"http\";
+    \"say \\\"hi\\\"\";
+    \"app\" -> \"http\";
+    \"app\" -> \"say \\\"hi\\\"\";
+}
+"
+        );
+    }
+
+    #[cfg(feature = "graphviz")]
+    #[test]
+    fn test_render() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("soydepend-render-{}.svg", std::process::id()));
+        match g.render_svg(&path) {
+            Ok(()) => {
+                let svg = std::fs::read_to_string(&path).unwrap();
+                assert!(svg.contains("<svg"));
+                std::fs::remove_file(&path).unwrap();
+            }
+            // Graphviz is not installed
+            Err(err) => assert_eq!(err.kind(), std::io::ErrorKind::NotFound),
+        }
+    }
+}
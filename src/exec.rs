@@ -0,0 +1,721 @@
+mod cache;
+#[cfg(feature = "fs")]
+mod files;
+#[cfg(feature = "async")]
+mod future;
+mod incremental;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "watch")]
+mod watch;
+
+pub use cache::Cache;
+#[cfg(feature = "fs")]
+pub use files::{Detect, FileError, FileTracker};
+#[cfg(feature = "watch")]
+pub use watch::{Event, Watcher};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::Graph;
+
+/// Failure of a node during execution
+#[derive(Debug)]
+pub struct Failure<T, E> {
+    pub node: T,
+    pub error: E,
+}
+
+/// What executors do with the rest of the graph after a node fails
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Policy {
+    /// Starts no new nodes after the first failure
+    #[default]
+    FailFast,
+    /// Skips deep dependents of failed nodes, and runs everything else
+    SkipDependents,
+    /// Runs every node that does not depend on failed nodes the way
+    /// [`Graph::is_dependend`] sees it: weak dependents of failed or skipped nodes
+    /// still run, as if their weak dependencies had succeeded.
+    /// Failures are reported once the run ends.
+    ContinueIndependent,
+}
+
+/// How many times a failed node is retried before it is considered failed
+#[derive(Clone, Debug, Default)]
+pub struct Retry {
+    /// Number of retries after the first attempt
+    pub retries: usize,
+    /// Delay before the first retry, doubled for every subsequent retry
+    pub backoff: Duration,
+}
+
+/// Handle for aborting an execution from elsewhere, e.g. another thread.
+///
+/// Clones share the same state, so a clone can be kept
+/// while the original is passed to the executor via [`Options`].
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Receives lifecycle events of nodes during execution,
+/// e.g. for rendering progress. All methods default to doing nothing.
+///
+/// Parallel executors may call the observer from multiple threads.
+pub trait Observer<T>: Send + Sync {
+    /// Called before every attempt to run node, starting from 1
+    fn on_start(&self, _node: &T, _attempt: usize) {}
+
+    /// Called once node has finished for good, after retries if any
+    fn on_finish(&self, _node: &T, _succeeded: bool) {}
+
+    /// Called when node will not be run, either because of a failure
+    /// or because the execution was cancelled
+    fn on_skip(&self, _node: &T) {}
+}
+
+/// Execution options shared by all executors
+#[derive(Clone)]
+pub struct Options<T> {
+    pub policy: Policy,
+    pub retry: Retry,
+    pub cancel: CancelToken,
+    pub observer: Option<Arc<dyn Observer<T>>>,
+    /// Restricts execution to these nodes, assuming the rest are already done.
+    /// Nodes outside of the set are left out of the report.
+    pub only: Option<HashSet<T>>,
+}
+
+/// Final status of a node after execution
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status<E> {
+    Succeeded,
+    Failed(E),
+    Skipped,
+    /// Not started because the execution was cancelled
+    Cancelled,
+}
+
+/// Statuses of every node in the graph after execution
+#[derive(Debug)]
+pub struct Report<T, E> {
+    pub statuses: HashMap<T, Status<E>>,
+    /// Number of attempts made for every node that was run
+    pub attempts: HashMap<T, usize>,
+}
+
+/// Internal ready-set bookkeeping shared by the executors.
+///
+/// A node becomes ready once all of its direct dependencies are completed.
+pub(crate) struct Scheduler<'a, T, E>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    graph: &'a Graph<T>,
+    options: Options<T>,
+    pending: HashMap<T, usize>,
+    ready: VecDeque<T>,
+    report: Report<T, E>,
+    halted: bool,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Runs f on every node in dependency order, i.e. a node is only visited
+    /// after all of its dependencies had succeeded.
+    ///
+    /// Execution stops at the first failure, leaving the remaining nodes unvisited.
+    pub fn execute<E, F>(&self, f: F) -> Result<(), Failure<T, E>>
+    where
+        F: FnMut(&T) -> Result<(), E>,
+    {
+        match self.execute_with(&Options::default(), f).into_failure() {
+            Some(failure) => Err(failure),
+            None => Ok(()),
+        }
+    }
+
+    /// Like [`Graph::execute`], but handles failures according to options
+    /// and reports the status of every node
+    pub fn execute_with<E, F>(&self, options: &Options<T>, mut f: F) -> Report<T, E>
+    where
+        F: FnMut(&T) -> Result<(), E>,
+    {
+        let mut scheduler = Scheduler::new(self, options);
+
+        while let Some(node) = scheduler.next() {
+            let (result, attempts) = run(options, &node, &mut f);
+            scheduler.finish(node, result, attempts);
+        }
+
+        scheduler.into_report()
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops executors from starting new nodes.
+    /// Nodes already in flight are allowed to finish without further retries.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> Options<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    /// Returns whether node is part of the execution
+    pub(crate) fn includes(&self, node: &T) -> bool {
+        self.only.as_ref().is_none_or(|only| only.contains(node))
+    }
+}
+
+impl<T> Options<T> {
+    pub(crate) fn observe<F>(&self, event: F)
+    where
+        F: FnOnce(&dyn Observer<T>),
+    {
+        if let Some(observer) = &self.observer {
+            event(observer.as_ref());
+        }
+    }
+}
+
+impl<T> Default for Options<T> {
+    fn default() -> Self {
+        Self {
+            policy: Policy::default(),
+            retry: Retry::default(),
+            cancel: CancelToken::default(),
+            observer: None,
+            only: None,
+        }
+    }
+}
+
+impl Retry {
+    /// Returns whether another attempt is allowed after the given number of attempts
+    pub(crate) fn allows(&self, attempts: usize) -> bool {
+        attempts <= self.retries
+    }
+
+    /// Returns the delay to wait after the given number of failed attempts
+    pub(crate) fn delay(&self, attempts: usize) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(u32::MAX as usize) as u32;
+        self.backoff.saturating_mul(2u32.saturating_pow(exponent))
+    }
+}
+
+impl<T, E> Report<T, E>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns whether every node succeeded
+    pub fn is_success(&self) -> bool {
+        self.statuses
+            .values()
+            .all(|status| matches!(status, Status::Succeeded))
+    }
+
+    pub fn status(&self, node: &T) -> Option<&Status<E>> {
+        self.statuses.get(node)
+    }
+
+    /// Returns the number of attempts made for node, 0 if it never ran
+    pub fn attempts(&self, node: &T) -> usize {
+        self.attempts.get(node).copied().unwrap_or(0)
+    }
+
+    pub fn succeeded(&self) -> HashSet<T> {
+        self.collect(|status| matches!(status, Status::Succeeded))
+    }
+
+    pub fn failed(&self) -> HashSet<T> {
+        self.collect(|status| matches!(status, Status::Failed(_)))
+    }
+
+    pub fn skipped(&self) -> HashSet<T> {
+        self.collect(|status| matches!(status, Status::Skipped))
+    }
+
+    pub fn cancelled(&self) -> HashSet<T> {
+        self.collect(|status| matches!(status, Status::Cancelled))
+    }
+
+    fn collect<P>(&self, predicate: P) -> HashSet<T>
+    where
+        P: Fn(&Status<E>) -> bool,
+    {
+        self.statuses
+            .iter()
+            .filter(|(_, status)| predicate(status))
+            .map(|(node, _)| node.clone())
+            .collect()
+    }
+
+    /// Returns any one of the failures
+    pub(crate) fn into_failure(self) -> Option<Failure<T, E>> {
+        self.statuses
+            .into_iter()
+            .find_map(|(node, status)| match status {
+                Status::Failed(error) => Some(Failure { node, error }),
+                _ => None,
+            })
+    }
+}
+
+impl<'a, T, E> Scheduler<'a, T, E>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub(crate) fn new(graph: &'a Graph<T>, options: &Options<T>) -> Self {
+        let mut pending = HashMap::new();
+        let mut ready = VecDeque::new();
+
+        for node in graph.nodes.iter().filter(|n| options.includes(n)) {
+            let count = graph.dependencies.get(node).map_or(0, |deps| {
+                deps.iter().filter(|d| options.includes(d)).count()
+            });
+
+            match count {
+                0 => ready.push_back(node.clone()),
+                _ => {
+                    pending.insert(node.clone(), count);
+                }
+            }
+        }
+
+        Self {
+            graph,
+            options: options.clone(),
+            pending,
+            ready,
+            report: Report {
+                statuses: HashMap::new(),
+                attempts: HashMap::new(),
+            },
+            halted: false,
+        }
+    }
+
+    /// Pops the next node whose dependencies are all completed
+    pub(crate) fn next(&mut self) -> Option<T> {
+        if self.halted || self.options.cancel.is_cancelled() {
+            return None;
+        }
+
+        while let Some(node) = self.ready.pop_front() {
+            if !self.report.statuses.contains_key(&node) {
+                return Some(node);
+            }
+        }
+
+        None
+    }
+
+    /// Records the result of node, possibly making its dependents ready
+    /// or skipped depending on the policy
+    pub(crate) fn finish(&mut self, node: T, result: Result<(), E>, attempts: usize) {
+        self.report.attempts.insert(node.clone(), attempts);
+        self.options
+            .observe(|observer| observer.on_finish(&node, result.is_ok()));
+
+        match result {
+            Ok(()) => {
+                self.complete(&node);
+                self.report.statuses.insert(node, Status::Succeeded);
+            }
+
+            Err(error) => {
+                match self.options.policy {
+                    Policy::FailFast => self.halted = true,
+                    Policy::SkipDependents => self.skip(self.graph.dependents(&node)),
+                    Policy::ContinueIndependent => self.skip_strong_dependents(&node),
+                }
+
+                self.report.statuses.insert(node, Status::Failed(error));
+            }
+        }
+    }
+
+    /// Marks every node without a status as skipped, or cancelled
+    /// if the execution was cancelled
+    pub(crate) fn into_report(mut self) -> Report<T, E> {
+        let unvisited = match self.options.cancel.is_cancelled() {
+            true => || Status::Cancelled,
+            false => || Status::Skipped,
+        };
+
+        for node in self.graph.nodes.iter().filter(|n| self.options.includes(n)) {
+            if !self.report.statuses.contains_key(node) {
+                self.options.observe(|observer| observer.on_skip(node));
+                self.report.statuses.insert(node.clone(), unvisited());
+            }
+        }
+
+        self.report
+    }
+
+    fn complete(&mut self, node: &T) {
+        let Some(dependents) = self.graph.dependents.get(node) else {
+            return;
+        };
+
+        for dependent in dependents {
+            self.release(dependent);
+        }
+    }
+
+    /// Counts one more dependency of node as completed
+    fn release(&mut self, node: &T) {
+        // Skipped nodes are no longer pending
+        let Some(remaining) = self.pending.get_mut(node) else {
+            return;
+        };
+
+        *remaining -= 1;
+        if *remaining == 0 {
+            self.pending.remove(node);
+            self.ready.push_back(node.clone());
+        }
+    }
+
+    /// Skips deep dependents of failed node through strong edges,
+    /// releasing the nodes that only depend weakly on failed or skipped ones
+    fn skip_strong_dependents(&mut self, failed: &T) {
+        let graph = self.graph;
+        let mut q = vec![failed];
+
+        while let Some(current) = q.pop() {
+            for dependent in graph.dependents.get(current).into_iter().flatten() {
+                if self.report.statuses.contains_key(dependent) {
+                    continue;
+                }
+
+                if graph.is_weak(dependent, current) {
+                    self.release(dependent);
+                } else if self.options.includes(dependent) {
+                    self.skip(HashSet::from([dependent.clone()]));
+                    q.push(dependent);
+                }
+            }
+        }
+    }
+
+    fn skip(&mut self, nodes: HashSet<T>) {
+        for node in nodes {
+            if self.report.statuses.contains_key(&node) || !self.options.includes(&node) {
+                continue;
+            }
+
+            self.pending.remove(&node);
+            self.options.observe(|observer| observer.on_skip(&node));
+            self.report.statuses.insert(node, Status::Skipped);
+        }
+    }
+}
+
+/// Calls f on node until it succeeds, retries run out or execution is cancelled,
+/// returning the last result and the number of attempts
+fn run<T, E, F>(options: &Options<T>, node: &T, mut f: F) -> (Result<(), E>, usize)
+where
+    F: FnMut(&T) -> Result<(), E>,
+{
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        options.observe(|observer| observer.on_start(node, attempts));
+
+        match f(node) {
+            Err(_) if options.retry.allows(attempts) && !options.cancel.is_cancelled() => {
+                std::thread::sleep(options.retry.delay(attempts))
+            }
+            result => return (result, attempts),
+        }
+    }
+}
+
+pub(crate) const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a over bytes, stable across runs and toolchains unlike the std hashers
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+impl<T, E> std::fmt::Display for Failure<T, E>
+where
+    T: std::fmt::Debug,
+    E: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "node {:?} failed: {}", self.node, self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    macro_rules! set {
+        ($($x:expr),*) => {
+            HashSet::from([$($x), *])
+        };
+    }
+
+    fn diamonds() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "a").unwrap();
+        g.depend("d", "b").unwrap();
+        g.depend("d", "c").unwrap();
+        g.depend("y", "x").unwrap();
+
+        g
+    }
+
+    #[test]
+    fn test_execute() {
+        let g = diamonds();
+
+        let mut done = Vec::new();
+        g.execute(|node| -> Result<(), ()> {
+            g.dependencies(node)
+                .iter()
+                .for_each(|dependency| assert!(done.contains(dependency)));
+
+            done.push(*node);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(done.len(), 6);
+
+        let mut done = HashSet::new();
+        let failure = g
+            .execute(|node| {
+                if *node == "b" {
+                    return Err("b failed");
+                }
+
+                done.insert(*node);
+                Ok(())
+            })
+            .expect_err("b should fail");
+
+        assert_eq!(failure.node, "b");
+        assert_eq!(failure.error, "b failed");
+        assert!(done.contains(&"a"));
+        assert!(!done.contains(&"d"));
+    }
+
+    #[test]
+    fn test_execute_with_policy() {
+        let g = diamonds();
+        let fail_b = |node: &&str| match *node {
+            "b" => Err("b failed"),
+            _ => Ok(()),
+        };
+
+        let options = Options {
+            policy: Policy::SkipDependents,
+            ..Default::default()
+        };
+        let report = g.execute_with(&options, fail_b);
+        assert!(!report.is_success());
+        assert_eq!(report.failed(), set!["b"]);
+        assert_eq!(report.skipped(), set!["d"]);
+        assert_eq!(report.succeeded(), set!["a", "c", "x", "y"]);
+        assert_eq!(report.status(&"b"), Some(&Status::Failed("b failed")));
+
+        let options = Options {
+            policy: Policy::ContinueIndependent,
+            ..Default::default()
+        };
+        let report = g.execute_with(&options, fail_b);
+        assert_eq!(report.failed(), set!["b"]);
+        assert_eq!(report.skipped(), set!["d"]);
+        assert_eq!(report.succeeded(), set!["a", "c", "x", "y"]);
+
+        let report = g.execute_with(&Options::default(), fail_b);
+        assert_eq!(report.failed(), set!["b"]);
+        assert!(report.skipped().contains(&"d"));
+        assert_eq!(report.statuses.len(), 6);
+
+        let report = g.execute_with(&Options::default(), |_| Ok::<(), ()>(()));
+        assert!(report.is_success());
+        assert!(report.skipped().is_empty());
+
+        // Weak dependents of failed and skipped nodes only run with ContinueIndependent
+        let mut g = diamonds();
+        g.depend_weak("plugin", "b").unwrap();
+        g.depend_weak("theme", "d").unwrap();
+        g.depend("skin", "theme").unwrap();
+
+        let options = Options {
+            policy: Policy::ContinueIndependent,
+            ..Default::default()
+        };
+        let report = g.execute_with(&options, fail_b);
+        assert_eq!(report.failed(), set!["b"]);
+        assert_eq!(report.skipped(), set!["d"]);
+        assert_eq!(
+            report.succeeded(),
+            set!["a", "c", "x", "y", "plugin", "theme", "skin"]
+        );
+
+        let options = Options {
+            policy: Policy::SkipDependents,
+            ..Default::default()
+        };
+        let report = g.execute_with(&options, fail_b);
+        assert_eq!(report.skipped(), set!["d", "plugin", "theme", "skin"]);
+    }
+
+    #[test]
+    fn test_execute_with_retry() {
+        let g = diamonds();
+        let options = Options {
+            policy: Policy::SkipDependents,
+            retry: Retry {
+                retries: 2,
+                backoff: Duration::from_millis(1),
+            },
+            ..Default::default()
+        };
+
+        // b is flaky and only succeeds on its third attempt,
+        // while c never succeeds
+        let mut calls = HashMap::new();
+        let report = g.execute_with(&options, |node| {
+            let count = calls.entry(*node).or_insert(0);
+            *count += 1;
+
+            match (*node, *count) {
+                ("b", 3) => Ok(()),
+                ("b", _) | ("c", _) => Err(*count),
+                _ => Ok(()),
+            }
+        });
+
+        assert_eq!(report.status(&"b"), Some(&Status::Succeeded));
+        assert_eq!(report.attempts(&"b"), 3);
+        assert_eq!(report.status(&"c"), Some(&Status::Failed(3)));
+        assert_eq!(report.attempts(&"c"), 3);
+        assert_eq!(report.attempts(&"a"), 1);
+        assert_eq!(report.status(&"d"), Some(&Status::Skipped));
+        assert_eq!(report.attempts(&"d"), 0);
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let retry = Retry {
+            retries: 3,
+            backoff: Duration::from_millis(10),
+        };
+
+        assert!(retry.allows(3));
+        assert!(!retry.allows(4));
+        assert_eq!(retry.delay(1), Duration::from_millis(10));
+        assert_eq!(retry.delay(2), Duration::from_millis(20));
+        assert_eq!(retry.delay(3), Duration::from_millis(40));
+    }
+
+    #[test]
+    fn test_execute_with_cancel() {
+        let g = diamonds();
+        let options = Options::default();
+
+        let mut done = HashSet::new();
+        let report = g.execute_with(&options, |node| -> Result<(), ()> {
+            // Cancel from within the first node
+            options.cancel.cancel();
+            done.insert(*node);
+            Ok(())
+        });
+
+        assert_eq!(done.len(), 1);
+        assert_eq!(report.succeeded(), done);
+        assert_eq!(report.cancelled().len(), 5);
+        assert!(report.skipped().is_empty());
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl Observer<&'static str> for Recorder {
+        fn on_start(&self, node: &&'static str, attempt: usize) {
+            self.record(format!("start {node} {attempt}"));
+        }
+
+        fn on_finish(&self, node: &&'static str, succeeded: bool) {
+            self.record(format!("finish {node} {succeeded}"));
+        }
+
+        fn on_skip(&self, node: &&'static str) {
+            self.record(format!("skip {node}"));
+        }
+    }
+
+    impl Recorder {
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[test]
+    fn test_execute_with_observer() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+
+        let recorder = Arc::new(Recorder::default());
+        let options = Options {
+            policy: Policy::SkipDependents,
+            retry: Retry {
+                retries: 1,
+                backoff: Duration::ZERO,
+            },
+            observer: Some(recorder.clone()),
+            ..Default::default()
+        };
+
+        g.execute_with(&options, |node| match *node {
+            "b" => Err(()),
+            _ => Ok(()),
+        });
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![
+                "start a 1",
+                "finish a true",
+                "start b 1",
+                "start b 2",
+                "finish b false",
+                "skip c",
+            ],
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+use super::{fnv1a, Options, Report, FNV_OFFSET};
+use crate::Graph;
+
+/// Remembers which nodes had succeeded with which inputs,
+/// so that unchanged nodes can be skipped by [`Graph::execute_cached`].
+///
+/// A node's key combines its own fingerprint with the keys of its
+/// direct dependencies, so a change anywhere below a node invalidates it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cache<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    keys: HashMap<T, u64>,
+}
+
+impl<T> Cache<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            keys: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, node: &T) -> bool {
+        self.keys.contains_key(node)
+    }
+
+    /// Forgets node, forcing it to run again
+    pub fn invalidate(&mut self, node: &T) {
+        self.keys.remove(node);
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Writes the cache as lines of hex key and node
+    pub fn write_to<W>(&self, mut w: W) -> std::io::Result<()>
+    where
+        W: Write,
+        T: std::fmt::Display,
+    {
+        for (node, key) in &self.keys {
+            writeln!(w, "{key:016x} {node}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a cache previously written with [`Cache::write_to`]
+    pub fn read_from<R>(r: R) -> std::io::Result<Self>
+    where
+        R: BufRead,
+        T: std::str::FromStr,
+    {
+        let invalid = |line: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("bad cache line: {line}"),
+            )
+        };
+
+        let mut keys = HashMap::new();
+        for line in r.lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, node) = line.split_once(' ').ok_or_else(|| invalid(&line))?;
+            let key = u64::from_str_radix(key, 16).map_err(|_| invalid(&line))?;
+            let node = node.parse().map_err(|_| invalid(&line))?;
+
+            keys.insert(node, key);
+        }
+
+        Ok(Self { keys })
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Like [`Graph::execute_with`], but skips nodes whose fingerprint and
+    /// dependency fingerprints are unchanged since they last succeeded.
+    ///
+    /// Up-to-date nodes are left out of the report,
+    /// and the cache is updated with the outcome of this run.
+    pub fn execute_cached<E, P, F>(
+        &self,
+        cache: &mut Cache<T>,
+        options: &Options<T>,
+        fingerprint: P,
+        f: F,
+    ) -> Report<T, E>
+    where
+        P: Fn(&T) -> u64,
+        F: FnMut(&T) -> Result<(), E>,
+    {
+        let keys = self.cache_keys(fingerprint);
+        let stale: HashSet<T> = keys
+            .iter()
+            .filter(|(node, key)| cache.keys.get(*node) != Some(*key))
+            .filter(|(node, _)| options.includes(node))
+            .map(|(node, _)| node.clone())
+            .collect();
+
+        let options = Options {
+            only: Some(stale),
+            ..options.clone()
+        };
+
+        let report = self.execute_with(&options, f);
+
+        // Drop nodes no longer in the graph
+        cache.keys.retain(|node, _| self.contains(node));
+        for node in report.statuses.keys() {
+            cache.keys.remove(node);
+        }
+
+        for node in report.succeeded() {
+            cache.keys.insert(node.clone(), keys[&node]);
+        }
+
+        report
+    }
+
+    /// Computes cache keys of every node in dependency order
+    fn cache_keys<P>(&self, fingerprint: P) -> HashMap<T, u64>
+    where
+        P: Fn(&T) -> u64,
+    {
+        let mut keys = HashMap::new();
+
+        for layer in self.layers() {
+            for node in layer {
+                let mut dependencies: Vec<u64> = self
+                    .dependencies
+                    .get(&node)
+                    .into_iter()
+                    .flatten()
+                    .map(|dependency| keys[dependency])
+                    .collect();
+
+                // Independent of iteration order
+                dependencies.sort_unstable();
+
+                let key = dependencies
+                    .into_iter()
+                    .fold(mix(FNV_OFFSET, fingerprint(&node)), mix);
+
+                keys.insert(node, key);
+            }
+        }
+
+        keys
+    }
+}
+
+fn mix(hash: u64, value: u64) -> u64 {
+    fnv1a(hash, &value.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::exec::Status;
+
+    #[test]
+    fn test_execute_cached() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("y", "x").unwrap();
+
+        let mut inputs = HashMap::from([("a", 1), ("b", 2), ("c", 3), ("x", 4), ("y", 5)]);
+        let mut cache = Cache::new();
+        let options = Options::default();
+
+        let mut ran = Vec::new();
+        let report = g.execute_cached(
+            &mut cache,
+            &options,
+            |node| inputs[node],
+            |node| {
+                ran.push(*node);
+                Ok::<(), ()>(())
+            },
+        );
+        assert!(report.is_success());
+        assert_eq!(ran.len(), 5);
+        assert_eq!(cache.len(), 5);
+
+        // Nothing changed
+        let mut ran = Vec::new();
+        let report = g.execute_cached(
+            &mut cache,
+            &options,
+            |node| inputs[node],
+            |node| {
+                ran.push(*node);
+                Ok::<(), ()>(())
+            },
+        );
+        assert!(ran.is_empty());
+        assert!(report.statuses.is_empty());
+
+        // Changing b invalidates b and c
+        inputs.insert("b", 20);
+        let mut ran = Vec::new();
+        let report = g.execute_cached(
+            &mut cache,
+            &options,
+            |node| inputs[node],
+            |node| {
+                ran.push(*node);
+                match *node {
+                    "c" => Err(()),
+                    _ => Ok(()),
+                }
+            },
+        );
+        assert_eq!(ran, vec!["b", "c"]);
+        assert_eq!(report.status(&"c"), Some(&Status::Failed(())));
+        assert!(!cache.contains(&"c"));
+
+        // Only the failed node runs again
+        let mut ran = Vec::new();
+        g.execute_cached(
+            &mut cache,
+            &options,
+            |node| inputs[node],
+            |node| {
+                ran.push(*node);
+                Ok::<(), ()>(())
+            },
+        );
+        assert_eq!(ran, vec!["c"]);
+        assert_eq!(cache.len(), 5);
+    }
+
+    #[test]
+    fn test_cache_persistence() {
+        let mut g = Graph::new();
+        g.depend("b".to_string(), "a".to_string()).unwrap();
+        g.depend("c".to_string(), "b".to_string()).unwrap();
+
+        let mut cache = Cache::new();
+        g.execute_cached(
+            &mut cache,
+            &Options::default(),
+            |node| node.len() as u64,
+            |_| Ok::<(), ()>(()),
+        );
+
+        let mut buf = Vec::new();
+        cache.write_to(&mut buf).unwrap();
+
+        let mut restored = Cache::<String>::read_from(buf.as_slice()).unwrap();
+        assert_eq!(restored, cache);
+
+        restored.invalidate(&"b".to_string());
+        let mut ran = Vec::new();
+        g.execute_cached(
+            &mut restored,
+            &Options::default(),
+            |node| node.len() as u64,
+            |node| {
+                ran.push(node.clone());
+                Ok::<(), ()>(())
+            },
+        );
+        assert_eq!(ran, vec!["b"]);
+
+        Cache::<String>::read_from("not-hex a\n".as_bytes()).expect_err("bad key");
+        Cache::<String>::read_from("missing-node\n".as_bytes()).expect_err("bad line");
+    }
+}
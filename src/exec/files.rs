@@ -0,0 +1,346 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::{fnv1a, FNV_OFFSET};
+use crate::{Error, Graph};
+
+/// How [`FileTracker`] decides whether a file has changed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Detect {
+    /// Compares modification time and size
+    #[default]
+    Modified,
+    /// Compares a hash of file content, reading every tracked file
+    Content,
+}
+
+/// Maps nodes to files, and finds nodes whose files
+/// have changed since they were last recorded.
+///
+/// Typical usage is to call [`FileTracker::mark_dirty`] before
+/// [`Graph::execute_dirty`], then [`FileTracker::record`]
+/// with the nodes that succeeded.
+#[derive(Clone, Debug, Default)]
+pub struct FileTracker<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    detect: Detect,
+    paths: HashMap<T, HashSet<PathBuf>>,
+    stamps: HashMap<PathBuf, Stamp>,
+}
+
+/// Observed state of a file, missing files have no stamp
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Stamp {
+    modified: u128,
+    len: u64,
+    hash: u64,
+}
+
+impl<T> FileTracker<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new(detect: Detect) -> Self {
+        Self {
+            detect,
+            paths: HashMap::new(),
+            stamps: HashMap::new(),
+        }
+    }
+
+    /// Associates path with node. A node may have many paths.
+    pub fn track<P>(&mut self, node: T, path: P)
+    where
+        P: Into<PathBuf>,
+    {
+        self.paths.entry(node).or_default().insert(path.into());
+    }
+
+    pub fn untrack(&mut self, node: &T) {
+        self.paths.remove(node);
+    }
+
+    pub fn paths(&self, node: &T) -> Option<&HashSet<PathBuf>> {
+        self.paths.get(node)
+    }
+
+    /// Returns paths tracked for any node
+    #[cfg(feature = "watch")]
+    pub(crate) fn all_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.paths.values().flatten()
+    }
+
+    /// Returns nodes with any file that differs from its recorded state,
+    /// including files that were never recorded
+    pub fn changed(&self) -> std::io::Result<HashSet<T>> {
+        let mut changed = HashSet::new();
+
+        for (node, paths) in &self.paths {
+            for path in paths {
+                if self.stamps.get(path) != self.stamp(path)?.as_ref() {
+                    changed.insert(node.clone());
+                    break;
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Marks changed nodes as dirty in graph, returning them
+    pub fn mark_dirty(&self, graph: &mut Graph<T>) -> Result<HashSet<T>, FileError> {
+        let changed = self.changed()?;
+        for node in &changed {
+            graph.mark_dirty(node)?;
+        }
+
+        Ok(changed)
+    }
+
+    /// Records the current state of files of nodes,
+    /// usually the ones that had just succeeded
+    pub fn record<'a, I>(&mut self, nodes: I) -> std::io::Result<()>
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        for node in nodes {
+            let Some(paths) = self.paths.get(node) else {
+                continue;
+            };
+
+            for path in paths {
+                match self.stamp(path)? {
+                    Some(stamp) => self.stamps.insert(path.clone(), stamp),
+                    None => self.stamps.remove(path),
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes recorded file states as lines of modification time,
+    /// size, hash and path. Node mappings are not written,
+    /// and are expected to be tracked again on startup.
+    pub fn write_to<W>(&self, mut w: W) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        for (path, stamp) in &self.stamps {
+            writeln!(
+                w,
+                "{} {} {:016x} {}",
+                stamp.modified,
+                stamp.len,
+                stamp.hash,
+                path.display()
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads file states previously written with [`FileTracker::write_to`]
+    pub fn read_from<R>(&mut self, r: R) -> std::io::Result<()>
+    where
+        R: BufRead,
+    {
+        let invalid = |line: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("bad file state line: {line}"),
+            )
+        };
+
+        for line in r.lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.splitn(4, ' ');
+            let mut next = || fields.next().ok_or_else(|| invalid(&line));
+            let (modified, len, hash, path) = (next()?, next()?, next()?, next()?);
+
+            let stamp = Stamp {
+                modified: modified.parse().map_err(|_| invalid(&line))?,
+                len: len.parse().map_err(|_| invalid(&line))?,
+                hash: u64::from_str_radix(hash, 16).map_err(|_| invalid(&line))?,
+            };
+
+            self.stamps.insert(PathBuf::from(path), stamp);
+        }
+
+        Ok(())
+    }
+
+    fn stamp(&self, path: &Path) -> std::io::Result<Option<Stamp>> {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_nanos();
+
+        // Only content is compared in content mode
+        let stamp = match self.detect {
+            Detect::Modified => Stamp {
+                modified,
+                len: metadata.len(),
+                hash: 0,
+            },
+            Detect::Content => Stamp {
+                modified: 0,
+                len: metadata.len(),
+                hash: fnv1a(FNV_OFFSET, &std::fs::read(path)?),
+            },
+        };
+
+        Ok(Some(stamp))
+    }
+}
+
+/// Error from [`FileTracker::mark_dirty`]
+#[derive(Debug)]
+pub enum FileError {
+    Io(std::io::Error),
+    Graph(Error),
+}
+
+impl From<std::io::Error> for FileError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Drops the nodes carried by err, which file errors have no type for
+impl<T> From<Error<T>> for FileError {
+    fn from(err: Error<T>) -> Self {
+        Self::Graph(err.map(drop))
+    }
+}
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {err}"),
+            Self::Graph(err) => write!(f, "graph error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Graph(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::exec::Options;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("soydepend-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_tracker() {
+        let dir = temp_dir("file-tracker");
+        let (lib, app) = (dir.join("lib.rs"), dir.join("app.rs"));
+        std::fs::write(&lib, "lib").unwrap();
+        std::fs::write(&app, "app").unwrap();
+
+        let mut g = Graph::new();
+        g.depend("app", "lib").unwrap();
+        g.depend("test", "app").unwrap();
+
+        let mut tracker = FileTracker::new(Detect::Content);
+        tracker.track("lib", &lib);
+        tracker.track("app", &app);
+
+        // Never recorded
+        assert_eq!(tracker.changed().unwrap(), HashSet::from(["lib", "app"]));
+        tracker.mark_dirty(&mut g).unwrap();
+
+        let report = g.execute_dirty(&Options::default(), |_| Ok::<(), ()>(()));
+        tracker.record(&report.succeeded()).unwrap();
+        assert!(tracker.changed().unwrap().is_empty());
+
+        // Same length, different content
+        std::fs::write(&app, "ppa").unwrap();
+        assert_eq!(tracker.mark_dirty(&mut g).unwrap(), HashSet::from(["app"]));
+        assert_eq!(g.affected(), HashSet::from(["app", "test"]));
+
+        // Persisted states survive restarts
+        let mut buf = Vec::new();
+        tracker.write_to(&mut buf).unwrap();
+
+        let mut restored = FileTracker::new(Detect::Content);
+        restored.track("lib", &lib);
+        restored.track("app", &app);
+        restored.read_from(buf.as_slice()).unwrap();
+        assert_eq!(restored.changed().unwrap(), HashSet::from(["app"]));
+
+        // Deleted files are changes too
+        std::fs::remove_file(&lib).unwrap();
+        assert_eq!(restored.changed().unwrap(), HashSet::from(["app", "lib"]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_tracker_modified() {
+        let dir = temp_dir("file-tracker-modified");
+        let path = dir.join("a.txt");
+        std::fs::write(&path, "a").unwrap();
+
+        let mut tracker = FileTracker::new(Detect::Modified);
+        tracker.track("a", &path);
+        tracker.record(&["a"]).unwrap();
+        assert!(tracker.changed().unwrap().is_empty());
+
+        std::fs::write(&path, "longer").unwrap();
+        assert_eq!(tracker.changed().unwrap(), HashSet::from(["a"]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_error() {
+        let dir = temp_dir("file-error");
+        let orphan = dir.join("orphan.rs");
+        std::fs::write(&orphan, "orphan").unwrap();
+
+        let mut tracker = FileTracker::new(Detect::Content);
+        tracker.track("orphan", &orphan);
+
+        // Converts into boxed errors, keeping the graph error as its source
+        let dirty = || -> Result<_, Box<dyn std::error::Error>> {
+            Ok(tracker.mark_dirty(&mut Graph::new())?)
+        };
+        let err = dirty().unwrap_err();
+        assert_eq!(err.to_string(), "graph error: no such node");
+        assert_eq!(err.source().unwrap().to_string(), "no such node");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
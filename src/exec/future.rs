@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+
+use super::{Options, Report, Scheduler, Status};
+use crate::Graph;
+
+/// Outcome of a task spawned by the async executor
+enum Outcome<T, E> {
+    /// An attempt to run node finished
+    Finished(T, Result<(), E>),
+    /// The backoff before retrying node passed, holding the error of the last attempt
+    Retry(T, E),
+}
+
+/// A node in flight, holding its permit until it is finished
+struct Flight {
+    attempts: usize,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + 'static,
+{
+    /// Async counterpart of [`Graph::execute`], running at most
+    /// limit futures concurrently as Tokio tasks. It must be awaited
+    /// within a Tokio runtime, with its time driver enabled for retries.
+    ///
+    /// After the first failure no new nodes are started, although futures
+    /// already in flight are driven to completion. Returns the result
+    /// of every node that was started.
+    pub async fn execute_async<E, F, Fut>(&self, limit: usize, f: F) -> HashMap<T, Result<(), E>>
+    where
+        E: Send + 'static,
+        F: FnMut(&T) -> Fut,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        let report = self.execute_async_with(limit, &Options::default(), f).await;
+
+        report
+            .statuses
+            .into_iter()
+            .filter_map(|(node, status)| match status {
+                Status::Succeeded => Some((node, Ok(()))),
+                Status::Failed(error) => Some((node, Err(error))),
+                Status::Skipped | Status::Cancelled => None,
+            })
+            .collect()
+    }
+
+    /// Like [`Graph::execute_async`], but handles failures according to options
+    /// and reports the status of every node
+    pub async fn execute_async_with<E, F, Fut>(
+        &self,
+        limit: usize,
+        options: &Options<T>,
+        mut f: F,
+    ) -> Report<T, E>
+    where
+        E: Send + 'static,
+        F: FnMut(&T) -> Fut,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        let permits = Arc::new(Semaphore::new(limit.max(1)));
+        let mut scheduler = Scheduler::new(self, options);
+        let mut tasks = JoinSet::new();
+        let mut in_flight: HashMap<T, Flight> = HashMap::new();
+
+        let mut start = |tasks: &mut JoinSet<Outcome<T, E>>, node: &T, attempt: usize| {
+            options.observe(|observer| observer.on_start(node, attempt));
+            let (node, fut) = (node.clone(), f(node));
+            tasks.spawn(async move { Outcome::Finished(node, fut.await) });
+        };
+
+        loop {
+            while let Ok(permit) = permits.clone().try_acquire_owned() {
+                let Some(node) = scheduler.next() else {
+                    break;
+                };
+
+                start(&mut tasks, &node, 1);
+                in_flight.insert(
+                    node,
+                    Flight {
+                        attempts: 1,
+                        _permit: permit,
+                    },
+                );
+            }
+
+            let Some(joined) = tasks.join_next().await else {
+                break;
+            };
+
+            // Panics of futures propagate as they would if polled in place
+            let outcome = joined.unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()));
+            let (node, result) = match outcome {
+                Outcome::Finished(node, Err(error))
+                    if options.retry.allows(in_flight[&node].attempts)
+                        && !options.cancel.is_cancelled() =>
+                {
+                    let delay = options.retry.delay(in_flight[&node].attempts);
+                    tasks.spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        Outcome::Retry(node, error)
+                    });
+                    continue;
+                }
+
+                // Cancelled nodes give up retrying
+                Outcome::Retry(node, error) if options.cancel.is_cancelled() => (node, Err(error)),
+                Outcome::Retry(node, _) => {
+                    let flight = in_flight.get_mut(&node).expect("retried node is in flight");
+                    flight.attempts += 1;
+                    start(&mut tasks, &node, flight.attempts);
+                    continue;
+                }
+
+                Outcome::Finished(node, result) => (node, result),
+            };
+
+            let flight = in_flight.remove(&node).expect("finished node is in flight");
+            scheduler.finish(node, result, flight.attempts);
+        }
+
+        scheduler.into_report()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tokio::task::yield_now;
+
+    use crate::exec::{Options, Policy, Retry, Status};
+    use crate::Graph;
+
+    #[tokio::test]
+    async fn test_execute_async() {
+        let mut g = Graph::new();
+        for i in 0..16 {
+            g.depend(format!("mid-{i}"), "root".to_string()).unwrap();
+            g.depend("top".to_string(), format!("mid-{i}")).unwrap();
+        }
+
+        let done = Arc::new(Mutex::new(HashSet::new()));
+        let current = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let results = g
+            .execute_async(4, |node| {
+                let node = node.clone();
+                let dependencies = g.dependencies(&node);
+                let (done, current, peak) = (done.clone(), current.clone(), peak.clone());
+
+                async move {
+                    dependencies
+                        .iter()
+                        .for_each(|dependency| assert!(done.lock().unwrap().contains(dependency)));
+
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    yield_now().await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+
+                    done.lock().unwrap().insert(node);
+                    Ok::<(), ()>(())
+                }
+            })
+            .await;
+
+        assert_eq!(results.len(), 18);
+        assert!(results.values().all(|result| result.is_ok()));
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+
+        let results = g
+            .execute_async(4, |node| {
+                let failed = node == "mid-3";
+                async move {
+                    yield_now().await;
+                    match failed {
+                        true => Err("mid-3 failed"),
+                        false => Ok(()),
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(results.get("mid-3").unwrap(), &Err("mid-3 failed"));
+        assert!(results.get("root").unwrap().is_ok());
+        assert!(!results.contains_key("top"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_async_with_policy() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("y", "x").unwrap();
+
+        let options = Options {
+            policy: Policy::SkipDependents,
+            ..Default::default()
+        };
+        let report = g
+            .execute_async_with(2, &options, |node| {
+                let failed = *node == "b";
+                async move {
+                    yield_now().await;
+                    match failed {
+                        true => Err(()),
+                        false => Ok(()),
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(report.succeeded(), HashSet::from(["a", "x", "y"]));
+        assert_eq!(report.failed(), HashSet::from(["b"]));
+        assert_eq!(report.skipped(), HashSet::from(["c"]));
+    }
+
+    #[tokio::test]
+    async fn test_execute_async_with_retry() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+
+        let options = Options {
+            retry: Retry {
+                retries: 1,
+                backoff: Duration::from_millis(5),
+            },
+            ..Default::default()
+        };
+
+        // a succeeds on retry, b never does
+        let mut calls = HashMap::new();
+        let report = g
+            .execute_async_with(2, &options, |node| {
+                let node = *node;
+                let count = calls.entry(node).or_insert(0);
+                *count += 1;
+                let count = *count;
+
+                async move {
+                    yield_now().await;
+                    match (node, count) {
+                        ("a", 1) | ("b", _) => Err(count),
+                        _ => Ok(()),
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(report.status(&"a"), Some(&Status::Succeeded));
+        assert_eq!(report.attempts(&"a"), 2);
+        assert_eq!(report.status(&"b"), Some(&Status::Failed(2)));
+        assert_eq!(report.attempts(&"b"), 2);
+        assert_eq!(report.status(&"c"), Some(&Status::Skipped));
+    }
+
+    #[tokio::test]
+    async fn test_execute_async_cancel() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "a").unwrap();
+        g.depend("d", "c").unwrap();
+
+        let options = Options {
+            retry: Retry {
+                retries: 10,
+                backoff: Duration::from_millis(1),
+            },
+            ..Default::default()
+        };
+
+        // a keeps failing, and its third attempt cancels the execution
+        let mut calls = 0;
+        let report = g
+            .execute_async_with(2, &options, |_| {
+                calls += 1;
+                if calls == 3 {
+                    options.cancel.cancel();
+                }
+
+                async { Err::<(), ()>(()) }
+            })
+            .await;
+
+        assert_eq!(calls, 3);
+        assert_eq!(report.attempts(&"a"), 3);
+        assert_eq!(report.failed(), HashSet::from(["a"]));
+        assert_eq!(report.cancelled(), HashSet::from(["b", "c", "d"]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_async_concurrent_retries() {
+        let mut g = Graph::new();
+        for i in 0..32 {
+            g.add_node(i);
+        }
+
+        let options = Options {
+            retry: Retry {
+                retries: 1,
+                backoff: Duration::from_millis(5),
+            },
+            ..Default::default()
+        };
+
+        // Every node waits to retry at once
+        let mut calls = HashSet::new();
+        let report = g
+            .execute_async_with(32, &options, |node| {
+                let first = calls.insert(*node);
+                async move {
+                    match first {
+                        true => Err(()),
+                        false => Ok(()),
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(report.succeeded().len(), 32);
+        assert!((0..32).all(|node| report.attempts(&node) == 2));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use super::{Options, Report};
+use crate::{Error, Graph};
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Marks node as changed, so that it and its dependents
+    /// are run by the next [`Graph::execute_dirty`]
+    pub fn mark_dirty(&mut self, node: &T) -> Result<(), Error<T>> {
+        if !self.contains(node) {
+            return Err(Error::NoSuchNode);
+        }
+
+        self.dirty.insert(node.clone());
+
+        Ok(())
+    }
+
+    pub fn is_dirty(&self, node: &T) -> bool {
+        self.dirty.contains(node)
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+
+    /// Returns dirty nodes and their deep dependents
+    pub fn affected(&self) -> HashSet<T> {
+        let mut affected = self.dirty.clone();
+        for node in &self.dirty {
+            affected.extend(self.dependents(node));
+        }
+
+        affected
+    }
+
+    /// Like [`Graph::execute_with`], but only runs affected nodes.
+    ///
+    /// Nodes that succeeded are marked clean, while the rest
+    /// of the affected nodes stay dirty for the next run.
+    pub fn execute_dirty<E, F>(&mut self, options: &Options<T>, f: F) -> Report<T, E>
+    where
+        F: FnMut(&T) -> Result<(), E>,
+    {
+        let affected = self.affected();
+        let options = Options {
+            only: Some(affected.clone()),
+            ..options.clone()
+        };
+
+        let report = self.execute_with(&options, f);
+        let succeeded = report.succeeded();
+        self.dirty = affected.difference(&succeeded).cloned().collect();
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::exec::{Options, Policy, Status};
+    use crate::Graph;
+
+    #[test]
+    fn test_affected() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("y", "x").unwrap();
+
+        assert!(g.affected().is_empty());
+        g.mark_dirty(&"z").expect_err("z is not in the graph");
+
+        g.mark_dirty(&"b").unwrap();
+        assert!(g.is_dirty(&"b"));
+        assert!(!g.is_dirty(&"c"));
+        assert_eq!(g.affected(), HashSet::from(["b", "c"]));
+
+        g.mark_dirty(&"x").unwrap();
+        assert_eq!(g.affected(), HashSet::from(["b", "c", "x", "y"]));
+
+        g.remove(&"y").unwrap();
+        g.remove(&"x").unwrap();
+        assert_eq!(g.affected(), HashSet::from(["b", "c"]));
+
+        g.clear_dirty();
+        assert!(g.affected().is_empty());
+    }
+
+    #[test]
+    fn test_execute_dirty() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("d", "c").unwrap();
+        g.depend("y", "x").unwrap();
+
+        g.mark_dirty(&"b").unwrap();
+
+        let options = Options {
+            policy: Policy::SkipDependents,
+            ..Default::default()
+        };
+
+        // c fails, leaving c and d dirty
+        let mut ran = Vec::new();
+        let report = g.execute_dirty(&options, |node| {
+            ran.push(*node);
+            match *node {
+                "c" => Err(()),
+                _ => Ok(()),
+            }
+        });
+
+        assert_eq!(ran, vec!["b", "c"]);
+        assert_eq!(report.statuses.len(), 3);
+        assert_eq!(report.status(&"d"), Some(&Status::Skipped));
+        assert_eq!(g.affected(), HashSet::from(["c", "d"]));
+
+        let mut ran = Vec::new();
+        let report = g.execute_dirty(&options, |node| {
+            ran.push(*node);
+            Ok::<(), ()>(())
+        });
+
+        assert_eq!(ran, vec!["c", "d"]);
+        assert!(report.is_success());
+        assert!(g.affected().is_empty());
+
+        let report = g.execute_dirty(&options, |_| Err(()));
+        assert!(report.statuses.is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::Mutex;
+
+use super::{run, Failure, Options, Report, Scheduler};
+use crate::Graph;
+
+struct State<'a, T, E>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    scheduler: Scheduler<'a, T, E>,
+    /// Tasks spawned that have yet to pick their node
+    queued: usize,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + Sync,
+{
+    /// Like [`Graph::execute`], but runs independent nodes concurrently
+    /// on the rayon thread pool.
+    ///
+    /// After the first failure no new nodes are started,
+    /// although nodes already in flight are allowed to finish.
+    pub fn execute_parallel<E, F>(&self, f: F) -> Result<(), Failure<T, E>>
+    where
+        E: Send,
+        F: Fn(&T) -> Result<(), E> + Sync,
+    {
+        match self
+            .execute_parallel_with(&Options::default(), f)
+            .into_failure()
+        {
+            Some(failure) => Err(failure),
+            None => Ok(()),
+        }
+    }
+
+    /// Like [`Graph::execute_parallel`], but handles failures according to options
+    /// and reports the status of every node
+    pub fn execute_parallel_with<E, F>(&self, options: &Options<T>, f: F) -> Report<T, E>
+    where
+        E: Send,
+        F: Fn(&T) -> Result<(), E> + Sync,
+    {
+        let state = Mutex::new(State {
+            scheduler: Scheduler::new(self, options),
+            queued: 0,
+        });
+        rayon::scope(|scope| spawn_ready(scope, &state, options, &f));
+
+        state.into_inner().unwrap().scheduler.into_report()
+    }
+}
+
+/// Spawns a task for every ready node, each of which spawns
+/// the nodes its completion made ready.
+///
+/// Tasks pick their node only once they start, so that
+/// nothing new starts after a failure or cancellation.
+fn spawn_ready<'s, 'a: 's, T, E, F>(
+    scope: &rayon::Scope<'s>,
+    state: &'s Mutex<State<'a, T, E>>,
+    options: &'s Options<T>,
+    f: &'s F,
+) where
+    T: Clone + Eq + std::hash::Hash + Send + Sync,
+    E: Send,
+    F: Fn(&T) -> Result<(), E> + Sync,
+{
+    let mut guard = state.lock().unwrap();
+    let spawned = guard.scheduler.ready.len().saturating_sub(guard.queued);
+    guard.queued += spawned;
+    drop(guard);
+
+    for _ in 0..spawned {
+        scope.spawn(move |scope| {
+            let mut guard = state.lock().unwrap();
+            guard.queued -= 1;
+            let Some(node) = guard.scheduler.next() else {
+                return;
+            };
+            drop(guard);
+
+            let (result, attempts) = run(options, &node, f);
+            state
+                .lock()
+                .unwrap()
+                .scheduler
+                .finish(node, result, attempts);
+
+            spawn_ready(scope, state, options, f);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    use crate::exec::{Options, Policy};
+    use crate::Graph;
+
+    fn fan_out() -> Graph<String> {
+        let mut g = Graph::new();
+        for i in 0..32 {
+            g.depend(format!("mid-{i}"), "root".to_string()).unwrap();
+            g.depend("top".to_string(), format!("mid-{i}")).unwrap();
+        }
+
+        g
+    }
+
+    #[test]
+    fn test_execute_parallel() {
+        let g = fan_out();
+
+        let done = Mutex::new(HashSet::new());
+        g.execute_parallel(|node| -> Result<(), ()> {
+            let mut done = done.lock().unwrap();
+            g.dependencies(node)
+                .iter()
+                .for_each(|dependency| assert!(done.contains(dependency)));
+
+            done.insert(node.clone());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(done.lock().unwrap().len(), 34);
+
+        let done = Mutex::new(HashSet::new());
+        let failure = g
+            .execute_parallel(|node| {
+                if node == "mid-7" {
+                    return Err("mid-7 failed");
+                }
+
+                done.lock().unwrap().insert(node.clone());
+                Ok(())
+            })
+            .expect_err("mid-7 should fail");
+
+        assert_eq!(failure.node, "mid-7");
+        assert!(done.lock().unwrap().contains("root"));
+        assert!(!done.lock().unwrap().contains("top"));
+    }
+
+    #[test]
+    fn test_execute_parallel_with_policy() {
+        let mut g = fan_out();
+        g.depend("other".to_string(), "base".to_string()).unwrap();
+
+        let options = Options {
+            policy: Policy::SkipDependents,
+            ..Default::default()
+        };
+        let report = g.execute_parallel_with(&options, |node| match node.as_str() {
+            "mid-7" => Err(()),
+            _ => Ok(()),
+        });
+
+        assert_eq!(report.failed(), HashSet::from(["mid-7".to_string()]));
+        assert_eq!(report.skipped(), HashSet::from(["top".to_string()]));
+        assert_eq!(report.succeeded().len(), 34);
+    }
+
+    #[test]
+    fn test_execute_parallel_cancel() {
+        let g = fan_out();
+        let options = Options::default();
+        let cancel = options.cancel.clone();
+
+        let report = g.execute_parallel_with(&options, |node| -> Result<(), ()> {
+            if node.starts_with("mid-") {
+                cancel.cancel();
+            }
+
+            Ok(())
+        });
+
+        // In-flight mids finish, the rest are cancelled
+        assert!(report.succeeded().contains("root"));
+        assert!(report.cancelled().contains("top"));
+        assert!(report.failed().is_empty());
+        assert!(report.skipped().is_empty());
+        assert_eq!(report.succeeded().len() + report.cancelled().len(), 34);
+    }
+}
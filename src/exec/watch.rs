@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+
+use super::FileTracker;
+use crate::Graph;
+
+/// A tracked node whose files have changed, along with its deep dependents
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    pub node: T,
+    pub dependents: HashSet<T>,
+}
+
+/// Watches files of a [`FileTracker`] for notifications from the operating system,
+/// and streams an [`Event`] for every node whose files changed.
+///
+/// Directories of tracked files are watched rather than the files themselves,
+/// so that files replaced by editors stay watched. Notifications only prompt
+/// the tracker to check its files, so nodes are only reported once their files
+/// differ from what the tracker recorded, and files that were never recorded
+/// are reported right away. The watcher stops when dropped.
+pub struct Watcher<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    events: Receiver<std::io::Result<Event<T>>>,
+    /// Dropping it disconnects the background thread from notifications
+    notify: Option<RecommendedWatcher>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<T> Watcher<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + 'static,
+{
+    /// Starts watching files of tracker, computing dependents from a snapshot of graph
+    pub fn spawn(graph: &Graph<T>, mut tracker: FileTracker<T>) -> std::io::Result<Self> {
+        let (notifications, received) = mpsc::channel();
+        let mut notify = notify::recommended_watcher(notifications).map_err(io_error)?;
+
+        let dirs: HashSet<&Path> = tracker
+            .all_paths()
+            .map(|path| match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            })
+            .collect();
+        for dir in dirs {
+            notify
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(io_error)?;
+        }
+
+        let graph = graph.clone();
+        let (sender, events) = mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            let mut notified = Ok(());
+            loop {
+                let sent = match notified {
+                    Ok(()) => report(&graph, &mut tracker, &sender),
+                    Err(err) => sender.send(Err(io_error(err))).is_ok(),
+                };
+                if !sent {
+                    return;
+                }
+
+                // Disconnected once the notify watcher is dropped
+                let Ok(notification) = received.recv() else {
+                    return;
+                };
+
+                // Changes often come in bursts, which one check covers
+                notified = std::iter::once(notification)
+                    .chain(received.try_iter())
+                    .try_for_each(|notification| notification.map(drop));
+            }
+        });
+
+        Ok(Self {
+            events,
+            notify: Some(notify),
+            handle: Some(handle),
+        })
+    }
+}
+
+impl<T> Watcher<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    /// Blocks until the next event, returning None once the watcher has stopped
+    pub fn recv(&self) -> Option<std::io::Result<Event<T>>> {
+        self.events.recv().ok()
+    }
+
+    /// Waits for the next event up to timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<std::io::Result<Event<T>>> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Returns the next event if one is available
+    pub fn try_recv(&self) -> Option<std::io::Result<Event<T>>> {
+        self.events.try_recv().ok()
+    }
+
+    /// Stops the background thread and waits for it to exit
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.notify.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<T> Drop for Watcher<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Sends an event for every node whose files changed since they were recorded,
+/// returning false once nobody receives events anymore
+fn report<T>(
+    graph: &Graph<T>,
+    tracker: &mut FileTracker<T>,
+    sender: &Sender<std::io::Result<Event<T>>>,
+) -> bool
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    let changed = match tracker.changed() {
+        Ok(changed) => changed,
+        Err(err) => return sender.send(Err(err)).is_ok(),
+    };
+
+    // Only report new changes on the next check
+    if let Err(err) = tracker.record(&changed) {
+        if sender.send(Err(err)).is_err() {
+            return false;
+        }
+    }
+
+    changed.into_iter().all(|node| {
+        let dependents = graph.dependents(&node);
+        sender.send(Ok(Event { node, dependents })).is_ok()
+    })
+}
+
+fn io_error(err: notify::Error) -> std::io::Error {
+    match err.kind {
+        notify::ErrorKind::Io(err) => err,
+        _ => std::io::Error::other(err),
+    }
+}
+
+impl<T> std::fmt::Display for Event<T>
+where
+    T: std::fmt::Debug + Eq + std::hash::Hash,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "node {:?} and its {} dependents are now dirty",
+            self.node,
+            self.dependents.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::exec::Detect;
+
+    #[test]
+    fn test_watcher() {
+        let dir = std::env::temp_dir().join(format!("soydepend-{}-watcher", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        std::fs::write(&path, "lib").unwrap();
+
+        let mut g = Graph::new();
+        g.depend("app", "lib").unwrap();
+        g.depend("test", "app").unwrap();
+
+        let mut tracker = FileTracker::new(Detect::Content);
+        tracker.track("lib", &path);
+        tracker.record(&["lib"]).unwrap();
+
+        let watcher = Watcher::spawn(&g, tracker).unwrap();
+        let timeout = Duration::from_millis(50);
+        assert!(watcher.recv_timeout(timeout).is_none());
+
+        std::fs::write(&path, "bil").unwrap();
+        let event = watcher
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.node, "lib");
+        assert_eq!(event.dependents, HashSet::from(["app", "test"]));
+        assert_eq!(
+            event.to_string(),
+            r#"node "lib" and its 2 dependents are now dirty"#
+        );
+
+        // Already reported
+        assert!(watcher.recv_timeout(timeout).is_none());
+
+        watcher.stop();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,297 @@
+//! C ABI for embedding string-keyed graphs, see `include/soydepend.h`
+//!
+//! Graphs are opaque handles from [`soydep_graph_new`], freed with [`soydep_graph_free`].
+//! Nodes are NUL-terminated UTF-8 strings, copied on the way in and out.
+//! Functions returning `int` return [`SOYDEP_OK`] or a negative error code,
+//! described by [`soydep_strerror`].
+
+use std::ffi::{c_char, c_int, CStr, CString};
+
+use crate::{Error, Graph};
+
+/// Opaque graph handle
+pub type SoydepGraph = Graph<String>;
+
+pub const SOYDEP_OK: c_int = 0;
+/// Null pointer or string that is not UTF-8
+pub const SOYDEP_EINVAL: c_int = -1;
+pub const SOYDEP_ECYCLE: c_int = -2;
+pub const SOYDEP_ESELF: c_int = -3;
+pub const SOYDEP_ENOEDGE: c_int = -4;
+pub const SOYDEP_ENONODE: c_int = -5;
+/// Any other [`Error`], e.g. edges forbidden by policies
+pub const SOYDEP_EOTHER: c_int = -6;
+/// Removed node has dependents
+pub const SOYDEP_EDEPENDED: c_int = -7;
+
+/// Returns a new empty graph, to be freed with [`soydep_graph_free`]
+#[no_mangle]
+pub extern "C" fn soydep_graph_new() -> *mut SoydepGraph {
+    Box::into_raw(Box::new(Graph::new()))
+}
+
+/// Frees graph, doing nothing if it is null
+///
+/// # Safety
+///
+/// graph must be null or come from [`soydep_graph_new`], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_graph_free(graph: *mut SoydepGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Makes dependent depend on dependency, see [`Graph::depend`]
+///
+/// # Safety
+///
+/// graph must be a live handle, and nodes null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_depend(
+    graph: *mut SoydepGraph,
+    dependent: *const c_char,
+    dependency: *const c_char,
+) -> c_int {
+    let (Some(graph), Some(dependent), Some(dependency)) =
+        (graph.as_mut(), node(dependent), node(dependency))
+    else {
+        return SOYDEP_EINVAL;
+    };
+
+    status(graph.depend(dependent, dependency).map(drop))
+}
+
+/// Removes the direct edge, see [`Graph::undepend`]
+///
+/// # Safety
+///
+/// graph must be a live handle, and nodes null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_undepend(
+    graph: *mut SoydepGraph,
+    dependent: *const c_char,
+    dependency: *const c_char,
+) -> c_int {
+    let (Some(graph), Some(dependent), Some(dependency)) =
+        (graph.as_mut(), node(dependent), node(dependency))
+    else {
+        return SOYDEP_EINVAL;
+    };
+
+    status(graph.undepend(&dependent, &dependency))
+}
+
+/// Removes node, failing if it has dependents, see [`Graph::remove`]
+///
+/// # Safety
+///
+/// graph must be a live handle, and node null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_remove(graph: *mut SoydepGraph, node: *const c_char) -> c_int {
+    let (Some(graph), Some(node)) = (graph.as_mut(), self::node(node)) else {
+        return SOYDEP_EINVAL;
+    };
+
+    status(graph.remove(&node))
+}
+
+/// Returns 1 if dependent depends on dependency in some way, 0 if not,
+/// or [`SOYDEP_EINVAL`]
+///
+/// # Safety
+///
+/// graph must be null or a live handle, and nodes null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_depends_on(
+    graph: *const SoydepGraph,
+    dependent: *const c_char,
+    dependency: *const c_char,
+) -> c_int {
+    let (Some(graph), Some(dependent), Some(dependency)) =
+        (graph.as_ref(), node(dependent), node(dependency))
+    else {
+        return SOYDEP_EINVAL;
+    };
+
+    graph.depends_on(&dependent, &dependency).into()
+}
+
+/// Returns sorted deep dependencies of node as an array of len strings,
+/// to be freed with [`soydep_strings_free`], or null on invalid arguments.
+///
+/// # Safety
+///
+/// graph must be null or a live handle, node null or a NUL-terminated string,
+/// and len null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_dependencies(
+    graph: *const SoydepGraph,
+    node: *const c_char,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let (Some(graph), Some(node), Some(len)) = (graph.as_ref(), self::node(node), len.as_mut())
+    else {
+        return std::ptr::null_mut();
+    };
+
+    strings(graph.dependencies(&node), len)
+}
+
+/// Returns sorted deep dependents of node, like [`soydep_dependencies`]
+///
+/// # Safety
+///
+/// graph must be null or a live handle, node null or a NUL-terminated string,
+/// and len null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_dependents(
+    graph: *const SoydepGraph,
+    node: *const c_char,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let (Some(graph), Some(node), Some(len)) = (graph.as_ref(), self::node(node), len.as_mut())
+    else {
+        return std::ptr::null_mut();
+    };
+
+    strings(graph.dependents(&node), len)
+}
+
+/// Frees an array from [`soydep_dependencies`] or [`soydep_dependents`],
+/// doing nothing if it is null
+///
+/// # Safety
+///
+/// strings and len must come from the same call, and strings not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn soydep_strings_free(strings: *mut *mut c_char, len: usize) {
+    if strings.is_null() {
+        return;
+    }
+
+    let strings = Box::from_raw(std::ptr::slice_from_raw_parts_mut(strings, len));
+    for s in strings.iter() {
+        drop(CString::from_raw(*s));
+    }
+}
+
+/// Returns a static description of status code
+#[no_mangle]
+pub extern "C" fn soydep_strerror(code: c_int) -> *const c_char {
+    let message = match code {
+        SOYDEP_OK => c"ok",
+        SOYDEP_EINVAL => c"invalid argument",
+        SOYDEP_ECYCLE => c"circular dependency",
+        SOYDEP_ESELF => c"depends on self",
+        SOYDEP_ENOEDGE => c"no such direct dependency relationship",
+        SOYDEP_ENONODE => c"no such node",
+        SOYDEP_EOTHER => c"operation rejected",
+        SOYDEP_EDEPENDED => c"dependents exist",
+        _ => c"unknown error",
+    };
+
+    message.as_ptr()
+}
+
+unsafe fn node(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(s).to_str().ok().map(String::from)
+}
+
+/// Returns nodes sorted as an owned array of C strings, writing its length to len
+fn strings(nodes: std::collections::HashSet<String>, len: &mut usize) -> *mut *mut c_char {
+    let mut nodes: Vec<String> = nodes.into_iter().collect();
+    nodes.sort();
+
+    // Nodes come from C strings, so they hold no NUL
+    let strings: Box<[*mut c_char]> = nodes
+        .into_iter()
+        .map(|s| CString::new(s).unwrap().into_raw())
+        .collect();
+
+    *len = strings.len();
+    Box::into_raw(strings).cast()
+}
+
+fn status<T>(result: Result<(), Error<T>>) -> c_int {
+    match result {
+        Ok(()) => SOYDEP_OK,
+        Err(Error::CircularDependency) => SOYDEP_ECYCLE,
+        Err(Error::DependsOnSelf) => SOYDEP_ESELF,
+        Err(Error::NoSuchDirectDependency) => SOYDEP_ENOEDGE,
+        Err(Error::NoSuchNode) => SOYDEP_ENONODE,
+        Err(Error::DependencyExists) => SOYDEP_EDEPENDED,
+        Err(_) => SOYDEP_EOTHER,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        unsafe {
+            let g = soydep_graph_new();
+            assert_eq!(
+                soydep_depend(g, c"app".as_ptr(), c"http".as_ptr()),
+                SOYDEP_OK
+            );
+            assert_eq!(
+                soydep_depend(g, c"http".as_ptr(), c"tls".as_ptr()),
+                SOYDEP_OK
+            );
+            assert_eq!(
+                soydep_depend(g, c"tls".as_ptr(), c"app".as_ptr()),
+                SOYDEP_ECYCLE
+            );
+            assert_eq!(
+                soydep_depend(g, c"a".as_ptr(), std::ptr::null()),
+                SOYDEP_EINVAL
+            );
+            assert_eq!(
+                CStr::from_ptr(soydep_strerror(SOYDEP_ECYCLE)),
+                c"circular dependency"
+            );
+
+            assert_eq!(soydep_depends_on(g, c"app".as_ptr(), c"tls".as_ptr()), 1);
+            assert_eq!(soydep_depends_on(g, c"tls".as_ptr(), c"app".as_ptr()), 0);
+
+            let mut len = 0;
+            let deps = soydep_dependencies(g, c"app".as_ptr(), &mut len);
+            let names: Vec<&CStr> = std::slice::from_raw_parts(deps, len)
+                .iter()
+                .map(|s| CStr::from_ptr(*s))
+                .collect();
+            assert_eq!(names, [c"http", c"tls"]);
+            soydep_strings_free(deps, len);
+
+            let dependents = soydep_dependents(g, c"tls".as_ptr(), &mut len);
+            assert_eq!(len, 2);
+            soydep_strings_free(dependents, len);
+
+            let deps = soydep_dependencies(g, c"tls".as_ptr(), &mut len);
+            assert_eq!(len, 0);
+            soydep_strings_free(deps, len);
+            assert!(soydep_dependencies(g, c"tls".as_ptr(), std::ptr::null_mut()).is_null());
+
+            assert_eq!(
+                soydep_undepend(g, c"http".as_ptr(), c"tls".as_ptr()),
+                SOYDEP_OK
+            );
+            assert_eq!(
+                soydep_undepend(g, c"http".as_ptr(), c"tls".as_ptr()),
+                SOYDEP_ENOEDGE
+            );
+            assert_eq!(soydep_remove(g, c"http".as_ptr()), SOYDEP_EDEPENDED);
+            assert_eq!(soydep_remove(g, c"tls".as_ptr()), SOYDEP_OK);
+
+            soydep_graph_free(g);
+            soydep_graph_free(std::ptr::null_mut());
+        }
+    }
+}
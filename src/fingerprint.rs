@@ -0,0 +1,86 @@
+use std::hash::{Hash, Hasher};
+
+use crate::exec::{fnv1a, FNV_OFFSET};
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Returns a hash of nodes and edges, independent of insertion order.
+    ///
+    /// Hashing is stable across runs, so fingerprints may be stored
+    /// to tell whether a graph changed, as long as the [`Hash`] impl of T
+    /// does not change.
+    pub fn fingerprint(&self) -> u64 {
+        let mut nodes: Vec<u64> = self.nodes.iter().map(|node| hash(&node)).collect();
+        let mut edges: Vec<u64> = self.edges().map(|edge| hash(&edge)).collect();
+
+        // Independent of iteration order
+        nodes.sort_unstable();
+        edges.sort_unstable();
+
+        let hash = fnv1a(FNV_OFFSET, &(nodes.len() as u64).to_le_bytes());
+        nodes
+            .into_iter()
+            .chain(edges)
+            .fold(hash, |hash, value| fnv1a(hash, &value.to_le_bytes()))
+    }
+}
+
+fn hash<H: Hash>(value: &H) -> u64 {
+    let mut hasher = Fnv(FNV_OFFSET);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// FNV-1a as a [`Hasher`], see [`fnv1a`]
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = fnv1a(self.0, bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let mut a = Graph::new();
+        a.depend("app", "http").unwrap();
+        a.depend("http", "tls").unwrap();
+        a.add_node("db");
+
+        let mut b = Graph::new();
+        b.add_node("db");
+        b.depend("http", "tls").unwrap();
+        b.depend("app", "http").unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        // Known value, which must not change between runs
+        assert_eq!(a.fingerprint(), 0xeea6cf39e7a4360b);
+        assert_eq!(Graph::<&str>::new().fingerprint(), 0xa8c7f832281a39c5);
+
+        b.depend("app", "tls").unwrap();
+        assert_ne!(a.fingerprint(), b.fingerprint());
+        b.undepend(&"app", &"tls").unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        // Same nodes, edges moved
+        let mut c = Graph::new();
+        c.depend("http", "app").unwrap();
+        c.depend("app", "tls").unwrap();
+        c.add_node("db");
+        assert_ne!(a.fingerprint(), c.fingerprint());
+
+        b.add_node("cache");
+        assert_ne!(a.fingerprint(), b.fingerprint());
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+
+use crate::{Error, Graph};
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Removes every node that is neither one of roots nor a deep dependency
+    /// of one, returning removed nodes. Roots missing from the graph are ignored,
+    /// and pinned nodes are always roots.
+    pub fn prune(&mut self, roots: &HashSet<T>) -> HashSet<T> {
+        let mut reachable: HashSet<&T> = HashSet::new();
+        let mut q: Vec<&T> = roots
+            .iter()
+            .filter(|root| self.contains(root))
+            .chain(&self.pinned)
+            .collect();
+        reachable.extend(q.iter().copied());
+
+        while let Some(current) = q.pop() {
+            for dependency in self.dependencies.get(current).into_iter().flatten() {
+                if reachable.insert(dependency) {
+                    q.push(dependency);
+                }
+            }
+        }
+
+        let unreachable: HashSet<T> = self
+            .nodes
+            .iter()
+            .filter(|node| !reachable.contains(node))
+            .cloned()
+            .collect();
+
+        for node in &unreachable {
+            self.delete(node);
+        }
+
+        unreachable
+    }
+
+    /// Turns garbage collection on or off. While on, [`Graph::undepend`]
+    /// and [`Graph::remove`] also remove dependencies left without dependents,
+    /// unless they are pinned.
+    ///
+    /// Weak dependents do not keep nodes, like with [`Graph::remove_autoremove`].
+    pub fn set_gc(&mut self, gc: bool) {
+        self.gc = gc;
+    }
+
+    pub fn gc(&self) -> bool {
+        self.gc
+    }
+
+    /// Marks node as explicitly wanted, like manually installed packages.
+    ///
+    /// Pinned nodes are never removed by garbage collection, [`Graph::prune`],
+    /// [`Graph::remove_force`] or [`Graph::remove_autoremove`],
+    /// only by [`Graph::remove`].
+    pub fn pin(&mut self, node: &T) -> Result<(), Error<T>> {
+        if !self.contains(node) {
+            return Err(Error::NoSuchNode);
+        }
+
+        self.touch_node(node);
+        self.pinned.insert(node.clone());
+
+        Ok(())
+    }
+
+    /// Lets node be removed again, returning whether it was pinned
+    pub fn unpin(&mut self, node: &T) -> bool {
+        self.touch_node(node);
+        self.pinned.remove(node)
+    }
+
+    pub fn is_pinned(&self, node: &T) -> bool {
+        self.pinned.contains(node)
+    }
+
+    /// Removes orphaned candidates and whatever they orphan in turn,
+    /// if garbage collection is on
+    pub(crate) fn collect_orphans<I>(&mut self, candidates: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        if !self.gc {
+            return;
+        }
+
+        let mut q: Vec<T> = candidates.into_iter().collect();
+        while let Some(current) = q.pop() {
+            if !self.contains(&current) || self.is_pinned(&current) || self.is_dependend(&current) {
+                continue;
+            }
+
+            q.extend(
+                self.dependencies
+                    .get(&current)
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            );
+            self.delete(&current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_no_dangling;
+
+    #[test]
+    fn test_prune() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("old-app", "http").unwrap();
+        g.depend("old-app", "xml").unwrap();
+        g.depend("xml", "zlib").unwrap();
+        g.depend("tool", "zlib").unwrap();
+
+        let removed = g.prune(&HashSet::from(["app", "tool", "gone"]));
+        assert_eq!(removed, HashSet::from(["old-app", "xml"]));
+        assert_eq!(
+            g.nodes,
+            HashSet::from(["app", "http", "tls", "tool", "zlib"])
+        );
+        assert_no_dangling(&g);
+
+        assert!(g.prune(&HashSet::from(["app", "tool"])).is_empty());
+        assert_eq!(g.prune(&HashSet::new()).len(), 5);
+        assert!(g.nodes.is_empty());
+        assert_no_dangling(&g);
+    }
+
+    #[test]
+    fn test_gc() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("http", "zlib").unwrap();
+        g.depend("png", "zlib").unwrap();
+        g.depend("app", "png").unwrap();
+
+        // Off by default
+        g.undepend(&"app", &"png").unwrap();
+        assert!(g.contains(&"png"));
+        g.depend("app", "png").unwrap();
+
+        g.set_gc(true);
+        assert!(g.gc());
+        g.pin(&"tls").unwrap();
+        g.pin(&"nope").expect_err("no such node");
+
+        g.undepend(&"app", &"http").unwrap();
+        assert!(!g.contains(&"http"));
+        assert!(g.contains(&"tls"));
+        assert!(g.contains(&"zlib"));
+        assert!(g.contains(&"app"));
+
+        g.remove(&"app").unwrap();
+        assert_eq!(g.nodes, HashSet::from(["tls"]));
+        assert_no_dangling(&g);
+
+        assert!(g.unpin(&"tls"));
+        assert!(!g.is_pinned(&"tls"));
+        g.remove(&"tls").unwrap();
+        assert!(g.pinned.is_empty());
+    }
+
+    #[test]
+    fn test_pinned() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("curl", "tls").unwrap();
+        g.depend("app", "json").unwrap();
+        g.pin(&"curl").unwrap();
+        g.pin(&"json").unwrap();
+
+        g.remove_force(&"curl");
+        assert!(g.contains(&"curl"));
+
+        // Pinned dependents lose their edges but stay
+        g.remove_force(&"tls");
+        assert!(!g.contains(&"app"));
+        assert!(g.contains(&"curl"));
+        assert!(g.dependencies(&"curl").is_empty());
+        assert!(g.contains(&"json"));
+        assert_no_dangling(&g);
+
+        g.depend("app", "json").unwrap();
+        g.depend("app", "yaml").unwrap();
+        assert_eq!(g.removal_plan(&"app").unwrap(), vec!["app", "yaml"]);
+        g.remove_autoremove(&"app");
+        assert_eq!(g.nodes, HashSet::from(["curl", "json"]));
+
+        g.depend("old", "xml").unwrap();
+        assert_eq!(g.prune(&HashSet::new()), HashSet::from(["old", "xml"]));
+        assert_eq!(g.nodes, HashSet::from(["curl", "json"]));
+
+        // Only remove removes pinned nodes
+        g.remove(&"json").unwrap();
+        assert!(!g.is_pinned(&"json"));
+    }
+}
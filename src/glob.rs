@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+
+use crate::Graph;
+
+impl Graph<String> {
+    /// Returns nodes matching glob pattern, where `*` matches any characters,
+    /// `?` matches one character, and `[abc]`, `[a-z]` or `[!a-z]` match one
+    /// character of a class. Special characters are matched literally
+    /// when escaped with `\`, and so is an unclosed `[`.
+    pub fn find_nodes(&self, pattern: &str) -> HashSet<String> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        self.nodes
+            .iter()
+            .filter(|node| matches(&pattern, &node.chars().collect::<Vec<_>>()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Returns whether name matches glob pattern, backtracking to the last `*`
+/// when the rest does not match
+pub(crate) fn matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Positions after the last star, and in name where it started matching
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => class(&pattern[p..], name[n]),
+            Some('\\') if p + 1 < pattern.len() => (pattern[p + 1] == name[n]).then_some(2),
+            Some(c) => (*c == name[n]).then_some(1),
+            None => None,
+        };
+
+        match (step, star) {
+            (Some(len), _) => {
+                p += len;
+                n += 1;
+            }
+            (None, Some((after, start))) => {
+                p = after;
+                n = start + 1;
+                star = Some((after, start + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Matches c against the class at the start of pattern, returning the length
+/// of the class if it matches. Unclosed classes match a literal `[`.
+fn class(pattern: &[char], c: char) -> Option<usize> {
+    let negated = matches!(pattern.get(1), Some('!' | '^'));
+    let start = if negated { 2 } else { 1 };
+
+    // A leading ] belongs to the class
+    let Some(end) = pattern
+        .iter()
+        .skip(start + 1)
+        .position(|c| *c == ']')
+        .map(|i| i + start + 1)
+    else {
+        return (c == '[').then_some(1);
+    };
+
+    let members = &pattern[start..end];
+    let mut found = false;
+    let mut i = 0;
+    while i < members.len() {
+        if i + 2 < members.len() && members[i + 1] == '-' {
+            found |= (members[i]..=members[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= members[i] == c;
+            i += 1;
+        }
+    }
+
+    (found != negated).then_some(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str, name: &str) -> bool {
+        matches(
+            &pattern.chars().collect::<Vec<_>>(),
+            &name.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob("libfoo-*", "libfoo-core"));
+        assert!(glob("libfoo-*", "libfoo-"));
+        assert!(!glob("libfoo-*", "libbar-core"));
+        assert!(glob("*-core", "libfoo-core"));
+        assert!(glob("*o*o*", "foo"));
+        assert!(!glob("*o*o*o*", "foo"));
+        assert!(glob("a*b*c", "aXbYbZc"));
+        assert!(!glob("a*b*c", "aXbYbZ"));
+        assert!(glob("", ""));
+        assert!(!glob("", "a"));
+        assert!(glob("**", ""));
+
+        assert!(glob("v?.?", "v1.2"));
+        assert!(!glob("v?.?", "v1.23"));
+        assert!(glob("é?", "éü"));
+
+        assert!(glob("lib[abc]", "libb"));
+        assert!(!glob("lib[abc]", "libd"));
+        assert!(glob("v[0-9]", "v7"));
+        assert!(glob("v[!0-9]", "vx"));
+        assert!(!glob("v[^0-9]", "v7"));
+        assert!(glob("[]]", "]"));
+        assert!(glob("[a-]", "-"));
+
+        assert!(glob("a\\*", "a*"));
+        assert!(!glob("a\\*", "ab"));
+        assert!(glob("a[", "a["));
+        assert!(glob("a\\", "a\\"));
+    }
+
+    #[test]
+    fn test_find_nodes() {
+        let mut g = Graph::new();
+        for node in ["libfoo-core", "libfoo-net", "libbar", "app"] {
+            g.add_node(node.to_string());
+        }
+
+        let mut found: Vec<String> = g.find_nodes("libfoo-*").into_iter().collect();
+        found.sort();
+        assert_eq!(found, ["libfoo-core", "libfoo-net"]);
+
+        assert_eq!(g.find_nodes("lib*").len(), 3);
+        assert_eq!(g.find_nodes("*").len(), 4);
+        assert!(g.find_nodes("nothing*").is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use crate::{edges_contain, insert_to_deps, Edges, Error, Graph};
+
+/// How queries treat group nodes, see [`Graph::group`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Groups {
+    /// Groups are replaced with their members
+    #[default]
+    Expand,
+    /// Groups are reported as nodes, and their members are not reached through them
+    Opaque,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Adds members to group. A group depends on all of its members,
+    /// so depending on the group means depending on every member.
+    ///
+    /// Groups may be members of other groups.
+    pub fn group<I>(&mut self, group: T, members: I) -> Result<(), Error<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for member in members {
+            self.depend(group.clone(), member.clone())?;
+            insert_to_deps(&mut self.groups, group.clone(), member);
+        }
+
+        Ok(())
+    }
+
+    /// Removes member from group, along with the edge between them
+    pub fn ungroup(&mut self, group: &T, member: &T) -> Result<(), Error<T>> {
+        if !edges_contain(&self.groups, group, member) {
+            return Err(Error::NoSuchDirectDependency);
+        }
+
+        self.undepend(group, member)
+    }
+
+    pub fn is_group(&self, node: &T) -> bool {
+        self.groups.contains_key(node)
+    }
+
+    /// Returns direct members of group
+    pub fn members(&self, group: &T) -> HashSet<T> {
+        self.groups.get(group).cloned().unwrap_or_default()
+    }
+
+    /// Like [`Graph::dependencies`], with groups treated according to mode
+    pub fn dependencies_with(&self, node: &T, mode: Groups) -> HashSet<T> {
+        self.dig_groups(node, mode, &self.dependencies, |g, current, other| {
+            edges_contain(&g.groups, current, other)
+        })
+    }
+
+    /// Like [`Graph::dependents`], with groups treated according to mode
+    pub fn dependents_with(&self, node: &T, mode: Groups) -> HashSet<T> {
+        self.dig_groups(node, mode, &self.dependents, |g, current, other| {
+            edges_contain(&g.groups, other, current)
+        })
+    }
+
+    /// Digs deep along edges, skipping membership edges if opaque
+    fn dig_groups<F>(&self, node: &T, mode: Groups, edges: &Edges<T>, membership: F) -> HashSet<T>
+    where
+        F: Fn(&Self, &T, &T) -> bool,
+    {
+        let mut result = HashSet::new();
+        let mut q = vec![node];
+
+        while let Some(current) = q.pop() {
+            for other in edges.get(current).into_iter().flatten() {
+                if mode == Groups::Opaque && membership(self, current, other) {
+                    continue;
+                }
+
+                if result.insert(other.clone()) {
+                    q.push(other);
+                }
+            }
+        }
+
+        if mode == Groups::Expand {
+            result.retain(|node| !self.is_group(node));
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_no_dangling;
+
+    fn targets() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.group("all-tests", ["unit-tests", "integration-tests"])
+            .unwrap();
+        g.group("all", ["all-tests", "docs"]).unwrap();
+        g.depend("unit-tests", "lib").unwrap();
+        g.depend("integration-tests", "lib").unwrap();
+        g.depend("ci", "all-tests").unwrap();
+
+        g
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut g = targets();
+
+        assert!(g.is_group(&"all-tests"));
+        assert!(!g.is_group(&"lib"));
+        assert_eq!(g.members(&"all"), HashSet::from(["all-tests", "docs"]));
+        assert!(g.depends_on(&"ci", &"unit-tests"));
+
+        assert_eq!(
+            g.dependencies_with(&"ci", Groups::Expand),
+            HashSet::from(["unit-tests", "integration-tests", "lib"])
+        );
+        assert_eq!(
+            g.dependencies_with(&"ci", Groups::Opaque),
+            HashSet::from(["all-tests"])
+        );
+        assert_eq!(
+            g.dependencies_with(&"all", Groups::Expand),
+            HashSet::from(["unit-tests", "integration-tests", "lib", "docs"])
+        );
+        assert_eq!(
+            g.dependents_with(&"lib", Groups::Expand),
+            HashSet::from(["unit-tests", "integration-tests", "ci"])
+        );
+        assert_eq!(
+            g.dependents_with(&"lib", Groups::Opaque),
+            HashSet::from(["unit-tests", "integration-tests"])
+        );
+
+        g.ungroup(&"all-tests", &"unit-tests").unwrap();
+        g.ungroup(&"ci", &"all-tests")
+            .expect_err("not a membership");
+        assert!(!g.depends_on(&"ci", &"unit-tests"));
+
+        // Removed members leave their groups
+        g.remove_force(&"docs");
+        assert!(!g.contains(&"all"));
+        assert!(!g.is_group(&"all"));
+        g.remove_force(&"integration-tests");
+        assert!(!g.is_group(&"all-tests"));
+        assert!(g.groups.is_empty());
+        assert_no_dangling(&g);
+    }
+}
@@ -0,0 +1,620 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    edges_contain, insert_to_deps, rm_from_deps, Condition, EdgeKind, Edges, Error, Graph, Listener,
+};
+
+/// Primitive change to a graph, as seen by listeners
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change<T> {
+    NodeAdded(T),
+    NodeRemoved(T),
+    EdgeAdded(T, T),
+    EdgeRemoved(T, T),
+}
+
+/// Collects changes made to the graph owned by [`History`]
+pub(crate) struct Recorder<T> {
+    changes: Mutex<Vec<Change<T>>>,
+}
+
+/// Attribute of a node or of a pair of nodes, which [`Change`] does not carry
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Key<T> {
+    /// Weight and pin of a node
+    Node(T),
+    /// Metadata key of a node
+    Meta(T, String),
+    /// Attributes of the edge from the first node to the second,
+    /// and other relations of the first node to the second
+    Pair(T, T),
+}
+
+/// Value of an attribute [`Key`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Attributes {
+    Node { weight: Option<u64>, pinned: bool },
+    Meta(Option<String>),
+    Pair(Pair),
+}
+
+/// Attributes of a pair of nodes, see [`Key::Pair`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Pair {
+    pub(crate) kinds: Option<HashSet<EdgeKind>>,
+    pub(crate) weak: bool,
+    pub(crate) suggested: bool,
+    pub(crate) condition: Option<Condition>,
+    pub(crate) weight: Option<u64>,
+    /// Whether the first node is a group with the second as member
+    pub(crate) member: bool,
+    /// Whether the first node is virtual and provided by the second
+    pub(crate) provided: bool,
+    pub(crate) conflict: bool,
+}
+
+/// Attributes touched while saving, as they were before being touched
+pub(crate) type Touched<T> = HashMap<Key<T>, Attributes>;
+
+/// Attribute touched by a step, as it was before and after the step
+#[derive(Debug)]
+struct Delta<T> {
+    key: Key<T>,
+    before: Attributes,
+    after: Attributes,
+}
+
+/// Mutations made by one step of history
+#[derive(Debug)]
+struct Step<T> {
+    changes: Vec<Change<T>>,
+    attributes: Vec<Delta<T>>,
+}
+
+/// Graph wrapper recording every mutation, so that it can be undone and redone.
+///
+/// Every call mutating the graph is one step of history,
+/// and [`History::edit`] groups many mutations into one step.
+///
+/// Steps also restore attributes of nodes and edges, such as weak edges,
+/// kinds, metadata and weights, saving only the ones each step touched.
+pub struct History<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    graph: Graph<T>,
+    recorder: Arc<Recorder<T>>,
+    undo: Vec<Step<T>>,
+    redo: Vec<Step<T>>,
+}
+
+impl<T> History<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + 'static,
+{
+    pub fn new(mut graph: Graph<T>) -> Self {
+        let recorder = Arc::new(Recorder::new());
+        graph.subscribe(recorder.clone());
+
+        Self {
+            graph,
+            recorder,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Returns the graph, dropping its history
+    pub fn into_inner(mut self) -> Graph<T> {
+        let recorder: Arc<dyn Listener<T>> = self.recorder.clone();
+        self.graph.unsubscribe(&recorder);
+
+        self.graph
+    }
+
+    /// Applies f to the graph as a single step of history
+    pub fn edit<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Graph<T>) -> R,
+    {
+        self.graph.save_touched();
+        let result = f(&mut self.graph);
+
+        let changes = self.recorder.take();
+        let attributes: Vec<_> = self
+            .graph
+            .take_touched()
+            .into_iter()
+            .filter_map(|(key, before)| {
+                let after = self.graph.attributes(&key);
+                (before != after).then_some(Delta { key, before, after })
+            })
+            .collect();
+
+        if !changes.is_empty() || !attributes.is_empty() {
+            self.undo.push(Step {
+                changes,
+                attributes,
+            });
+            self.redo.clear();
+        }
+
+        result
+    }
+
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
+        self.edit(|g| g.depend(dependent, dependency))
+    }
+
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
+        self.edit(|g| g.undepend(dependent, dependency))
+    }
+
+    pub fn remove(&mut self, target: &T) -> Result<(), Error<T>> {
+        self.edit(|g| g.remove(target))
+    }
+
+    pub fn remove_force(&mut self, target: &T) {
+        self.edit(|g| g.remove_force(target))
+    }
+
+    pub fn remove_autoremove(&mut self, target: &T) {
+        self.edit(|g| g.remove_autoremove(target))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Reverts the last step, returning false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(step) = self.undo.pop() else {
+            return false;
+        };
+
+        for change in step.changes.iter().rev() {
+            self.graph.apply(&change.inverse());
+        }
+
+        for delta in &step.attributes {
+            self.graph.set_attributes(&delta.key, &delta.before);
+        }
+        self.recorder.take();
+        self.redo.push(step);
+
+        true
+    }
+
+    /// Reapplies the last undone step, returning false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.redo.pop() else {
+            return false;
+        };
+
+        for change in &step.changes {
+            self.graph.apply(change);
+        }
+
+        for delta in &step.attributes {
+            self.graph.set_attributes(&delta.key, &delta.after);
+        }
+        self.recorder.take();
+        self.undo.push(step);
+
+        true
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Starts saving attributes as they were before being touched,
+    /// see [`Graph::take_touched`]
+    pub(crate) fn save_touched(&mut self) {
+        self.touched = Some(Touched::new());
+    }
+
+    /// Stops saving attributes, returning the ones touched since
+    /// [`Graph::save_touched`] with their values from before
+    pub(crate) fn take_touched(&mut self) -> Touched<T> {
+        self.touched.take().unwrap_or_default()
+    }
+
+    /// Saves weight and pin of node before they change, if saving
+    pub(crate) fn touch_node(&mut self, node: &T) {
+        if self.touched.is_some() {
+            self.touch(Key::Node(node.clone()));
+        }
+    }
+
+    /// Saves metadata key of node before it changes, if saving
+    pub(crate) fn touch_meta(&mut self, node: &T, key: &str) {
+        if self.touched.is_some() {
+            self.touch(Key::Meta(node.clone(), key.to_string()));
+        }
+    }
+
+    /// Saves attributes of the pair before they change, if saving
+    pub(crate) fn touch_pair(&mut self, a: &T, b: &T) {
+        if self.touched.is_some() {
+            self.touch(Key::Pair(a.clone(), b.clone()));
+        }
+    }
+
+    fn touch(&mut self, key: Key<T>) {
+        if self
+            .touched
+            .as_ref()
+            .is_some_and(|touched| !touched.contains_key(&key))
+        {
+            let attributes = self.attributes(&key);
+            self.touched
+                .as_mut()
+                .expect("touched is saving")
+                .insert(key, attributes);
+        }
+    }
+
+    /// Returns the current value of key
+    pub(crate) fn attributes(&self, key: &Key<T>) -> Attributes {
+        match key {
+            Key::Node(node) => Attributes::Node {
+                weight: self.weights.nodes.get(node).copied(),
+                pinned: self.pinned.contains(node),
+            },
+            Key::Meta(node, key) => Attributes::Meta(self.get_meta(node, key).map(String::from)),
+            Key::Pair(a, b) => Attributes::Pair(Pair {
+                kinds: self.kinds.get(a).and_then(|kinds| kinds.get(b)).cloned(),
+                weak: edges_contain(&self.weak, b, a),
+                suggested: edges_contain(&self.suggested, b, a),
+                condition: self
+                    .conditions
+                    .get(a)
+                    .and_then(|conditions| conditions.get(b))
+                    .cloned(),
+                weight: self
+                    .weights
+                    .edges
+                    .get(a)
+                    .and_then(|weights| weights.get(b))
+                    .copied(),
+                member: edges_contain(&self.groups, a, b),
+                provided: edges_contain(&self.provides, a, b),
+                conflict: edges_contain(&self.conflicts, a, b),
+            }),
+        }
+    }
+
+    /// Sets key to value without validation, e.g. to restore an earlier value
+    pub(crate) fn set_attributes(&mut self, key: &Key<T>, value: &Attributes) {
+        match (key, value) {
+            (Key::Node(node), Attributes::Node { weight, pinned }) => {
+                match weight {
+                    Some(weight) => self.weights.nodes.insert(node.clone(), *weight),
+                    None => self.weights.nodes.remove(node),
+                };
+
+                match pinned {
+                    true => self.pinned.insert(node.clone()),
+                    false => self.pinned.remove(node),
+                };
+            }
+            (Key::Meta(node, key), Attributes::Meta(value)) => match value {
+                Some(value) => {
+                    self.meta
+                        .entry(node.clone())
+                        .or_default()
+                        .insert(key.clone(), value.clone());
+                }
+                None => {
+                    self.remove_meta(node, key);
+                }
+            },
+            (Key::Pair(a, b), Attributes::Pair(pair)) => {
+                self.forget_kinds(a, b);
+                if let Some(kinds) = &pair.kinds {
+                    self.kinds
+                        .entry(a.clone())
+                        .or_default()
+                        .insert(b.clone(), kinds.clone());
+                }
+
+                set_contains(&mut self.weak, b, a, pair.weak);
+                set_contains(&mut self.suggested, b, a, pair.suggested);
+
+                self.forget_condition(a, b);
+                if let Some(condition) = &pair.condition {
+                    self.conditions
+                        .entry(a.clone())
+                        .or_default()
+                        .insert(b.clone(), condition.clone());
+                }
+
+                self.forget_edge_weight(a, b);
+                if let Some(weight) = pair.weight {
+                    self.weights
+                        .edges
+                        .entry(a.clone())
+                        .or_default()
+                        .insert(b.clone(), weight);
+                }
+
+                set_contains(&mut self.groups, a, b, pair.member);
+                set_contains(&mut self.provides, a, b, pair.provided);
+                set_contains(&mut self.conflicts, a, b, pair.conflict);
+            }
+            _ => unreachable!("attributes of another key"),
+        }
+    }
+
+    /// Applies change without validation, notifying listeners.
+    ///
+    /// Changes already in effect are ignored, so replaying them is harmless.
+    pub(crate) fn apply(&mut self, change: &Change<T>) {
+        match change {
+            Change::NodeAdded(node) => self.insert_node(node),
+            Change::NodeRemoved(node) => self.delete(node),
+            Change::EdgeAdded(dependent, dependency)
+                if self.depends_on_directly(dependent, dependency) => {}
+            Change::EdgeRemoved(dependent, dependency)
+                if !self.depends_on_directly(dependent, dependency) => {}
+            Change::EdgeAdded(dependent, dependency) => {
+                insert_to_deps(&mut self.dependents, dependency.clone(), dependent.clone());
+                insert_to_deps(
+                    &mut self.dependencies,
+                    dependent.clone(),
+                    dependency.clone(),
+                );
+                self.counters.edge_added();
+                self.edge_count += 1;
+                self.edge_changed(dependent, dependency);
+                self.listeners
+                    .notify(|listener| listener.on_edge_added(dependent, dependency));
+            }
+            Change::EdgeRemoved(dependent, dependency) => {
+                rm_from_deps(&mut self.dependencies, dependent, dependency);
+                rm_from_deps(&mut self.dependents, dependency, dependent);
+                self.forget_edge(dependent, dependency);
+                self.counters.edge_removed();
+                self.edge_count -= 1;
+                self.edge_changed(dependent, dependency);
+                self.listeners
+                    .notify(|listener| listener.on_edge_removed(dependent, dependency));
+            }
+        }
+    }
+}
+
+impl<T> Change<T>
+where
+    T: Clone,
+{
+    pub(crate) fn inverse(&self) -> Self {
+        match self {
+            Self::NodeAdded(node) => Self::NodeRemoved(node.clone()),
+            Self::NodeRemoved(node) => Self::NodeAdded(node.clone()),
+            Self::EdgeAdded(dependent, dependency) => {
+                Self::EdgeRemoved(dependent.clone(), dependency.clone())
+            }
+            Self::EdgeRemoved(dependent, dependency) => {
+                Self::EdgeAdded(dependent.clone(), dependency.clone())
+            }
+        }
+    }
+}
+
+/// Adds or removes the edge from a to b in edges
+fn set_contains<T>(edges: &mut Edges<T>, a: &T, b: &T, contains: bool)
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    match contains {
+        true => insert_to_deps(edges, a.clone(), b.clone()),
+        false => rm_from_deps(edges, a, b),
+    }
+}
+
+impl<T> Recorder<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            changes: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn take(&self) -> Vec<Change<T>> {
+        std::mem::take(&mut self.changes.lock().unwrap())
+    }
+
+    fn record(&self, change: Change<T>) {
+        self.changes.lock().unwrap().push(change);
+    }
+}
+
+impl<T> Listener<T> for Recorder<T>
+where
+    T: Clone + Send,
+{
+    fn on_node_added(&self, node: &T) {
+        self.record(Change::NodeAdded(node.clone()));
+    }
+
+    fn on_edge_added(&self, dependent: &T, dependency: &T) {
+        self.record(Change::EdgeAdded(dependent.clone(), dependency.clone()));
+    }
+
+    fn on_edge_removed(&self, dependent: &T, dependency: &T) {
+        self.record(Change::EdgeRemoved(dependent.clone(), dependency.clone()));
+    }
+
+    fn on_node_removed(&self, node: &T) {
+        self.record(Change::NodeRemoved(node.clone()));
+    }
+}
+
+impl<T> std::ops::Deref for History<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    type Target = Graph<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::assert_no_dangling;
+
+    #[test]
+    fn test_history() {
+        let mut h = History::new(Graph::new());
+        assert!(!h.undo());
+
+        h.depend("b", "a").unwrap();
+        h.depend("c", "b").unwrap();
+        h.depend("a", "c")
+            .expect_err("failed mutations are not recorded");
+
+        assert!(h.undo());
+        assert!(!h.contains(&"c"));
+        assert!(h.depends_on(&"b", &"a"));
+        assert_no_dangling(&h);
+
+        assert!(h.redo());
+        assert!(h.depends_on(&"c", &"a"));
+        assert!(!h.redo());
+
+        h.remove_force(&"a");
+        assert!(h.nodes.is_empty());
+
+        assert!(h.undo());
+        assert!(h.depends_on(&"c", &"a"));
+        assert!(h.depends_on_directly(&"c", &"b"));
+        assert_no_dangling(&h);
+
+        // Undepend leaves nodes in place
+        h.undepend(&"c", &"b").unwrap();
+        assert!(h.contains(&"c"));
+        assert!(h.undo());
+        assert!(h.depends_on_directly(&"c", &"b"));
+
+        // New edits drop undone steps
+        assert!(h.undo());
+        h.depend("x", "a").unwrap();
+        assert!(!h.can_redo());
+
+        h.edit(|g| {
+            g.depend("y", "x").unwrap();
+            g.depend("z", "y").unwrap();
+        });
+
+        assert!(h.undo());
+        assert!(!h.contains(&"y"));
+        assert!(!h.contains(&"z"));
+        assert!(h.contains(&"x"));
+
+        while h.undo() {}
+        assert!(h.nodes.is_empty());
+
+        let g = h.into_inner();
+        assert!(g.listeners.is_empty());
+    }
+
+    #[test]
+    fn test_history_attributes() {
+        use crate::{EdgeKind, Level};
+
+        let mut h = History::new(Graph::new());
+        h.edit(|g| g.depend_weak("a", "b")).unwrap();
+        h.edit(|g| g.depend_kind("a", "c", EdgeKind::Dev)).unwrap();
+        h.edit(|g| g.depend_level("a", "d", Level::Suggests))
+            .unwrap();
+
+        for _ in 0..3 {
+            assert!(h.undo());
+        }
+        assert!(h.nodes.is_empty());
+        assert!(h.weak.is_empty() && h.kinds.is_empty() && h.suggested.is_empty());
+
+        while h.redo() {}
+        assert!(h.is_weak(&"a", &"b"));
+        assert!(!h.is_dependend(&"b"));
+        assert_eq!(h.kinds(&"a", &"c"), HashSet::from([EdgeKind::Dev]));
+        assert_eq!(h.level(&"a", &"d"), Some(Level::Suggests));
+
+        // Strengthening an edge is undone too
+        h.depend("a", "b").unwrap();
+        assert!(!h.is_weak(&"a", &"b"));
+        assert!(h.undo());
+        assert!(h.is_weak(&"a", &"b"));
+
+        // Attribute-only edits are steps of their own
+        h.edit(|g| {
+            g.set_meta(&"c", "version", "1.0").unwrap();
+            g.set_weight(&"c", 5).unwrap();
+            g.pin(&"c").unwrap();
+        });
+        assert!(h.undo());
+        assert_eq!(h.get_meta(&"c", "version"), None);
+        assert!(!h.is_pinned(&"c"));
+        assert!(h.redo());
+
+        // Removed nodes come back with their metadata and weights
+        h.edit(|g| {
+            g.undepend(&"a", &"c").unwrap();
+            g.remove(&"c").unwrap();
+        });
+        assert!(h.undo());
+        assert_eq!(h.get_meta(&"c", "version"), Some("1.0"));
+        assert_eq!(h.weight(&"c"), 5);
+        assert!(h.is_pinned(&"c"));
+        assert_eq!(h.kinds(&"a", &"c"), HashSet::from([EdgeKind::Dev]));
+
+        assert!(h.redo());
+        assert!(!h.contains(&"c"));
+        assert!(h.meta.is_empty());
+        assert_no_dangling(&h);
+    }
+
+    #[test]
+    fn test_history_touched() {
+        let mut h = History::new(Graph::new());
+        h.edit(|g| {
+            for i in 1..100 {
+                g.depend_weak(i, 0).unwrap();
+                g.set_weight(&i, i).unwrap();
+            }
+        });
+
+        // Steps save only the attributes they touched
+        h.edit(|g| g.set_edge_weight(&1, &0, 3)).unwrap();
+        assert_eq!(h.undo.last().unwrap().attributes.len(), 1);
+        h.edit(|g| g.conflict(1, 2)).unwrap();
+        assert_eq!(h.undo.last().unwrap().attributes.len(), 2);
+        h.edit(|g| g.provide(3, 100)).unwrap();
+
+        h.remove_force(&1);
+        h.remove_force(&3);
+        assert!(!h.conflicts_with(&2, &1));
+        assert!(!h.is_virtual(&100));
+
+        assert!(h.undo());
+        assert!(h.undo());
+        assert!(h.is_weak(&1, &0));
+        assert_eq!(h.weight(&1), 1);
+        assert_eq!(h.edge_weight(&1, &0), 3);
+        assert!(h.conflicts_with(&1, &2) && h.conflicts_with(&2, &1));
+        assert_eq!(h.providers(&100), HashSet::from([3]));
+        assert_no_dangling(&h);
+    }
+}
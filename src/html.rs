@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+
+use crate::Graph;
+
+/// Page template, with node and link arrays filled in by [`Graph::to_html`]
+const PAGE: &str = include_str!("html/page.html");
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Display,
+{
+    /// Returns a self-contained HTML page drawing the graph with a force-directed
+    /// layout, where nodes can be searched, and clicking a node highlights
+    /// its deep dependents. The page loads no external resources.
+    pub fn to_html(&self) -> String {
+        let mut nodes: Vec<(String, &T)> = self.nodes.iter().map(|n| (n.to_string(), n)).collect();
+        nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let index: HashMap<&T, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (_, n))| (*n, i))
+            .collect();
+
+        let mut names = String::from("[");
+        for (i, (name, _)) in nodes.iter().enumerate() {
+            if i > 0 {
+                names.push(',');
+            }
+
+            push_js_string(&mut names, name);
+        }
+        names.push(']');
+
+        let mut links: Vec<(usize, usize)> = self
+            .dependencies
+            .iter()
+            .flat_map(|(dependent, dependencies)| {
+                let index = &index;
+                dependencies
+                    .iter()
+                    .map(move |dependency| (index[dependent], index[dependency]))
+            })
+            .collect();
+        links.sort_unstable();
+
+        let mut edges = String::from("[");
+        for (i, (dependent, dependency)) in links.iter().enumerate() {
+            if i > 0 {
+                edges.push(',');
+            }
+
+            let _ = write!(edges, "[{dependent},{dependency}]");
+        }
+        edges.push(']');
+
+        PAGE.replace("/*NODES*/", &names)
+            .replace("/*LINKS*/", &edges)
+    }
+}
+
+/// Writes s as a JavaScript string literal that is also safe inside a script tag
+fn push_js_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            // Keeps </script> and <!-- from ending the script
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "db").unwrap();
+        g.depend("db", "</script>\"x\"").unwrap();
+
+        let html = g.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(r#"const nodes = ["\u003c/script\u003e\"x\"","app","db","http"];"#));
+        assert!(html.contains("const links = [[1,2],[1,3],[2,0]];"));
+        assert_eq!(html.matches("</script>").count(), 1);
+        assert!(!html.contains("src="));
+
+        let empty = Graph::<&str>::new().to_html();
+        assert!(empty.contains("const nodes = [];"));
+        assert!(empty.contains("const links = [];"));
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+
+use crate::{Error, Graph};
+
+/// What breaks if a node goes away, see [`Graph::impact_of_removal`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemovalImpact<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    /// Direct dependents that lose a required dependency
+    pub broken: HashSet<T>,
+    /// Deep dependents that break in turn, not including broken ones
+    pub affected: HashSet<T>,
+    /// Dependencies left without any dependent once the target and every
+    /// broken or affected node are gone, and which are not pinned
+    pub orphaned: HashSet<T>,
+}
+
+impl<T> RemovalImpact<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    /// Returns whether removing the node breaks nothing and orphans nothing
+    pub fn is_empty(&self) -> bool {
+        self.broken.is_empty() && self.affected.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns what removing target would break, without removing it.
+    ///
+    /// Weak dependents outlive their dependencies, so they are never
+    /// broken or affected, like in [`Graph::remove_force`].
+    pub fn impact_of_removal(&self, target: &T) -> Result<RemovalImpact<T>, Error<T>> {
+        if !self.contains(target) {
+            return Err(Error::NoSuchNode);
+        }
+
+        let broken: HashSet<T> = self.strong_dependents_iter(target).cloned().collect();
+        let mut affected = self.dependents_filtered(target, |a, b| !self.is_weak(a, b));
+        affected.retain(|node| !broken.contains(node));
+
+        let mut gone: HashSet<&T> = broken.iter().chain(&affected).collect();
+        gone.insert(target);
+
+        let mut q: Vec<&T> = gone.iter().copied().collect();
+        let mut orphaned = HashSet::new();
+        while let Some(current) = q.pop() {
+            for dependency in self.dependencies.get(current).into_iter().flatten() {
+                if gone.contains(dependency) || self.is_pinned(dependency) {
+                    continue;
+                }
+
+                let exclusive = self
+                    .strong_dependents_iter(dependency)
+                    .all(|d| gone.contains(d));
+
+                if exclusive {
+                    gone.insert(dependency);
+                    orphaned.insert(dependency.clone());
+                    q.push(dependency);
+                }
+            }
+        }
+
+        Ok(RemovalImpact {
+            broken,
+            affected,
+            orphaned,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impact_of_removal() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("cli", "http").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("tls", "crypto").unwrap();
+        g.depend("http", "log").unwrap();
+        g.depend("db", "log").unwrap();
+        g.depend("http", "pinned").unwrap();
+        g.depend_weak("editor", "http").unwrap();
+        g.depend("app", "mime").unwrap();
+        g.pin(&"pinned").unwrap();
+
+        let impact = g.impact_of_removal(&"http").unwrap();
+        assert_eq!(impact.broken, HashSet::from(["app", "cli"]));
+        assert!(impact.affected.is_empty());
+        assert_eq!(impact.orphaned, HashSet::from(["tls", "crypto", "mime"]));
+
+        let impact = g.impact_of_removal(&"tls").unwrap();
+        assert_eq!(impact.broken, HashSet::from(["http"]));
+        assert_eq!(impact.affected, HashSet::from(["app", "cli"]));
+        assert_eq!(impact.orphaned, HashSet::from(["crypto", "mime"]));
+
+        // Nothing was actually removed
+        assert!(g.contains(&"http"));
+        assert!(g.impact_of_removal(&"editor").unwrap().is_empty());
+        assert!(matches!(
+            g.impact_of_removal(&"missing"),
+            Err(Error::NoSuchNode)
+        ));
+    }
+}
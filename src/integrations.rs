@@ -0,0 +1,155 @@
+//! Importers building graphs from other tools' dependency data
+//!
+//! Importers implement [`GraphSource`], so that other crates can add theirs
+//! for ecosystems this crate does not cover, and collect them in a [`Registry`].
+
+use std::collections::BTreeMap;
+
+use crate::Graph;
+
+#[cfg(feature = "cargo")]
+pub mod cargo;
+
+#[cfg(feature = "make")]
+pub mod make;
+
+#[cfg(feature = "npm")]
+pub mod npm;
+
+#[cfg(feature = "systemd")]
+pub mod systemd;
+
+/// Errors of any source, boxed since each importer has its own
+pub type SourceError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Importer of a graph from somewhere, e.g. a lockfile.
+///
+/// Closures returning a graph are sources.
+pub trait GraphSource<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn load(&self) -> Result<Graph<T>, SourceError>;
+}
+
+impl<T, F> GraphSource<T> for F
+where
+    T: Clone + Eq + std::hash::Hash,
+    F: Fn() -> Result<Graph<T>, SourceError>,
+{
+    fn load(&self) -> Result<Graph<T>, SourceError> {
+        self()
+    }
+}
+
+/// Named graph sources
+pub struct Registry<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    sources: BTreeMap<String, Box<dyn GraphSource<T> + Send + Sync>>,
+}
+
+impl<T> Registry<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Registers source under name, returning whether it replaced another
+    pub fn register<S>(&mut self, name: impl Into<String>, source: S) -> bool
+    where
+        S: GraphSource<T> + Send + Sync + 'static,
+    {
+        self.sources.insert(name.into(), Box::new(source)).is_some()
+    }
+
+    /// Unregisters source name, returning whether it was registered
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.sources.remove(name).is_some()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.sources.contains_key(name)
+    }
+
+    /// Returns names of registered sources in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(String::as_str)
+    }
+
+    /// Loads the graph of source name, or returns None if it is not registered
+    pub fn load(&self, name: &str) -> Option<Result<Graph<T>, SourceError>> {
+        self.sources.get(name).map(|source| source.load())
+    }
+}
+
+impl<T> Default for Registry<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::fmt::Debug for Registry<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static [(&'static str, &'static str)]);
+
+    impl GraphSource<&'static str> for Fixed {
+        fn load(&self) -> Result<Graph<&'static str>, SourceError> {
+            let mut g = Graph::new();
+            for (dependent, dependency) in self.0 {
+                g.depend(*dependent, *dependency)?;
+            }
+
+            Ok(g)
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::new();
+        assert!(!registry.register("fixed", Fixed(&[("app", "lib")])));
+        assert!(!registry.register("broken", || Err("no such file".into())));
+        assert!(!registry.register("cycle", Fixed(&[("a", "b"), ("b", "a")])));
+
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            ["broken", "cycle", "fixed"]
+        );
+        assert_eq!(format!("{registry:?}"), r#"["broken", "cycle", "fixed"]"#);
+
+        let g = registry.load("fixed").unwrap().unwrap();
+        assert!(g.depends_on(&"app", &"lib"));
+
+        let err = registry.load("broken").unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "no such file");
+        registry.load("cycle").unwrap().expect_err("cycle");
+        assert!(registry.load("missing").is_none());
+
+        assert!(registry.register("fixed", Fixed(&[])));
+        let g = registry.load("fixed").unwrap().unwrap();
+        assert_eq!(g.nodes().count(), 0);
+
+        assert!(registry.unregister("broken"));
+        assert!(!registry.unregister("broken"));
+        assert!(!registry.contains("broken"));
+    }
+}
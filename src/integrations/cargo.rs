@@ -0,0 +1,253 @@
+//! Graphs of Cargo workspaces, from `cargo metadata`
+//!
+//! Packages are keyed by `name@version`. Workspace members are pinned,
+//! and edges carry their [`EdgeKind`]s.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{GraphSource, SourceError};
+use crate::json::{self, Value};
+use crate::{EdgeKind, Error, Graph};
+
+#[derive(Debug)]
+pub enum CargoError {
+    /// Running cargo failed
+    Io(std::io::Error),
+    /// Cargo exited unsuccessfully, with its stderr
+    Cargo(String),
+    /// Metadata is not valid JSON, or misses fields
+    Metadata(String),
+    /// Dependencies form a cycle, which dev-dependencies may do
+    Graph(Error<String>),
+}
+
+impl std::fmt::Display for CargoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "running cargo: {err}"),
+            Self::Cargo(stderr) => write!(f, "cargo metadata failed: {stderr}"),
+            Self::Metadata(message) => write!(f, "invalid cargo metadata: {message}"),
+            Self::Graph(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for CargoError {}
+
+/// [`GraphSource`] running `cargo metadata` in dir, see [`workspace_graph`]
+#[derive(Clone, Debug)]
+pub struct Workspace {
+    pub dir: PathBuf,
+    pub dev: bool,
+}
+
+impl GraphSource<String> for Workspace {
+    fn load(&self) -> Result<Graph<String>, SourceError> {
+        Ok(workspace_graph(&self.dir, self.dev)?)
+    }
+}
+
+/// Runs `cargo metadata` in dir and builds the graph of its workspace,
+/// see [`metadata_graph`]
+pub fn workspace_graph<P: AsRef<Path>>(dir: P, dev: bool) -> Result<Graph<String>, CargoError> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args(["metadata", "--format-version", "1"])
+        .current_dir(dir)
+        .output()
+        .map_err(CargoError::Io)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CargoError::Cargo(stderr.trim().to_string()));
+    }
+
+    let json = String::from_utf8(output.stdout)
+        .map_err(|_| CargoError::Metadata("output is not UTF-8".to_string()))?;
+
+    metadata_graph(&json, dev)
+}
+
+/// Builds the graph of resolved packages from `cargo metadata --format-version 1` output.
+///
+/// Dev-dependencies are left out unless dev is true,
+/// since they may legitimately form cycles.
+pub fn metadata_graph(json: &str, dev: bool) -> Result<Graph<String>, CargoError> {
+    let metadata = json::parse(json).map_err(|err| CargoError::Metadata(err.to_string()))?;
+    let missing = |field: &str| CargoError::Metadata(format!("missing {field}"));
+
+    let mut names: HashMap<&str, String> = HashMap::new();
+    for package in metadata
+        .get("packages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| missing("packages"))?
+    {
+        let field = |name| package.get(name).and_then(Value::as_str);
+        let (Some(id), Some(name), Some(version)) = (field("id"), field("name"), field("version"))
+        else {
+            return Err(missing("package id, name or version"));
+        };
+
+        names.insert(id, format!("{name}@{version}"));
+    }
+
+    let name = |id: &str| {
+        names
+            .get(id)
+            .cloned()
+            .ok_or_else(|| CargoError::Metadata(format!("unknown package {id}")))
+    };
+
+    let nodes = metadata
+        .get("resolve")
+        .and_then(|resolve| resolve.get("nodes"))
+        .and_then(Value::as_array)
+        .ok_or_else(|| missing("resolve, which --no-deps leaves out"))?;
+
+    let mut g = Graph::new();
+    for node in nodes {
+        let id = node
+            .get("id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| missing("node id"))?;
+        let dependent = name(id)?;
+        g.add_node(dependent.clone());
+
+        for dep in node
+            .get("deps")
+            .and_then(Value::as_array)
+            .unwrap_or_default()
+        {
+            let pkg = dep
+                .get("pkg")
+                .and_then(Value::as_str)
+                .ok_or_else(|| missing("dep pkg"))?;
+            let dependency = name(pkg)?;
+
+            let kinds = dep
+                .get("dep_kinds")
+                .and_then(Value::as_array)
+                .unwrap_or_default()
+                .iter()
+                .map(|kind| match kind.get("kind").and_then(Value::as_str) {
+                    Some("dev") => EdgeKind::Dev,
+                    Some("build") => EdgeKind::Build,
+                    _ => EdgeKind::Runtime,
+                })
+                .filter(|kind| dev || *kind != EdgeKind::Dev);
+
+            for kind in kinds {
+                g.depend_kind(dependent.clone(), dependency.clone(), kind)
+                    .map_err(CargoError::Graph)?;
+            }
+        }
+    }
+
+    for member in metadata
+        .get("workspace_members")
+        .and_then(Value::as_array)
+        .unwrap_or_default()
+    {
+        let member = name(
+            member
+                .as_str()
+                .ok_or_else(|| missing("workspace member id"))?,
+        )?;
+        g.pin(&member).map_err(CargoError::Graph)?;
+    }
+
+    Ok(g)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const METADATA: &str = r#"{
+        "packages": [
+            {"id": "app 0.1.0 (path+file:///ws/app)", "name": "app", "version": "0.1.0"},
+            {"id": "core 0.1.0 (path+file:///ws/core)", "name": "core", "version": "0.1.0"},
+            {"id": "cc 1.0.0 (registry)", "name": "cc", "version": "1.0.0"},
+            {"id": "serde 1.0.0 (registry)", "name": "serde", "version": "1.0.0"}
+        ],
+        "workspace_members": [
+            "app 0.1.0 (path+file:///ws/app)",
+            "core 0.1.0 (path+file:///ws/core)"
+        ],
+        "resolve": {
+            "nodes": [
+                {"id": "app 0.1.0 (path+file:///ws/app)", "deps": [
+                    {"name": "core", "pkg": "core 0.1.0 (path+file:///ws/core)",
+                     "dep_kinds": [{"kind": null, "target": null}]},
+                    {"name": "cc", "pkg": "cc 1.0.0 (registry)",
+                     "dep_kinds": [{"kind": "build", "target": null}]}
+                ]},
+                {"id": "core 0.1.0 (path+file:///ws/core)", "deps": [
+                    {"name": "serde", "pkg": "serde 1.0.0 (registry)",
+                     "dep_kinds": [{"kind": null, "target": null}, {"kind": "dev", "target": null}]},
+                    {"name": "app", "pkg": "app 0.1.0 (path+file:///ws/app)",
+                     "dep_kinds": [{"kind": "dev", "target": null}]}
+                ]},
+                {"id": "cc 1.0.0 (registry)", "deps": []},
+                {"id": "serde 1.0.0 (registry)", "deps": []}
+            ],
+            "root": null
+        }
+    }"#;
+
+    #[test]
+    fn test_metadata_graph() {
+        let g = metadata_graph(METADATA, false).unwrap();
+        let (app, core, cc, serde) = (
+            "app@0.1.0".to_string(),
+            "core@0.1.0".to_string(),
+            "cc@1.0.0".to_string(),
+            "serde@1.0.0".to_string(),
+        );
+
+        assert!(g.depends_on(&app, &serde));
+        assert!(!g.depends_on(&core, &app));
+        assert_eq!(g.kinds(&app, &cc), HashSet::from([EdgeKind::Build]));
+        assert_eq!(g.kinds(&core, &serde), HashSet::from([EdgeKind::Runtime]));
+        assert!(g.is_pinned(&app));
+        assert!(g.is_pinned(&core));
+        assert!(!g.is_pinned(&serde));
+
+        // core dev-depends on app, which depends on core
+        assert!(matches!(
+            metadata_graph(METADATA, true),
+            Err(CargoError::Graph(Error::CircularDependency))
+        ));
+
+        assert!(matches!(
+            metadata_graph(r#"{"packages": [], "resolve": null}"#, false),
+            Err(CargoError::Metadata(_))
+        ));
+        assert!(matches!(
+            metadata_graph("{", false),
+            Err(CargoError::Metadata(_))
+        ));
+    }
+
+    #[test]
+    fn test_workspace_graph() {
+        let g = workspace_graph(env!("CARGO_MANIFEST_DIR"), true).unwrap();
+        let this = format!("soydepend@{}", env!("CARGO_PKG_VERSION"));
+
+        assert!(g.contains(&this));
+        assert!(g.is_pinned(&this));
+
+        let err = workspace_graph("/", false).unwrap_err();
+        assert!(matches!(err, CargoError::Cargo(_)), "{err}");
+
+        let source = Workspace {
+            dir: env!("CARGO_MANIFEST_DIR").into(),
+            dev: false,
+        };
+        assert!(source.load().unwrap().contains(&this));
+    }
+}
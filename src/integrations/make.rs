@@ -0,0 +1,230 @@
+//! Graphs of Makefile targets
+//!
+//! Rule lines `targets: prerequisites | order-only` become edges from each
+//! target to its prerequisites, with order-only prerequisites as weak edges.
+//! Variables are not expanded, and pattern and special rules are skipped.
+
+use std::path::PathBuf;
+
+use super::{GraphSource, SourceError};
+use crate::{Graph, LoadError};
+
+/// [`GraphSource`] reading the Makefile at path
+#[derive(Clone, Debug)]
+pub struct Makefile(pub PathBuf);
+
+impl GraphSource<String> for Makefile {
+    fn load(&self) -> Result<Graph<String>, SourceError> {
+        let source = std::fs::read_to_string(&self.0)?;
+        Ok(makefile_graph(&source)?)
+    }
+}
+
+/// Builds the graph of targets declared in Makefile source.
+///
+/// Targets may appear in many rules, and their prerequisites add up.
+pub fn makefile_graph(source: &str) -> Result<Graph<String>, LoadError> {
+    let mut g = Graph::new();
+
+    for (line, rule) in rules(source) {
+        let Some((targets, prerequisites)) = split_rule(&rule) else {
+            continue;
+        };
+
+        let targets: Vec<&str> = targets
+            .split_whitespace()
+            .filter(|target| !is_special(target))
+            .collect();
+
+        if targets.iter().any(|target| target.contains('%')) {
+            continue;
+        }
+
+        let (normal, order_only) = prerequisites.split_once('|').unwrap_or((prerequisites, ""));
+        for target in targets {
+            g.add_node(target.to_string());
+
+            let edges = normal
+                .split_whitespace()
+                .map(|p| (p, false))
+                .chain(order_only.split_whitespace().map(|p| (p, true)));
+
+            for (prerequisite, weak) in edges {
+                let (dependent, dependency) = (target.to_string(), prerequisite.to_string());
+                let result = match weak {
+                    false => g.depend(dependent, dependency).map(drop),
+                    true => g.depend_weak(dependent, dependency),
+                };
+
+                result.map_err(|err| {
+                    LoadError::new(line, target, format!("{prerequisite}: {err}"))
+                })?;
+            }
+        }
+    }
+
+    Ok(g)
+}
+
+/// Returns logical lines outside recipes and define blocks, with comments
+/// stripped and continuations joined, along with their first line numbers
+fn rules(source: &str) -> Vec<(usize, String)> {
+    let mut rules = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    let mut define = false;
+
+    for (i, raw) in source.lines().enumerate() {
+        if pending.is_none() {
+            let word = raw.split_whitespace().next();
+            if define {
+                define = word != Some("endef");
+                continue;
+            }
+
+            if raw.starts_with('\t') {
+                continue;
+            }
+
+            if word == Some("define") {
+                define = true;
+                continue;
+            }
+        }
+
+        let (start, mut text) = pending.take().unwrap_or((i + 1, String::new()));
+        match raw.strip_suffix('\\') {
+            Some(continued) => {
+                text.push_str(continued);
+                text.push(' ');
+                pending = Some((start, text));
+            }
+            None => {
+                text.push_str(raw);
+                rules.push((start, strip_comment(&text).to_string()));
+            }
+        }
+    }
+
+    if let Some((start, text)) = pending {
+        rules.push((start, strip_comment(&text).to_string()));
+    }
+
+    rules
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '#' if !escaped => return &line[..i],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+
+    line
+}
+
+/// Splits a rule line into targets and prerequisites, or returns None
+/// if the line is not a rule, e.g. an assignment or a directive
+fn split_rule(line: &str) -> Option<(&str, &str)> {
+    let colon = line.find(':')?;
+    if line[..colon].contains('=') {
+        return None;
+    }
+
+    let rest = line[colon + 1..].trim_start_matches(':');
+    if rest.starts_with('=') {
+        return None;
+    }
+
+    // Recipes may follow a semicolon
+    let prerequisites = rest.split_once(';').map_or(rest, |(p, _)| p);
+
+    // Target-specific variables and static pattern rules
+    if prerequisites.contains(['=', ':']) {
+        return None;
+    }
+
+    Some((&line[..colon], prerequisites))
+}
+
+/// Returns whether target is a special target like `.PHONY`
+fn is_special(target: &str) -> bool {
+    target
+        .strip_prefix('.')
+        .is_some_and(|name| name.chars().all(|c| c.is_ascii_uppercase() || c == '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAKEFILE: &str = "\
+CC := cc
+CFLAGS = -O2 # optimize
+.PHONY: all clean
+
+all: app docs
+
+app: main.o util.o | build
+\t$(CC) -o $@ $^
+
+main.o util.o: common.h
+%.o: %.c
+\t$(CC) $(CFLAGS) -c $<
+
+app: lib.a \\
+  config.h # joined
+debug: CFLAGS += -g
+
+define RECIPE
+fake: target
+endef
+
+docs:: README.md ; touch docs
+build:
+";
+
+    #[test]
+    fn test_makefile_graph() {
+        let g = makefile_graph(MAKEFILE).unwrap();
+        let key = |s: &str| s.to_string();
+
+        let mut targets: Vec<&str> = g.nodes().map(String::as_str).collect();
+        targets.sort();
+        assert_eq!(
+            targets,
+            [
+                "README.md",
+                "all",
+                "app",
+                "build",
+                "common.h",
+                "config.h",
+                "docs",
+                "lib.a",
+                "main.o",
+                "util.o",
+            ]
+        );
+
+        assert!(g.depends_on(&key("all"), &key("common.h")));
+        assert!(g.depends_on_directly(&key("app"), &key("config.h")));
+        assert!(g.is_weak(&key("app"), &key("build")));
+        assert!(!g.is_weak(&key("app"), &key("main.o")));
+        assert!(g.depends_on_directly(&key("docs"), &key("README.md")));
+    }
+
+    #[test]
+    fn test_makefile_errors() {
+        let err = makefile_graph("a: b\n\nb: a\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 3: b: a: circular dependency");
+
+        let err = makefile_graph("x: \\\n  x\n").unwrap_err();
+        assert_eq!(err, LoadError::new(1, "x", "x: depends on self"));
+
+        let source = Makefile("/nonexistent/Makefile".into());
+        source.load().expect_err("missing file");
+    }
+}
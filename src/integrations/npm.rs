@@ -0,0 +1,294 @@
+//! Graphs of npm projects, from `package-lock.json`
+//!
+//! Lockfile versions 2 and 3 are supported, which list every installed
+//! package under `packages`. Packages are keyed by `name@version`,
+//! or by name alone when unversioned. The root package and workspaces
+//! are pinned, and edges carry their [`EdgeKind`]s.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{GraphSource, SourceError};
+use crate::json::{self, Value};
+use crate::{EdgeKind, Error, Graph};
+
+#[derive(Debug)]
+pub enum NpmError {
+    /// Reading the lockfile failed
+    Io(std::io::Error),
+    /// Lockfile is not valid JSON, or misses fields
+    Lockfile(String),
+    /// Lockfile version 1, which predates `packages`
+    UnsupportedVersion,
+    /// A dependency is installed nowhere its dependent could load it from
+    Unresolved { package: String, dependency: String },
+    /// Dependencies form a cycle, which npm allows
+    Graph(Error<String>),
+}
+
+impl std::fmt::Display for NpmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "reading lockfile: {err}"),
+            Self::Lockfile(message) => write!(f, "invalid package-lock.json: {message}"),
+            Self::UnsupportedVersion => write!(f, "unsupported lockfile version, expected 2 or 3"),
+            Self::Unresolved {
+                package,
+                dependency,
+            } => write!(f, "{package}: unresolved dependency {dependency}"),
+            Self::Graph(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for NpmError {}
+
+/// [`GraphSource`] reading the lockfile at path
+#[derive(Clone, Debug)]
+pub struct Lockfile(pub PathBuf);
+
+impl GraphSource<String> for Lockfile {
+    fn load(&self) -> Result<Graph<String>, SourceError> {
+        Ok(read_lockfile(&self.0)?)
+    }
+}
+
+/// Reads the lockfile at path and builds its graph, see [`lockfile_graph`]
+pub fn read_lockfile<P: AsRef<Path>>(path: P) -> Result<Graph<String>, NpmError> {
+    let json = std::fs::read_to_string(path).map_err(NpmError::Io)?;
+    lockfile_graph(&json)
+}
+
+/// Builds the graph of installed packages from `package-lock.json` contents.
+///
+/// Dependencies resolve like Node does, from the closest `node_modules`
+/// up to the project root. Missing optional and peer dependencies are skipped.
+pub fn lockfile_graph(json: &str) -> Result<Graph<String>, NpmError> {
+    let lock = json::parse(json).map_err(|err| NpmError::Lockfile(err.to_string()))?;
+
+    match lock.get("lockfileVersion") {
+        Some(Value::Number(version)) if *version >= 2.0 => {}
+        Some(Value::Number(_)) => return Err(NpmError::UnsupportedVersion),
+        _ => return Err(NpmError::Lockfile("missing lockfileVersion".to_string())),
+    }
+
+    let packages: HashMap<&str, &Value> = lock
+        .get("packages")
+        .and_then(Value::as_object)
+        .ok_or(NpmError::UnsupportedVersion)?
+        .iter()
+        .map(|member| (member.key.as_str(), &member.value))
+        .collect();
+
+    let mut paths: Vec<&str> = packages
+        .iter()
+        .filter(|(_, entry)| entry.get("link") != Some(&Value::Bool(true)))
+        .map(|(path, _)| *path)
+        .collect();
+    paths.sort_unstable();
+
+    let key = |path: &str| -> Result<String, NpmError> {
+        let entry = packages[path];
+        let field = |name| entry.get(name).and_then(Value::as_str);
+        let name = match field("name") {
+            Some(name) => name,
+            None if path.is_empty() => lock
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| NpmError::Lockfile("missing root package name".to_string()))?,
+            None => path
+                .rsplit_once("node_modules/")
+                .map_or(path, |(_, name)| name),
+        };
+
+        Ok(match field("version") {
+            Some(version) => format!("{name}@{version}"),
+            None => name.to_string(),
+        })
+    };
+
+    let mut g = Graph::new();
+    for path in &paths {
+        g.add_node(key(path)?);
+    }
+
+    let sections = [
+        ("dependencies", EdgeKind::Runtime, true),
+        ("devDependencies", EdgeKind::Dev, true),
+        ("optionalDependencies", EdgeKind::Optional, false),
+        ("peerDependencies", EdgeKind::Runtime, false),
+    ];
+
+    for path in &paths {
+        let dependent = key(path)?;
+
+        for (section, kind, required) in sections {
+            for member in packages[path]
+                .get(section)
+                .and_then(Value::as_object)
+                .unwrap_or_default()
+            {
+                let Some(found) = resolve(&packages, path, &member.key) else {
+                    if required {
+                        return Err(NpmError::Unresolved {
+                            package: dependent,
+                            dependency: member.key.clone(),
+                        });
+                    }
+
+                    continue;
+                };
+
+                g.depend_kind(dependent.clone(), key(found)?, kind)
+                    .map_err(NpmError::Graph)?;
+            }
+        }
+
+        // Only the root and workspaces live outside node_modules
+        if !path.contains("node_modules/") {
+            g.pin(&dependent).map_err(NpmError::Graph)?;
+        }
+    }
+
+    Ok(g)
+}
+
+/// Returns the path of the package that name loads from the package at path,
+/// following workspace links
+fn resolve<'a>(packages: &HashMap<&'a str, &Value>, path: &str, name: &str) -> Option<&'a str> {
+    let mut dir = path;
+    loop {
+        let candidate = match dir {
+            "" => format!("node_modules/{name}"),
+            dir => format!("{dir}/node_modules/{name}"),
+        };
+
+        if let Some((found, entry)) = packages.get_key_value(candidate.as_str()) {
+            if entry.get("link") != Some(&Value::Bool(true)) {
+                return Some(found);
+            }
+
+            let target = entry.get("resolved").and_then(Value::as_str)?;
+            return packages.get_key_value(target).map(|(target, _)| *target);
+        }
+
+        if dir.is_empty() {
+            return None;
+        }
+
+        dir = match dir.rfind("/node_modules/") {
+            Some(i) => &dir[..i],
+            None if dir.starts_with("node_modules/") => "",
+            None => dir.rfind('/').map_or("", |i| &dir[..i]),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const LOCKFILE: &str = r#"{
+        "name": "shop",
+        "version": "1.0.0",
+        "lockfileVersion": 3,
+        "packages": {
+            "": {
+                "name": "shop",
+                "version": "1.0.0",
+                "workspaces": ["packages/ui"],
+                "dependencies": {"ui": "*", "debug": "^4.0.0"},
+                "devDependencies": {"@types/node": "^20.0.0"},
+                "optionalDependencies": {"fsevents": "^2.0.0"}
+            },
+            "node_modules/@types/node": {"version": "20.1.0", "dev": true},
+            "node_modules/debug": {
+                "version": "4.3.4",
+                "dependencies": {"ms": "2.1.2"}
+            },
+            "node_modules/debug/node_modules/ms": {"version": "2.1.2"},
+            "node_modules/ms": {"version": "2.0.0"},
+            "node_modules/ui": {"resolved": "packages/ui", "link": true},
+            "packages/ui": {
+                "name": "ui",
+                "dependencies": {"ms": "2.0.0"},
+                "peerDependencies": {"react": "*"}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_lockfile_graph() {
+        let g = lockfile_graph(LOCKFILE).unwrap();
+        let key = |s: &str| s.to_string();
+
+        assert_eq!(
+            g.nodes().cloned().collect::<HashSet<_>>(),
+            HashSet::from(
+                [
+                    "shop@1.0.0",
+                    "@types/node@20.1.0",
+                    "debug@4.3.4",
+                    "ms@2.1.2",
+                    "ms@2.0.0",
+                    "ui",
+                ]
+                .map(key)
+            )
+        );
+
+        // Nested node_modules win over hoisted ones
+        assert!(g.depends_on(&key("debug@4.3.4"), &key("ms@2.1.2")));
+        assert!(!g.depends_on(&key("debug@4.3.4"), &key("ms@2.0.0")));
+
+        // Workspaces resolve through their links and up to the root
+        assert!(g.depends_on(&key("shop@1.0.0"), &key("ui")));
+        assert!(g.depends_on(&key("ui"), &key("ms@2.0.0")));
+        assert_eq!(
+            g.kinds(&key("shop@1.0.0"), &key("@types/node@20.1.0")),
+            HashSet::from([EdgeKind::Dev])
+        );
+
+        assert!(g.is_pinned(&key("shop@1.0.0")));
+        assert!(g.is_pinned(&key("ui")));
+        assert!(!g.is_pinned(&key("debug@4.3.4")));
+    }
+
+    #[test]
+    fn test_lockfile_errors() {
+        assert!(matches!(
+            lockfile_graph(r#"{"lockfileVersion": 1, "dependencies": {}}"#),
+            Err(NpmError::UnsupportedVersion)
+        ));
+        assert!(matches!(lockfile_graph("{"), Err(NpmError::Lockfile(_))));
+
+        let err = lockfile_graph(
+            r#"{"lockfileVersion": 2, "packages": {
+                "": {"name": "app", "dependencies": {"left-pad": "*"}}
+            }}"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "app: unresolved dependency left-pad");
+
+        let cycle = r#"{"lockfileVersion": 3, "packages": {
+            "node_modules/a": {"version": "1.0.0", "dependencies": {"b": "*"}},
+            "node_modules/b": {"version": "1.0.0", "dependencies": {"a": "*"}}
+        }}"#;
+        assert!(matches!(
+            lockfile_graph(cycle),
+            Err(NpmError::Graph(Error::CircularDependency))
+        ));
+
+        assert!(matches!(
+            read_lockfile("/nonexistent/package-lock.json"),
+            Err(NpmError::Io(_))
+        ));
+
+        let err = Lockfile("/nonexistent/package-lock.json".into())
+            .load()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("reading lockfile: "), "{err}");
+    }
+}
@@ -0,0 +1,250 @@
+//! Graphs of systemd units, from unit files
+//!
+//! `Requires=`, `BindsTo=` and `Requisite=` become edges, while `Wants=`
+//! and ordering with `After=` and `Before=` become weak edges.
+//! `WantedBy=` and `RequiredBy=` under `[Install]` add edges from the named units.
+
+use std::path::PathBuf;
+
+use super::{GraphSource, SourceError};
+use crate::{Graph, LoadError};
+
+/// Suffixes of unit files read by [`UnitDir`]
+const SUFFIXES: &[&str] = &[
+    "automount",
+    "device",
+    "mount",
+    "path",
+    "scope",
+    "service",
+    "slice",
+    "socket",
+    "swap",
+    "target",
+    "timer",
+];
+
+/// [`GraphSource`] reading every unit file in a directory, e.g. `/etc/systemd/system`
+#[derive(Clone, Debug)]
+pub struct UnitDir(pub PathBuf);
+
+impl GraphSource<String> for UnitDir {
+    fn load(&self) -> Result<Graph<String>, SourceError> {
+        let mut units = Vec::new();
+        for entry in std::fs::read_dir(&self.0)? {
+            let path = entry?.path();
+            let is_unit = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| SUFFIXES.contains(&ext));
+
+            if !is_unit || !path.is_file() {
+                continue;
+            }
+
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            units.push((name, std::fs::read_to_string(&path)?));
+        }
+
+        units.sort();
+        let units = units
+            .iter()
+            .map(|(name, source)| (name.as_str(), source.as_str()));
+
+        Ok(units_graph(units)?)
+    }
+}
+
+/// Builds the graph of units from their names and unit file contents.
+///
+/// Errors are keyed by unit and directive, e.g. `app.service.Requires`.
+pub fn units_graph<'a, I>(units: I) -> Result<Graph<String>, LoadError>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut g = Graph::new();
+    let units: Vec<_> = units.into_iter().collect();
+    for (name, _) in &units {
+        g.add_node(name.to_string());
+    }
+
+    for (name, source) in units {
+        for (directive, value, line) in directives(source) {
+            let (strong, reverse) = match directive.as_str() {
+                "Requires" | "BindsTo" | "Requisite" => (true, false),
+                "Wants" | "After" => (false, false),
+                "Before" | "WantedBy" => (false, true),
+                "RequiredBy" => (true, true),
+                _ => continue,
+            };
+
+            for other in value.split_whitespace() {
+                let (dependent, dependency) = match reverse {
+                    false => (name.to_string(), other.to_string()),
+                    true => (other.to_string(), name.to_string()),
+                };
+
+                let result = match strong {
+                    true => g.depend(dependent, dependency).map(drop),
+                    false => g.depend_weak(dependent, dependency),
+                };
+
+                result.map_err(|err| {
+                    LoadError::new(
+                        line,
+                        format!("{name}.{directive}"),
+                        format!("{other}: {err}"),
+                    )
+                })?;
+            }
+        }
+    }
+
+    Ok(g)
+}
+
+/// Returns dependency directives of a unit file with their lines,
+/// dropping earlier assignments of directives reset with an empty value
+fn directives(source: &str) -> Vec<(String, String, usize)> {
+    let mut directives: Vec<(String, String, usize)> = Vec::new();
+    let mut section = "";
+    let mut pending: Option<(usize, String)> = None;
+
+    for (i, raw) in source.lines().enumerate() {
+        let (start, mut line) = pending.take().unwrap_or((i + 1, String::new()));
+        if let Some(continued) = raw.strip_suffix('\\') {
+            line.push_str(continued);
+            line.push(' ');
+            pending = Some((start, line));
+            continue;
+        }
+
+        line.push_str(raw);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = match name {
+                "Unit" => "Unit",
+                "Install" => "Install",
+                _ => "",
+            };
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let key = key.trim();
+        let known = match section {
+            "Unit" => [
+                "Requires",
+                "BindsTo",
+                "Requisite",
+                "Wants",
+                "After",
+                "Before",
+            ]
+            .contains(&key),
+            "Install" => ["WantedBy", "RequiredBy"].contains(&key),
+            _ => false,
+        };
+
+        if !known {
+            continue;
+        }
+
+        let value = value.trim();
+        if value.is_empty() {
+            directives.retain(|(directive, _, _)| directive != key);
+            continue;
+        }
+
+        directives.push((key.to_string(), value.to_string(), start));
+    }
+
+    directives
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APP: &str = "\
+[Unit]
+Description=App
+# Requires=ignored.service
+Requires=db.service
+Wants=cache.service \\
+  metrics.service
+Wants=
+Wants=cache.service
+After=db.service network.target
+
+[Service]
+ExecStart=/usr/bin/app
+After=not-a-directive.service
+
+[Install]
+WantedBy=multi-user.target
+";
+
+    const DB: &str = "\
+[Unit]
+Before=app.service
+";
+
+    #[test]
+    fn test_units_graph() {
+        let g = units_graph([("app.service", APP), ("db.service", DB)]).unwrap();
+        let key = |s: &str| s.to_string();
+
+        let mut units: Vec<&str> = g.nodes().map(String::as_str).collect();
+        units.sort();
+        assert_eq!(
+            units,
+            [
+                "app.service",
+                "cache.service",
+                "db.service",
+                "multi-user.target",
+                "network.target",
+            ]
+        );
+
+        let app = key("app.service");
+        assert!(!g.is_weak(&app, &key("db.service")));
+        assert!(g.is_weak(&app, &key("cache.service")));
+        assert!(g.is_weak(&app, &key("network.target")));
+        assert!(g.is_weak(&key("multi-user.target"), &app));
+        assert!(!g.contains(&key("metrics.service")));
+        assert!(!g.contains(&key("not-a-directive.service")));
+    }
+
+    #[test]
+    fn test_units_errors() {
+        let err = units_graph([
+            ("a.service", "[Unit]\nAfter=b.service\n"),
+            ("b.service", "[Unit]\n\nRequires=a.service\n"),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3: b.service.Requires: a.service: circular dependency"
+        );
+
+        let dir = std::env::temp_dir().join(format!("soydep-systemd-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.service"), APP).unwrap();
+        std::fs::write(dir.join("README"), "Requires=nothing").unwrap();
+
+        let g = UnitDir(dir.clone()).load().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(g.nodes().count(), 5);
+
+        UnitDir(dir).load().expect_err("missing directory");
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Returns whether other has the same shape, i.e. whether nodes
+    /// can be relabeled so that both graphs have exactly the same edges.
+    ///
+    /// Nodes are first told apart by repeatedly refining their degrees
+    /// with those of their neighbors, then matched by backtracking,
+    /// which is fast for dependency graphs but exponential at worst.
+    pub fn is_isomorphic<U>(&self, other: &Graph<U>) -> bool
+    where
+        U: Clone + Eq + Hash,
+    {
+        let (a, b) = (Indexed::new(self), Indexed::new(other));
+        if a.len() != b.len() || a.edges.len() != b.edges.len() {
+            return false;
+        }
+
+        let Some((colors_a, colors_b)) = refine(&a, &b) else {
+            return false;
+        };
+
+        // Match the most constrained nodes first
+        let mut class_sizes: HashMap<usize, usize> = HashMap::new();
+        for color in &colors_a {
+            *class_sizes.entry(*color).or_default() += 1;
+        }
+
+        let mut order: Vec<usize> = (0..a.len()).collect();
+        order.sort_by_key(|node| (class_sizes[&colors_a[*node]], colors_a[*node]));
+
+        let candidates: Vec<Vec<usize>> = order
+            .iter()
+            .map(|node| {
+                (0..b.len())
+                    .filter(|c| colors_b[*c] == colors_a[*node])
+                    .collect()
+            })
+            .collect();
+
+        // mapping[node of a] is its node in b
+        let mut mapping: Vec<Option<usize>> = vec![None; a.len()];
+        let mut used = vec![false; b.len()];
+        let mut next = vec![0; a.len()];
+        let mut depth = 0;
+
+        while depth < order.len() {
+            let node = order[depth];
+            if let Some(previous) = mapping[node].take() {
+                used[previous] = false;
+            }
+
+            let found = candidates[depth][next[depth]..]
+                .iter()
+                .position(|c| !used[*c] && consistent(&a, &b, &mapping, node, *c));
+
+            match found {
+                Some(offset) => {
+                    let candidate = candidates[depth][next[depth] + offset];
+                    next[depth] += offset + 1;
+                    mapping[node] = Some(candidate);
+                    used[candidate] = true;
+                    depth += 1;
+                }
+                None if depth == 0 => return false,
+                None => {
+                    next[depth] = 0;
+                    depth -= 1;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Returns whether relabel maps nodes one-to-one onto those of other,
+    /// and edges onto exactly the edges of other
+    pub fn is_isomorphic_under<U, F>(&self, other: &Graph<U>, relabel: F) -> bool
+    where
+        U: Clone + Eq + Hash,
+        F: Fn(&T) -> U,
+    {
+        let labels: HashMap<&T, U> = self.nodes.iter().map(|n| (n, relabel(n))).collect();
+        let images: HashSet<&U> = labels.values().collect();
+
+        images.len() == self.nodes.len()
+            && other.nodes.len() == self.nodes.len()
+            && images.iter().all(|label| other.contains(label))
+            && self.edges().count() == other.edges().count()
+            && self.edges().all(|(dependent, dependency)| {
+                other.depends_on_directly(&labels[dependent], &labels[dependency])
+            })
+    }
+}
+
+/// Graph with nodes numbered, for matching
+struct Indexed {
+    dependencies: Vec<Vec<usize>>,
+    dependents: Vec<Vec<usize>>,
+    edges: HashSet<(usize, usize)>,
+}
+
+impl Indexed {
+    fn new<T>(graph: &Graph<T>) -> Self
+    where
+        T: Clone + Eq + Hash,
+    {
+        let index: HashMap<&T, usize> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n, i))
+            .collect();
+        let mut indexed = Self {
+            dependencies: vec![Vec::new(); index.len()],
+            dependents: vec![Vec::new(); index.len()],
+            edges: HashSet::new(),
+        };
+
+        for (dependent, dependency) in graph.edges() {
+            let (dependent, dependency) = (index[dependent], index[dependency]);
+            indexed.dependencies[dependent].push(dependency);
+            indexed.dependents[dependency].push(dependent);
+            indexed.edges.insert((dependent, dependency));
+        }
+
+        indexed
+    }
+
+    fn len(&self) -> usize {
+        self.dependencies.len()
+    }
+}
+
+/// Colors nodes of both graphs alike, starting from their degrees and refining
+/// with colors of their neighbors until the partition settles.
+/// Returns None as soon as the graphs have different numbers of nodes of some color.
+fn refine(a: &Indexed, b: &Indexed) -> Option<(Vec<usize>, Vec<usize>)> {
+    let degrees = |g: &Indexed, node: usize| (g.dependencies[node].len(), g.dependents[node].len());
+
+    let mut ids: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut color = |key| {
+        let next = ids.len();
+        *ids.entry(key).or_insert(next)
+    };
+
+    let mut colors_a: Vec<usize> = (0..a.len()).map(|n| color(degrees(a, n))).collect();
+    let mut colors_b: Vec<usize> = (0..b.len()).map(|n| color(degrees(b, n))).collect();
+    let mut classes = 0;
+
+    loop {
+        let histogram = |colors: &[usize]| {
+            let mut histogram: HashMap<usize, usize> = HashMap::new();
+            for color in colors {
+                *histogram.entry(*color).or_default() += 1;
+            }
+            histogram
+        };
+
+        let histogram_a = histogram(&colors_a);
+        if histogram_a != histogram(&colors_b) {
+            return None;
+        }
+
+        if histogram_a.len() == classes {
+            return Some((colors_a, colors_b));
+        }
+        classes = histogram_a.len();
+
+        type Signature = (usize, Vec<usize>, Vec<usize>);
+        let mut ids: HashMap<Signature, usize> = HashMap::new();
+        let mut recolor = |g: &Indexed, colors: &[usize]| -> Vec<usize> {
+            (0..g.len())
+                .map(|node| {
+                    let neighbors = |edges: &[usize]| {
+                        let mut neighbors: Vec<usize> = edges.iter().map(|n| colors[*n]).collect();
+                        neighbors.sort_unstable();
+                        neighbors
+                    };
+
+                    let signature = (
+                        colors[node],
+                        neighbors(&g.dependencies[node]),
+                        neighbors(&g.dependents[node]),
+                    );
+
+                    let next = ids.len();
+                    *ids.entry(signature).or_insert(next)
+                })
+                .collect()
+        };
+
+        colors_a = recolor(a, &colors_a);
+        colors_b = recolor(b, &colors_b);
+    }
+}
+
+/// Returns whether mapping node of a to candidate of b keeps every edge
+/// between node and already mapped nodes
+fn consistent(
+    a: &Indexed,
+    b: &Indexed,
+    mapping: &[Option<usize>],
+    node: usize,
+    candidate: usize,
+) -> bool {
+    let dependencies = a.dependencies[node]
+        .iter()
+        .filter_map(|n| mapping[*n])
+        .all(|dependency| b.edges.contains(&(candidate, dependency)));
+
+    let dependents = a.dependents[node]
+        .iter()
+        .filter_map(|n| mapping[*n])
+        .all(|dependent| b.edges.contains(&(dependent, candidate)));
+
+    dependencies && dependents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&'static str, &'static str)]) -> Graph<&'static str> {
+        let mut g = Graph::new();
+        for (dependent, dependency) in edges {
+            g.depend(*dependent, *dependency).unwrap();
+        }
+        g
+    }
+
+    #[test]
+    fn test_is_isomorphic() {
+        let a = graph(&[
+            ("app", "http"),
+            ("app", "db"),
+            ("http", "tls"),
+            ("db", "tls"),
+        ]);
+        let mut b = Graph::new();
+        for (dependent, dependency) in [(1, 2), (1, 3), (2, 4), (3, 4)] {
+            b.depend(dependent, dependency).unwrap();
+        }
+
+        assert!(a.is_isomorphic(&b));
+        assert!(b.is_isomorphic(&a));
+        assert!(a.is_isomorphic(&a));
+        assert!(Graph::<u8>::new().is_isomorphic(&Graph::<u8>::new()));
+
+        // Same degrees, different shape
+        let chain = graph(&[("a", "b"), ("b", "c"), ("d", "e")]);
+        let fork = graph(&[("a", "b"), ("c", "b"), ("d", "e")]);
+        assert!(!chain.is_isomorphic(&fork));
+
+        // Edge reversed
+        b.undepend(&3, &4).unwrap();
+        b.depend(4, 3).unwrap();
+        assert!(!a.is_isomorphic(&b));
+
+        let mut c = a.clone();
+        c.add_node("lonely");
+        assert!(!a.is_isomorphic(&c));
+    }
+
+    #[test]
+    fn test_is_isomorphic_backtracking() {
+        // Two chains of two and one of three, refinement cannot tell
+        // apart nodes of same-length chains, so matching must backtrack
+        let a = graph(&[("a", "b"), ("c", "d"), ("e", "f"), ("f", "g")]);
+        let b = graph(&[("x", "y"), ("y", "z"), ("p", "q"), ("r", "s")]);
+        assert!(a.is_isomorphic(&b));
+
+        // Regular-looking graphs, identical degrees everywhere
+        let mut cycles = Graph::new();
+        let mut cross = Graph::new();
+        for i in 0..4u8 {
+            cycles.depend(i, 4 + i).unwrap();
+            cycles.depend(i, 4 + (i + 1) % 4).unwrap();
+            cross.depend(i, 4 + i).unwrap();
+            cross.depend(i, 4 + (i + 2) % 4).unwrap();
+        }
+        assert!(!cycles.is_isomorphic(&cross));
+
+        let mut dense = Graph::new();
+        let mut renamed = Graph::new();
+        for i in 0..30u32 {
+            for j in (0..i).filter(|j| (i * 7 + j * 3) % 5 == 0) {
+                dense.depend(i, j).unwrap();
+                renamed
+                    .depend(format!("n{}", 100 - i), format!("n{}", 100 - j))
+                    .unwrap();
+            }
+        }
+        assert!(dense.is_isomorphic(&renamed));
+        assert!(dense.is_isomorphic_under(&renamed, |n| format!("n{}", 100 - n)));
+    }
+
+    #[test]
+    fn test_is_isomorphic_under() {
+        let a = graph(&[("app", "http"), ("http", "tls")]);
+        let mut b = Graph::new();
+        b.depend("APP".to_string(), "HTTP".to_string()).unwrap();
+        b.depend("HTTP".to_string(), "TLS".to_string()).unwrap();
+
+        assert!(a.is_isomorphic_under(&b, |n| n.to_uppercase()));
+        assert!(!a.is_isomorphic_under(&b, |_| "APP".to_string()));
+        assert!(!a.is_isomorphic_under(&b, |n| n.to_string()));
+
+        let swapped = |n: &&str| match *n {
+            "app" => "HTTP".to_string(),
+            "http" => "APP".to_string(),
+            _ => "TLS".to_string(),
+        };
+        assert!(!a.is_isomorphic_under(&b, swapped));
+    }
+}
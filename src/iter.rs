@@ -0,0 +1,354 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+use crate::{Edges, Graph};
+
+/// Which relation a traversal follows from its start node
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Towards what the start node depends on
+    Dependencies,
+    /// Towards what depends on the start node
+    Dependents,
+}
+
+impl Direction {
+    /// Returns the opposite direction
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::Dependencies => Self::Dependents,
+            Self::Dependents => Self::Dependencies,
+        }
+    }
+
+    pub(crate) fn edges<T>(self, graph: &Graph<T>) -> &Edges<T>
+    where
+        T: Clone + Eq + Hash,
+    {
+        match self {
+            Self::Dependencies => &graph.dependencies,
+            Self::Dependents => &graph.dependents,
+        }
+    }
+}
+
+/// Edge filter keeping every edge, used by unfiltered traversals
+pub type KeepAll<T> = fn(&T, &T) -> bool;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Returns a lazy depth-first walk over dependencies from node,
+    /// yielding each node before its dependencies, starting with node
+    pub fn dfs_pre(&self, node: &T) -> DfsPre<'_, T> {
+        self.dfs_pre_filtered(node, keep_all)
+    }
+
+    /// Like [`Graph::dfs_pre`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dfs_pre_filtered<F>(&self, node: &T, keep: F) -> DfsPre<'_, T, F>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        DfsPre {
+            step: Step::new(self, Direction::Dependencies, keep),
+            stack: self.nodes.get(node).into_iter().collect(),
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Returns a lazy depth-first walk over dependencies from node,
+    /// yielding each node after all of its dependencies, ending with node.
+    ///
+    /// This is the evaluation order of node.
+    pub fn dfs_post(&self, node: &T) -> DfsPost<'_, T> {
+        self.dfs_post_filtered(node, keep_all)
+    }
+
+    /// Like [`Graph::dfs_post`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dfs_post_filtered<F>(&self, node: &T, keep: F) -> DfsPost<'_, T, F>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.postorder(node, Direction::Dependencies, keep)
+    }
+
+    /// Returns a lazy breadth-first walk from node in direction,
+    /// yielding each node with its least number of edges away from node,
+    /// starting with node at depth 0
+    pub fn bfs(&self, node: &T, direction: Direction) -> Bfs<'_, T> {
+        self.bfs_filtered(node, direction, keep_all)
+    }
+
+    /// Like [`Graph::bfs`], but follows only edges for which
+    /// keep(dependent, dependency) holds, whichever the direction
+    pub fn bfs_filtered<F>(&self, node: &T, direction: Direction, keep: F) -> Bfs<'_, T, F>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let start = self.nodes.get(node);
+        Bfs {
+            step: Step::new(self, direction, keep),
+            queue: start.map(|n| (n, 0)).into_iter().collect(),
+            visited: start.into_iter().collect(),
+        }
+    }
+
+    /// Returns a postorder walk from node in direction
+    pub(crate) fn postorder<F>(&self, node: &T, direction: Direction, keep: F) -> DfsPost<'_, T, F>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        DfsPost {
+            step: Step::new(self, direction, keep),
+            stack: self
+                .nodes
+                .get(node)
+                .map(|n| (n, false))
+                .into_iter()
+                .collect(),
+            visited: HashSet::new(),
+        }
+    }
+}
+
+pub(crate) fn keep_all<T>(_: &T, _: &T) -> bool {
+    true
+}
+
+/// Finds the neighbors of nodes in a direction, through kept edges only
+#[derive(Clone, Debug)]
+pub(crate) struct Step<'a, T, F> {
+    edges: &'a Edges<T>,
+    direction: Direction,
+    keep: F,
+}
+
+impl<'a, T, F> Step<'a, T, F>
+where
+    T: Clone + Eq + Hash,
+    F: Fn(&T, &T) -> bool,
+{
+    pub(crate) fn new(graph: &'a Graph<T>, direction: Direction, keep: F) -> Self {
+        Self {
+            edges: direction.edges(graph),
+            direction,
+            keep,
+        }
+    }
+
+    pub(crate) fn next<'s>(&'s self, node: &'a T) -> impl Iterator<Item = &'a T> + 's {
+        self.edges
+            .get(node)
+            .into_iter()
+            .flatten()
+            .filter(move |next| match self.direction {
+                Direction::Dependencies => (self.keep)(node, next),
+                Direction::Dependents => (self.keep)(next, node),
+            })
+    }
+}
+
+/// Preorder iterator, see [`Graph::dfs_pre`]
+#[derive(Clone, Debug)]
+pub struct DfsPre<'a, T, F = KeepAll<T>> {
+    step: Step<'a, T, F>,
+    stack: Vec<&'a T>,
+    visited: HashSet<&'a T>,
+}
+
+impl<'a, T, F> Iterator for DfsPre<'a, T, F>
+where
+    T: Clone + Eq + Hash,
+    F: Fn(&T, &T) -> bool,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            if !self.visited.insert(node) {
+                continue;
+            }
+
+            let next = self.step.next(node);
+            self.stack
+                .extend(next.filter(|next| !self.visited.contains(next)));
+
+            return Some(node);
+        }
+
+        None
+    }
+}
+
+/// Postorder iterator, see [`Graph::dfs_post`]
+#[derive(Clone, Debug)]
+pub struct DfsPost<'a, T, F = KeepAll<T>> {
+    step: Step<'a, T, F>,
+    /// Nodes with whether they have been expanded
+    stack: Vec<(&'a T, bool)>,
+    visited: HashSet<&'a T>,
+}
+
+impl<'a, T, F> Iterator for DfsPost<'a, T, F>
+where
+    T: Clone + Eq + Hash,
+    F: Fn(&T, &T) -> bool,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, expanded)) = self.stack.pop() {
+            if expanded {
+                return Some(node);
+            }
+
+            // Nodes may be pushed many times, but expanded only the first time
+            if !self.visited.insert(node) {
+                continue;
+            }
+
+            self.stack.push((node, true));
+
+            let next = self.step.next(node);
+            self.stack.extend(
+                next.filter(|next| !self.visited.contains(next))
+                    .map(|next| (next, false)),
+            );
+        }
+
+        None
+    }
+}
+
+/// Breadth-first iterator, see [`Graph::bfs`]
+#[derive(Clone, Debug)]
+pub struct Bfs<'a, T, F = KeepAll<T>> {
+    step: Step<'a, T, F>,
+    queue: VecDeque<(&'a T, usize)>,
+    /// Nodes ever queued, so each is yielded at its least depth
+    visited: HashSet<&'a T>,
+}
+
+impl<'a, T, F> Iterator for Bfs<'a, T, F>
+where
+    T: Clone + Eq + Hash,
+    F: Fn(&T, &T) -> bool,
+{
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth) = self.queue.pop_front()?;
+        for next in self.step.next(node) {
+            if self.visited.insert(next) {
+                self.queue.push_back((next, depth + 1));
+            }
+        }
+
+        Some((node, depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph<&'static str> {
+        let mut g = crate::test_graph();
+        g.depend("app", "libc").unwrap();
+        g.depend("http", "log").unwrap();
+        g
+    }
+
+    #[test]
+    fn test_dfs_pre() {
+        let g = graph();
+        let order: Vec<&str> = g.dfs_pre(&"app").copied().collect();
+
+        assert_eq!(order[0], "app");
+        assert_eq!(order.len(), 5);
+        assert_eq!(
+            order.iter().copied().collect::<HashSet<_>>(),
+            HashSet::from(["app", "http", "log", "tls", "libc"])
+        );
+
+        // Each node but the first is reached from an earlier one
+        for (i, node) in order.iter().enumerate().skip(1) {
+            assert!(order[..i].iter().any(|n| g.depends_on_directly(n, node)));
+        }
+
+        assert_eq!(g.dfs_pre(&"libc").collect::<Vec<_>>(), [&"libc"]);
+        assert_eq!(g.dfs_pre(&"missing").count(), 0);
+    }
+
+    #[test]
+    fn test_dfs_post() {
+        let g = graph();
+        let order: Vec<&str> = g.dfs_post(&"app").copied().collect();
+
+        assert_eq!(order.len(), 5);
+        assert_eq!(order[0], "libc");
+        assert_eq!(order[4], "app");
+        for (i, node) in order.iter().enumerate() {
+            assert!(order[i + 1..]
+                .iter()
+                .all(|later| !g.depends_on(node, later)));
+        }
+
+        // Lazy, so taking the first few does not walk the rest
+        assert_eq!(g.dfs_post(&"http").take(1).collect::<Vec<_>>(), [&"libc"]);
+        assert_eq!(g.dfs_post(&"missing").count(), 0);
+    }
+
+    #[test]
+    fn test_filtered() {
+        let g = graph();
+        let not_log = |_: &&str, dependency: &&str| *dependency != "log";
+
+        let pre: HashSet<&str> = g.dfs_pre_filtered(&"app", not_log).copied().collect();
+        assert_eq!(pre, HashSet::from(["app", "http", "tls", "libc"]));
+
+        let post: Vec<&str> = g.dfs_post_filtered(&"http", not_log).copied().collect();
+        assert_eq!(post, ["libc", "tls", "http"]);
+
+        // Filters see edges as dependent then dependency either way
+        let walk: Vec<(&str, usize)> = g
+            .bfs_filtered(&"libc", Direction::Dependents, |dependent, _| {
+                *dependent != "tls"
+            })
+            .map(|(n, d)| (*n, d))
+            .collect();
+        assert_eq!(walk.len(), 4);
+        assert!(walk.contains(&("app", 1)));
+        assert!(walk.contains(&("http", 2)));
+    }
+
+    #[test]
+    fn test_bfs() {
+        let g = graph();
+
+        let walk: Vec<(&str, usize)> = g
+            .bfs(&"app", Direction::Dependencies)
+            .map(|(n, d)| (*n, d))
+            .collect();
+        assert_eq!(walk[0], ("app", 0));
+        assert_eq!(walk[4], ("tls", 2));
+        let mut first: Vec<_> = walk[1..4].to_vec();
+        first.sort();
+        assert_eq!(first, [("http", 1), ("libc", 1), ("log", 1)]);
+
+        let depths: Vec<usize> = g
+            .bfs(&"libc", Direction::Dependents)
+            .map(|(_, d)| d)
+            .collect();
+        assert_eq!(depths, [0, 1, 1, 1, 2]);
+
+        assert_eq!(
+            g.bfs(&"tls", Direction::Dependents.reverse()).last(),
+            Some((&"libc", 1))
+        );
+        assert_eq!(g.bfs(&"missing", Direction::Dependencies).count(), 0);
+    }
+}
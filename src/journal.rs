@@ -0,0 +1,1086 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::history::{Attributes, Change, Key, Recorder};
+use crate::{insert_to_deps, EdgeKind, Error, Graph, Level, Listener};
+
+const SNAPSHOT: &str = "snapshot";
+const JOURNAL: &str = "journal";
+/// Version of the journal and snapshot format, stated on their first line.
+/// Files without it predate escaping, and are rewritten when opened.
+///
+/// Version 2 adds levels, kinds, weights and pins.
+const VERSION: u32 = 2;
+
+/// Entries read by [`replay`] from a journal or snapshot
+struct Replayed {
+    entries: usize,
+    /// Whether the file starts with a version line
+    versioned: bool,
+    /// Length of the file up to the end of its last complete line
+    len: u64,
+}
+
+/// File the journal is appended to, a trait so that tests can fail writes
+trait Log: Write + Send + Sync {
+    fn len(&self) -> std::io::Result<u64>;
+    fn set_len(&self, len: u64) -> std::io::Result<()>;
+    fn sync_data(&self) -> std::io::Result<()>;
+}
+
+/// Line of a journal or snapshot
+enum Entry<T> {
+    Change(Change<T>),
+    SetMeta(T, String, String),
+    RemoveMeta(T, String),
+    /// Level of an existing edge, telling whether it is weak
+    Level(T, T, Level),
+    /// Explicit kinds of an existing edge, where none means runtime
+    Kinds(T, T, Vec<EdgeKind>),
+    EdgeWeight(T, T, Option<u64>),
+    NodeWeight(T, Option<u64>),
+    Pin(T, bool),
+}
+
+/// Error from a mutation of a [`JournaledGraph`], which is not applied
+#[derive(Debug)]
+pub enum JournalError<T> {
+    Io(std::io::Error),
+    Graph(Error<T>),
+}
+
+/// Graph wrapper appending every mutation to a write-ahead log in a directory,
+/// so that its state survives crashes.
+///
+/// A mutation whose entries cannot be written is undone, and the journal
+/// is truncated back to where it was, so that memory and disk never disagree.
+/// Should the truncation fail too, every later edit fails until compaction.
+///
+/// [`JournaledGraph::open`] loads the last snapshot and replays the log on top of it.
+/// Once the log grows past the compaction threshold, the whole graph is written
+/// to a new snapshot and the log is truncated.
+///
+/// Nodes are stored as their [`Display`](std::fmt::Display) form, one entry per line,
+/// with backslashes, tabs and newlines escaped.
+///
+/// Along with nodes and edges, the journal keeps metadata, weights, pins,
+/// and the kinds and levels of edges, including weak edges.
+/// Groups, providers, conflicts and conditions are not journaled,
+/// and are gone once the graph is opened again.
+pub struct JournaledGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    graph: Graph<T>,
+    recorder: Arc<Recorder<T>>,
+    dir: PathBuf,
+    journal: Box<dyn Log>,
+    entries: usize,
+    compact_after: usize,
+    /// Whether the journal may end with entries of an undone mutation
+    poisoned: bool,
+}
+
+impl<T> JournaledGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + 'static + std::fmt::Display + std::str::FromStr,
+{
+    /// Opens the journal in dir, creating it if it does not exist
+    pub fn open<P>(dir: P) -> std::io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut graph = Graph::new();
+        let snapshot = replay(&mut graph, &dir.join(SNAPSHOT))?;
+        let log = replay(&mut graph, &dir.join(JOURNAL))?;
+        let journal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(JOURNAL))?;
+
+        let recorder = Arc::new(Recorder::new());
+        graph.subscribe(recorder.clone());
+
+        let mut g = Self {
+            graph,
+            recorder,
+            dir,
+            journal: Box::new(journal),
+            entries: log.entries,
+            compact_after: 1024,
+            poisoned: false,
+        };
+
+        if snapshot.legacy() || log.legacy() {
+            g.compact()?;
+        } else if !log.versioned {
+            // New journal, or one torn while writing its header
+            g.journal.set_len(0)?;
+            write_header(&mut g.journal)?;
+            g.journal.sync_data()?;
+        } else if g.journal.len()? > log.len {
+            // Drop the torn last line, so that appends start on a line of their own
+            g.journal.set_len(log.len)?;
+            g.journal.sync_data()?;
+        }
+
+        Ok(g)
+    }
+
+    /// Sets how many log entries trigger compaction, 0 disables it
+    pub fn compact_after(&mut self, entries: usize) {
+        self.compact_after = entries;
+    }
+
+    /// Returns the graph, leaving the journal on disk as is
+    pub fn into_inner(mut self) -> Graph<T> {
+        let recorder: Arc<dyn Listener<T>> = self.recorder.clone();
+        self.graph.unsubscribe(&recorder);
+
+        self.graph
+    }
+
+    /// Applies f to the graph, durably logging the mutations it made.
+    ///
+    /// If they cannot be logged, the mutations are undone and the error is returned.
+    pub fn edit<R, F>(&mut self, f: F) -> std::io::Result<R>
+    where
+        F: FnOnce(&mut Graph<T>) -> R,
+    {
+        if self.poisoned {
+            return Err(std::io::Error::other(
+                "journal holds an undone mutation, compact it or open it again",
+            ));
+        }
+
+        self.graph.save_touched();
+        let result = f(&mut self.graph);
+
+        let changes = self.recorder.take();
+        let touched: Vec<_> = self.graph.take_touched().into_iter().collect();
+
+        let mut entries: Vec<Entry<T>> = changes.iter().cloned().map(Entry::Change).collect();
+        for (key, before) in &touched {
+            entries.extend(attribute_entries(&self.graph, key.clone(), before.clone()));
+        }
+
+        if let Err(err) = self.append(&entries) {
+            for change in changes.iter().rev() {
+                self.graph.apply(&change.inverse());
+            }
+
+            for (key, before) in &touched {
+                self.graph.set_attributes(key, before);
+            }
+            self.recorder.take();
+
+            return Err(err);
+        }
+
+        // Entries are durable by now, so a failed compaction keeps the mutation
+        self.entries += entries.len();
+        if self.compact_after != 0 && self.entries >= self.compact_after {
+            self.compact()?;
+        }
+
+        Ok(result)
+    }
+
+    /// Like [`Graph::set_meta`], durably logging the metadata
+    pub fn set_meta(
+        &mut self,
+        node: &T,
+        key: &str,
+        value: &str,
+    ) -> Result<Option<String>, JournalError<T>> {
+        Ok(self.edit(|g| g.set_meta(node, key, value))??)
+    }
+
+    /// Like [`Graph::remove_meta`], durably logging the removal
+    pub fn remove_meta(&mut self, node: &T, key: &str) -> std::io::Result<Option<String>> {
+        self.edit(|g| g.remove_meta(node, key))
+    }
+
+    /// Appends entries to the journal and syncs it.
+    ///
+    /// On failure the journal is truncated back to its length before,
+    /// so that no torn or unsynced entry is left for the next append to follow.
+    fn append(&mut self, entries: &[Entry<T>]) -> std::io::Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let len = self.journal.len()?;
+        let written =
+            write_entries(&mut *self.journal, entries).and_then(|()| self.journal.sync_data());
+        if written.is_err() {
+            let truncated = self
+                .journal
+                .set_len(len)
+                .and_then(|()| self.journal.sync_data());
+            self.poisoned = truncated.is_err();
+        }
+
+        written
+    }
+
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, JournalError<T>> {
+        Ok(self.edit(|g| g.depend(dependent, dependency))??)
+    }
+
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), JournalError<T>> {
+        Ok(self.edit(|g| g.undepend(dependent, dependency))??)
+    }
+
+    pub fn remove(&mut self, target: &T) -> Result<(), JournalError<T>> {
+        Ok(self.edit(|g| g.remove(target))??)
+    }
+
+    pub fn remove_force(&mut self, target: &T) -> std::io::Result<()> {
+        self.edit(|g| g.remove_force(target))
+    }
+
+    pub fn remove_autoremove(&mut self, target: &T) -> std::io::Result<()> {
+        self.edit(|g| g.remove_autoremove(target))
+    }
+
+    /// Writes the graph to a new snapshot and truncates the log.
+    ///
+    /// The snapshot is renamed into place before the log is truncated,
+    /// and replaying an already applied log is harmless,
+    /// so a crash at any point leaves a recoverable state.
+    pub fn compact(&mut self) -> std::io::Result<()> {
+        let tmp = self.dir.join(format!("{SNAPSHOT}.tmp"));
+
+        let file = File::create(&tmp)?;
+        let mut w = BufWriter::new(&file);
+        write_header(&mut w)?;
+        for node in &self.graph.nodes {
+            write_entry(&mut w, &Entry::Change(Change::NodeAdded(node.clone())))?;
+        }
+
+        for (dependent, dependencies) in &self.graph.dependencies {
+            for dependency in dependencies {
+                let change = Change::EdgeAdded(dependent.clone(), dependency.clone());
+                write_entry(&mut w, &Entry::Change(change))?;
+            }
+        }
+
+        for (dependency, dependents) in &self.graph.weak {
+            for dependent in dependents {
+                let level = self.graph.level(dependent, dependency);
+                let level = level.expect("weak edges are edges");
+                let entry = Entry::Level(dependent.clone(), dependency.clone(), level);
+                write_entry(&mut w, &entry)?;
+            }
+        }
+
+        for (dependent, kinds) in &self.graph.kinds {
+            for (dependency, kinds) in kinds {
+                let entry = Entry::Kinds(
+                    dependent.clone(),
+                    dependency.clone(),
+                    kinds.iter().copied().collect(),
+                );
+                write_entry(&mut w, &entry)?;
+            }
+        }
+
+        for (dependent, weights) in &self.graph.weights.edges {
+            for (dependency, weight) in weights {
+                let entry = Entry::EdgeWeight(dependent.clone(), dependency.clone(), Some(*weight));
+                write_entry(&mut w, &entry)?;
+            }
+        }
+
+        for (node, weight) in &self.graph.weights.nodes {
+            write_entry(&mut w, &Entry::NodeWeight(node.clone(), Some(*weight)))?;
+        }
+
+        for node in &self.graph.pinned {
+            write_entry(&mut w, &Entry::Pin(node.clone(), true))?;
+        }
+
+        for (node, meta) in &self.graph.meta {
+            for (key, value) in meta {
+                let entry = Entry::SetMeta(node.clone(), key.clone(), value.clone());
+                write_entry(&mut w, &entry)?;
+            }
+        }
+
+        w.flush()?;
+        drop(w);
+        file.sync_all()?;
+
+        std::fs::rename(&tmp, self.dir.join(SNAPSHOT))?;
+        self.journal.set_len(0)?;
+        write_header(&mut self.journal)?;
+        self.journal.sync_data()?;
+        self.entries = 0;
+        self.poisoned = false;
+
+        Ok(())
+    }
+}
+
+impl Replayed {
+    /// Returns whether the file has entries written before versioning
+    fn legacy(&self) -> bool {
+        !self.versioned && self.entries > 0
+    }
+}
+
+/// Applies every complete entry in path to graph.
+///
+/// A torn last line, left by a crash mid-write, is ignored.
+fn replay<T>(graph: &mut Graph<T>, path: &Path) -> std::io::Result<Replayed>
+where
+    T: Clone + Eq + std::hash::Hash + std::str::FromStr,
+{
+    let mut replayed = Replayed {
+        entries: 0,
+        versioned: false,
+        len: 0,
+    };
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(replayed),
+        Err(err) => return Err(err),
+    };
+
+    let mut r = BufReader::new(file);
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        let n = r.read_line(&mut line)?;
+        if n == 0 || !line.ends_with('\n') {
+            break;
+        }
+
+        replayed.len += n as u64;
+
+        let line = line.trim_end_matches('\n');
+        if replayed.entries == 0 && !replayed.versioned {
+            if let Some(version) = line.strip_prefix("v\t") {
+                check_version(version)?;
+                replayed.versioned = true;
+                continue;
+            }
+        }
+
+        match read_entry(line, replayed.versioned)? {
+            Entry::Change(change) => graph.apply(&change),
+            Entry::SetMeta(node, key, value) => {
+                // Metadata of nodes removed later in the log
+                if graph.contains(&node) {
+                    graph.set_meta(&node, &key, value).ok();
+                }
+            }
+            Entry::RemoveMeta(node, key) => {
+                graph.remove_meta(&node, &key);
+            }
+            // Attributes of edges removed later in the log
+            Entry::Level(dependent, dependency, _) | Entry::Kinds(dependent, dependency, _)
+                if !graph.depends_on_directly(&dependent, &dependency) => {}
+            Entry::Level(dependent, dependency, level) => {
+                graph.strengthen(&dependent, &dependency);
+                if level != Level::Depends {
+                    insert_to_deps(&mut graph.weak, dependency.clone(), dependent.clone());
+                }
+                if level == Level::Suggests {
+                    insert_to_deps(&mut graph.suggested, dependency, dependent);
+                }
+            }
+            Entry::Kinds(dependent, dependency, kinds) => {
+                graph.forget_kinds(&dependent, &dependency);
+                if !kinds.is_empty() {
+                    graph
+                        .kinds
+                        .entry(dependent)
+                        .or_default()
+                        .insert(dependency, kinds.into_iter().collect());
+                }
+            }
+            Entry::EdgeWeight(dependent, dependency, weight) => match weight {
+                Some(weight) => {
+                    graph.set_edge_weight(&dependent, &dependency, weight).ok();
+                }
+                None => graph.forget_edge_weight(&dependent, &dependency),
+            },
+            Entry::NodeWeight(node, weight) => match weight {
+                Some(weight) => {
+                    graph.set_weight(&node, weight).ok();
+                }
+                None => graph.forget_weight(&node),
+            },
+            Entry::Pin(node, true) => {
+                graph.pin(&node).ok();
+            }
+            Entry::Pin(node, false) => {
+                graph.unpin(&node);
+            }
+        }
+
+        replayed.entries += 1;
+    }
+
+    Ok(replayed)
+}
+
+/// Returns entries logging the change of key since before,
+/// skipping attributes dropped along with their nodes or edges
+fn attribute_entries<T>(graph: &Graph<T>, key: Key<T>, before: Attributes) -> Vec<Entry<T>>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    let mut entries = Vec::new();
+    let after = graph.attributes(&key);
+
+    match (key, after, before) {
+        (Key::Node(node), _, _) | (Key::Meta(node, _), _, _) if !graph.contains(&node) => {}
+        (Key::Pair(a, b), _, _) if !graph.depends_on_directly(&a, &b) => {}
+        (
+            Key::Node(node),
+            Attributes::Node { weight, pinned },
+            Attributes::Node {
+                weight: weight_before,
+                pinned: pinned_before,
+            },
+        ) => {
+            if weight != weight_before {
+                entries.push(Entry::NodeWeight(node.clone(), weight));
+            }
+            if pinned != pinned_before {
+                entries.push(Entry::Pin(node, pinned));
+            }
+        }
+        (Key::Meta(node, key), Attributes::Meta(value), before) => {
+            if before != Attributes::Meta(value.clone()) {
+                entries.push(match value {
+                    Some(value) => Entry::SetMeta(node, key, value),
+                    None => Entry::RemoveMeta(node, key),
+                });
+            }
+        }
+        (Key::Pair(a, b), Attributes::Pair(pair), Attributes::Pair(before)) => {
+            if (pair.weak, pair.suggested) != (before.weak, before.suggested) {
+                let level = graph.level(&a, &b).expect("edge exists");
+                entries.push(Entry::Level(a.clone(), b.clone(), level));
+            }
+            if pair.kinds != before.kinds {
+                let kinds = pair.kinds.into_iter().flatten().collect();
+                entries.push(Entry::Kinds(a.clone(), b.clone(), kinds));
+            }
+            if pair.weight != before.weight {
+                entries.push(Entry::EdgeWeight(a, b, pair.weight));
+            }
+        }
+        _ => unreachable!("attributes of another key"),
+    }
+
+    entries
+}
+
+fn write_entries<T, W>(w: W, entries: &[Entry<T>]) -> std::io::Result<()>
+where
+    T: std::fmt::Display,
+    W: Write,
+{
+    let mut w = BufWriter::new(w);
+    for entry in entries {
+        write_entry(&mut w, entry)?;
+    }
+
+    w.flush()
+}
+
+fn write_header<W>(w: &mut W) -> std::io::Result<()>
+where
+    W: Write,
+{
+    writeln!(w, "v\t{VERSION}")
+}
+
+fn check_version(version: &str) -> std::io::Result<()> {
+    match version.parse::<u32>() {
+        Ok(version) if (1..=VERSION).contains(&version) => Ok(()),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unsupported journal version {version}, expected at most {VERSION}"),
+        )),
+    }
+}
+
+fn write_entry<T, W>(w: &mut W, entry: &Entry<T>) -> std::io::Result<()>
+where
+    T: std::fmt::Display,
+    W: Write,
+{
+    let e = |field: &dyn std::fmt::Display| escape(&field.to_string());
+
+    match entry {
+        Entry::Change(Change::NodeAdded(node)) => writeln!(w, "+n\t{}", e(node)),
+        Entry::Change(Change::NodeRemoved(node)) => writeln!(w, "-n\t{}", e(node)),
+        Entry::Change(Change::EdgeAdded(dependent, dependency)) => {
+            writeln!(w, "+e\t{}\t{}", e(dependent), e(dependency))
+        }
+        Entry::Change(Change::EdgeRemoved(dependent, dependency)) => {
+            writeln!(w, "-e\t{}\t{}", e(dependent), e(dependency))
+        }
+        Entry::SetMeta(node, key, value) => {
+            writeln!(w, "+m\t{}\t{}\t{}", e(node), e(key), e(value))
+        }
+        Entry::RemoveMeta(node, key) => writeln!(w, "-m\t{}\t{}", e(node), e(key)),
+        Entry::Level(dependent, dependency, level) => {
+            writeln!(
+                w,
+                "=l\t{}\t{}\t{}",
+                e(dependent),
+                e(dependency),
+                level.name()
+            )
+        }
+        Entry::Kinds(dependent, dependency, kinds) => {
+            let mut kinds: Vec<&str> = kinds.iter().map(|kind| kind.name()).collect();
+            kinds.sort();
+            let kinds = kinds.join(",");
+            writeln!(w, "=k\t{}\t{}\t{kinds}", e(dependent), e(dependency))
+        }
+        Entry::EdgeWeight(dependent, dependency, Some(weight)) => {
+            writeln!(w, "+ew\t{}\t{}\t{weight}", e(dependent), e(dependency))
+        }
+        Entry::EdgeWeight(dependent, dependency, None) => {
+            writeln!(w, "-ew\t{}\t{}", e(dependent), e(dependency))
+        }
+        Entry::NodeWeight(node, Some(weight)) => writeln!(w, "+nw\t{}\t{weight}", e(node)),
+        Entry::NodeWeight(node, None) => writeln!(w, "-nw\t{}", e(node)),
+        Entry::Pin(node, true) => writeln!(w, "+p\t{}", e(node)),
+        Entry::Pin(node, false) => writeln!(w, "-p\t{}", e(node)),
+    }
+}
+
+/// Escapes the characters separating fields and entries
+fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Reverses [`escape`], keeping unknown escapes as they are
+fn unescape(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+/// Parses an entry, whose fields are escaped unless it predates versioning
+fn read_entry<T>(line: &str, escaped: bool) -> std::io::Result<Entry<T>>
+where
+    T: std::str::FromStr,
+{
+    let invalid = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("bad journal line: {line}"),
+        )
+    };
+
+    let mut fields = line.split('\t');
+    let op = fields.next().ok_or_else(invalid)?;
+    let mut field = || {
+        fields
+            .next()
+            .map(|field| match escaped {
+                true => unescape(field),
+                false => field.to_string(),
+            })
+            .ok_or_else(invalid)
+    };
+    let node = |field: String| -> std::io::Result<T> { field.parse().map_err(|_| invalid()) };
+    let weight = |field: String| -> std::io::Result<u64> { field.parse().map_err(|_| invalid()) };
+    let level = |field: String| Level::from_name(&field).ok_or_else(invalid);
+    let kinds = |field: String| -> std::io::Result<Vec<EdgeKind>> {
+        field
+            .split(',')
+            .filter(|kind| !kind.is_empty())
+            .map(|kind| EdgeKind::from_name(kind).ok_or_else(invalid))
+            .collect()
+    };
+
+    let entry = match op {
+        "+n" => Entry::Change(Change::NodeAdded(node(field()?)?)),
+        "-n" => Entry::Change(Change::NodeRemoved(node(field()?)?)),
+        "+e" => Entry::Change(Change::EdgeAdded(node(field()?)?, node(field()?)?)),
+        "-e" => Entry::Change(Change::EdgeRemoved(node(field()?)?, node(field()?)?)),
+        "+m" => Entry::SetMeta(node(field()?)?, field()?, field()?),
+        "-m" => Entry::RemoveMeta(node(field()?)?, field()?),
+        "=l" => Entry::Level(node(field()?)?, node(field()?)?, level(field()?)?),
+        "=k" => Entry::Kinds(node(field()?)?, node(field()?)?, kinds(field()?)?),
+        "+ew" => Entry::EdgeWeight(node(field()?)?, node(field()?)?, Some(weight(field()?)?)),
+        "-ew" => Entry::EdgeWeight(node(field()?)?, node(field()?)?, None),
+        "+nw" => Entry::NodeWeight(node(field()?)?, Some(weight(field()?)?)),
+        "-nw" => Entry::NodeWeight(node(field()?)?, None),
+        "+p" => Entry::Pin(node(field()?)?, true),
+        "-p" => Entry::Pin(node(field()?)?, false),
+        _ => return Err(invalid()),
+    };
+
+    Ok(entry)
+}
+
+impl<T> From<std::io::Error> for JournalError<T> {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl<T> From<Error<T>> for JournalError<T> {
+    fn from(err: Error<T>) -> Self {
+        Self::Graph(err)
+    }
+}
+
+impl<T> std::fmt::Display for JournalError<T>
+where
+    T: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {err}"),
+            Self::Graph(err) => write!(f, "graph error: {err}"),
+        }
+    }
+}
+
+impl<T> std::error::Error for JournalError<T>
+where
+    T: std::fmt::Debug + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Graph(err) => Some(err),
+        }
+    }
+}
+
+impl Log for File {
+    fn len(&self) -> std::io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn set_len(&self, len: u64) -> std::io::Result<()> {
+        File::set_len(self, len)
+    }
+
+    fn sync_data(&self) -> std::io::Result<()> {
+        File::sync_data(self)
+    }
+}
+
+impl<T> std::ops::Deref for JournaledGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    type Target = Graph<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_no_dangling;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("soydepend-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        dir
+    }
+
+    #[test]
+    fn test_journaled_graph() {
+        let dir = temp_dir("journal");
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.depend("b".into(), "a".into()).unwrap();
+        g.depend("c".into(), "b".into()).unwrap();
+        g.depend("d".into(), "c".into()).unwrap();
+        g.depend("a".into(), "d".into()).expect_err("cycle");
+        g.edit(|g| g.set_max_depth(Some(2))).unwrap();
+        match g.depend("e".into(), "d".into()) {
+            Err(JournalError::Graph(Error::TooDeep { chain })) => {
+                assert_eq!(chain, ["e", "d", "c", "b", "a"])
+            }
+            other => panic!("expected a too deep chain, got {other:?}"),
+        }
+        g.edit(|g| g.set_max_depth(None)).unwrap();
+        g.remove(&"d".into()).unwrap();
+        g.undepend(&"c".into(), &"b".into()).unwrap();
+        drop(g);
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert!(g.depends_on(&"b".into(), &"a".into()));
+        assert!(g.contains(&"c".into()));
+        assert!(!g.contains(&"d".into()));
+        assert!(!g.depends_on(&"c".into(), &"b".into()));
+        assert_no_dangling(&g);
+        drop(g);
+
+        // Torn last line is ignored
+        let mut journal = OpenOptions::new()
+            .append(true)
+            .open(dir.join(JOURNAL))
+            .unwrap();
+        journal.write_all(b"+e\tc\t").unwrap();
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(g.nodes.len(), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Journal file accepting only budget more bytes, as if the disk filled up
+    struct Full {
+        file: File,
+        budget: usize,
+    }
+
+    impl Write for Full {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.budget == 0 {
+                return Err(std::io::Error::other("no space left on device"));
+            }
+
+            let n = self.file.write(&buf[..buf.len().min(self.budget)])?;
+            self.budget -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.file.flush()
+        }
+    }
+
+    impl Log for Full {
+        fn len(&self) -> std::io::Result<u64> {
+            self.file.len()
+        }
+
+        fn set_len(&self, len: u64) -> std::io::Result<()> {
+            Log::set_len(&self.file, len)
+        }
+
+        fn sync_data(&self) -> std::io::Result<()> {
+            Log::sync_data(&self.file)
+        }
+    }
+
+    #[test]
+    fn test_journaled_graph_failed_append() {
+        let dir = temp_dir("full");
+        let full = |budget| {
+            let file = OpenOptions::new()
+                .append(true)
+                .open(dir.join(JOURNAL))
+                .unwrap();
+            Box::new(Full { file, budget })
+        };
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.depend("b".into(), "a".into()).unwrap();
+        let len = g.journal.len().unwrap();
+
+        // Space for the new node, but not the whole edge
+        g.journal = full(8);
+        let err = g.depend("c".into(), "b".into()).unwrap_err();
+        assert!(matches!(err, JournalError::Io(_)));
+        assert!(!g.contains(&"c".into()));
+        assert!(!g.depends_on(&"c".into(), &"b".into()));
+        assert_eq!(g.journal.len().unwrap(), len);
+
+        g.journal = full(usize::MAX);
+        g.depend("d".into(), "a".into()).unwrap();
+        let expected = g.into_inner();
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(g.nodes, expected.nodes);
+        assert_eq!(g.dependencies, expected.dependencies);
+        assert!(!g.contains(&"c".into()));
+        assert_no_dangling(&g);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_journaled_graph_append_after_torn_line() {
+        let dir = temp_dir("torn");
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.depend("c".into(), "a".into()).unwrap();
+        drop(g);
+
+        let mut journal = OpenOptions::new()
+            .append(true)
+            .open(dir.join(JOURNAL))
+            .unwrap();
+        journal.write_all(b"+e\tc\t").unwrap();
+        drop(journal);
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.edit(|g| g.add_node("x".into())).unwrap();
+        g.depend("x".into(), "a".into()).unwrap();
+        let expected = g.into_inner();
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(g.nodes, expected.nodes);
+        assert_eq!(g.dependencies, expected.dependencies);
+        assert!(!g.contains(&"+n".into()));
+        assert!(g.contains(&"x".into()));
+        assert_no_dangling(&g);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_journaled_graph_compact() {
+        let dir = temp_dir("compact");
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.compact_after(8);
+        for i in 0..10 {
+            g.depend(format!("n{}", i + 1), format!("n{i}")).unwrap();
+        }
+
+        let journal = std::fs::read_to_string(dir.join(JOURNAL)).unwrap();
+        assert!(journal.lines().count() < 8);
+        assert!(dir.join(SNAPSHOT).exists());
+
+        g.set_meta(&"n3".into(), "version", "1.0").unwrap();
+        g.set_meta(&"n3".into(), "path", "/n3").unwrap();
+        g.set_meta(&"n10".into(), "version", "2.0").unwrap();
+        g.set_meta(&"n11".into(), "version", "2.0")
+            .expect_err("no such node");
+        g.compact().unwrap();
+
+        g.set_meta(&"n3".into(), "version", "1.1").unwrap();
+        assert_eq!(
+            g.remove_meta(&"n3".into(), "path").unwrap(),
+            Some("/n3".into())
+        );
+
+        let reopened = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(reopened.meta, g.meta);
+        assert_eq!(reopened.get_meta(&"n3".into(), "version"), Some("1.1"));
+        drop(reopened);
+
+        g.remove_autoremove(&"n10".into()).unwrap();
+        let expected = g.into_inner();
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(g.nodes, expected.nodes);
+        assert_eq!(g.dependencies, expected.dependencies);
+        assert_eq!(g.meta, expected.meta);
+        assert!(g.nodes.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_journaled_graph_escapes() {
+        let dir = temp_dir("escapes");
+        let node = "a\tb\\n".to_string();
+        let value = "line 1\nline 2\tcolumn \\t";
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.depend(node.clone(), "c\nd".into()).unwrap();
+        g.set_meta(&node, "k\te\ny", value).unwrap();
+        drop(g);
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert!(g.depends_on_directly(&node, &"c\nd".into()));
+        assert_eq!(g.get_meta(&node, "k\te\ny"), Some(value));
+
+        // Snapshots are escaped the same way
+        g.compact().unwrap();
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(g.get_meta(&node, "k\te\ny"), Some(value));
+
+        assert_eq!(unescape(&escape(value)), value);
+        assert_eq!(unescape("\\x\\"), "\\x\\");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_journaled_graph_versions() {
+        let dir = temp_dir("versions");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Files written before versioning hold fields unescaped
+        std::fs::write(dir.join(SNAPSHOT), "+n\ta\n+n\tb\n+e\ta\tb\n").unwrap();
+        std::fs::write(dir.join(JOURNAL), "+m\ta\tpath\tC:\\new\n").unwrap();
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert!(g.depends_on_directly(&"a".into(), &"b".into()));
+        assert_eq!(g.get_meta(&"a".into(), "path"), Some("C:\\new"));
+        drop(g);
+
+        // and are rewritten in the current version when opened
+        let header = format!("v\t{VERSION}\n");
+        assert_eq!(std::fs::read_to_string(dir.join(JOURNAL)).unwrap(), header);
+        assert!(std::fs::read_to_string(dir.join(SNAPSHOT))
+            .unwrap()
+            .starts_with(&header));
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(g.get_meta(&"a".into(), "path"), Some("C:\\new"));
+        drop(g);
+
+        std::fs::write(dir.join(JOURNAL), format!("v\t{}\n+n\tc\n", VERSION + 1)).unwrap();
+        let err = JournaledGraph::<String>::open(&dir).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_journaled_graph_crash_after_snapshot() {
+        let dir = temp_dir("crash");
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.depend("b".into(), "a".into()).unwrap();
+        g.depend("c".into(), "a".into()).unwrap();
+        g.undepend(&"b".into(), &"a".into()).unwrap();
+        g.depend("c".into(), "b".into()).unwrap();
+
+        // Crash after the snapshot is renamed into place, before the log is truncated
+        let journal = std::fs::read(dir.join(JOURNAL)).unwrap();
+        g.compact().unwrap();
+        let expected = g.into_inner();
+        std::fs::write(dir.join(JOURNAL), journal).unwrap();
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(g.nodes, expected.nodes);
+        assert_eq!(g.dependencies, expected.dependencies);
+        assert_eq!(g.edge_count(), 2);
+        assert_no_dangling(&g);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_journaled_graph_attributes() {
+        use std::collections::HashSet;
+
+        let dir = temp_dir("attributes");
+        let s = |name: &str| name.to_string();
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        g.edit(|g| {
+            g.depend(s("app"), s("core")).unwrap();
+            g.depend_weak(s("plugin"), s("core")).unwrap();
+            g.depend_level(s("app"), s("docs"), Level::Suggests)
+                .unwrap();
+            g.depend_level(s("app"), s("man"), Level::Recommends)
+                .unwrap();
+            g.depend_kind(s("app"), s("cc"), EdgeKind::Build).unwrap();
+            g.depend_kind(s("app"), s("cc"), EdgeKind::Dev).unwrap();
+            g.set_weight(&s("core"), 3).unwrap();
+            g.set_edge_weight(&s("app"), &s("core"), 5).unwrap();
+            g.pin(&s("app")).unwrap();
+        })
+        .unwrap();
+
+        // Strengthened edges stay strong
+        g.depend(s("app"), s("man")).unwrap();
+        g.edit(|g| g.unpin(&s("app"))).unwrap();
+        g.edit(|g| g.pin(&s("core"))).unwrap().unwrap();
+        let expected = g.into_inner();
+
+        let check = |g: &Graph<String>| {
+            assert!(!g.is_dependend(&s("plugin")));
+            assert!(g.is_weak(&s("plugin"), &s("core")));
+            assert_eq!(g.strong_dependents(&s("core")), HashSet::from([s("app")]));
+            assert_eq!(g.level(&s("app"), &s("docs")), Some(Level::Suggests));
+            assert_eq!(g.level(&s("app"), &s("man")), Some(Level::Depends));
+            assert_eq!(
+                g.kinds(&s("app"), &s("cc")),
+                HashSet::from([EdgeKind::Build, EdgeKind::Dev])
+            );
+            assert_eq!(g.weight(&s("core")), 3);
+            assert_eq!(g.edge_weight(&s("app"), &s("core")), 5);
+            assert!(g.is_pinned(&s("core")) && !g.is_pinned(&s("app")));
+            assert_eq!(g.weak, expected.weak);
+            assert_eq!(g.suggested, expected.suggested);
+            assert_eq!(g.kinds, expected.kinds);
+            assert_eq!(g.pinned, expected.pinned);
+        };
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        check(&g);
+
+        g.compact().unwrap();
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        check(&g);
+
+        // Attributes go away with their edges and nodes
+        g.undepend(&s("app"), &s("cc")).unwrap();
+        g.depend(s("app"), s("cc")).unwrap();
+        drop(g);
+
+        let mut g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert_eq!(
+            g.kinds(&s("app"), &s("cc")),
+            HashSet::from([EdgeKind::Runtime])
+        );
+        g.edit(|g| g.unpin(&s("core"))).unwrap();
+        g.remove_force(&s("core")).unwrap();
+        drop(g);
+
+        let g = JournaledGraph::<String>::open(&dir).unwrap();
+        assert!(!g.contains(&s("core")));
+        assert!(g.weak.is_empty() && g.suggested.is_empty() && g.kinds.is_empty());
+        assert!(g.weights.nodes.is_empty() && g.weights.edges.is_empty());
+        assert!(g.pinned.is_empty());
+        assert_no_dangling(&g);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,576 @@
+//! Just enough JSON for graph files and importers
+
+use std::fmt::Write;
+
+use crate::Graph;
+
+/// Version of the layout written by [`Graph::to_versioned_json`],
+/// bumped whenever fields are added.
+///
+/// Version 2 added edge kinds, weak edges, metadata and node weights.
+/// Version 3 added suggested edges and edge weights.
+pub const SCHEMA_VERSION: u64 = 3;
+
+/// Deepest nesting of arrays and objects accepted by [`parse`],
+/// so that untrusted documents cannot overflow the stack
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in document order
+    Object(Vec<Member>),
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Member {
+    pub(crate) key: String,
+    /// Byte offset of the key in the source
+    pub(crate) offset: usize,
+    pub(crate) value: Value,
+}
+
+/// Invalid JSON at byte offset
+#[derive(Debug, PartialEq)]
+pub(crate) struct ParseError {
+    pub(crate) offset: usize,
+    pub(crate) message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl Value {
+    /// Returns value of member key, if this is an object with such member
+    #[cfg(any(feature = "cargo", feature = "npm"))]
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(members) => members.iter().find(|m| m.key == key).map(|m| &m.value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_object(&self) -> Option<&[Member]> {
+        match self {
+            Self::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn parse(source: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser {
+        chars: source.char_indices().peekable(),
+        source,
+        depth: 0,
+    };
+
+    let value = parser.value()?;
+    parser.whitespace();
+    match parser.chars.next() {
+        None => Ok(value),
+        Some((i, c)) => Err(error(i, format!("unexpected {c:?}"))),
+    }
+}
+
+/// Appends s to out as a JSON string literal
+pub(crate) fn push_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Returns the 1-based line of byte offset in source
+pub(crate) fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+fn error(offset: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        offset,
+        message: message.into(),
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    source: &'a str,
+    /// Arrays and objects around the current value
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.whitespace();
+        match self.chars.peek().copied() {
+            Some((i, '{' | '[')) if self.depth == MAX_DEPTH => {
+                Err(error(i, "document nested too deeply"))
+            }
+            Some((_, '{')) => self.nested(Self::object),
+            Some((_, '[')) => self.nested(Self::array),
+            Some((_, '"')) => self.string().map(Value::String),
+            Some((_, 't')) => self.literal("true", Value::Bool(true)),
+            Some((_, 'f')) => self.literal("false", Value::Bool(false)),
+            Some((_, 'n')) => self.literal("null", Value::Null),
+            Some((_, c)) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some((i, c)) => Err(error(i, format!("unexpected {c:?}"))),
+            None => Err(self.end()),
+        }
+    }
+
+    fn nested<F>(&mut self, f: F) -> Result<Value, ParseError>
+    where
+        F: FnOnce(&mut Self) -> Result<Value, ParseError>,
+    {
+        self.depth += 1;
+        let value = f(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn object(&mut self) -> Result<Value, ParseError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+
+        self.whitespace();
+        if self.eat('}') {
+            return Ok(Value::Object(members));
+        }
+
+        loop {
+            self.whitespace();
+            let offset = self.offset();
+            let key = self.string()?;
+            self.whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            members.push(Member { key, offset, value });
+
+            self.whitespace();
+            if self.eat('}') {
+                return Ok(Value::Object(members));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+
+        self.whitespace();
+        if self.eat(']') {
+            return Ok(Value::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+
+            self.whitespace();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut s = String::new();
+
+        loop {
+            let (i, c) = self.chars.next().ok_or_else(|| self.end())?;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let (_, escape) = self.chars.next().ok_or_else(|| self.end())?;
+                    match escape {
+                        '"' | '\\' | '/' => s.push(escape),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'n' => s.push('\n'),
+                        'r' => s.push('\r'),
+                        't' => s.push('\t'),
+                        'u' => s.push(self.unicode(i)?),
+                        _ => return Err(error(i, format!("invalid escape {escape:?}"))),
+                    }
+                }
+                c if c.is_control() => return Err(error(i, "control character in string")),
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Reads the hex digits of a \u escape at offset, joining surrogate pairs
+    fn unicode(&mut self, offset: usize) -> Result<char, ParseError> {
+        let high = self.hex(offset)?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| error(offset, "invalid \\u escape"));
+        }
+
+        if !(self.eat('\\') && self.eat('u')) {
+            return Err(error(offset, "unpaired surrogate"));
+        }
+
+        let low = self.hex(offset)?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return Err(error(offset, "unpaired surrogate"));
+        }
+
+        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+            .ok_or_else(|| error(offset, "invalid \\u escape"))
+    }
+
+    fn hex(&mut self, offset: usize) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let (_, c) = self.chars.next().ok_or_else(|| self.end())?;
+            code = code * 16
+                + c.to_digit(16)
+                    .ok_or_else(|| error(offset, "invalid \\u escape"))?;
+        }
+
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.offset();
+        let mut end = start;
+        while let Some((i, c)) = self.chars.peek().copied() {
+            if !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                break;
+            }
+
+            end = i + c.len_utf8();
+            self.chars.next();
+        }
+
+        self.source[start..end]
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| error(start, "invalid number"))
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, ParseError> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+
+        Ok(value)
+    }
+
+    fn whitespace(&mut self) {
+        while self
+            .chars
+            .next_if(|(_, c)| c.is_ascii_whitespace())
+            .is_some()
+        {}
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.chars.next_if(|(_, c)| *c == expected).is_some()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((i, c)) => Err(error(i, format!("expected {expected:?}, found {c:?}"))),
+            None => Err(error(self.source.len(), format!("expected {expected:?}"))),
+        }
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.source.len(), |(i, _)| *i)
+    }
+
+    fn end(&self) -> ParseError {
+        error(self.source.len(), "unexpected end of input")
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + std::fmt::Display,
+{
+    /// Returns a JSON object mapping nodes to their direct dependencies,
+    /// as read by [`Graph::from_json_str`].
+    ///
+    /// Nodes and dependencies are sorted by their display form.
+    pub fn to_json(&self) -> String {
+        let mut nodes: Vec<(String, &T)> = self.nodes.iter().map(|n| (n.to_string(), n)).collect();
+        nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut json = String::from("{");
+        for (i, (name, node)) in nodes.into_iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            let mut dependencies: Vec<String> = self
+                .dependencies
+                .get(node)
+                .into_iter()
+                .flatten()
+                .map(|d| d.to_string())
+                .collect();
+            dependencies.sort();
+
+            push_string(&mut json, &name);
+            json.push_str(":[");
+            for (j, dependency) in dependencies.iter().enumerate() {
+                if j > 0 {
+                    json.push(',');
+                }
+
+                push_string(&mut json, dependency);
+            }
+            json.push(']');
+        }
+        json.push('}');
+
+        json
+    }
+
+    /// Like [`Graph::to_json`], but wraps the nodes in a document
+    /// stating its [`SCHEMA_VERSION`], along with attributes of nodes and edges:
+    ///
+    /// ```json
+    /// {
+    ///   "version": 3,
+    ///   "nodes": {"app": ["db", "log"], "db": [], "log": []},
+    ///   "kinds": {"app": {"db": ["build", "runtime"]}},
+    ///   "weak": {"app": ["log"]},
+    ///   "suggests": {"app": ["log"]},
+    ///   "meta": {"db": {"version": "1.0"}},
+    ///   "weights": {"db": 3},
+    ///   "edge_weights": {"app": {"db": 2}}
+    /// }
+    /// ```
+    ///
+    /// Weak edges are recommended unless also listed in `suggests`.
+    /// Every map is sorted by the display form of nodes.
+    pub fn to_versioned_json(&self) -> String {
+        let name = |node: &T| node.to_string();
+        let sorted_names = |nodes: &std::collections::HashSet<T>| {
+            let mut names: Vec<String> = nodes.iter().map(name).collect();
+            names.sort();
+            names
+        };
+
+        // Both are keyed by dependency, but documents key edges by dependent
+        let by_dependent = |edges: &crate::Edges<T>| {
+            let mut by_dependent: std::collections::HashMap<String, Vec<String>> =
+                std::collections::HashMap::new();
+            for (dependency, dependents) in edges {
+                for dependent in sorted_names(dependents) {
+                    by_dependent
+                        .entry(dependent)
+                        .or_default()
+                        .push(name(dependency));
+                }
+            }
+            by_dependent
+        };
+        let push_edges = |json: &mut String, edges: &crate::Edges<T>| {
+            push_map(
+                json,
+                &by_dependent(edges),
+                String::clone,
+                |json, dependencies| {
+                    let mut dependencies = dependencies.clone();
+                    dependencies.sort();
+                    push_array(json, dependencies.iter().map(String::as_str));
+                },
+            );
+        };
+
+        let mut json = format!(
+            "{{\"version\":{SCHEMA_VERSION},\"nodes\":{}",
+            self.to_json()
+        );
+
+        json.push_str(",\"kinds\":");
+        push_map(&mut json, &self.kinds, name, |json, kinds| {
+            push_map(json, kinds, name, |json, kinds| {
+                let mut kinds: Vec<&str> = kinds.iter().map(|kind| kind.name()).collect();
+                kinds.sort();
+                push_array(json, kinds);
+            });
+        });
+
+        json.push_str(",\"weak\":");
+        push_edges(&mut json, &self.weak);
+
+        json.push_str(",\"suggests\":");
+        push_edges(&mut json, &self.suggested);
+
+        json.push_str(",\"meta\":");
+        push_map(&mut json, &self.meta, name, |json, meta| {
+            push_map(json, meta, String::clone, |json, value| {
+                push_string(json, value)
+            });
+        });
+
+        json.push_str(",\"weights\":");
+        push_map(&mut json, &self.weights.nodes, name, |json, weight| {
+            let _ = write!(json, "{weight}");
+        });
+
+        json.push_str(",\"edge_weights\":");
+        push_map(&mut json, &self.weights.edges, name, |json, weights| {
+            push_map(json, weights, name, |json, weight| {
+                let _ = write!(json, "{weight}");
+            });
+        });
+
+        json.push('}');
+        json
+    }
+}
+
+/// Pushes map as an object sorted by key, with values pushed by push_value
+fn push_map<K, V, N, F>(
+    out: &mut String,
+    map: &std::collections::HashMap<K, V>,
+    name: N,
+    mut push_value: F,
+) where
+    N: Fn(&K) -> String,
+    F: FnMut(&mut String, &V),
+{
+    let mut entries: Vec<(String, &V)> =
+        map.iter().map(|(key, value)| (name(key), value)).collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    out.push('{');
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+
+        push_string(out, &key);
+        out.push(':');
+        push_value(out, value);
+    }
+    out.push('}');
+}
+
+fn push_array<'a, I>(out: &mut String, items: I)
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    out.push('[');
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+
+        push_string(out, item);
+    }
+    out.push(']');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let value = parse(r#" {"a": [1, -2.5e1, true, null], "bé😀": {}} "#).unwrap();
+        let members = value.as_object().unwrap();
+        assert_eq!(
+            members[0].value.as_array().unwrap(),
+            [
+                Value::Number(1.0),
+                Value::Number(-25.0),
+                Value::Bool(true),
+                Value::Null,
+            ]
+        );
+        assert_eq!(members[1].key, "bé😀");
+        assert_eq!(members[1].value, Value::Object(vec![]));
+        assert_eq!(members[1].offset, 32);
+
+        assert_eq!(
+            parse(r#""\ud83d\ude00 é \" \\ \n""#).unwrap(),
+            Value::String("😀 é \" \\ \n".to_string())
+        );
+
+        assert_eq!(parse("[1,]").unwrap_err().offset, 3);
+        assert_eq!(
+            parse("{\"a\" 1}").unwrap_err().to_string(),
+            "expected ':', found '1' at byte 5"
+        );
+        parse("\"abc").expect_err("unterminated");
+        parse("[] []").expect_err("trailing value");
+        parse("tru").expect_err("bad literal");
+        parse(r#""\ud83d""#).expect_err("unpaired surrogate");
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut g = Graph::new();
+        g.depend("app", "say \"hi\"").unwrap();
+        g.depend("app", "http").unwrap();
+        g.add_node("db");
+
+        let json = g.to_json();
+        assert_eq!(
+            json,
+            r#"{"app":["http","say \"hi\""],"db":[],"http":[],"say \"hi\"":[]}"#
+        );
+
+        let loaded = Graph::from_json_str(&json).unwrap();
+        assert!(loaded.depends_on_directly(&"app".to_string(), &"say \"hi\"".to_string()));
+        assert_eq!(loaded.to_json(), json);
+        assert_eq!(Graph::<&str>::new().to_json(), "{}");
+    }
+
+    #[test]
+    fn test_line_of() {
+        assert_eq!(line_of("a\nb\nc", 0), 1);
+        assert_eq!(line_of("a\nb\nc", 2), 2);
+        assert_eq!(line_of("a\nb\nc", 99), 3);
+    }
+}
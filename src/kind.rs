@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Error, Graph};
+
+/// Kinds of dependency edges, recorded per edge by [`Graph::depend_kind`].
+///
+/// Edges added with [`Graph::depend`] are runtime edges,
+/// and an edge may be of many kinds at once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EdgeKind {
+    #[default]
+    Runtime,
+    Build,
+    Dev,
+    Optional,
+}
+
+impl EdgeKind {
+    /// Returns the lowercase name of the kind, as used in JSON documents
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Runtime => "runtime",
+            Self::Build => "build",
+            Self::Dev => "dev",
+            Self::Optional => "optional",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        [Self::Runtime, Self::Build, Self::Dev, Self::Optional]
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+/// Explicit kinds of edges, keyed by dependent then dependency.
+///
+/// Edges missing here are implicitly [`EdgeKind::Runtime`].
+pub(crate) type Kinds<T> = HashMap<T, HashMap<T, HashSet<EdgeKind>>>;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Like [`Graph::depend`], but adds kind to the kinds of the edge
+    pub fn depend_kind(
+        &mut self,
+        dependent: T,
+        dependency: T,
+        kind: EdgeKind,
+    ) -> Result<(), Error<T>> {
+        let mut kinds = self.kinds(&dependent, &dependency);
+        self.depend(dependent.clone(), dependency.clone())?;
+
+        // Overwrites the runtime kind depend adds to existing edges
+        kinds.insert(kind);
+        self.kinds
+            .entry(dependent)
+            .or_default()
+            .insert(dependency, kinds);
+
+        Ok(())
+    }
+
+    /// Returns kinds of the edge, which is empty if there is no such edge
+    pub fn kinds(&self, dependent: &T, dependency: &T) -> HashSet<EdgeKind> {
+        if !self.depends_on_directly(dependent, dependency) {
+            return HashSet::new();
+        }
+
+        self.kinds
+            .get(dependent)
+            .and_then(|kinds| kinds.get(dependency))
+            .cloned()
+            .unwrap_or_else(|| HashSet::from([EdgeKind::Runtime]))
+    }
+
+    /// Returns deep dependencies of node, following only edges of kind
+    pub fn dependencies_of_kind(&self, node: &T, kind: EdgeKind) -> HashSet<T> {
+        self.dependencies_filtered(node, |a, b| self.kinds(a, b).contains(&kind))
+    }
+
+    /// Returns deep dependents of node, following only edges of kind
+    pub fn dependents_of_kind(&self, node: &T, kind: EdgeKind) -> HashSet<T> {
+        self.dependents_filtered(node, |a, b| self.kinds(a, b).contains(&kind))
+    }
+
+    /// Drops explicit kinds of a removed edge
+    pub(crate) fn forget_kinds(&mut self, dependent: &T, dependency: &T) {
+        let Some(kinds) = self.kinds.get_mut(dependent) else {
+            return;
+        };
+
+        kinds.remove(dependency);
+        if kinds.is_empty() {
+            self.kinds.remove(dependent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_no_dangling;
+
+    #[test]
+    fn test_edge_kinds() {
+        let mut g = Graph::new();
+        g.depend("app", "libc").unwrap();
+        g.depend_kind("app", "cc", EdgeKind::Build).unwrap();
+        g.depend_kind("cc", "libc", EdgeKind::Runtime).unwrap();
+        g.depend_kind("app", "test-lib", EdgeKind::Dev).unwrap();
+        g.depend_kind("test-lib", "libc", EdgeKind::Runtime)
+            .unwrap();
+
+        assert_eq!(g.kinds(&"app", &"libc"), HashSet::from([EdgeKind::Runtime]));
+        assert_eq!(g.kinds(&"app", &"cc"), HashSet::from([EdgeKind::Build]));
+        assert!(g.kinds(&"libc", &"app").is_empty());
+
+        assert_eq!(
+            g.dependencies_of_kind(&"app", EdgeKind::Runtime),
+            HashSet::from(["libc"])
+        );
+        assert_eq!(
+            g.dependencies_of_kind(&"app", EdgeKind::Build),
+            HashSet::from(["cc"])
+        );
+        assert_eq!(
+            g.dependents_of_kind(&"libc", EdgeKind::Runtime),
+            HashSet::from(["app", "cc", "test-lib"])
+        );
+        assert_eq!(
+            g.dependents_of_kind(&"cc", EdgeKind::Build),
+            HashSet::from(["app"])
+        );
+
+        // Kinds accumulate on existing edges
+        g.depend_kind("app", "libc", EdgeKind::Build).unwrap();
+        g.depend("app", "cc").unwrap();
+        assert_eq!(
+            g.kinds(&"app", &"libc"),
+            HashSet::from([EdgeKind::Runtime, EdgeKind::Build])
+        );
+        assert_eq!(
+            g.kinds(&"app", &"cc"),
+            HashSet::from([EdgeKind::Runtime, EdgeKind::Build])
+        );
+
+        // Removed edges forget their kinds
+        g.undepend(&"app", &"cc").unwrap();
+        g.remove_force(&"test-lib");
+        assert!(!g.contains(&"app"));
+        g.depend("app", "cc").unwrap();
+        assert_eq!(g.kinds(&"app", &"cc"), HashSet::from([EdgeKind::Runtime]));
+        assert_eq!(g.kinds.keys().collect::<Vec<_>>(), vec![&"cc"]);
+        assert_no_dangling(&g);
+
+        g.depend_kind("libc", "app", EdgeKind::Dev)
+            .expect_err("kinds do not bypass cycle detection");
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns every edge whose dependent depends on a node in a higher layer,
+    /// where layers are ordered from lowest to highest.
+    ///
+    /// Nodes may depend on nodes in the same or lower layers,
+    /// and nodes outside of layers are not constrained.
+    pub fn layering_violations(&self, layers: &[HashSet<T>]) -> Vec<(T, T)> {
+        let ranks: HashMap<&T, usize> = layers
+            .iter()
+            .enumerate()
+            .flat_map(|(rank, layer)| layer.iter().map(move |node| (node, rank)))
+            .collect();
+
+        let mut violations = Vec::new();
+        for (dependent, dependencies) in &self.dependencies {
+            let Some(rank) = ranks.get(dependent) else {
+                continue;
+            };
+
+            for dependency in dependencies {
+                if ranks.get(dependency).is_some_and(|other| other > rank) {
+                    violations.push((dependent.clone(), dependency.clone()));
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// Asserts that no node depends on a node in a higher layer,
+/// see [`Graph::layering_violations`]
+pub fn assert_layering<T>(g: &Graph<T>, layers: &[HashSet<T>])
+where
+    T: Clone + Eq + std::hash::Hash + std::fmt::Debug,
+{
+    let violations = g.layering_violations(layers);
+    assert!(
+        violations.is_empty(),
+        "layering violated by edges {violations:?}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layers() -> Vec<HashSet<&'static str>> {
+        vec![
+            HashSet::from(["db", "log"]),
+            HashSet::from(["service", "cache"]),
+            HashSet::from(["api", "cli"]),
+        ]
+    }
+
+    #[test]
+    fn test_layering() {
+        let mut g = Graph::new();
+        g.depend("api", "service").unwrap();
+        g.depend("service", "db").unwrap();
+        g.depend("service", "cache").unwrap();
+        g.depend("cli", "log").unwrap();
+        g.depend("script", "api").unwrap();
+        assert_layering(&g, &layers());
+
+        g.depend("db", "cache").unwrap();
+        g.depend("log", "cli").unwrap_err();
+        g.depend("cache", "cli").unwrap();
+
+        let mut violations = g.layering_violations(&layers());
+        violations.sort();
+        assert_eq!(violations, vec![("cache", "cli"), ("db", "cache")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "layering violated")]
+    fn test_assert_layering() {
+        let mut g = Graph::new();
+        g.depend("db", "api").unwrap();
+        assert_layering(&g, &layers());
+    }
+}
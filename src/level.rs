@@ -0,0 +1,258 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{edges_contain, insert_to_deps, rm_from_deps, Error, Graph};
+
+/// Apt-style strength of a dependency edge, strongest first.
+///
+/// [`Level::Depends`] edges are regular edges, while [`Level::Recommends`]
+/// and [`Level::Suggests`] edges are weak edges (see [`Graph::depend_weak`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Level {
+    Depends,
+    Recommends,
+    Suggests,
+}
+
+impl Level {
+    /// Returns the lowercase name of the level, as used in journals
+    #[cfg(feature = "fs")]
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Depends => "depends",
+            Self::Recommends => "recommends",
+            Self::Suggests => "suggests",
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        [Self::Depends, Self::Recommends, Self::Suggests]
+            .into_iter()
+            .find(|level| level.name() == name)
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Adds dependency edge at level. An existing edge is only ever
+    /// raised to a stronger level, never lowered.
+    pub fn depend_level(
+        &mut self,
+        dependent: T,
+        dependency: T,
+        level: Level,
+    ) -> Result<(), Error<T>> {
+        let current = self.level(&dependent, &dependency);
+
+        match level {
+            Level::Depends => self.depend(dependent, dependency).map(drop),
+            Level::Recommends => {
+                self.depend_weak(dependent.clone(), dependency.clone())?;
+                rm_from_deps(&mut self.suggested, &dependency, &dependent);
+
+                Ok(())
+            }
+            Level::Suggests => {
+                self.depend_weak(dependent.clone(), dependency.clone())?;
+                if current.is_none() {
+                    insert_to_deps(&mut self.suggested, dependency, dependent);
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns level of the edge, or None if there is no such edge
+    pub fn level(&self, dependent: &T, dependency: &T) -> Option<Level> {
+        if !self.depends_on_directly(dependent, dependency) {
+            return None;
+        }
+
+        if !self.is_weak(dependent, dependency) {
+            return Some(Level::Depends);
+        }
+
+        match edges_contain(&self.suggested, dependency, dependent) {
+            true => Some(Level::Suggests),
+            false => Some(Level::Recommends),
+        }
+    }
+
+    /// Returns direct dependencies recommended by node
+    pub fn recommends(&self, node: &T) -> HashSet<T> {
+        self.direct_at(node, Level::Recommends)
+    }
+
+    /// Returns direct dependencies suggested by node
+    pub fn suggests(&self, node: &T) -> HashSet<T> {
+        self.direct_at(node, Level::Suggests)
+    }
+
+    /// Returns deep dependencies of node, following only edges
+    /// at level or stronger
+    pub fn dependencies_at(&self, node: &T, level: Level) -> HashSet<T> {
+        let mut result = HashSet::new();
+        let mut q = vec![node];
+
+        while let Some(current) = q.pop() {
+            for dependency in self.dependencies.get(current).into_iter().flatten() {
+                if self.level(current, dependency) > Some(level) {
+                    continue;
+                }
+
+                if result.insert(dependency.clone()) {
+                    q.push(dependency);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns target and what it pulls in at level or stronger,
+    /// in install order: every node comes after its dependencies.
+    ///
+    /// The level acts as the install policy, e.g. [`Level::Depends`]
+    /// installs the bare minimum while [`Level::Recommends`] also
+    /// installs recommended nodes. Plans with conflicting nodes
+    /// fail with [`Error::Conflict`].
+    pub fn install_plan(&self, target: &T, level: Level) -> Result<Vec<T>, Error<T>> {
+        if !self.contains(target) {
+            return Err(Error::NoSuchNode);
+        }
+
+        let mut members = self.dependencies_at(target, level);
+        members.insert(target.clone());
+
+        let mut pending: HashMap<&T, usize> = members
+            .iter()
+            .map(|node| {
+                let count = self
+                    .dependencies
+                    .get(node)
+                    .into_iter()
+                    .flatten()
+                    .filter(|dependency| members.contains(*dependency))
+                    .count();
+
+                (node, count)
+            })
+            .collect();
+
+        let mut ready: Vec<&T> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(node, _)| *node)
+            .collect();
+
+        let mut plan = Vec::with_capacity(members.len());
+        while let Some(node) = ready.pop() {
+            plan.push(node.clone());
+
+            for dependent in self.dependents.get(node).into_iter().flatten() {
+                if let Some(count) = pending.get_mut(dependent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(dependent);
+                    }
+                }
+            }
+        }
+
+        if let Some((a, b)) = self.find_conflict(&plan) {
+            return Err(Error::Conflict { a, b });
+        }
+
+        Ok(plan)
+    }
+
+    fn direct_at(&self, node: &T, level: Level) -> HashSet<T> {
+        self.dependencies
+            .get(node)
+            .into_iter()
+            .flatten()
+            .filter(|dependency| self.level(node, dependency) == Some(level))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apt() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.depend_level("vim", "libc", Level::Depends).unwrap();
+        g.depend_level("vim", "vim-runtime", Level::Recommends)
+            .unwrap();
+        g.depend_level("vim-runtime", "libc", Level::Depends)
+            .unwrap();
+        g.depend_level("vim", "ctags", Level::Suggests).unwrap();
+        g.depend_level("ctags", "libc", Level::Depends).unwrap();
+
+        g
+    }
+
+    #[test]
+    fn test_levels() {
+        let mut g = apt();
+
+        assert_eq!(g.level(&"vim", &"libc"), Some(Level::Depends));
+        assert_eq!(g.level(&"vim", &"vim-runtime"), Some(Level::Recommends));
+        assert_eq!(g.level(&"vim", &"ctags"), Some(Level::Suggests));
+        assert_eq!(g.level(&"libc", &"vim"), None);
+
+        assert_eq!(g.recommends(&"vim"), HashSet::from(["vim-runtime"]));
+        assert_eq!(g.suggests(&"vim"), HashSet::from(["ctags"]));
+        assert!(!g.is_dependend(&"ctags"));
+
+        assert_eq!(
+            g.dependencies_at(&"vim", Level::Depends),
+            HashSet::from(["libc"])
+        );
+        assert_eq!(
+            g.dependencies_at(&"vim", Level::Recommends),
+            HashSet::from(["libc", "vim-runtime"])
+        );
+        assert_eq!(
+            g.dependencies_at(&"vim", Level::Suggests),
+            g.dependencies(&"vim")
+        );
+
+        // Levels are only raised
+        g.depend_level("vim", "vim-runtime", Level::Suggests)
+            .unwrap();
+        assert_eq!(g.level(&"vim", &"vim-runtime"), Some(Level::Recommends));
+        g.depend_level("vim", "ctags", Level::Recommends).unwrap();
+        assert_eq!(g.level(&"vim", &"ctags"), Some(Level::Recommends));
+        g.depend_level("vim", "ctags", Level::Depends).unwrap();
+        assert_eq!(g.level(&"vim", &"ctags"), Some(Level::Depends));
+        assert!(g.suggested.is_empty());
+
+        g.depend_level("vim", "gvim", Level::Suggests).unwrap();
+        g.remove(&"gvim").unwrap();
+        assert!(g.suggested.is_empty());
+    }
+
+    #[test]
+    fn test_install_plan() {
+        let g = apt();
+
+        assert_eq!(
+            g.install_plan(&"vim", Level::Depends).unwrap(),
+            vec!["libc", "vim"]
+        );
+
+        let plan = g.install_plan(&"vim", Level::Suggests).unwrap();
+        assert_eq!(plan.len(), 4);
+        assert_eq!(plan[0], "libc");
+        assert_eq!(plan[3], "vim");
+
+        g.install_plan(&"emacs", Level::Depends)
+            .expect_err("no such node");
+    }
+}
@@ -1,8 +1,131 @@
+mod antichain;
+mod audit;
+mod bounded;
+mod cache;
+mod cluster;
+mod component;
+mod concurrent;
+mod condition;
+mod conflict;
+mod counters;
+mod critical;
+mod depth;
+mod diamond;
+mod diff;
+mod disk;
+mod dot;
+pub mod exec;
+#[cfg(feature = "capi")]
+pub mod ffi;
+mod fingerprint;
+mod gc;
+mod glob;
+mod group;
+mod history;
+mod html;
+mod impact;
+pub mod integrations;
+mod isomorphism;
+mod iter;
+#[cfg(feature = "fs")]
+mod journal;
+mod json;
+mod kind;
+mod layering;
+mod level;
+mod listen;
+mod load;
+mod matrix;
+mod memo;
+mod merge;
+mod mermaid;
+mod meta;
+pub mod metrics;
+mod namespace;
+mod overlay;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod persistent;
+mod policy;
+mod provide;
+mod provider;
+mod publish;
+#[cfg(feature = "pyo3")]
+mod python;
+mod query;
+mod rank;
+mod reachability;
+pub mod report;
+pub mod resolve;
+mod shard;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trace;
+mod transaction;
+mod traverse;
+mod tree;
+mod versioned;
+mod visit;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod weak;
+mod weight;
+mod why;
+
+pub use audit::{AuditEntry, AuditedGraph};
+pub use bounded::{BoundedGraph, Quotas};
+pub use cluster::Clusters;
+pub use concurrent::ConcurrentGraph;
+pub use condition::Condition;
+pub use counters::Stats;
+pub use depth::DepthStats;
+pub use diff::Diff;
+pub use disk::DiskGraph;
+pub use group::Groups;
+pub use history::{Change, History};
+pub use impact::RemovalImpact;
+pub use iter::{Bfs, DfsPost, DfsPre, Direction, KeepAll};
+#[cfg(feature = "fs")]
+pub use journal::{JournalError, JournaledGraph};
+pub use json::SCHEMA_VERSION;
+pub use kind::EdgeKind;
+pub use layering::assert_layering;
+pub use level::Level;
+pub use listen::Listener;
+pub use load::LoadError;
+pub use merge::{merge3, Merge, MergeConflict};
+pub use namespace::{NamespacePolicy, Scoped};
+pub use overlay::Overlay;
+pub use persistent::PersistentGraph;
+pub use policy::EdgePolicy;
+pub use provider::{AsyncDependencyProvider, CachingProvider, DependencyProvider};
+pub use publish::{FrozenGraph, Publisher, Reader};
+#[cfg(feature = "pyo3")]
+pub use python::PyGraph;
+pub use query::{Query, QueryError};
+pub use reachability::Reachability;
+pub use transaction::Transaction;
+pub use versioned::VersionedGraph;
+pub use visit::{Control, Visitor};
+#[cfg(feature = "wasm")]
+pub use wasm::WasmGraph;
+
 use std::collections::{HashMap, HashSet};
 
+use cache::QueryCache;
+use condition::Conditions;
+use counters::Counters;
+use history::Touched;
+use kind::Kinds;
+use listen::Listeners;
+use memo::Memo;
+use meta::Meta;
+use policy::Policies;
+use weight::Weights;
+
 type Edges<T> = HashMap<T, HashSet<T>>;
 
-#[derive(Clone, Default, Debug)]
+#[derive(Default, Debug)]
 pub struct Graph<T>
 where
     T: Clone + Eq + std::hash::Hash,
@@ -10,15 +133,93 @@ where
     pub(crate) nodes: HashSet<T>,
     pub(crate) dependents: Edges<T>,
     pub(crate) dependencies: Edges<T>,
+    pub(crate) dirty: HashSet<T>,
+    pub(crate) kinds: Kinds<T>,
+    /// Weak dependents, keyed by dependency
+    pub(crate) weak: Edges<T>,
+    /// Weak dependents at [`Level::Suggests`], keyed by dependency
+    pub(crate) suggested: Edges<T>,
+    /// Providers, keyed by virtual node
+    pub(crate) provides: Edges<T>,
+    /// Members, keyed by group
+    pub(crate) groups: Edges<T>,
+    /// Conflicting nodes, in both directions
+    pub(crate) conflicts: Edges<T>,
+    pub(crate) conditions: Conditions<T>,
+    pub(crate) meta: Meta<T>,
+    pub(crate) weights: Weights<T>,
+    pub(crate) listeners: Listeners<T>,
+    pub(crate) policies: Policies<T>,
+    pub(crate) max_depth: Option<usize>,
+    /// Nodes kept by garbage collection, see [`Graph::pin`]
+    pub(crate) pinned: HashSet<T>,
+    pub(crate) gc: bool,
+    pub(crate) counters: Counters,
+    pub(crate) edge_count: usize,
+    pub(crate) cache: QueryCache<T>,
+    pub(crate) epoch: u64,
+    pub(crate) memo: Memo<T>,
+    /// Attributes saved before being touched, see [`Graph::save_touched`]
+    pub(crate) touched: Option<Touched<T>>,
 }
 
-#[derive(Debug)]
-pub enum Error {
+/// Written by hand so that clones start without listeners:
+/// mutating a copy must not notify observers of the original.
+impl<T> Clone for Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            dependents: self.dependents.clone(),
+            dependencies: self.dependencies.clone(),
+            dirty: self.dirty.clone(),
+            kinds: self.kinds.clone(),
+            weak: self.weak.clone(),
+            suggested: self.suggested.clone(),
+            provides: self.provides.clone(),
+            groups: self.groups.clone(),
+            conflicts: self.conflicts.clone(),
+            conditions: self.conditions.clone(),
+            meta: self.meta.clone(),
+            weights: self.weights.clone(),
+            listeners: Listeners::default(),
+            policies: self.policies.clone(),
+            max_depth: self.max_depth,
+            pinned: self.pinned.clone(),
+            gc: self.gc,
+            counters: self.counters.clone(),
+            edge_count: self.edge_count,
+            cache: self.cache.clone(),
+            epoch: self.epoch,
+            memo: self.memo.clone(),
+            touched: None,
+        }
+    }
+}
+
+/// Graph errors, carrying nodes of type T where they help explain the error
+pub enum Error<T = ()> {
     CircularDependency,
+    /// Nodes declared conflicting with [`Graph::conflict`] would both be in a plan
+    Conflict {
+        a: T,
+        b: T,
+    },
     DependencyExists,
     DependsOnSelf,
+    Forbidden,
+    InvalidMatrix,
+    NoProvider,
     NoSuchDirectDependency,
     NoSuchNode,
+    QuotaExceeded,
+    /// The edge would create a chain longer than [`Graph::max_depth`],
+    /// from its topmost dependent down to its deepest dependency
+    TooDeep {
+        chain: Vec<T>,
+    },
 }
 
 impl<T> Graph<T>
@@ -30,19 +231,60 @@ where
             nodes: HashSet::default(),
             dependents: HashMap::default(),
             dependencies: HashMap::default(),
+            dirty: HashSet::default(),
+            kinds: HashMap::default(),
+            weak: HashMap::default(),
+            suggested: HashMap::default(),
+            provides: HashMap::default(),
+            groups: HashMap::default(),
+            conflicts: HashMap::default(),
+            conditions: HashMap::default(),
+            meta: HashMap::default(),
+            weights: Weights::default(),
+            listeners: Listeners::default(),
+            policies: Policies::default(),
+            max_depth: None,
+            pinned: HashSet::default(),
+            gc: false,
+            counters: Counters::default(),
+            edge_count: 0,
+            cache: QueryCache::default(),
+            epoch: 0,
+            memo: Memo::default(),
+            touched: None,
         }
     }
 
-    /// Adds dependency edges to the graph
-    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<(), Error> {
+    /// Adds dependency edges to the graph,
+    /// returning whether the edge is new rather than already in the graph
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
         if dependent == dependency {
             return Err(Error::DependsOnSelf);
         }
 
-        if self.depends_on(&dependency, &dependent) {
+        let circular = {
+            let _span = trace::span!("cycle_check");
+            let reached = dig_deep(&self.dependencies, &dependency);
+            self.counters.visit(reached.len());
+            reached.contains(&dependent)
+        };
+
+        if circular {
+            trace::event!("rejected circular dependency");
             return Err(Error::CircularDependency);
         }
 
+        let exists = self.depends_on_directly(&dependent, &dependency);
+        if !exists && !self.policies.allows(&dependent, &dependency) {
+            return Err(Error::Forbidden);
+        }
+
+        if !exists {
+            if let Some(chain) = self.too_deep(&dependent, &dependency) {
+                return Err(Error::TooDeep { chain });
+            }
+        }
+
         insert_to_deps(
             &mut self.dependents, //
             dependency.clone(),
@@ -55,22 +297,91 @@ where
             dependency.clone(),
         );
 
-        self.nodes.insert(dependent);
-        self.nodes.insert(dependency);
+        self.insert_node(&dependent);
+        self.insert_node(&dependency);
 
-        Ok(())
+        self.touch_pair(&dependent, &dependency);
+        if let Some(kinds) = self
+            .kinds
+            .get_mut(&dependent)
+            .and_then(|kinds| kinds.get_mut(&dependency))
+        {
+            kinds.insert(EdgeKind::Runtime);
+        }
+
+        self.strengthen(&dependent, &dependency);
+        self.forget_condition(&dependent, &dependency);
+
+        if !exists {
+            self.counters.edge_added();
+            self.edge_count += 1;
+            self.edge_changed(&dependent, &dependency);
+            self.listeners
+                .notify(|listener| listener.on_edge_added(&dependent, &dependency));
+        }
+
+        Ok(!exists)
+    }
+
+    /// Adds node without edges, if it is not already in the graph
+    pub fn add_node(&mut self, node: T) {
+        self.insert_node(&node);
+    }
+
+    /// Reserves room for at least additional more direct dependencies of node,
+    /// adding node if it is not already in the graph
+    pub fn reserve_dependencies(&mut self, node: T, additional: usize) {
+        self.insert_node(&node);
+        self.dependencies
+            .entry(node)
+            .or_default()
+            .reserve(additional);
+    }
+
+    /// Internal method for adding node without edges
+    pub(crate) fn insert_node(&mut self, node: &T) {
+        if self.nodes.insert(node.clone()) {
+            self.epoch += 1;
+            self.listeners
+                .notify(|listener| listener.on_node_added(node));
+        }
     }
 
     /// Removes dependency edges from the graph
-    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error> {
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
         if !self.depends_on_directly(dependent, dependency) {
             return Err(Error::NoSuchDirectDependency);
         }
 
+        self.unlink(dependent, dependency);
+        self.collect_orphans([dependency.clone()]);
+
+        Ok(())
+    }
+
+    /// Like [`Graph::undepend`], but returns whether the edge was removed
+    /// instead of failing when there is no such edge
+    pub fn remove_edge(&mut self, dependent: &T, dependency: &T) -> bool {
+        self.undepend(dependent, dependency).is_ok()
+    }
+
+    /// Internal method for removing an existing edge
+    fn unlink(&mut self, dependent: &T, dependency: &T) {
         rm_from_deps(&mut self.dependencies, dependent, dependency);
         rm_from_deps(&mut self.dependents, dependency, dependent);
+        self.forget_edge(dependent, dependency);
 
-        Ok(())
+        self.counters.edge_removed();
+        self.edge_count -= 1;
+        self.edge_changed(dependent, dependency);
+        self.listeners
+            .notify(|listener| listener.on_edge_removed(dependent, dependency));
+    }
+
+    /// Internal method for invalidating what depends on an added or removed edge
+    pub(crate) fn edge_changed(&mut self, dependent: &T, dependency: &T) {
+        self.epoch += 1;
+        self.cache.edge_changed(dependent, dependency);
     }
 
     #[inline(always)]
@@ -86,24 +397,79 @@ where
 
     /// Returns deep dependencies of node
     pub fn dependencies(&self, node: &T) -> HashSet<T> {
-        dig_deep(&self.dependencies, node)
+        self.cached_query(node, Direction::Dependencies)
     }
 
     /// Returns deep dependents of node
     pub fn dependents(&self, node: &T) -> HashSet<T> {
-        dig_deep(&self.dependents, node)
+        self.cached_query(node, Direction::Dependents)
+    }
+
+    /// Internal method for transitive queries, going through the query cache
+    fn cached_query(&self, node: &T, direction: Direction) -> HashSet<T> {
+        if let Some(result) = self.cache.get(direction, node) {
+            self.counters.cache_hit();
+            return result;
+        }
+
+        let result = dig_deep(direction.edges(self), node);
+        self.counters.query(result.len());
+        self.cache.insert(direction, node, &result);
+        result
+    }
+
+    /// Returns the number of nodes
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Returns the number of direct dependencies of node
+    pub fn dependency_count(&self, node: &T) -> usize {
+        self.dependencies.get(node).map_or(0, HashSet::len)
+    }
+
+    /// Returns the number of direct dependents of node, including weak ones
+    pub fn dependent_count(&self, node: &T) -> usize {
+        self.dependents.get(node).map_or(0, HashSet::len)
     }
 
     /// Returns whether dependent depends on dependency in some way
     pub fn depends_on(&self, dependent: &T, dependency: &T) -> bool {
-        self.dependencies(dependent).contains(dependency)
+        if let Some(answer) = self.memo.get(self.epoch, dependent, dependency) {
+            self.counters.cache_hit();
+            return answer;
+        }
+
+        let answer = self.dependencies(dependent).contains(dependency);
+        self.memo.insert(self.epoch, dependent, dependency, answer);
+        answer
     }
 
-    /// Returns whether the node is depended on by other
+    /// Returns whether the node is depended on by other,
+    /// ignoring weak dependents
     pub fn is_dependend(&self, node: &T) -> bool {
-        self.dependents
-            .get(node)
-            .is_some_and(|deps| !deps.is_empty())
+        self.strong_dependents_iter(node).next().is_some()
+    }
+
+    /// Returns all nodes, in no particular order
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter()
+    }
+
+    /// Returns all edges as (dependent, dependency), in no particular order
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T)> {
+        self.dependencies
+            .iter()
+            .flat_map(|(dependent, dependencies)| {
+                dependencies
+                    .iter()
+                    .map(move |dependency| (dependent, dependency))
+            })
     }
 
     pub fn leaves(&self) -> HashSet<T> {
@@ -111,7 +477,7 @@ where
 
         self.nodes
             .iter()
-            .filter(|n| !self.dependencies.contains_key(n))
+            .filter(|n| self.dependency_count(n) == 0)
             .for_each(|n| {
                 leaves.insert(n.clone());
             });
@@ -120,6 +486,9 @@ where
     }
 
     pub fn layers(&self) -> Vec<HashSet<T>> {
+        let span = trace::span!("layers", nodes, layers);
+        span.record("nodes", self.nodes.len());
+
         let mut layers = Vec::new();
         let mut cloned = self.clone();
 
@@ -132,30 +501,72 @@ where
             layers.push(leaves);
         }
 
+        span.record("layers", layers.len());
         layers
     }
 
+    /// Internal method for dropping what is attached to a removed edge
+    pub(crate) fn forget_edge(&mut self, dependent: &T, dependency: &T) {
+        self.touch_pair(dependent, dependency);
+        self.forget_kinds(dependent, dependency);
+        self.strengthen(dependent, dependency);
+        rm_from_deps(&mut self.groups, dependent, dependency);
+        self.forget_edge_weight(dependent, dependency);
+        self.forget_condition(dependent, dependency);
+    }
+
     /// Internal method for complete removal of the target
     fn delete(&mut self, target: &T) {
-        if let Some(dependencies) = self.dependencies.get(target) {
-            dependencies
-                .iter()
-                .for_each(|dependency| rm_from_deps(&mut self.dependents, dependency, target));
+        let dependencies = self.dependencies.remove(target);
+        let dependents = self.dependents.remove(target);
+
+        for dependency in dependencies.iter().flatten() {
+            rm_from_deps(&mut self.dependents, dependency, target);
+            self.forget_edge(target, dependency);
+            self.counters.edge_removed();
+            self.edge_count -= 1;
+            self.edge_changed(target, dependency);
+            self.listeners
+                .notify(|listener| listener.on_edge_removed(target, dependency));
         }
 
-        if let Some(dependents) = self.dependents.get(target) {
-            dependents
-                .iter()
-                .for_each(|dependent| rm_from_deps(&mut self.dependencies, dependent, target));
+        for dependent in dependents.iter().flatten() {
+            rm_from_deps(&mut self.dependencies, dependent, target);
+            self.forget_edge(dependent, target);
+            self.counters.edge_removed();
+            self.edge_count -= 1;
+            self.edge_changed(dependent, target);
+            self.listeners
+                .notify(|listener| listener.on_edge_removed(dependent, target));
         }
 
-        self.dependencies.remove(target);
-        self.dependents.remove(target);
-        self.nodes.remove(target);
+        self.touch_node(target);
+        if self.touched.is_some() {
+            let keys: Vec<String> = self
+                .meta
+                .get(target)
+                .into_iter()
+                .flat_map(|meta| meta.keys().cloned())
+                .collect();
+            keys.iter().for_each(|key| self.touch_meta(target, key));
+        }
+
+        self.dirty.remove(target);
+        self.pinned.remove(target);
+        self.cache.node_removed(target);
+        self.forget_provider(target);
+        self.meta.remove(target);
+        self.forget_weight(target);
+        self.forget_conflicts(target);
+        if self.nodes.remove(target) {
+            self.epoch += 1;
+            self.listeners
+                .notify(|listener| listener.on_node_removed(target));
+        }
     }
 
     /// Removes undepended target node
-    pub fn remove(&mut self, target: &T) -> Result<(), Error> {
+    pub fn remove(&mut self, target: &T) -> Result<(), Error<T>> {
         if !self.contains(target) {
             return Err(Error::NoSuchNode);
         }
@@ -164,12 +575,22 @@ where
             return Err(Error::DependencyExists);
         }
 
+        let dependencies = self.dependencies.get(target).cloned();
         self.delete(target);
+        self.collect_orphans(dependencies.into_iter().flatten());
 
         Ok(())
     }
 
+    /// Removes target along with its deep dependents.
+    ///
+    /// Pinned nodes are never removed: pinned dependents only lose
+    /// their edges, and pinned targets are left as they are.
     pub fn remove_force(&mut self, target: &T) {
+        if self.is_pinned(target) {
+            return;
+        }
+
         let mut q = vec![target.clone()];
 
         while !q.is_empty() {
@@ -183,8 +604,12 @@ where
                     .unwrap()
                     .iter()
                     .for_each(|dependent| {
-                        self.undepend(dependent, &current).unwrap();
-                        q.push(dependent.clone());
+                        // Weak dependents outlive their dependencies
+                        let weak = self.is_weak(dependent, &current);
+                        self.unlink(dependent, &current);
+                        if !weak && !self.is_pinned(dependent) {
+                            q.push(dependent.clone());
+                        }
                     });
             }
 
@@ -196,7 +621,7 @@ where
                     .unwrap()
                     .iter()
                     .for_each(|dependency| {
-                        self.undepend(&current, dependency).unwrap();
+                        self.unlink(&current, dependency);
                     });
             }
 
@@ -204,7 +629,13 @@ where
         }
     }
 
+    /// Like [`Graph::remove_force`], but also removes dependencies
+    /// that end up without dependents, unless they are pinned
     pub fn remove_autoremove(&mut self, target: &T) {
+        if self.is_pinned(target) {
+            return;
+        }
+
         let mut q = vec![target.clone()];
 
         while !q.is_empty() {
@@ -218,8 +649,12 @@ where
                     .unwrap()
                     .iter()
                     .for_each(|dependent| {
-                        self.undepend(dependent, &current).unwrap();
-                        q.push(dependent.clone());
+                        // Weak dependents outlive their dependencies
+                        let weak = self.is_weak(dependent, &current);
+                        self.unlink(dependent, &current);
+                        if !weak && !self.is_pinned(dependent) {
+                            q.push(dependent.clone());
+                        }
                     });
             }
 
@@ -231,16 +666,15 @@ where
                     .unwrap()
                     .iter()
                     .for_each(|dependency| {
-                        if let Some(siblings) = self.dependents.get(dependency) {
-                            assert!(!siblings.is_empty());
-                            assert!(siblings.contains(&current));
+                        let needed = self
+                            .strong_dependents_iter(dependency)
+                            .any(|sibling| sibling != &current);
 
-                            if siblings.len() == 1 {
-                                q.push(dependency.clone());
-                            }
+                        if !needed && !self.is_pinned(dependency) {
+                            q.push(dependency.clone());
                         }
 
-                        self.undepend(&current, dependency).unwrap();
+                        self.unlink(&current, dependency);
                     });
             }
 
@@ -248,12 +682,73 @@ where
         }
     }
 
+    /// Returns nodes that can be safely removed along with target,
+    /// in removal order: target first, followed by its dependencies
+    /// that are no longer depended on by nodes outside of the plan.
+    pub fn removal_plan(&self, target: &T) -> Result<Vec<T>, Error<T>> {
+        if !self.contains(target) {
+            return Err(Error::NoSuchNode);
+        }
+
+        if self.is_dependend(target) {
+            return Err(Error::DependencyExists);
+        }
+
+        let mut plan = vec![target.clone()];
+        let mut planned = HashSet::from([target.clone()]);
+        let mut i = 0;
+
+        while i < plan.len() {
+            let current = plan[i].clone();
+            i += 1;
+
+            let Some(dependencies) = self.dependencies.get(&current) else {
+                continue;
+            };
+
+            for dependency in dependencies {
+                if planned.contains(dependency) || self.is_pinned(dependency) {
+                    continue;
+                }
+
+                // A dependency is only visited again when another of its
+                // dependents gets planned, so it joins the plan right after
+                // the last of its dependents.
+                let exclusive = self
+                    .strong_dependents_iter(dependency)
+                    .all(|d| planned.contains(d));
+
+                if exclusive {
+                    planned.insert(dependency.clone());
+                    plan.push(dependency.clone());
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+
     /// Shrinks graph to minimal memory allocation,
     /// while keeping values intact.
     pub fn realloc(&mut self) {
+        // Drops reservations of nodes that never got dependencies
+        self.dependencies
+            .retain(|_, dependencies| !dependencies.is_empty());
+
         self.nodes.shrink_to_fit();
         self.dependents.shrink_to_fit();
         self.dependencies.shrink_to_fit();
+        self.dirty.shrink_to_fit();
+        self.kinds.shrink_to_fit();
+        self.weak.shrink_to_fit();
+        self.suggested.shrink_to_fit();
+        self.provides.shrink_to_fit();
+        self.groups.shrink_to_fit();
+        self.conflicts.shrink_to_fit();
+        self.conditions.shrink_to_fit();
+        self.meta.shrink_to_fit();
+        self.weights.shrink_to_fit();
+        self.pinned.shrink_to_fit();
     }
 }
 
@@ -283,6 +778,19 @@ where
     }
 }
 
+/// Returns the graph shared by traversal tests: app depends on http and log,
+/// http on tls, and both tls and log on libc
+#[cfg(test)]
+pub(crate) fn test_graph() -> Graph<&'static str> {
+    let mut g = Graph::new();
+    g.depend("app", "http").unwrap();
+    g.depend("app", "log").unwrap();
+    g.depend("http", "tls").unwrap();
+    g.depend("tls", "libc").unwrap();
+    g.depend("log", "libc").unwrap();
+    g
+}
+
 fn insert_to_deps<T>(edges: &mut HashMap<T, HashSet<T>>, key: T, value: T)
 where
     T: Clone + Eq + std::hash::Hash,
@@ -302,6 +810,7 @@ fn dig_deep<T>(edges: &HashMap<T, HashSet<T>>, node: &T) -> HashSet<T>
 where
     T: Clone + Eq + std::hash::Hash,
 {
+    let span = trace::span!("dig_deep", nodes);
     let mut search_next = vec![node];
     let mut result = HashSet::<T>::new();
 
@@ -327,6 +836,7 @@ where
         search_next = discovered;
     }
 
+    span.record("nodes", result.len());
     result
 }
 
@@ -380,19 +890,86 @@ fn test_pop_queue() {
     });
 }
 
-impl std::fmt::Display for Error {
+impl<T> Error<T> {
+    /// Converts the nodes carried by the error,
+    /// e.g. to `()` for errors without a node type
+    pub fn map<U, F>(self, mut f: F) -> Error<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        match self {
+            Self::CircularDependency => Error::CircularDependency,
+            Self::Conflict { a, b } => Error::Conflict { a: f(a), b: f(b) },
+            Self::DependencyExists => Error::DependencyExists,
+            Self::DependsOnSelf => Error::DependsOnSelf,
+            Self::Forbidden => Error::Forbidden,
+            Self::InvalidMatrix => Error::InvalidMatrix,
+            Self::NoProvider => Error::NoProvider,
+            Self::NoSuchDirectDependency => Error::NoSuchDirectDependency,
+            Self::NoSuchNode => Error::NoSuchNode,
+            Self::QuotaExceeded => Error::QuotaExceeded,
+            Self::TooDeep { chain } => Error::TooDeep {
+                chain: chain.into_iter().map(f).collect(),
+            },
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Error<T>
+where
+    T: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CircularDependency => write!(f, "CircularDependency"),
+            Self::Conflict { a, b } => f
+                .debug_struct("Conflict")
+                .field("a", a)
+                .field("b", b)
+                .finish(),
+            Self::DependencyExists => write!(f, "DependencyExists"),
+            Self::DependsOnSelf => write!(f, "DependsOnSelf"),
+            Self::Forbidden => write!(f, "Forbidden"),
+            Self::InvalidMatrix => write!(f, "InvalidMatrix"),
+            Self::NoProvider => write!(f, "NoProvider"),
+            Self::NoSuchDirectDependency => write!(f, "NoSuchDirectDependency"),
+            Self::NoSuchNode => write!(f, "NoSuchNode"),
+            Self::QuotaExceeded => write!(f, "QuotaExceeded"),
+            Self::TooDeep { chain } => f.debug_struct("TooDeep").field("chain", chain).finish(),
+        }
+    }
+}
+
+/// Nodes are shown in their [`Debug`](std::fmt::Debug) form
+impl<T> std::fmt::Display for Error<T>
+where
+    T: std::fmt::Debug,
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::CircularDependency => write!(f, "circular dependency"),
+            Self::Conflict { a, b } => write!(f, "conflicting nodes {a:?} and {b:?}"),
             Self::DependencyExists => write!(f, "dependencies exist"),
             Self::DependsOnSelf => write!(f, "depends on self"),
+            Self::Forbidden => write!(f, "edge forbidden by policy"),
+            Self::InvalidMatrix => write!(f, "adjacency matrix does not match its labels"),
+            Self::NoProvider => write!(f, "no provider for virtual node"),
             Self::NoSuchDirectDependency => write!(f, "no such direct dependency relationship"),
             Self::NoSuchNode => write!(f, "no such node"),
+            Self::QuotaExceeded => write!(f, "graph size quota exceeded"),
+            Self::TooDeep { chain } => write!(
+                f,
+                "dependency chain of {} edges too deep",
+                chain.len().saturating_sub(1)
+            ),
         }
     }
 }
 
+impl<T> std::error::Error for Error<T> where T: std::fmt::Debug {}
+
 #[cfg(test)]
+#[allow(clippy::redundant_static_lifetimes, clippy::needless_borrow)]
 mod tests {
     use super::*;
 
@@ -483,6 +1060,96 @@ mod tests {
         assert!(g.depends_on(&"human", &"god"));
     }
 
+    #[test]
+    fn test_depend_new_edge() {
+        let mut g = Graph::new();
+
+        assert!(g.depend("b", "a").unwrap());
+        assert!(!g.depend("b", "a").unwrap());
+        assert!(g.depend("c", "a").unwrap());
+
+        // Strengthening a weak edge adds no edge
+        g.depend_weak("d", "a").unwrap();
+        assert!(!g.depend("d", "a").unwrap());
+
+        g.depend("a", "c").unwrap_err();
+    }
+
+    #[test]
+    fn test_reserve_dependencies() {
+        let mut g = Graph::new();
+        g.reserve_dependencies("app", 100);
+        g.reserve_dependencies("cli", 10);
+
+        assert!(g.dependencies[&"app"].capacity() >= 100);
+        assert_eq!(g.leaves(), HashSet::from(["app", "cli"]));
+        assert_no_dangling(&g);
+
+        for dependency in ["a", "b", "c"] {
+            g.depend("app", dependency).unwrap();
+        }
+        assert!(g.dependencies[&"app"].capacity() >= 100);
+        assert_eq!(g.dependency_count(&"app"), 3);
+        assert_eq!(g.layers().len(), 2);
+
+        g.realloc();
+        assert!(!g.dependencies.contains_key(&"cli"));
+        assert!(g.contains(&"cli"));
+        assert_no_dangling(&g);
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("c", "a").unwrap();
+        g.depend("c", "a").unwrap();
+        g.add_node("d");
+        assert_eq!((g.node_count(), g.edge_count()), (4, 3));
+
+        g.undepend(&"c", &"a").unwrap();
+        assert_eq!(g.edge_count(), 2);
+
+        g.remove_force(&"a");
+        assert_eq!((g.node_count(), g.edge_count()), (1, 0));
+    }
+
+    #[test]
+    fn test_degree() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "log").unwrap();
+        g.depend("http", "log").unwrap();
+        g.depend_weak("cli", "log").unwrap();
+
+        assert_eq!(g.dependency_count(&"app"), 2);
+        assert_eq!(g.dependency_count(&"log"), 0);
+        assert_eq!(g.dependent_count(&"log"), 3);
+        assert_eq!(g.dependent_count(&"app"), 0);
+        assert_eq!(g.dependent_count(&"missing"), 0);
+
+        g.undepend(&"app", &"log").unwrap();
+        assert_eq!(g.dependency_count(&"app"), 1);
+        assert_eq!(g.dependent_count(&"log"), 2);
+    }
+
+    #[test]
+    fn test_remove_edge() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+
+        assert!(g.remove_edge(&"c", &"b"));
+        assert!(!g.remove_edge(&"c", &"b"));
+        assert!(!g.remove_edge(&"c", &"a"));
+        assert!(!g.remove_edge(&"x", &"y"));
+
+        assert!(!g.depends_on(&"c", &"a"));
+        assert!(g.depends_on_directly(&"b", &"a"));
+        assert_no_dangling(&g);
+    }
+
     #[test]
     fn test_depends_on_directly() {
         let mut g = Graph::new();
@@ -976,4 +1643,53 @@ mod tests {
         assert_eq!(g.dependencies(&"blackhole"), set![BIGBANG]);
         assert_eq!(g.dependencies(&"whitehole"), set![BIGBANG, "blackhole"],);
     }
+
+    #[test]
+    fn test_removal_plan() {
+        let mut g = default_graph();
+        g.depend("moon", PLANET).unwrap();
+        g.depend("comet", STARDUST).unwrap();
+
+        g.removal_plan(&PROTO_PLANET)
+            .expect_err("proto-planet is depended on by planet");
+        g.removal_plan(&"sun").expect_err("sun is not in the graph");
+
+        // stardust is still needed by comet
+        assert_eq!(
+            g.removal_plan(&"moon").unwrap(),
+            vec!["moon", PLANET, PROTO_PLANET, STAR],
+        );
+
+        // stardust is still needed by star
+        let plan = g.removal_plan(&"comet").unwrap();
+        assert_eq!(plan, vec!["comet"]);
+
+        g.remove_force(&STAR);
+        let plan = g.removal_plan(&"comet").unwrap();
+        assert_eq!(plan, vec!["comet", STARDUST, BIGBANG]);
+
+        // shared dependency b is planned after both a and c
+        let mut g = Graph::new();
+        g.depend("x", "a").unwrap();
+        g.depend("x", "c").unwrap();
+        g.depend("a", "b").unwrap();
+        g.depend("c", "b").unwrap();
+        g.depend("c", "d").unwrap();
+        g.depend("y", "d").unwrap();
+
+        let plan = g.removal_plan(&"x").unwrap();
+        assert_eq!(plan[0], "x");
+        assert_eq!(
+            plan.iter().collect::<HashSet<_>>(),
+            set![&"x", &"a", &"b", &"c"]
+        );
+
+        let position = |node| plan.iter().position(|n| *n == node).unwrap();
+        assert!(position("a") < position("b"));
+        assert!(position("c") < position("b"));
+
+        // plan does not mutate the graph
+        assert!(g.contains(&"x"));
+        assert_no_dangling(&g);
+    }
 }
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use crate::Graph;
+
+/// Receives mutations of a [`Graph`], e.g. for keeping external indexes in sync.
+/// All methods default to doing nothing.
+pub trait Listener<T>: Send + Sync {
+    /// Called after a new node is added, before the edge that introduced it
+    fn on_node_added(&self, _node: &T) {}
+
+    /// Called after a new edge is added
+    fn on_edge_added(&self, _dependent: &T, _dependency: &T) {}
+
+    /// Called after an edge is removed, including edges removed with their nodes
+    fn on_edge_removed(&self, _dependent: &T, _dependency: &T) {}
+
+    /// Called after node is removed, following removals of its edges
+    fn on_node_removed(&self, _node: &T) {}
+}
+
+/// Listeners registered on a graph, which clones of the graph start without
+pub(crate) struct Listeners<T>(Vec<Arc<dyn Listener<T>>>);
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Registers listener for mutations made on the graph from now on
+    pub fn subscribe(&mut self, listener: Arc<dyn Listener<T>>) {
+        self.listeners.0.push(listener);
+    }
+
+    /// Removes listener previously registered with [`Graph::subscribe`]
+    pub fn unsubscribe(&mut self, listener: &Arc<dyn Listener<T>>) {
+        self.listeners.0.retain(|l| !Arc::ptr_eq(l, listener));
+    }
+
+    /// Removes all registered listeners
+    pub fn unsubscribe_all(&mut self) {
+        self.listeners.0.clear();
+    }
+}
+
+impl<T> Listeners<T> {
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn notify<F>(&self, event: F)
+    where
+        F: Fn(&dyn Listener<T>),
+    {
+        self.0.iter().for_each(|listener| event(listener.as_ref()));
+    }
+}
+
+impl<T> Default for Listeners<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> std::fmt::Debug for Listeners<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Listeners({})", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl Listener<&'static str> for Recorder {
+        fn on_edge_added(&self, dependent: &&'static str, dependency: &&'static str) {
+            self.record(format!("+ {dependent} -> {dependency}"));
+        }
+
+        fn on_edge_removed(&self, dependent: &&'static str, dependency: &&'static str) {
+            self.record(format!("- {dependent} -> {dependency}"));
+        }
+
+        fn on_node_removed(&self, node: &&'static str) {
+            self.record(format!("x {node}"));
+        }
+    }
+
+    impl Recorder {
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut self.events.lock().unwrap())
+        }
+    }
+
+    #[test]
+    fn test_listener() {
+        let recorder = Arc::new(Recorder::default());
+        let mut g = Graph::new();
+        g.subscribe(recorder.clone());
+
+        g.depend("b", "a").unwrap();
+        g.depend("b", "a").unwrap(); // not a new edge
+        g.depend("c", "b").unwrap();
+        g.depend("a", "c").expect_err("circular dependency");
+        assert_eq!(recorder.take(), vec!["+ b -> a", "+ c -> b"]);
+
+        // Queries do not emit events
+        g.layers();
+        g.removal_plan(&"c").unwrap();
+        assert!(recorder.take().is_empty());
+
+        g.undepend(&"c", &"b").unwrap();
+        assert_eq!(recorder.take(), vec!["- c -> b"]);
+
+        g.remove(&"b").unwrap();
+        assert_eq!(recorder.take(), vec!["- b -> a", "x b"]);
+
+        g.depend("c", "a").unwrap();
+        g.depend("d", "c").unwrap();
+        recorder.take();
+
+        g.remove_force(&"c");
+        let events = recorder.take();
+        assert!(events.contains(&"- d -> c".to_string()));
+        assert!(events.contains(&"- c -> a".to_string()));
+        assert!(events.contains(&"x c".to_string()));
+        assert!(events.contains(&"x d".to_string()));
+
+        g.unsubscribe_all();
+        g.depend("e", "a").unwrap();
+        assert!(recorder.take().is_empty());
+    }
+
+    #[test]
+    fn test_listener_clone() {
+        let recorder = Arc::new(Recorder::default());
+        let mut g = Graph::new();
+        g.subscribe(recorder.clone());
+        g.depend("b", "a").unwrap();
+        recorder.take();
+
+        // Clones start without the listeners of the original
+        let mut cloned = g.clone();
+        assert!(cloned.listeners.is_empty());
+        cloned.remove_force(&"a");
+        cloned.depend("c", "b").unwrap();
+        assert!(recorder.take().is_empty());
+
+        g.depend("c", "b").unwrap();
+        assert_eq!(recorder.take(), vec!["+ c -> b"]);
+    }
+}
@@ -0,0 +1,527 @@
+//! Loaders for dependency declarations in config files
+
+#[cfg(feature = "toml")]
+mod toml;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+use crate::json::Member;
+
+/// Invalid dependency file, pointing at the offending key
+#[derive(Debug, PartialEq, Eq)]
+pub struct LoadError {
+    /// 1-based line of the error
+    pub line: usize,
+    /// Dotted path of the offending key, e.g. `app.deps`,
+    /// or empty if the error is not about a key
+    pub key: String,
+    pub message: String,
+}
+
+impl LoadError {
+    pub(crate) fn new(line: usize, key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.key.is_empty() {
+            true => write!(f, "line {}: {}", self.line, self.message),
+            false => write!(f, "line {}: {}: {}", self.line, self.key, self.message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Declarations collected by parsers, checked and turned into graphs by [`build`]
+pub(crate) struct Declaration {
+    pub(crate) node: String,
+    pub(crate) line: usize,
+    /// Dependencies with their lines
+    pub(crate) deps: Vec<(String, usize)>,
+}
+
+/// Builds graph from declarations, rejecting redeclared nodes and invalid edges
+pub(crate) fn build(declarations: Vec<Declaration>) -> Result<crate::Graph<String>, LoadError> {
+    let span = crate::trace::span!("load", nodes, edges);
+    let mut g = crate::Graph::new();
+    let mut declared = std::collections::HashSet::new();
+
+    for declaration in &declarations {
+        if !declared.insert(&declaration.node) {
+            return Err(LoadError::new(
+                declaration.line,
+                &declaration.node,
+                "node declared more than once",
+            ));
+        }
+
+        g.add_node(declaration.node.clone());
+    }
+
+    for Declaration { node, deps, .. } in declarations {
+        for (dependency, line) in deps {
+            g.depend(node.clone(), dependency.clone()).map_err(|err| {
+                LoadError::new(line, format!("{node}.deps"), format!("{dependency}: {err}"))
+            })?;
+        }
+    }
+
+    span.record("nodes", g.nodes.len());
+    span.record("edges", g.edges().count());
+    Ok(g)
+}
+
+impl crate::Graph<String> {
+    /// Loads a graph from TOML with one table per node, listing its dependencies:
+    ///
+    /// ```toml
+    /// [app]
+    /// deps = ["http", "db"]
+    ///
+    /// [db]
+    /// ```
+    ///
+    /// Errors point at the line of the offending key or value.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(source: &str) -> Result<Self, LoadError> {
+        build(toml::parse(source)?)
+    }
+
+    /// Loads a graph from YAML with one mapping per node, listing its dependencies:
+    ///
+    /// ```yaml
+    /// app:
+    ///   deps: [http, db]
+    /// http:
+    ///   deps:
+    ///     - tls
+    /// db:
+    /// ```
+    ///
+    /// Syntax errors point at their line, while errors in the schema
+    /// point at the line declaring the offending node.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(source: &str) -> Result<Self, LoadError> {
+        build(yaml::parse(source)?)
+    }
+
+    /// Loads a graph from a JSON object mapping nodes to their dependencies:
+    ///
+    /// ```json
+    /// {"app": ["http", "db"], "db": []}
+    /// ```
+    ///
+    /// or from a document of any version up to [`SCHEMA_VERSION`](crate::SCHEMA_VERSION),
+    /// as written by [`Graph::to_versioned_json`](crate::Graph::to_versioned_json).
+    /// Fields missing from older documents are left empty, fields it does not know
+    /// are ignored, and documents of newer versions are rejected.
+    pub fn from_json_str(source: &str) -> Result<Self, LoadError> {
+        let line = |offset| crate::json::line_of(source, offset);
+        let value = crate::json::parse(source)
+            .map_err(|err| LoadError::new(line(err.offset), "", err.message))?;
+
+        let root = value
+            .as_object()
+            .ok_or_else(|| LoadError::new(1, "", "expected an object of nodes"))?;
+
+        // Unversioned objects map nodes to arrays, so a number marks a document
+        let version = root
+            .iter()
+            .find(|member| member.key == "version" && member.value.as_number().is_some());
+        let members = match version {
+            Some(version) => versioned_nodes(root, version, line(version.offset))?,
+            None => root,
+        };
+
+        let mut declarations = Vec::with_capacity(members.len());
+        for member in members {
+            let line = line(member.offset);
+            let invalid = || LoadError::new(line, &member.key, "expected an array of names");
+
+            let deps = member
+                .value
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|dependency| {
+                    let dependency = dependency.as_str().ok_or_else(invalid)?;
+                    Ok((dependency.to_string(), line))
+                })
+                .collect::<Result<_, _>>()?;
+
+            declarations.push(Declaration {
+                node: member.key.clone(),
+                line,
+                deps,
+            });
+        }
+
+        let mut g = build(declarations)?;
+        if version.is_some() {
+            attributes(&mut g, root, line)?;
+        }
+
+        Ok(g)
+    }
+}
+
+/// Sets attributes of nodes and edges from the fields of a versioned JSON document
+fn attributes<L>(g: &mut crate::Graph<String>, root: &[Member], line: L) -> Result<(), LoadError>
+where
+    L: Fn(usize) -> usize,
+{
+    for dependent in field(root, "kinds", &line)? {
+        let key = format!("kinds.{}", dependent.key);
+        for dependency in object(dependent, &key, &line)? {
+            let key = format!("{key}.{}", dependency.key);
+            let line = line(dependency.offset);
+            if !g.depends_on_directly(&dependent.key, &dependency.key) {
+                return Err(LoadError::new(line, key, "no such edge"));
+            }
+
+            let kinds = names(dependency, &key, line)?
+                .into_iter()
+                .map(|name| {
+                    crate::EdgeKind::from_name(name)
+                        .ok_or_else(|| LoadError::new(line, &key, format!("unknown kind {name}")))
+                })
+                .collect::<Result<_, _>>()?;
+
+            g.kinds
+                .entry(dependent.key.clone())
+                .or_default()
+                .insert(dependency.key.clone(), kinds);
+        }
+    }
+
+    for dependent in field(root, "weak", &line)? {
+        let key = format!("weak.{}", dependent.key);
+        let line = line(dependent.offset);
+        for dependency in names(dependent, &key, line)? {
+            if !g.depends_on_directly(&dependent.key, &dependency.to_string()) {
+                return Err(LoadError::new(
+                    line,
+                    key,
+                    format!("{dependency}: no such edge"),
+                ));
+            }
+
+            crate::insert_to_deps(&mut g.weak, dependency.to_string(), dependent.key.clone());
+        }
+    }
+
+    for dependent in field(root, "suggests", &line)? {
+        let key = format!("suggests.{}", dependent.key);
+        let line = line(dependent.offset);
+        for dependency in names(dependent, &key, line)? {
+            if !g.is_weak(&dependent.key, &dependency.to_string()) {
+                return Err(LoadError::new(
+                    line,
+                    key,
+                    format!("{dependency}: no such weak edge"),
+                ));
+            }
+
+            crate::insert_to_deps(
+                &mut g.suggested,
+                dependency.to_string(),
+                dependent.key.clone(),
+            );
+        }
+    }
+
+    for node in field(root, "meta", &line)? {
+        let key = format!("meta.{}", node.key);
+        if !g.contains(&node.key) {
+            return Err(LoadError::new(line(node.offset), key, "no such node"));
+        }
+
+        for entry in object(node, &key, &line)? {
+            let value = entry.value.as_str().ok_or_else(|| {
+                let key = format!("{key}.{}", entry.key);
+                LoadError::new(line(entry.offset), key, "expected a string")
+            })?;
+
+            g.set_meta(&node.key, &entry.key, value)
+                .expect("node was checked");
+        }
+    }
+
+    for node in field(root, "weights", &line)? {
+        let key = format!("weights.{}", node.key);
+        let line = line(node.offset);
+        if !g.contains(&node.key) {
+            return Err(LoadError::new(line, key, "no such node"));
+        }
+
+        let weight = weight(node, &key, line)?;
+
+        g.set_weight(&node.key, weight).expect("node was checked");
+    }
+
+    for dependent in field(root, "edge_weights", &line)? {
+        let key = format!("edge_weights.{}", dependent.key);
+        for dependency in object(dependent, &key, &line)? {
+            let key = format!("{key}.{}", dependency.key);
+            let line = line(dependency.offset);
+            let weight = weight(dependency, &key, line)?;
+
+            g.set_edge_weight(&dependent.key, &dependency.key, weight)
+                .map_err(|_| LoadError::new(line, key, "no such edge"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the members of the object in field name of root, or none if it is missing
+fn field<'a, L>(root: &'a [Member], name: &str, line: L) -> Result<&'a [Member], LoadError>
+where
+    L: Fn(usize) -> usize,
+{
+    match root.iter().find(|member| member.key == name) {
+        Some(member) => object(member, name, line),
+        None => Ok(&[]),
+    }
+}
+
+fn object<'a, L>(member: &'a Member, key: &str, line: L) -> Result<&'a [Member], LoadError>
+where
+    L: Fn(usize) -> usize,
+{
+    member
+        .value
+        .as_object()
+        .ok_or_else(|| LoadError::new(line(member.offset), key, "expected an object"))
+}
+
+fn names<'a>(member: &'a Member, key: &str, line: usize) -> Result<Vec<&'a str>, LoadError> {
+    let invalid = || LoadError::new(line, key, "expected an array of names");
+    member
+        .value
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|name| name.as_str().ok_or_else(invalid))
+        .collect()
+}
+
+fn weight(member: &Member, key: &str, line: usize) -> Result<u64, LoadError> {
+    member
+        .value
+        .as_number()
+        .filter(|weight| weight.fract() == 0.0 && *weight >= 0.0)
+        .map(|weight| weight as u64)
+        .ok_or_else(|| LoadError::new(line, key, "expected a non-negative integer"))
+}
+
+/// Returns the nodes of a versioned JSON document
+fn versioned_nodes<'a>(
+    members: &'a [Member],
+    version: &Member,
+    line: usize,
+) -> Result<&'a [Member], LoadError> {
+    let number = version.value.as_number().unwrap_or_default();
+    if number.fract() != 0.0 || number < 1.0 {
+        return Err(LoadError::new(
+            line,
+            "version",
+            "expected a positive integer",
+        ));
+    }
+
+    if number > crate::SCHEMA_VERSION as f64 {
+        return Err(LoadError::new(
+            line,
+            "version",
+            format!(
+                "unsupported schema version {number}, expected at most {}",
+                crate::SCHEMA_VERSION
+            ),
+        ));
+    }
+
+    members
+        .iter()
+        .find(|member| member.key == "nodes")
+        .ok_or_else(|| LoadError::new(line, "nodes", "missing object of nodes"))?
+        .value
+        .as_object()
+        .ok_or_else(|| LoadError::new(line, "nodes", "expected an object of nodes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Graph;
+
+    #[test]
+    fn test_from_json_str() {
+        let g = Graph::from_json_str(r#"{"a": ["b"], "b": [], "c": []}"#).unwrap();
+        assert!(g.depends_on_directly(&"a".to_string(), &"b".to_string()));
+        assert!(g.contains(&"c".to_string()));
+
+        let err = |source: &str| Graph::from_json_str(source).unwrap_err().to_string();
+        assert_eq!(err("[]"), "line 1: expected an object of nodes");
+        assert_eq!(
+            err("{\n  \"a\": \"b\"\n}"),
+            "line 2: a: expected an array of names"
+        );
+        assert_eq!(
+            err(r#"{"a": [1]}"#),
+            "line 1: a: expected an array of names"
+        );
+        assert_eq!(
+            err("{\"a\": [\"b\"],\n\"b\": [\"a\"]}"),
+            "line 2: b.deps: a: circular dependency"
+        );
+        assert_eq!(err("{\n\"a\" 1}"), "line 2: expected ':', found '1'");
+        assert_eq!(
+            err(&"[".repeat(200_000)),
+            "line 1: document nested too deeply"
+        );
+
+        let nested = |depth| format!("{{\"a\": [{}]}}", "[".repeat(depth) + &"]".repeat(depth));
+        assert_eq!(err(&nested(126)), "line 1: a: expected an array of names");
+        assert_eq!(err(&nested(127)), "line 1: document nested too deeply");
+    }
+
+    #[test]
+    fn test_versioned_json() {
+        use crate::EdgeKind;
+        use std::collections::HashSet;
+
+        let s = |s: &str| s.to_string();
+        let mut g = Graph::new();
+        g.depend_kind(s("a"), s("b"), EdgeKind::Build).unwrap();
+        g.depend_weak(s("a"), s("c")).unwrap();
+        g.set_meta(&s("b"), "version", "1.0\t\"beta\"").unwrap();
+        g.set_weight(&s("c"), 3).unwrap();
+
+        let json = g.to_versioned_json();
+        assert_eq!(
+            json,
+            r#"{"version":3,"nodes":{"a":["b","c"],"b":[],"c":[]},"kinds":{"a":{"b":["build"]}},"weak":{"a":["c"]},"suggests":{},"meta":{"b":{"version":"1.0\t\"beta\""}},"weights":{"c":3},"edge_weights":{}}"#
+        );
+
+        let loaded = Graph::from_json_str(&json).unwrap();
+        assert!(loaded.diff(&g).is_empty());
+        assert_eq!(
+            loaded.kinds(&s("a"), &s("b")),
+            HashSet::from([EdgeKind::Build])
+        );
+        assert!(loaded.is_weak(&s("a"), &s("c")));
+        assert!(!loaded.is_dependend(&s("c")));
+        assert_eq!(loaded.meta, g.meta);
+        assert_eq!(loaded.weight(&s("c")), 3);
+        assert_eq!(loaded.to_versioned_json(), json);
+
+        // Documents from before attributes were added still load
+        let v1 = Graph::from_json_str(r#"{"version":1,"nodes":{"a":["b"],"b":[]}}"#).unwrap();
+        assert!(v1.depends_on_directly(&s("a"), &s("b")));
+        assert!(v1.kinds.is_empty() && v1.meta.is_empty());
+
+        // Unversioned graphs still load, even with a node called version
+        let legacy = Graph::from_json_str(r#"{"version": ["a"], "a": []}"#).unwrap();
+        assert!(legacy.depends_on_directly(&s("version"), &s("a")));
+
+        // Fields added by later versions are ignored
+        let g = Graph::from_json_str(
+            r#"{"version": 2, "groups": {"a": ["b"]}, "nodes": {"a": ["b"]}}"#,
+        )
+        .unwrap();
+        assert!(g.depends_on_directly(&s("a"), &s("b")));
+
+        let err = |source: &str| Graph::from_json_str(source).unwrap_err().to_string();
+        assert_eq!(
+            err(r#"{"version": 4, "nodes": {}}"#),
+            "line 1: version: unsupported schema version 4, expected at most 3"
+        );
+        assert_eq!(
+            err(r#"{"version": 1.5, "nodes": {}}"#),
+            "line 1: version: expected a positive integer"
+        );
+        assert_eq!(
+            err(r#"{"version": 1}"#),
+            "line 1: nodes: missing object of nodes"
+        );
+        assert_eq!(
+            err(r#"{"version": 1, "nodes": []}"#),
+            "line 1: nodes: expected an object of nodes"
+        );
+        assert_eq!(
+            err(r#"{"version": 2, "nodes": {"a": []}, "kinds": {"a": {"b": ["dev"]}}}"#),
+            "line 1: kinds.a.b: no such edge"
+        );
+        assert_eq!(
+            err(r#"{"version": 2, "nodes": {"a": ["b"]}, "kinds": {"a": {"b": ["test"]}}}"#),
+            "line 1: kinds.a.b: unknown kind test"
+        );
+        assert_eq!(
+            err(r#"{"version": 2, "nodes": {"a": ["b"]}, "weak": {"b": ["a"]}}"#),
+            "line 1: weak.b: a: no such edge"
+        );
+        assert_eq!(
+            err(r#"{"version": 2, "nodes": {}, "meta": {"a": {}}}"#),
+            "line 1: meta.a: no such node"
+        );
+        assert_eq!(
+            err(r#"{"version": 2, "nodes": {"a": []}, "weights": {"a": -1}}"#),
+            "line 1: weights.a: expected a non-negative integer"
+        );
+        assert_eq!(
+            err(r#"{"version": 3, "nodes": {"a": ["b"]}, "suggests": {"a": ["b"]}}"#),
+            "line 1: suggests.a: b: no such weak edge"
+        );
+        assert_eq!(
+            err(r#"{"version": 3, "nodes": {"a": []}, "edge_weights": {"a": {"b": 1}}}"#),
+            "line 1: edge_weights.a.b: no such edge"
+        );
+        assert_eq!(
+            err(r#"{"version": 3, "nodes": {"a": ["b"]}, "edge_weights": {"a": {"b": 0.5}}}"#),
+            "line 1: edge_weights.a.b: expected a non-negative integer"
+        );
+    }
+
+    #[test]
+    fn test_versioned_json_round_trip() {
+        use crate::{EdgeKind, Level};
+
+        let s = |s: &str| s.to_string();
+        let mut g = Graph::new();
+        g.depend_kind(s("app"), s("db"), EdgeKind::Build).unwrap();
+        g.depend_kind(s("app"), s("db"), EdgeKind::Runtime).unwrap();
+        g.depend_level(s("app"), s("log"), Level::Recommends)
+            .unwrap();
+        g.depend_level(s("app"), s("docs"), Level::Suggests)
+            .unwrap();
+        g.depend_level(s("db"), s("docs"), Level::Suggests).unwrap();
+        g.set_meta(&s("db"), "version", "1.0").unwrap();
+        g.set_meta(&s("db"), "license", "MIT").unwrap();
+        g.set_weight(&s("db"), 3).unwrap();
+        g.set_edge_weight(&s("app"), &s("db"), 2).unwrap();
+        g.set_edge_weight(&s("app"), &s("docs"), 5).unwrap();
+
+        let loaded = Graph::from_json_str(&g.to_versioned_json()).unwrap();
+        assert!(loaded.diff(&g).is_empty());
+        assert_eq!(loaded.kinds, g.kinds);
+        assert_eq!(loaded.weak, g.weak);
+        assert_eq!(loaded.suggested, g.suggested);
+        assert_eq!(loaded.meta, g.meta);
+        assert_eq!(loaded.weights.nodes, g.weights.nodes);
+        assert_eq!(loaded.weights.edges, g.weights.edges);
+        for (dependent, dependency) in [("app", "db"), ("app", "log"), ("app", "docs")] {
+            assert_eq!(
+                loaded.level(&s(dependent), &s(dependency)),
+                g.level(&s(dependent), &s(dependency))
+            );
+        }
+        assert_eq!(loaded.to_versioned_json(), g.to_versioned_json());
+    }
+}
@@ -0,0 +1,120 @@
+use toml::de::{DeTable, DeValue};
+
+use super::{Declaration, LoadError};
+
+pub(crate) fn parse(source: &str) -> Result<Vec<Declaration>, LoadError> {
+    let line = |offset| crate::json::line_of(source, offset);
+    let document = DeTable::parse(source).map_err(|err| {
+        let offset = err.span().map_or(0, |span| span.start);
+        LoadError::new(line(offset), "", err.message())
+    })?;
+
+    let mut declarations = Vec::new();
+    for (node, table) in document.get_ref().iter() {
+        let name = node.get_ref().to_string();
+        let DeValue::Table(table) = table.get_ref() else {
+            return Err(LoadError::new(
+                line(node.span().start),
+                name,
+                "expected a [node] table",
+            ));
+        };
+
+        let mut deps = Vec::new();
+        for (key, value) in table.iter() {
+            let path = format!("{name}.{}", key.get_ref());
+            if key.get_ref() != "deps" {
+                let line = line(key.span().start);
+                return Err(LoadError::new(line, path, "unknown key, expected deps"));
+            }
+
+            let DeValue::Array(array) = value.get_ref() else {
+                let line = line(value.span().start);
+                return Err(LoadError::new(line, path, "expected an array of strings"));
+            };
+
+            for dependency in array.iter() {
+                let line = line(dependency.span().start);
+                let DeValue::String(dependency) = dependency.get_ref() else {
+                    return Err(LoadError::new(line, path, "expected a string"));
+                };
+
+                deps.push((dependency.to_string(), line));
+            }
+        }
+
+        declarations.push(Declaration {
+            node: name,
+            line: line(node.span().start),
+            deps,
+        });
+    }
+
+    Ok(declarations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::Graph;
+
+    #[test]
+    fn test_from_toml_str() {
+        let g = Graph::from_toml_str(
+            r#"
+# Services
+[app]
+deps = ["http", 'db'] # trailing comment
+
+["web ui"]
+deps = [
+    "app",  # multi-line
+    "assets#1",
+]
+
+[db]
+deps = []
+
+[scratch]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            g.nodes().cloned().collect::<HashSet<_>>(),
+            HashSet::from(["app", "http", "db", "web ui", "assets#1", "scratch"].map(String::from))
+        );
+        assert!(g.depends_on(&"web ui".to_string(), &"db".to_string()));
+        assert!(g.depends_on_directly(&"web ui".to_string(), &"assets#1".to_string()));
+    }
+
+    #[test]
+    fn test_from_toml_str_errors() {
+        let err = |source: &str| Graph::from_toml_str(source).unwrap_err().to_string();
+
+        assert_eq!(err("deps = []"), "line 1: deps: expected a [node] table");
+        assert_eq!(
+            err("[app]\nversion = \"1\""),
+            "line 2: app.version: unknown key, expected deps"
+        );
+        assert_eq!(
+            err("[app]\ndeps = \"http\""),
+            "line 2: app.deps: expected an array of strings"
+        );
+        assert_eq!(
+            err("[app]\ndeps = [1]"),
+            "line 2: app.deps: expected a string"
+        );
+        assert_eq!(
+            err("[app]\ndeps = [\"a\",\n"),
+            "line 2: unclosed array, expected `]`"
+        );
+        assert_eq!(err("[app]\n[app]"), "line 2: duplicate key");
+        assert_eq!(
+            err("[a]\ndeps = [\"b\"]\n[b]\ndeps = [\"a\"]"),
+            "line 4: b.deps: a: circular dependency"
+        );
+        assert_eq!(err("[a b]"), "line 1: unclosed table, expected `]`");
+    }
+}
@@ -0,0 +1,176 @@
+use serde_yaml::Value;
+
+use super::{Declaration, LoadError};
+
+pub(crate) fn parse(source: &str) -> Result<Vec<Declaration>, LoadError> {
+    let document: Value = serde_yaml::from_str(source).map_err(|err| {
+        let line = err.location().map_or(1, |location| location.line());
+        LoadError::new(line, "", message(&err))
+    })?;
+
+    let nodes = match document {
+        Value::Null => return Ok(Vec::new()),
+        Value::Mapping(nodes) => nodes,
+        _ => return Err(LoadError::new(1, "", "expected a mapping of nodes")),
+    };
+
+    let mut declarations = Vec::new();
+    for (node, value) in nodes {
+        let node = name(&node).ok_or_else(|| LoadError::new(1, "", "expected a node name"))?;
+        let line = line_of_node(source, &node);
+
+        let keys = match value {
+            Value::Null => serde_yaml::Mapping::new(),
+            Value::Mapping(keys) => keys,
+            _ => return Err(LoadError::new(line, node, "expected a mapping")),
+        };
+
+        let mut deps = Vec::new();
+        for (key, value) in keys {
+            let key = name(&key).unwrap_or_default();
+            let path = format!("{node}.{key}");
+            if key != "deps" {
+                return Err(LoadError::new(line, path, "unknown key, expected deps"));
+            }
+
+            let dependencies = match value {
+                Value::Null => Vec::new(),
+                Value::Sequence(dependencies) => dependencies,
+                _ => return Err(LoadError::new(line, path, "expected a sequence")),
+            };
+
+            for dependency in &dependencies {
+                let dependency = name(dependency)
+                    .ok_or_else(|| LoadError::new(line, &path, "expected a name"))?;
+                deps.push((dependency, line));
+            }
+        }
+
+        declarations.push(Declaration { node, line, deps });
+    }
+
+    Ok(declarations)
+}
+
+/// Returns scalar as a node name
+fn name(scalar: &Value) -> Option<String> {
+    match scalar {
+        Value::String(name) => Some(name.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(bool) => Some(bool.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the line declaring node at the top level, or 1 if it is not found,
+/// e.g. in flow mappings. Values carry no positions, so errors within a node
+/// point at the line declaring it.
+fn line_of_node(source: &str, node: &str) -> usize {
+    let quoted = [
+        format!("'{}'", node.replace('\'', "''")),
+        format!("{node:?}"),
+    ];
+
+    source
+        .lines()
+        .position(|text| {
+            let Some((key, _)) = text.split_once(':') else {
+                return false;
+            };
+
+            !text.starts_with([' ', '\t', '#'])
+                && (key == node || quoted.contains(&key.to_string()))
+        })
+        .map_or(1, |i| i + 1)
+}
+
+/// Returns the message of err, without the location reported along with it
+fn message(err: &serde_yaml::Error) -> String {
+    let message = err.to_string();
+    let Some(location) = err.location() else {
+        return message;
+    };
+
+    let suffix = format!(" at line {} column {}", location.line(), location.column());
+    match message.strip_suffix(&suffix) {
+        Some(message) => message.to_string(),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::Graph;
+
+    #[test]
+    fn test_from_yaml_str() {
+        let g = Graph::from_yaml_str(
+            r#"---
+# Services
+app:
+  deps: [http, "db"]  # flow sequence
+
+'web ui':
+    deps:
+    - app
+    - "assets #1"
+    -   it's
+
+db:
+  deps: []
+scratch:
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            g.nodes().cloned().collect::<HashSet<_>>(),
+            HashSet::from(
+                [
+                    "app",
+                    "http",
+                    "db",
+                    "web ui",
+                    "assets #1",
+                    "it's",
+                    "scratch"
+                ]
+                .map(String::from)
+            )
+        );
+        assert!(g.depends_on(&"web ui".to_string(), &"db".to_string()));
+    }
+
+    #[test]
+    fn test_from_yaml_str_errors() {
+        let err = |source: &str| Graph::from_yaml_str(source).unwrap_err().to_string();
+
+        assert_eq!(err("[app]"), "line 1: expected a mapping of nodes");
+        assert_eq!(err("  deps: []"), "line 1: deps: expected a mapping");
+        assert_eq!(err("app: http"), "line 1: app: expected a mapping");
+        assert_eq!(
+            err("app:\n  version: 1"),
+            "line 1: app.version: unknown key, expected deps"
+        );
+        assert_eq!(
+            err("app:\n  deps: http"),
+            "line 1: app.deps: expected a sequence"
+        );
+        assert_eq!(
+            err("app:\n  deps: [a, [b]]"),
+            "line 1: app.deps: expected a name"
+        );
+        assert_eq!(
+            err("app:\n  deps:\n    - a\n  deps: []"),
+            r#"line 2: app: duplicate entry with key "deps""#
+        );
+        assert!(err("app:\n  deps: []\n    deps: []").starts_with("line 3: "));
+        assert!(err("a: [b").starts_with("line 2: "));
+        assert_eq!(
+            err("a:\n  deps: [b]\nb:\n  deps:\n    - a"),
+            "line 3: b.deps: a: circular dependency"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use crate::{Error, Graph};
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Builds a graph from labels and an adjacency matrix like
+    /// [`Graph::to_adjacency_matrix`] returns, where row i has column j set
+    /// if labels\[i\] depends directly on labels\[j\].
+    ///
+    /// Fails with [`Error::InvalidMatrix`] if the matrix is not square with
+    /// a row per label or labels repeat, and like [`Graph::depend`] on
+    /// set diagonals and cycles.
+    pub fn from_adjacency_matrix<R>(labels: Vec<T>, matrix: &[R]) -> Result<Self, Error<T>>
+    where
+        R: AsRef<[bool]>,
+    {
+        let unique: HashSet<&T> = labels.iter().collect();
+        if unique.len() != labels.len()
+            || matrix.len() != labels.len()
+            || matrix.iter().any(|row| row.as_ref().len() != labels.len())
+        {
+            return Err(Error::InvalidMatrix);
+        }
+
+        let mut g = Self::new();
+        for (dependent, row) in labels.iter().zip(matrix) {
+            g.add_node(dependent.clone());
+            for (dependency, &set) in labels.iter().zip(row.as_ref()) {
+                if set {
+                    g.depend(dependent.clone(), dependency.clone())?;
+                }
+            }
+        }
+
+        Ok(g)
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Ord,
+{
+    /// Returns the nodes in sorted order, and the adjacency matrix where
+    /// row i has column j set if node i depends directly on node j
+    pub fn to_adjacency_matrix(&self) -> (Vec<T>, Vec<Vec<bool>>) {
+        let mut labels: Vec<T> = self.nodes.iter().cloned().collect();
+        labels.sort();
+
+        let matrix = labels
+            .iter()
+            .map(|dependent| {
+                labels
+                    .iter()
+                    .map(|dependency| self.depends_on_directly(dependent, dependency))
+                    .collect()
+            })
+            .collect();
+
+        (labels, matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_adjacency_matrix() {
+        let mut g = Graph::new();
+        g.depend("c", "a").unwrap();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.add_node("d");
+
+        let (labels, matrix) = g.to_adjacency_matrix();
+        assert_eq!(labels, ["a", "b", "c", "d"]);
+        assert_eq!(
+            matrix,
+            [
+                [false, false, false, false],
+                [true, false, false, false],
+                [true, true, false, false],
+                [false, false, false, false],
+            ]
+        );
+
+        let (labels, matrix) = Graph::<u8>::new().to_adjacency_matrix();
+        assert!(labels.is_empty() && matrix.is_empty());
+    }
+
+    #[test]
+    fn test_from_adjacency_matrix() {
+        let mut g = Graph::new();
+        g.depend("c", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.add_node("d");
+
+        let (labels, matrix) = g.to_adjacency_matrix();
+        let round = Graph::from_adjacency_matrix(labels, &matrix).unwrap();
+        assert!(round.diff(&g).is_empty());
+
+        let from = |labels: Vec<&'static str>, matrix: &[[bool; 2]]| {
+            Graph::from_adjacency_matrix(labels, matrix)
+        };
+        assert!(from(vec!["a", "b"], &[[false, true], [false, false]])
+            .unwrap()
+            .depends_on_directly(&"a", &"b"));
+        assert!(matches!(
+            from(vec!["a", "b"], &[[false, true], [true, false]]),
+            Err(Error::CircularDependency)
+        ));
+        assert!(matches!(
+            from(vec!["a", "b"], &[[true, false], [false, false]]),
+            Err(Error::DependsOnSelf)
+        ));
+        assert!(matches!(
+            from(vec!["a", "a"], &[[false; 2], [false; 2]]),
+            Err(Error::InvalidMatrix)
+        ));
+        assert!(matches!(
+            from(vec!["a", "b", "c"], &[[false; 2], [false; 2]]),
+            Err(Error::InvalidMatrix)
+        ));
+        assert!(matches!(
+            Graph::from_adjacency_matrix(vec!["a"], &[vec![false, false]]),
+            Err(Error::InvalidMatrix)
+        ));
+    }
+}
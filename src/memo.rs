@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::Graph;
+
+/// Answers of [`Graph::depends_on`], valid for one mutation epoch
+#[derive(Debug)]
+pub(crate) struct Memo<T> {
+    /// Only changed through `&mut Graph`, so that disabled memoization is checked without locking
+    enabled: bool,
+    answers: Mutex<Answers<T>>,
+}
+
+#[derive(Clone, Debug)]
+struct Answers<T> {
+    /// Epoch the answers were computed in
+    epoch: u64,
+    answers: HashMap<(T, T), bool>,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns the number of mutations to the graph's nodes and edges so far,
+    /// so that equal epochs mean the same nodes and edges
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Turns memoization of [`Graph::depends_on`] on or off. While on,
+    /// repeated questions are answered without walking the graph until
+    /// the next mutation, which drops all memoized answers.
+    pub fn set_memoize(&mut self, memoize: bool) {
+        self.memo.enabled = memoize;
+        self.memo.answers.get_mut().unwrap().answers.clear();
+    }
+
+    pub fn memoize(&self) -> bool {
+        self.memo.enabled
+    }
+}
+
+impl<T> Memo<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub(crate) fn get(&self, epoch: u64, dependent: &T, dependency: &T) -> Option<bool> {
+        if !self.enabled {
+            return None;
+        }
+
+        let answers = self.answers.lock().unwrap();
+        if answers.epoch != epoch {
+            return None;
+        }
+
+        answers
+            .answers
+            .get(&(dependent.clone(), dependency.clone()))
+            .copied()
+    }
+
+    pub(crate) fn insert(&self, epoch: u64, dependent: &T, dependency: &T, answer: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut answers = self.answers.lock().unwrap();
+        if answers.epoch != epoch {
+            answers.epoch = epoch;
+            answers.answers.clear();
+        }
+
+        answers
+            .answers
+            .insert((dependent.clone(), dependency.clone()), answer);
+    }
+}
+
+impl<T> Default for Memo<T> {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            answers: Mutex::new(Answers {
+                epoch: 0,
+                answers: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl<T> Clone for Memo<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            enabled: self.enabled,
+            answers: Mutex::new(self.answers.lock().unwrap().clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memoize() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+        g.depend("c", "b").unwrap();
+        g.set_memoize(true);
+        assert!(g.memoize());
+
+        let epoch = g.epoch();
+        assert!(g.depends_on(&"c", &"a"));
+        assert!(g.depends_on(&"c", &"a"));
+        assert!(!g.depends_on(&"a", &"c"));
+        assert!(!g.depends_on(&"a", &"c"));
+        assert_eq!(g.stats().cache_hits, 2);
+
+        // Failed mutations leave the epoch alone
+        g.depend("a", "c").unwrap_err();
+        g.depend("c", "b").unwrap();
+        assert_eq!(g.epoch(), epoch);
+
+        g.undepend(&"b", &"a").unwrap();
+        assert!(g.epoch() > epoch);
+        assert!(!g.depends_on(&"c", &"a"));
+        assert_eq!(g.stats().cache_hits, 2);
+
+        g.set_memoize(false);
+        g.depends_on(&"c", &"a");
+        g.depends_on(&"c", &"a");
+        assert_eq!(g.stats().cache_hits, 2);
+    }
+}
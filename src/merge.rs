@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+
+use crate::{Error, Graph};
+
+/// Changes of both sides of [`merge3`] that cannot both be kept
+#[derive(Debug)]
+pub enum MergeConflict<T> {
+    /// One side removed node while the other added edge to or from it.
+    /// The merged graph keeps node and the edge.
+    RemovedNodeUsed { node: T, edge: (T, T) },
+    /// One side removed node, but removing it from the merged graph failed.
+    /// The merged graph keeps node.
+    RemovedNode { node: T, error: Error<T> },
+    /// Theirs added edge, which failed on top of our changes,
+    /// e.g. by closing a cycle with edges we added. Edges added by ours
+    /// only fail where the merged graph is more restrictive than ours,
+    /// e.g. when ours raised the maximum depth.
+    /// The merged graph does not have the edge.
+    Edge { edge: (T, T), error: Error<T> },
+    /// Edges added by the sides make a node depend on both nodes of a conflict
+    /// declared with [`Graph::conflict`], e.g. one added by each side,
+    /// failing its install plans. The merged graph keeps the edges.
+    Declared { a: T, b: T },
+}
+
+/// Result of [`merge3`]
+#[derive(Debug)]
+pub struct Merge<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub graph: Graph<T>,
+    pub conflicts: Vec<MergeConflict<T>>,
+}
+
+impl<T> Merge<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Combines ours and theirs, two edited copies of base, by applying
+/// both of their changes from base to a copy of base.
+///
+/// Changes made by only one side, or by both sides alike, are merged as is,
+/// while conflicting changes are reported and resolved as documented
+/// on [`MergeConflict`].
+///
+/// Only nodes and edges are merged, as in [`Graph::diff`]. Everything else
+/// comes from base, including conflicts, policies and the maximum depth,
+/// and edges added by either side lose their kinds, levels, weights and
+/// conditions, as do nodes their metadata.
+pub fn merge3<T>(base: &Graph<T>, ours: &Graph<T>, theirs: &Graph<T>) -> Merge<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    let (ours, theirs) = (base.diff(ours), base.diff(theirs));
+    let mut conflicts = Vec::new();
+
+    // Nodes removed by one side but still used by the other are kept
+    let mut kept = HashSet::new();
+    for (removing, other) in [(&ours, &theirs), (&theirs, &ours)] {
+        for node in &removing.removed_nodes {
+            for (dependent, dependency) in &other.added_edges {
+                if dependent == node || dependency == node {
+                    kept.insert(node.clone());
+                    conflicts.push(MergeConflict::RemovedNodeUsed {
+                        node: node.clone(),
+                        edge: (dependent.clone(), dependency.clone()),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut graph = base.clone();
+    for (dependent, dependency) in ours.removed_edges.union(&theirs.removed_edges) {
+        graph.remove_edge(dependent, dependency);
+    }
+
+    for node in ours.removed_nodes.union(&theirs.removed_nodes) {
+        if !kept.contains(node) && graph.contains(node) {
+            // The removing side removed all edges of node, and the other added none
+            if let Err(error) = graph.remove(node) {
+                conflicts.push(MergeConflict::RemovedNode {
+                    node: node.clone(),
+                    error,
+                });
+            }
+        }
+    }
+
+    for node in ours.added_nodes.union(&theirs.added_nodes) {
+        graph.add_node(node.clone());
+    }
+
+    // Our edges are added first, so that edges of theirs fail on top of ours
+    for (dependent, dependency) in ours.added_edges.iter().chain(&theirs.added_edges) {
+        if let Err(error) = graph.depend(dependent.clone(), dependency.clone()) {
+            conflicts.push(MergeConflict::Edge {
+                edge: (dependent.clone(), dependency.clone()),
+                error,
+            });
+        }
+    }
+
+    // Nodes whose dependencies grew may now pull in both nodes of a conflict
+    let mut declared = HashSet::new();
+    if !graph.conflicts.is_empty() {
+        let mut grown = HashSet::new();
+        for (dependent, _) in ours.added_edges.iter().chain(&theirs.added_edges) {
+            if graph.contains(dependent) {
+                grown.extend(graph.dependents(dependent));
+                grown.insert(dependent.clone());
+            }
+        }
+
+        for node in &grown {
+            let mut plan = graph.dependencies(node);
+            plan.insert(node.clone());
+
+            if let Some((a, b)) = graph.find_conflict(&plan) {
+                if !declared.contains(&(b.clone(), a.clone())) {
+                    declared.insert((a, b));
+                }
+            }
+        }
+    }
+
+    for (a, b) in declared {
+        conflicts.push(MergeConflict::Declared { a, b });
+    }
+
+    Merge { graph, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("app", "xml").unwrap();
+        g.depend("cli", "log").unwrap();
+        g
+    }
+
+    #[test]
+    fn test_merge3() {
+        let base = base();
+
+        let mut ours = base.clone();
+        ours.undepend(&"app", &"xml").unwrap();
+        ours.remove(&"xml").unwrap();
+        ours.depend("app", "json").unwrap();
+
+        let mut theirs = base.clone();
+        theirs.depend("app", "json").unwrap();
+        theirs.depend("http", "log").unwrap();
+        theirs.undepend(&"cli", &"log").unwrap();
+
+        let merge = merge3(&base, &ours, &theirs);
+        assert!(merge.is_clean());
+
+        let g = merge.graph;
+        assert!(!g.contains(&"xml"));
+        assert!(g.depends_on_directly(&"app", &"json"));
+        assert!(g.depends_on_directly(&"http", &"log"));
+        assert!(!g.depends_on_directly(&"cli", &"log"));
+        assert!(g.depends_on(&"app", &"tls"));
+    }
+
+    #[test]
+    fn test_merge3_conflicts() {
+        let base = base();
+
+        let mut ours = base.clone();
+        ours.undepend(&"app", &"xml").unwrap();
+        ours.remove(&"xml").unwrap();
+        ours.depend("tls", "log").unwrap();
+
+        let mut theirs = base.clone();
+        theirs.depend("xml", "tls").unwrap();
+        theirs.depend("log", "http").unwrap();
+
+        let merge = merge3(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts.len(), 2);
+        assert!(merge.conflicts.iter().any(|c| matches!(
+            c,
+            MergeConflict::RemovedNodeUsed {
+                node: "xml",
+                edge: ("xml", "tls")
+            }
+        )));
+        assert!(merge.conflicts.iter().any(|c| matches!(
+            c,
+            MergeConflict::Edge {
+                edge: ("log", "http"),
+                error: Error::CircularDependency
+            }
+        )));
+
+        // Kept node loses its edges removed by ours, but keeps theirs
+        let g = merge.graph;
+        assert!(g.depends_on_directly(&"xml", &"tls"));
+        assert!(!g.depends_on_directly(&"app", &"xml"));
+        assert!(g.depends_on_directly(&"tls", &"log"));
+        assert!(!g.depends_on_directly(&"log", &"http"));
+    }
+
+    #[test]
+    fn test_merge3_ours_too_deep() {
+        let mut base = Graph::new();
+        base.set_max_depth(Some(2));
+        base.depend("a", "b").unwrap();
+
+        // Ours lifted the limit, which the merged graph does not
+        let mut ours = base.clone();
+        ours.set_max_depth(None);
+        ours.depend("b", "c").unwrap();
+        ours.depend("c", "d").unwrap();
+
+        let merge = merge3(&base, &ours, &base);
+        assert!(merge.conflicts.iter().any(|c| matches!(
+            c,
+            MergeConflict::Edge {
+                error: Error::TooDeep { .. },
+                ..
+            }
+        )));
+        assert!(!merge.graph.depends_on(&"a", &"d"));
+    }
+
+    #[test]
+    fn test_merge3_declared_conflict() {
+        let mut base = base();
+        base.conflict("postfix", "exim").unwrap();
+
+        let mut ours = base.clone();
+        ours.depend("http", "postfix").unwrap();
+
+        let mut theirs = base.clone();
+        theirs.depend("app", "exim").unwrap();
+
+        let merge = merge3(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(matches!(
+            merge.conflicts[0],
+            MergeConflict::Declared {
+                a: "postfix",
+                b: "exim"
+            } | MergeConflict::Declared {
+                a: "exim",
+                b: "postfix"
+            }
+        ));
+        assert!(merge.graph.depends_on(&"app", &"postfix"));
+        assert!(merge.graph.depends_on(&"app", &"exim"));
+
+        // Conflicting nodes pulled in by unrelated nodes merge cleanly
+        let mut theirs = base.clone();
+        theirs.depend("log", "exim").unwrap();
+        assert!(merge3(&base, &ours, &theirs).is_clean());
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt::{Display, Write};
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Display,
+{
+    /// Returns the graph as a Mermaid flowchart, with edges pointing
+    /// from dependents to dependencies. Like [`Graph::to_dot`],
+    /// nodes are sorted by their display form.
+    ///
+    /// Nodes get generated ids, and are labeled with their display form.
+    pub fn to_mermaid(&self) -> String {
+        let mut nodes: Vec<(String, &T)> = self.nodes.iter().map(|n| (n.to_string(), n)).collect();
+        nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut mermaid = String::from("graph LR\n");
+        for (i, (name, _)) in nodes.iter().enumerate() {
+            let _ = writeln!(mermaid, "    n{i}[\"{}\"]", escape(name));
+        }
+
+        let ids: std::collections::HashMap<&T, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (_, n))| (*n, i))
+            .collect();
+
+        let mut edges: Vec<(usize, usize)> = self
+            .dependencies
+            .iter()
+            .flat_map(|(dependent, dependencies)| {
+                let ids = &ids;
+                dependencies
+                    .iter()
+                    .map(move |dependency| (ids[dependent], ids[dependency]))
+            })
+            .collect();
+        edges.sort_unstable();
+
+        for (dependent, dependency) in edges {
+            let _ = writeln!(mermaid, "    n{dependent} --> n{dependency}");
+        }
+
+        mermaid
+    }
+}
+
+/// Escapes labels with Mermaid entity codes
+fn escape(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_mermaid() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "say \"hi\"").unwrap();
+        g.depend("http", "say \"hi\"").unwrap();
+
+        assert_eq!(
+            g.to_mermaid(),
+            "\
+graph LR
+    n0[\"app\"]
+    n1[\"http\"]
+    n2[\"say #quot;hi#quot;\"]
+    n0 --> n1
+    n0 --> n2
+    n1 --> n2
+"
+        );
+    }
+}
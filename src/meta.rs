@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use crate::{Error, Graph};
+
+/// Metadata of nodes, keyed by node then metadata key
+pub(crate) type Meta<T> = HashMap<T, HashMap<String, String>>;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Sets metadata key of node to value, returning the previous value.
+    ///
+    /// Metadata lives as long as its node, and is dropped when the node is removed.
+    pub fn set_meta<V>(&mut self, node: &T, key: &str, value: V) -> Result<Option<String>, Error<T>>
+    where
+        V: Into<String>,
+    {
+        if !self.contains(node) {
+            return Err(Error::NoSuchNode);
+        }
+
+        self.touch_meta(node, key);
+        Ok(self
+            .meta
+            .entry(node.clone())
+            .or_default()
+            .insert(key.to_string(), value.into()))
+    }
+
+    pub fn get_meta(&self, node: &T, key: &str) -> Option<&str> {
+        self.meta
+            .get(node)
+            .and_then(|meta| meta.get(key))
+            .map(String::as_str)
+    }
+
+    /// Returns all metadata of node
+    pub fn meta(&self, node: &T) -> Option<&HashMap<String, String>> {
+        self.meta.get(node)
+    }
+
+    /// Removes metadata key of node, returning its value
+    pub fn remove_meta(&mut self, node: &T, key: &str) -> Option<String> {
+        self.touch_meta(node, key);
+        let meta = self.meta.get_mut(node)?;
+        let value = meta.remove(key);
+
+        if meta.is_empty() {
+            self.meta.remove(node);
+        }
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta() {
+        let mut g = Graph::new();
+        g.depend("app", "libc").unwrap();
+
+        assert_eq!(g.set_meta(&"libc", "version", "2.39").unwrap(), None);
+        assert_eq!(
+            g.set_meta(&"libc", "version", "2.40").unwrap(),
+            Some("2.39".to_string())
+        );
+        g.set_meta(&"libc", "path", "/usr/lib/libc.so").unwrap();
+        g.set_meta(&"app", "description", "an app").unwrap();
+        g.set_meta(&"sun", "version", "1")
+            .expect_err("no such node");
+
+        assert_eq!(g.get_meta(&"libc", "version"), Some("2.40"));
+        assert_eq!(g.get_meta(&"libc", "license"), None);
+        assert_eq!(g.meta(&"libc").unwrap().len(), 2);
+        assert!(g.meta(&"sun").is_none());
+
+        assert_eq!(
+            g.remove_meta(&"app", "description"),
+            Some("an app".to_string())
+        );
+        assert!(g.meta(&"app").is_none());
+
+        // Metadata goes away with its node
+        g.remove_force(&"libc");
+        assert!(g.meta.is_empty());
+
+        g.depend("app", "libc").unwrap();
+        assert_eq!(g.get_meta(&"libc", "version"), None);
+    }
+}
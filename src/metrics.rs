@@ -0,0 +1,136 @@
+//! Centrality metrics over the dependency graph, for finding structural chokepoints.
+//!
+//! Distances count edges along dependency edges, from dependent to dependency.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::Graph;
+
+/// Returns betweenness centrality of every node: the number of shortest paths
+/// between other nodes that pass through it, with each pair of nodes split evenly
+/// among its shortest paths.
+///
+/// Nodes every path from dependents to dependencies must go through score highest.
+pub fn betweenness<T>(g: &Graph<T>) -> HashMap<T, f64>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    let mut scores: HashMap<&T, f64> = g.nodes.iter().map(|node| (node, 0.0)).collect();
+
+    // Brandes' algorithm, one breadth-first search per source
+    for source in &g.nodes {
+        let mut order = Vec::new();
+        let mut predecessors: HashMap<&T, Vec<&T>> = HashMap::new();
+        let mut paths: HashMap<&T, f64> = HashMap::from([(source, 1.0)]);
+        let mut distances: HashMap<&T, usize> = HashMap::from([(source, 0)]);
+        let mut q = VecDeque::from([source]);
+
+        while let Some(current) = q.pop_front() {
+            order.push(current);
+            let distance = distances[current];
+
+            for next in g.dependencies.get(current).into_iter().flatten() {
+                if !distances.contains_key(next) {
+                    distances.insert(next, distance + 1);
+                    q.push_back(next);
+                }
+
+                if distances[next] == distance + 1 {
+                    *paths.entry(next).or_default() += paths[current];
+                    predecessors.entry(next).or_default().push(current);
+                }
+            }
+        }
+
+        let mut dependency: HashMap<&T, f64> = HashMap::new();
+        for node in order.into_iter().rev() {
+            let share = (1.0 + dependency.get(node).copied().unwrap_or_default()) / paths[node];
+            for predecessor in predecessors.get(node).into_iter().flatten() {
+                *dependency.entry(predecessor).or_default() += paths[predecessor] * share;
+            }
+
+            if node != source {
+                *scores.get_mut(node).unwrap() += dependency.get(node).copied().unwrap_or_default();
+            }
+        }
+    }
+
+    scores
+        .into_iter()
+        .map(|(node, score)| (node.clone(), score))
+        .collect()
+}
+
+/// Returns harmonic centrality of every node: the sum of 1/d over every
+/// node depending on it at distance d.
+///
+/// Unlike counting dependents, nearby dependents weigh more than distant ones.
+pub fn harmonic<T>(g: &Graph<T>) -> HashMap<T, f64>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    g.nodes
+        .iter()
+        .map(|node| {
+            let mut score = 0.0;
+            let mut distances: HashMap<&T, usize> = HashMap::from([(node, 0)]);
+            let mut q = VecDeque::from([node]);
+
+            while let Some(current) = q.pop_front() {
+                let distance = distances[current] + 1;
+
+                for dependent in g.dependents.get(current).into_iter().flatten() {
+                    if distances.contains_key(dependent) {
+                        continue;
+                    }
+
+                    score += 1.0 / distance as f64;
+                    distances.insert(dependent, distance);
+                    q.push_back(dependent);
+                }
+            }
+
+            (node.clone(), score)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn services() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.depend("web", "api").unwrap();
+        g.depend("cli", "api").unwrap();
+        g.depend("api", "auth").unwrap();
+        g.depend("api", "db").unwrap();
+        g.depend("auth", "db").unwrap();
+
+        g
+    }
+
+    #[test]
+    fn test_betweenness() {
+        let scores = betweenness(&services());
+
+        // web and cli reach auth and db only through api
+        assert_eq!(scores[&"api"], 4.0);
+        // Shortest path api to db skips auth
+        assert_eq!(scores[&"auth"], 0.0);
+        assert_eq!(scores[&"web"], 0.0);
+        assert_eq!(scores[&"db"], 0.0);
+        assert_eq!(scores.len(), 5);
+    }
+
+    #[test]
+    fn test_harmonic() {
+        let scores = harmonic(&services());
+
+        assert_eq!(scores[&"web"], 0.0);
+        assert_eq!(scores[&"api"], 2.0);
+        assert_eq!(scores[&"auth"], 1.0 + 0.5 + 0.5);
+        // api and auth directly, web and cli at 2
+        assert_eq!(scores[&"db"], 3.0);
+    }
+}
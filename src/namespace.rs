@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::{EdgePolicy, Graph};
+
+/// Node identifier scoped to a namespace, e.g. a service, package or config,
+/// so that domains may share a graph without prefixing names.
+///
+/// Displays as `ns:id`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Scoped<N, T> {
+    pub ns: N,
+    pub id: T,
+}
+
+impl<N, T> Scoped<N, T> {
+    pub fn new(ns: N, id: T) -> Self {
+        Self { ns, id }
+    }
+}
+
+impl<N, T> std::fmt::Display for Scoped<N, T>
+where
+    N: std::fmt::Display,
+    T: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.ns, self.id)
+    }
+}
+
+impl<N, T> Graph<Scoped<N, T>>
+where
+    N: Clone + Eq + Hash,
+    T: Clone + Eq + Hash,
+{
+    /// Returns namespaces of all nodes
+    pub fn namespaces(&self) -> HashSet<N> {
+        self.nodes.iter().map(|node| node.ns.clone()).collect()
+    }
+
+    /// Returns nodes in namespace ns
+    pub fn namespace(&self, ns: &N) -> HashSet<Scoped<N, T>> {
+        self.nodes
+            .iter()
+            .filter(|node| node.ns == *ns)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns deep dependencies of node that are in namespace ns,
+    /// including those reached through other namespaces
+    pub fn dependencies_in(&self, node: &Scoped<N, T>, ns: &N) -> HashSet<Scoped<N, T>> {
+        let mut dependencies = self.dependencies(node);
+        dependencies.retain(|dependency| dependency.ns == *ns);
+        dependencies
+    }
+
+    /// Returns direct edges whose ends are in different namespaces
+    pub fn cross_namespace_edges(&self) -> Vec<(Scoped<N, T>, Scoped<N, T>)> {
+        self.edges()
+            .filter(|(dependent, dependency)| dependent.ns != dependency.ns)
+            .map(|(dependent, dependency)| (dependent.clone(), dependency.clone()))
+            .collect()
+    }
+
+    /// Returns other namespaces that nodes in namespace ns depend on directly
+    pub fn namespace_dependencies(&self, ns: &N) -> HashSet<N> {
+        self.edges()
+            .filter(|(dependent, dependency)| dependent.ns == *ns && dependency.ns != *ns)
+            .map(|(_, dependency)| dependency.ns.clone())
+            .collect()
+    }
+}
+
+/// [`EdgePolicy`] allowing edges within namespaces,
+/// and across them only from and to allowed namespaces
+#[derive(Clone, Debug)]
+pub struct NamespacePolicy<N> {
+    /// Pairs of dependent and dependency namespaces
+    pub allowed: HashSet<(N, N)>,
+}
+
+impl<N> NamespacePolicy<N>
+where
+    N: Eq + Hash,
+{
+    /// Returns a policy forbidding every cross-namespace edge
+    pub fn new() -> Self {
+        Self {
+            allowed: HashSet::new(),
+        }
+    }
+
+    /// Allows nodes in namespace dependent to depend on nodes in namespace dependency
+    pub fn allow(&mut self, dependent: N, dependency: N) {
+        self.allowed.insert((dependent, dependency));
+    }
+}
+
+impl<N> Default for NamespacePolicy<N>
+where
+    N: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, T> EdgePolicy<Scoped<N, T>> for NamespacePolicy<N>
+where
+    N: Clone + Eq + Hash + Send + Sync,
+{
+    fn allows(&self, dependent: &Scoped<N, T>, dependency: &Scoped<N, T>) -> bool {
+        dependent.ns == dependency.ns
+            || self
+                .allowed
+                .contains(&(dependent.ns.clone(), dependency.ns.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_namespaces() {
+        let (svc, pkg) = (|id| Scoped::new("svc", id), |id| Scoped::new("pkg", id));
+
+        let mut g = Graph::new();
+        g.depend(svc("api"), svc("auth")).unwrap();
+        g.depend(svc("api"), pkg("http")).unwrap();
+        g.depend(svc("auth"), pkg("jwt")).unwrap();
+        g.depend(pkg("jwt"), pkg("crypto")).unwrap();
+        g.add_node(Scoped::new("cfg", "api.toml"));
+
+        assert_eq!(g.namespaces(), HashSet::from(["svc", "pkg", "cfg"]));
+        assert_eq!(
+            g.namespace(&"svc"),
+            HashSet::from([svc("api"), svc("auth")])
+        );
+        assert_eq!(
+            g.dependencies_in(&svc("api"), &"pkg"),
+            HashSet::from([pkg("http"), pkg("jwt"), pkg("crypto")])
+        );
+        assert_eq!(g.namespace_dependencies(&"svc"), HashSet::from(["pkg"]));
+        assert!(g.namespace_dependencies(&"pkg").is_empty());
+
+        let mut cross = g.cross_namespace_edges();
+        cross.sort();
+        assert_eq!(
+            cross,
+            [(svc("api"), pkg("http")), (svc("auth"), pkg("jwt"))]
+        );
+
+        assert_eq!(svc("api").to_string(), "svc:api");
+    }
+
+    #[test]
+    fn test_namespace_policy() {
+        let mut policy = NamespacePolicy::new();
+        policy.allow("svc", "pkg");
+
+        let mut g = Graph::new();
+        g.add_policy(Arc::new(policy));
+
+        g.depend(Scoped::new("svc", "api"), Scoped::new("svc", "auth"))
+            .unwrap();
+        g.depend(Scoped::new("svc", "api"), Scoped::new("pkg", "http"))
+            .unwrap();
+        assert!(matches!(
+            g.depend(Scoped::new("pkg", "http"), Scoped::new("svc", "auth")),
+            Err(Error::Forbidden)
+        ));
+        assert!(matches!(
+            g.depend(Scoped::new("svc", "api"), Scoped::new("cfg", "api.toml")),
+            Err(Error::Forbidden)
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashSet;
+
+use crate::{edges_contain, insert_to_deps, rm_from_deps, Diff, Direction, Edges, Error, Graph};
+
+/// Hypothetical edits on top of a graph, see [`Graph::overlay`].
+///
+/// Queries on the overlay see its edits, while the base graph is neither
+/// changed nor cloned. Dropping the overlay discards the edits, and
+/// [`Overlay::into_diff`] turns them into a [`Diff`] for [`Graph::apply_diff`].
+///
+/// Unlike the graph, the overlay does not check max depth
+/// and never collects orphans.
+#[derive(Clone, Debug)]
+pub struct Overlay<'a, T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    base: &'a Graph<T>,
+    /// Nodes missing from base
+    added_nodes: HashSet<T>,
+    /// Nodes of base
+    removed_nodes: HashSet<T>,
+    /// Edges missing from base, keyed by dependent
+    added: Edges<T>,
+    /// Same edges as added, keyed by dependency
+    added_rev: Edges<T>,
+    /// Edges of base, keyed by dependent
+    removed: Edges<T>,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns an empty overlay on top of the graph, for trying out edits
+    pub fn overlay(&self) -> Overlay<'_, T> {
+        Overlay {
+            base: self,
+            added_nodes: HashSet::new(),
+            removed_nodes: HashSet::new(),
+            added: Edges::new(),
+            added_rev: Edges::new(),
+            removed: Edges::new(),
+        }
+    }
+}
+
+impl<T> Overlay<'_, T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn contains(&self, node: &T) -> bool {
+        self.added_nodes.contains(node)
+            || (self.base.contains(node) && !self.removed_nodes.contains(node))
+    }
+
+    /// Returns whether dependent depends directly on dependency
+    pub fn depends_on_directly(&self, dependent: &T, dependency: &T) -> bool {
+        edges_contain(&self.added, dependent, dependency)
+            || (self.base.depends_on_directly(dependent, dependency)
+                && !edges_contain(&self.removed, dependent, dependency))
+    }
+
+    /// Returns deep dependencies of node
+    pub fn dependencies(&self, node: &T) -> HashSet<T> {
+        self.reach(node, Direction::Dependencies)
+    }
+
+    /// Returns deep dependents of node
+    pub fn dependents(&self, node: &T) -> HashSet<T> {
+        self.reach(node, Direction::Dependents)
+    }
+
+    /// Returns whether dependent depends on dependency in some way
+    pub fn depends_on(&self, dependent: &T, dependency: &T) -> bool {
+        self.dependencies(dependent).contains(dependency)
+    }
+
+    /// Adds node without edges, if it is not already in the overlay
+    pub fn add_node(&mut self, node: T) {
+        if self.contains(&node) {
+            return;
+        }
+
+        if !self.removed_nodes.remove(&node) {
+            self.added_nodes.insert(node);
+        }
+    }
+
+    /// Like [`Graph::depend`], checking the edge against the overlay
+    /// and the policies of the base graph
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
+        if dependent == dependency {
+            return Err(Error::DependsOnSelf);
+        }
+
+        if self.depends_on(&dependency, &dependent) {
+            return Err(Error::CircularDependency);
+        }
+
+        if self.depends_on_directly(&dependent, &dependency) {
+            return Ok(false);
+        }
+
+        if !self.base.policies.allows(&dependent, &dependency) {
+            return Err(Error::Forbidden);
+        }
+
+        self.add_node(dependent.clone());
+        self.add_node(dependency.clone());
+
+        if edges_contain(&self.removed, &dependent, &dependency) {
+            rm_from_deps(&mut self.removed, &dependent, &dependency);
+        } else {
+            insert_to_deps(&mut self.added_rev, dependency.clone(), dependent.clone());
+            insert_to_deps(&mut self.added, dependent, dependency);
+        }
+
+        Ok(true)
+    }
+
+    /// Like [`Graph::undepend`]
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
+        if !self.depends_on_directly(dependent, dependency) {
+            return Err(Error::NoSuchDirectDependency);
+        }
+
+        self.unlink(dependent, dependency);
+
+        Ok(())
+    }
+
+    /// Like [`Graph::remove`], removing target with the edges to its
+    /// dependencies and from its weak dependents
+    pub fn remove(&mut self, target: &T) -> Result<(), Error<T>> {
+        if !self.contains(target) {
+            return Err(Error::NoSuchNode);
+        }
+
+        let dependents: Vec<T> = self.next(target, Direction::Dependents).cloned().collect();
+        if dependents.iter().any(|d| !self.base.is_weak(d, target)) {
+            return Err(Error::DependencyExists);
+        }
+
+        let dependencies: Vec<T> = self
+            .next(target, Direction::Dependencies)
+            .cloned()
+            .collect();
+
+        for dependent in &dependents {
+            self.unlink(dependent, target);
+        }
+
+        for dependency in &dependencies {
+            self.unlink(target, dependency);
+        }
+
+        if !self.added_nodes.remove(target) {
+            self.removed_nodes.insert(target.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the edits as changes from the base graph,
+    /// to be committed with [`Graph::apply_diff`]
+    pub fn into_diff(self) -> Diff<T> {
+        let pairs = |edges: Edges<T>| -> HashSet<(T, T)> {
+            edges
+                .into_iter()
+                .flat_map(|(dependent, dependencies)| {
+                    dependencies
+                        .into_iter()
+                        .map(move |dependency| (dependent.clone(), dependency))
+                })
+                .collect()
+        };
+
+        Diff {
+            added_nodes: self.added_nodes,
+            removed_nodes: self.removed_nodes,
+            added_edges: pairs(self.added),
+            removed_edges: pairs(self.removed),
+        }
+    }
+
+    /// Removes an edge known to be in the overlay
+    fn unlink(&mut self, dependent: &T, dependency: &T) {
+        if edges_contain(&self.added, dependent, dependency) {
+            rm_from_deps(&mut self.added, dependent, dependency);
+            rm_from_deps(&mut self.added_rev, dependency, dependent);
+        } else {
+            insert_to_deps(&mut self.removed, dependent.clone(), dependency.clone());
+        }
+    }
+
+    /// Returns direct neighbors of node in direction
+    fn next<'s>(&'s self, node: &'s T, direction: Direction) -> impl Iterator<Item = &'s T> {
+        let added = match direction {
+            Direction::Dependencies => &self.added,
+            Direction::Dependents => &self.added_rev,
+        };
+
+        direction
+            .edges(self.base)
+            .get(node)
+            .into_iter()
+            .flatten()
+            .filter(move |other| {
+                let (dependent, dependency) = match direction {
+                    Direction::Dependencies => (node, *other),
+                    Direction::Dependents => (*other, node),
+                };
+
+                !edges_contain(&self.removed, dependent, dependency)
+            })
+            .chain(added.get(node).into_iter().flatten())
+    }
+
+    fn reach(&self, node: &T, direction: Direction) -> HashSet<T> {
+        let mut result = HashSet::new();
+        let mut q = vec![node];
+
+        while let Some(current) = q.pop() {
+            for next in self.next(current, direction) {
+                if result.insert(next.clone()) {
+                    q.push(next);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("app", "xml").unwrap();
+        g
+    }
+
+    #[test]
+    fn test_overlay() {
+        let g = graph();
+        let mut o = g.overlay();
+
+        o.undepend(&"app", &"xml").unwrap();
+        o.remove(&"xml").unwrap();
+        assert!(o.depend("app", "json").unwrap());
+        assert!(!o.depend("app", "http").unwrap());
+        o.depend("tls", "openssl").unwrap();
+
+        assert!(!o.contains(&"xml"));
+        assert!(o.depends_on(&"app", &"openssl"));
+        assert_eq!(
+            o.dependents(&"openssl"),
+            HashSet::from(["tls", "http", "app"])
+        );
+        assert!(matches!(
+            o.depend("openssl", "app"),
+            Err(Error::CircularDependency)
+        ));
+        assert!(matches!(o.remove(&"tls"), Err(Error::DependencyExists)));
+        assert!(matches!(
+            o.undepend(&"app", &"xml"),
+            Err(Error::NoSuchDirectDependency)
+        ));
+
+        // Base is untouched
+        assert!(g.depends_on_directly(&"app", &"xml"));
+        assert!(!g.contains(&"json"));
+
+        // Undoing edits in the overlay leaves nothing to commit
+        let mut undo = g.overlay();
+        undo.undepend(&"http", &"tls").unwrap();
+        undo.depend("app", "log").unwrap();
+        undo.depend("http", "tls").unwrap();
+        undo.undepend(&"app", &"log").unwrap();
+        undo.remove(&"log").unwrap();
+        assert!(undo.into_diff().is_empty());
+    }
+
+    #[test]
+    fn test_overlay_commit() {
+        let mut g = graph();
+        let mut o = g.overlay();
+        o.undepend(&"app", &"xml").unwrap();
+        o.remove(&"xml").unwrap();
+        o.depend("app", "json").unwrap();
+        o.add_node("log");
+
+        let diff = o.into_diff();
+        assert_eq!(diff.added_nodes, HashSet::from(["json", "log"]));
+        assert_eq!(diff.removed_nodes, HashSet::from(["xml"]));
+        assert_eq!(diff.added_edges, HashSet::from([("app", "json")]));
+        assert_eq!(diff.removed_edges, HashSet::from([("app", "xml")]));
+
+        let old = g.clone();
+        g.apply_diff(&diff).unwrap();
+        assert!(g.depends_on_directly(&"app", &"json"));
+        assert!(g.contains(&"log"));
+        assert!(!g.contains(&"xml"));
+        assert_eq!(old.diff(&g), diff);
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use rayon::prelude::*;
+
+use crate::{Edges, Graph};
+
+/// Frontier size below which expanding on one thread beats splitting it up
+pub(crate) const MIN_CHUNK: usize = 256;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + Sync,
+{
+    /// Like [`Graph::dependencies`], but expands each level of the search
+    /// on the rayon thread pool, for graphs with millions of edges
+    pub fn par_dependencies(&self, node: &T) -> HashSet<T> {
+        let dependencies = dig_deep_parallel(&self.dependencies, node);
+        self.counters.query(dependencies.len());
+        dependencies
+    }
+
+    /// Like [`Graph::dependents`], but expands each level of the search
+    /// on the rayon thread pool, for graphs with millions of edges
+    pub fn par_dependents(&self, node: &T) -> HashSet<T> {
+        let dependents = dig_deep_parallel(&self.dependents, node);
+        self.counters.query(dependents.len());
+        dependents
+    }
+}
+
+fn dig_deep_parallel<T>(edges: &Edges<T>, node: &T) -> HashSet<T>
+where
+    T: Clone + Eq + std::hash::Hash + Send + Sync,
+{
+    let span = crate::trace::span!("dig_deep_parallel", nodes);
+    let mut result = HashSet::new();
+    let mut frontier = vec![node];
+
+    while !frontier.is_empty() {
+        let discovered: Vec<&T> = frontier
+            .par_iter()
+            .with_min_len(MIN_CHUNK)
+            .flat_map_iter(|current| edges.get(*current).into_iter().flatten())
+            .collect();
+
+        frontier = discovered
+            .into_iter()
+            .filter(|n| result.insert((*n).clone()))
+            .collect();
+    }
+
+    span.record("nodes", result.len());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_dependencies() {
+        let mut g = Graph::new();
+        for i in 1..2000 {
+            g.depend(i, i / 2).unwrap();
+            g.depend(i, (i - 1) / 3).unwrap_or_default();
+        }
+
+        for node in [0, 1, 7, 500, 1999] {
+            assert_eq!(g.par_dependencies(&node), g.dependencies(&node));
+            assert_eq!(g.par_dependents(&node), g.dependents(&node));
+        }
+
+        assert_eq!(g.par_dependents(&0).len(), 1999);
+        assert!(g.par_dependencies(&5000).is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::{Error, Graph};
+
+/// Adjacency sets of a [`PersistentGraph`]
+type Edges<T> = im::HashMap<T, im::HashSet<T>>;
+
+/// Immutable graph whose mutations return a new graph,
+/// sharing unchanged parts with the old one.
+///
+/// Nodes and edges live in persistent hash maps from `im`, so cloning
+/// is O(1) with respect to graph size, and a mutation only copies
+/// the paths of the maps and adjacency sets it touches.
+#[derive(Clone, Debug)]
+pub struct PersistentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    nodes: im::HashSet<T>,
+    dependents: Edges<T>,
+    dependencies: Edges<T>,
+}
+
+impl<T> PersistentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            nodes: im::HashSet::new(),
+            dependents: Edges::new(),
+            dependencies: Edges::new(),
+        }
+    }
+
+    /// Returns a new graph with the dependency edge added,
+    /// along with whether the edge is new rather than already in the graph
+    pub fn depend(&self, dependent: T, dependency: T) -> Result<(Self, bool), Error<T>> {
+        if dependent == dependency {
+            return Err(Error::DependsOnSelf);
+        }
+
+        if self.depends_on(&dependency, &dependent) {
+            return Err(Error::CircularDependency);
+        }
+
+        if self.depends_on_directly(&dependent, &dependency) {
+            return Ok((self.clone(), false));
+        }
+
+        let mut g = self.clone();
+        insert_edge(&mut g.dependents, &dependency, &dependent);
+        insert_edge(&mut g.dependencies, &dependent, &dependency);
+        g.nodes.insert(dependent);
+        g.nodes.insert(dependency);
+
+        Ok((g, true))
+    }
+
+    /// Returns a new graph with the dependency edge removed
+    pub fn undepend(&self, dependent: &T, dependency: &T) -> Result<Self, Error<T>> {
+        if !self.depends_on_directly(dependent, dependency) {
+            return Err(Error::NoSuchDirectDependency);
+        }
+
+        let mut g = self.clone();
+        remove_edge(&mut g.dependencies, dependent, dependency);
+        remove_edge(&mut g.dependents, dependency, dependent);
+
+        Ok(g)
+    }
+
+    /// Returns a new graph with undepended target removed
+    pub fn remove(&self, target: &T) -> Result<Self, Error<T>> {
+        if !self.contains(target) {
+            return Err(Error::NoSuchNode);
+        }
+
+        if self.is_dependend(target) {
+            return Err(Error::DependencyExists);
+        }
+
+        let mut g = self.clone();
+        if let Some(dependencies) = g.dependencies.remove(target) {
+            for dependency in dependencies.iter() {
+                remove_edge(&mut g.dependents, dependency, target);
+            }
+        }
+
+        g.nodes.remove(target);
+
+        Ok(g)
+    }
+
+    pub fn contains(&self, node: &T) -> bool {
+        self.nodes.contains(node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether dependent depends directly on dependency
+    pub fn depends_on_directly(&self, dependent: &T, dependency: &T) -> bool {
+        self.dependencies
+            .get(dependent)
+            .is_some_and(|deps| deps.contains(dependency))
+    }
+
+    /// Returns deep dependencies of node
+    pub fn dependencies(&self, node: &T) -> HashSet<T> {
+        dig_deep(&self.dependencies, node)
+    }
+
+    /// Returns deep dependents of node
+    pub fn dependents(&self, node: &T) -> HashSet<T> {
+        dig_deep(&self.dependents, node)
+    }
+
+    /// Returns whether dependent depends on dependency in some way
+    pub fn depends_on(&self, dependent: &T, dependency: &T) -> bool {
+        self.dependencies(dependent).contains(dependency)
+    }
+
+    /// Returns whether the node is depended on by other
+    pub fn is_dependend(&self, node: &T) -> bool {
+        self.dependents
+            .get(node)
+            .is_some_and(|deps| !deps.is_empty())
+    }
+
+    /// Copies the graph into a mutable [`Graph`]
+    pub fn to_graph(&self) -> Graph<T> {
+        let mut g = Graph::new();
+        for node in self.nodes.iter() {
+            g.insert_node(node);
+        }
+
+        for (dependent, dependencies) in self.dependencies.iter() {
+            for dependency in dependencies.iter() {
+                g.depend(dependent.clone(), dependency.clone())
+                    .unwrap_or_else(|err| {
+                        panic!("persistent graph should be acyclic: {}", err.map(drop))
+                    });
+            }
+        }
+
+        g
+    }
+}
+
+impl<T> Default for PersistentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<&Graph<T>> for PersistentGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn from(graph: &Graph<T>) -> Self {
+        let edges = |edges: &crate::Edges<T>| {
+            edges
+                .iter()
+                .map(|(key, set)| (key.clone(), set.iter().cloned().collect()))
+                .collect()
+        };
+
+        Self {
+            nodes: graph.nodes.iter().cloned().collect(),
+            dependents: edges(&graph.dependents),
+            dependencies: edges(&graph.dependencies),
+        }
+    }
+}
+
+fn insert_edge<T>(edges: &mut Edges<T>, key: &T, value: &T)
+where
+    T: Clone + Eq + Hash,
+{
+    edges.entry(key.clone()).or_default().insert(value.clone());
+}
+
+fn remove_edge<T>(edges: &mut Edges<T>, key: &T, value: &T)
+where
+    T: Clone + Eq + Hash,
+{
+    let Some(set) = edges.get_mut(key) else {
+        return;
+    };
+
+    set.remove(value);
+    if set.is_empty() {
+        edges.remove(key);
+    }
+}
+
+fn dig_deep<T>(edges: &Edges<T>, node: &T) -> HashSet<T>
+where
+    T: Clone + Eq + Hash,
+{
+    let mut result = HashSet::new();
+    let mut q = vec![node];
+
+    while let Some(current) = q.pop() {
+        for next in edges.get(current).into_iter().flat_map(|set| set.iter()) {
+            if result.insert(next.clone()) {
+                q.push(next);
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_no_dangling;
+
+    #[test]
+    fn test_persistent_graph() {
+        let empty = PersistentGraph::new();
+        let (v1, new) = empty.depend("b", "a").unwrap();
+        assert!(new);
+        let (v2, _) = v1.depend("c", "b").unwrap();
+        let v3 = v2.undepend(&"b", &"a").unwrap();
+
+        v2.depend("a", "c").expect_err("circular dependency");
+
+        assert!(empty.is_empty());
+        assert_eq!(v1.len(), 2);
+        assert!(!v1.contains(&"c"));
+
+        assert!(v2.depends_on(&"c", &"a"));
+        assert_eq!(v2.dependents(&"a"), HashSet::from(["b", "c"]));
+        assert!(!v3.depends_on(&"c", &"a"));
+        assert!(v3.contains(&"a"));
+
+        v3.remove(&"b").expect_err("b is depended on by c");
+        let v4 = v3.remove(&"c").unwrap();
+        assert!(!v4.contains(&"c"));
+        assert!(!v4.is_dependend(&"b"));
+        assert!(v3.is_dependend(&"b"));
+
+        // Untouched adjacency sets are shared as is
+        let (v5, _) = v2.depend("d", "a").unwrap();
+        let (same, new) = v5.depend("d", "a").unwrap();
+        assert!(!new);
+        assert!(same.dependencies.ptr_eq(&v5.dependencies));
+        assert!(v2.dependencies[&"c"].ptr_eq(&v5.dependencies[&"c"]));
+        assert!(!v2.dependents[&"a"].ptr_eq(&v5.dependents[&"a"]));
+
+        let g = v5.to_graph();
+        assert_no_dangling(&g);
+        assert!(g.depends_on(&"c", &"a"));
+        assert!(g.depends_on(&"d", &"a"));
+
+        let round_trip = PersistentGraph::from(&g);
+        assert_eq!(round_trip.dependents(&"a"), HashSet::from(["b", "c", "d"]));
+    }
+}
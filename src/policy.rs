@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use crate::Graph;
+
+/// Validates new edges before [`Graph::depend`] adds them,
+/// e.g. for enforcing architectural constraints.
+///
+/// Closures taking dependent and dependency are policies.
+pub trait EdgePolicy<T>: Send + Sync {
+    /// Returns whether dependent may depend directly on dependency
+    fn allows(&self, dependent: &T, dependency: &T) -> bool;
+}
+
+impl<T, F> EdgePolicy<T> for F
+where
+    F: Fn(&T, &T) -> bool + Send + Sync,
+{
+    fn allows(&self, dependent: &T, dependency: &T) -> bool {
+        self(dependent, dependency)
+    }
+}
+
+/// Policies registered on a graph, shared by clones of the graph
+#[derive(Clone)]
+pub(crate) struct Policies<T>(Vec<Arc<dyn EdgePolicy<T>>>);
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Registers policy for edges added from now on. Existing edges are not checked.
+    ///
+    /// Edges rejected by any policy fail with [`Error::Forbidden`](crate::Error::Forbidden).
+    pub fn add_policy(&mut self, policy: Arc<dyn EdgePolicy<T>>) {
+        self.policies.0.push(policy);
+    }
+
+    /// Removes policy previously registered with [`Graph::add_policy`]
+    pub fn remove_policy(&mut self, policy: &Arc<dyn EdgePolicy<T>>) {
+        self.policies.0.retain(|p| !Arc::ptr_eq(p, policy));
+    }
+
+    /// Removes all registered policies
+    pub fn clear_policies(&mut self) {
+        self.policies.0.clear();
+    }
+}
+
+impl<T> Policies<T> {
+    pub(crate) fn allows(&self, dependent: &T, dependency: &T) -> bool {
+        self.0
+            .iter()
+            .all(|policy| policy.allows(dependent, dependency))
+    }
+}
+
+impl<T> Default for Policies<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> std::fmt::Debug for Policies<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Policies({})", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_edge_policy() {
+        let mut g = Graph::new();
+        g.depend("test::e2e", "app::server").unwrap();
+
+        let no_tests: Arc<dyn EdgePolicy<&str>> =
+            Arc::new(|dependent: &&str, dependency: &&str| {
+                !(dependent.starts_with("app::") && dependency.starts_with("test::"))
+            });
+        g.add_policy(no_tests.clone());
+
+        assert!(matches!(
+            g.depend("app::server", "test::fixtures"),
+            Err(Error::Forbidden)
+        ));
+        assert!(!g.contains(&"test::fixtures"));
+
+        g.depend("app::server", "app::db").unwrap();
+        g.depend("test::e2e", "test::fixtures").unwrap();
+
+        // Existing edges are not checked again
+        g.add_policy(Arc::new(|_: &&str, dependency: &&str| {
+            *dependency != "app::server"
+        }));
+        g.depend("test::e2e", "app::server").unwrap();
+        assert!(matches!(
+            g.depend("app::cli", "app::server"),
+            Err(Error::Forbidden)
+        ));
+
+        g.remove_policy(&no_tests);
+        g.depend("app::db", "test::fixtures").unwrap();
+
+        g.clear_policies();
+        g.depend("app::cli", "app::server").unwrap();
+    }
+}
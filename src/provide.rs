@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{edges_contain, insert_to_deps, rm_from_deps, Error, Graph, Level};
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Records that provider satisfies dependencies on virtual_name.
+    ///
+    /// A node with providers is virtual: [`Graph::resolve_plan`] replaces it
+    /// with one of its providers. Removing a provider drops what it provides.
+    pub fn provide(&mut self, provider: T, virtual_name: T) -> Result<(), Error<T>> {
+        if provider == virtual_name {
+            return Err(Error::DependsOnSelf);
+        }
+
+        if self.is_virtual(&provider) {
+            return Err(Error::CircularDependency);
+        }
+
+        self.insert_node(&provider);
+        self.touch_pair(&virtual_name, &provider);
+        insert_to_deps(&mut self.provides, virtual_name, provider);
+
+        Ok(())
+    }
+
+    /// Stops provider from satisfying virtual_name
+    pub fn unprovide(&mut self, provider: &T, virtual_name: &T) -> Result<(), Error<T>> {
+        if !edges_contain(&self.provides, virtual_name, provider) {
+            return Err(Error::NoProvider);
+        }
+
+        self.touch_pair(virtual_name, provider);
+        rm_from_deps(&mut self.provides, virtual_name, provider);
+
+        Ok(())
+    }
+
+    /// Returns whether node has providers
+    pub fn is_virtual(&self, node: &T) -> bool {
+        self.provides.contains_key(node)
+    }
+
+    /// Returns nodes providing virtual_name
+    pub fn providers(&self, virtual_name: &T) -> HashSet<T> {
+        self.provides.get(virtual_name).cloned().unwrap_or_default()
+    }
+
+    /// Like [`Graph::install_plan`], but replaces every virtual node
+    /// with the provider chosen by pick.
+    ///
+    /// pick is called once per virtual node with its providers, and every
+    /// dependency on the virtual node then resolves to the same provider.
+    /// Returns [`Error::NoProvider`] if pick returns None or a node
+    /// that is not a provider, and [`Error::Conflict`] if the chosen
+    /// providers conflict with the rest of the plan.
+    pub fn resolve_plan<P>(&self, target: &T, level: Level, mut pick: P) -> Result<Vec<T>, Error<T>>
+    where
+        P: FnMut(&T, &HashSet<T>) -> Option<T>,
+    {
+        if !self.contains(target) && !self.is_virtual(target) {
+            return Err(Error::NoSuchNode);
+        }
+
+        let mut chosen: HashMap<T, T> = HashMap::new();
+        let mut resolve = |node: &T| -> Result<T, Error<T>> {
+            let Some(providers) = self.provides.get(node) else {
+                return Ok(node.clone());
+            };
+
+            if let Some(provider) = chosen.get(node) {
+                return Ok(provider.clone());
+            }
+
+            let provider = pick(node, providers)
+                .filter(|provider| providers.contains(provider))
+                .ok_or(Error::NoProvider)?;
+
+            chosen.insert(node.clone(), provider.clone());
+            Ok(provider)
+        };
+
+        // Depth-first postorder, so that dependencies come first
+        let mut plan = Vec::new();
+        let mut done = HashSet::new();
+        let mut visiting = HashSet::new();
+        let mut stack = vec![(resolve(target)?, false)];
+
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                visiting.remove(&node);
+                done.insert(node.clone());
+                plan.push(node);
+                continue;
+            }
+
+            if done.contains(&node) {
+                continue;
+            }
+
+            // A provider reaching its own virtual node
+            if !visiting.insert(node.clone()) {
+                return Err(Error::CircularDependency);
+            }
+
+            stack.push((node.clone(), true));
+            for dependency in self.dependencies.get(&node).into_iter().flatten() {
+                if self.level(&node, dependency) > Some(level) {
+                    continue;
+                }
+
+                let dependency = resolve(dependency)?;
+                if visiting.contains(&dependency) {
+                    return Err(Error::CircularDependency);
+                }
+
+                if !done.contains(&dependency) {
+                    stack.push((dependency, false));
+                }
+            }
+        }
+
+        if let Some((a, b)) = self.find_conflict(&plan) {
+            return Err(Error::Conflict { a, b });
+        }
+
+        Ok(plan)
+    }
+
+    /// Drops everything provided by a removed provider
+    pub(crate) fn forget_provider(&mut self, provider: &T) {
+        if self.provides.is_empty() {
+            return;
+        }
+
+        if self.touched.is_some() {
+            let virtuals: Vec<T> = self
+                .provides
+                .iter()
+                .filter(|(_, providers)| providers.contains(provider))
+                .map(|(virtual_name, _)| virtual_name.clone())
+                .collect();
+
+            for virtual_name in &virtuals {
+                self.touch_pair(virtual_name, provider);
+            }
+        }
+
+        self.provides.retain(|_, providers| {
+            providers.remove(provider);
+            !providers.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mail() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.depend("mutt", "mail-transport-agent").unwrap();
+        g.depend("cron", "mail-transport-agent").unwrap();
+        g.depend("mutt", "libc").unwrap();
+        g.depend("postfix", "libc").unwrap();
+        g.depend("postfix", "libssl").unwrap();
+        g.provide("postfix", "mail-transport-agent").unwrap();
+        g.provide("exim", "mail-transport-agent").unwrap();
+
+        g
+    }
+
+    #[test]
+    fn test_provide() {
+        let mut g = mail();
+
+        assert!(g.is_virtual(&"mail-transport-agent"));
+        assert!(!g.is_virtual(&"postfix"));
+        assert!(g.contains(&"exim"));
+        assert_eq!(
+            g.providers(&"mail-transport-agent"),
+            HashSet::from(["postfix", "exim"])
+        );
+
+        g.provide("libc", "libc").expect_err("provides self");
+        g.provide("mail-transport-agent", "mta")
+            .expect_err("virtual nodes cannot provide");
+
+        g.unprovide(&"exim", &"mail-transport-agent").unwrap();
+        g.unprovide(&"exim", &"mail-transport-agent")
+            .expect_err("no longer provides");
+
+        g.remove(&"postfix").unwrap();
+        assert!(!g.is_virtual(&"mail-transport-agent"));
+        assert!(g.provides.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_plan() {
+        let mut g = mail();
+
+        let mut picked = Vec::new();
+        let plan = g
+            .resolve_plan(&"mutt", Level::Depends, |virtual_name, providers| {
+                picked.push(*virtual_name);
+                providers.get("postfix").copied()
+            })
+            .unwrap();
+
+        assert_eq!(picked, vec!["mail-transport-agent"]);
+        assert_eq!(plan.len(), 4);
+        assert!(!plan.contains(&"mail-transport-agent"));
+        assert_eq!(plan.last(), Some(&"mutt"));
+
+        let position = |node| plan.iter().position(|n| *n == node).unwrap();
+        assert!(position("libc") < position("postfix"));
+        assert!(position("libssl") < position("postfix"));
+        assert!(position("postfix") < position("mutt"));
+
+        // The same choice is reused for every dependent
+        g.depend("system", "mutt").unwrap();
+        g.depend("system", "cron").unwrap();
+        let mut calls = 0;
+        let plan = g
+            .resolve_plan(&"system", Level::Depends, |_, _| {
+                calls += 1;
+                Some("exim")
+            })
+            .unwrap();
+        assert_eq!(calls, 1);
+        assert!(plan.contains(&"exim"));
+        assert!(!plan.contains(&"postfix"));
+
+        g.resolve_plan(&"mutt", Level::Depends, |_, _| None)
+            .expect_err("no provider picked");
+        g.resolve_plan(&"mutt", Level::Depends, |_, _| Some("libc"))
+            .expect_err("libc is not a provider");
+
+        // Provider depending on its own virtual node
+        g.depend("exim", "cron").unwrap();
+        g.resolve_plan(&"cron", Level::Depends, |_, _| Some("exim"))
+            .expect_err("circular provider");
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Mutex;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use crate::{Error, Graph};
+
+/// Source of dependencies discovered on demand, e.g. a package registry,
+/// used by [`Graph::expand_from`].
+///
+/// Closures taking a node and returning its dependencies are providers.
+pub trait DependencyProvider<T> {
+    /// Returns direct dependencies of node
+    fn deps_of(&self, node: &T) -> Vec<T>;
+}
+
+/// Async counterpart of [`DependencyProvider`], used by [`Graph::expand_from_async`].
+///
+/// Implementations may use `async fn deps_of`.
+pub trait AsyncDependencyProvider<T> {
+    /// Returns direct dependencies of node
+    fn deps_of(&self, node: &T) -> impl Future<Output = Vec<T>>;
+}
+
+impl<T, F> DependencyProvider<T> for F
+where
+    F: Fn(&T) -> Vec<T>,
+{
+    fn deps_of(&self, node: &T) -> Vec<T> {
+        self(node)
+    }
+}
+
+/// Provider wrapper memoizing answers of the provider it wraps,
+/// so that overlapping expansions do not ask twice.
+///
+/// Answers expire after ttl if it is set, or live until invalidated.
+pub struct CachingProvider<P, T> {
+    provider: P,
+    ttl: Option<Duration>,
+    answers: Mutex<HashMap<T, (Instant, Vec<T>)>>,
+}
+
+impl<P, T> CachingProvider<P, T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new(provider: P, ttl: Option<Duration>) -> Self {
+        Self {
+            provider,
+            ttl,
+            answers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the wrapped provider, dropping the cache
+    pub fn into_inner(self) -> P {
+        self.provider
+    }
+
+    /// Returns number of cached answers, including expired ones
+    pub fn len(&self) -> usize {
+        self.answers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets the answer for node, so that the next call asks again
+    pub fn invalidate(&self, node: &T) {
+        self.answers.lock().unwrap().remove(node);
+    }
+
+    pub fn clear(&self) {
+        self.answers.lock().unwrap().clear();
+    }
+
+    fn get(&self, node: &T) -> Option<Vec<T>> {
+        let mut answers = self.answers.lock().unwrap();
+        let (fetched, dependencies) = answers.get(node)?;
+
+        if self.ttl.is_some_and(|ttl| fetched.elapsed() >= ttl) {
+            answers.remove(node);
+            return None;
+        }
+
+        Some(dependencies.clone())
+    }
+
+    fn put(&self, node: &T, dependencies: &[T]) {
+        self.answers
+            .lock()
+            .unwrap()
+            .insert(node.clone(), (Instant::now(), dependencies.to_vec()));
+    }
+}
+
+impl<P, T> DependencyProvider<T> for CachingProvider<P, T>
+where
+    P: DependencyProvider<T>,
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn deps_of(&self, node: &T) -> Vec<T> {
+        if let Some(dependencies) = self.get(node) {
+            return dependencies;
+        }
+
+        let dependencies = self.provider.deps_of(node);
+        self.put(node, &dependencies);
+
+        dependencies
+    }
+}
+
+impl<P, T> AsyncDependencyProvider<T> for CachingProvider<P, T>
+where
+    P: AsyncDependencyProvider<T>,
+    T: Clone + Eq + std::hash::Hash,
+{
+    async fn deps_of(&self, node: &T) -> Vec<T> {
+        if let Some(dependencies) = self.get(node) {
+            return dependencies;
+        }
+
+        let dependencies = self.provider.deps_of(node).await;
+        self.put(node, &dependencies);
+
+        dependencies
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Discovers the graph reachable from root by asking provider for the
+    /// dependencies of every node reached, each at most once,
+    /// and returns the nodes that were asked about.
+    ///
+    /// Stops at the first dependency that would close a cycle, leaving
+    /// the edges discovered so far in the graph.
+    pub fn expand_from<P>(&mut self, root: T, provider: &P) -> Result<HashSet<T>, Error<T>>
+    where
+        P: DependencyProvider<T> + ?Sized,
+    {
+        self.insert_node(&root);
+
+        let mut expanded = HashSet::from([root.clone()]);
+        let mut q = vec![root];
+
+        while let Some(node) = q.pop() {
+            for dependency in provider.deps_of(&node) {
+                self.depend(node.clone(), dependency.clone())?;
+
+                if expanded.insert(dependency.clone()) {
+                    q.push(dependency);
+                }
+            }
+        }
+
+        Ok(expanded)
+    }
+
+    /// Like [`Graph::expand_from`], but asks an async provider about
+    /// at most limit nodes concurrently. The returned future is runtime-agnostic.
+    ///
+    /// Edges are added, and checked for cycles, as soon as each answer arrives.
+    /// The first cycle drops the requests still in flight.
+    pub async fn expand_from_async<P>(
+        &mut self,
+        root: T,
+        provider: &P,
+        limit: usize,
+    ) -> Result<HashSet<T>, Error<T>>
+    where
+        P: AsyncDependencyProvider<T> + ?Sized,
+    {
+        let limit = limit.max(1);
+        self.insert_node(&root);
+
+        let mut expanded = HashSet::from([root.clone()]);
+        let mut q = vec![root];
+        let mut in_flight = Vec::new();
+
+        std::future::poll_fn(|cx| loop {
+            while in_flight.len() < limit {
+                let Some(node) = q.pop() else {
+                    break;
+                };
+
+                in_flight.push(Box::pin(fetch(provider, node)));
+            }
+
+            if in_flight.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut progressed = false;
+            let mut i = 0;
+
+            while i < in_flight.len() {
+                let Poll::Ready((node, dependencies)) = in_flight[i].as_mut().poll(cx) else {
+                    i += 1;
+                    continue;
+                };
+
+                drop(in_flight.swap_remove(i));
+                progressed = true;
+
+                for dependency in dependencies {
+                    if let Err(err) = self.depend(node.clone(), dependency.clone()) {
+                        return Poll::Ready(Err(err));
+                    }
+
+                    if expanded.insert(dependency.clone()) {
+                        q.push(dependency);
+                    }
+                }
+            }
+
+            if !progressed {
+                return Poll::Pending;
+            }
+        })
+        .await?;
+
+        Ok(expanded)
+    }
+}
+
+/// Asks provider about an owned node, so that requests can be held together
+async fn fetch<T, P>(provider: &P, node: T) -> (T, Vec<T>)
+where
+    P: AsyncDependencyProvider<T> + ?Sized,
+{
+    let dependencies = provider.deps_of(&node).await;
+    (node, dependencies)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::task::{Context, Wake, Waker};
+
+    use super::*;
+    use crate::assert_no_dangling;
+
+    struct ThreadWaker(std::thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Minimal executor for driving futures in tests
+    pub(crate) fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = std::pin::pin!(fut);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    /// Returns Pending once before completing
+    pub(crate) async fn yield_now() {
+        let mut yielded = false;
+        std::future::poll_fn(|cx| {
+            if yielded {
+                return Poll::Ready(());
+            }
+
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+
+    struct Registry {
+        packages: HashMap<&'static str, Vec<&'static str>>,
+        fetched: RefCell<Vec<&'static str>>,
+    }
+
+    impl DependencyProvider<&'static str> for Registry {
+        fn deps_of(&self, node: &&'static str) -> Vec<&'static str> {
+            self.fetched.borrow_mut().push(node);
+            self.packages.get(node).cloned().unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn test_expand_from() {
+        let registry = Registry {
+            packages: HashMap::from([
+                ("app", vec!["web", "log"]),
+                ("web", vec!["log", "http"]),
+                ("http", vec!["log"]),
+                ("cli", vec!["log"]),
+            ]),
+            fetched: RefCell::new(Vec::new()),
+        };
+
+        let mut g = Graph::new();
+        let expanded = g.expand_from("app", &registry).unwrap();
+
+        assert_eq!(expanded, HashSet::from(["app", "web", "log", "http"]));
+        assert_eq!(registry.fetched.borrow().len(), 4);
+        assert!(!g.contains(&"cli"));
+        assert!(g.depends_on(&"app", &"http"));
+        assert_no_dangling(&g);
+
+        let mut g = Graph::new();
+        g.expand_from("leaf", &|_: &&str| Vec::new()).unwrap();
+        assert!(g.contains(&"leaf"));
+
+        let cyclic = |node: &&str| match *node {
+            "a" => vec!["b"],
+            "b" => vec!["a"],
+            _ => vec![],
+        };
+        assert!(matches!(
+            Graph::new().expand_from("a", &cyclic),
+            Err(Error::CircularDependency)
+        ));
+    }
+
+    #[test]
+    fn test_caching_provider() {
+        let registry = Registry {
+            packages: HashMap::from([("app", vec!["web", "log"]), ("web", vec!["log"])]),
+            fetched: RefCell::new(Vec::new()),
+        };
+
+        let cache = CachingProvider::new(registry, None);
+        Graph::new().expand_from("app", &cache).unwrap();
+        Graph::new().expand_from("web", &cache).unwrap();
+
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.provider.fetched.borrow().len(), 3);
+
+        cache.invalidate(&"web");
+        Graph::new().expand_from("app", &cache).unwrap();
+        assert_eq!(
+            *cache.provider.fetched.borrow(),
+            vec!["app", "log", "web", "web"]
+        );
+
+        cache.clear();
+        assert!(cache.is_empty());
+
+        // Expired answers are fetched again
+        let cache = CachingProvider::new(cache.into_inner(), Some(Duration::from_millis(20)));
+        Graph::new().expand_from("web", &cache).unwrap();
+        Graph::new().expand_from("web", &cache).unwrap();
+        assert_eq!(cache.provider.fetched.borrow().len(), 6);
+
+        std::thread::sleep(Duration::from_millis(30));
+        Graph::new().expand_from("web", &cache).unwrap();
+        assert_eq!(cache.provider.fetched.borrow().len(), 8);
+
+        // Async providers are cached too
+        let registry = AsyncRegistry {
+            packages: HashMap::from([("app", vec!["log"])]),
+            current: Cell::new(0),
+            peak: Cell::new(0),
+        };
+        let cache = CachingProvider::new(registry, None);
+        block_on(Graph::new().expand_from_async("app", &cache, 2)).unwrap();
+        block_on(Graph::new().expand_from_async("app", &cache, 2)).unwrap();
+        assert_eq!(cache.len(), 2);
+    }
+
+    struct AsyncRegistry {
+        packages: HashMap<&'static str, Vec<&'static str>>,
+        current: Cell<usize>,
+        peak: Cell<usize>,
+    }
+
+    impl AsyncDependencyProvider<&'static str> for AsyncRegistry {
+        async fn deps_of(&self, node: &&'static str) -> Vec<&'static str> {
+            self.current.set(self.current.get() + 1);
+            self.peak.set(self.peak.get().max(self.current.get()));
+            yield_now().await;
+            self.current.set(self.current.get() - 1);
+
+            self.packages.get(node).cloned().unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn test_expand_from_async() {
+        let registry = AsyncRegistry {
+            packages: HashMap::from([
+                ("app", vec!["a", "b", "c", "d"]),
+                ("a", vec!["log"]),
+                ("b", vec!["log"]),
+                ("c", vec!["log", "http"]),
+                ("d", vec![]),
+                ("http", vec!["log"]),
+            ]),
+            current: Cell::new(0),
+            peak: Cell::new(0),
+        };
+
+        let mut g = Graph::new();
+        let expanded = block_on(g.expand_from_async("app", &registry, 3)).unwrap();
+
+        assert_eq!(expanded.len(), 7);
+        assert_eq!(registry.peak.get(), 3);
+        assert!(g.depends_on(&"app", &"log"));
+        assert!(g.depends_on_directly(&"http", &"log"));
+        assert_no_dangling(&g);
+
+        let mut registry = registry;
+        registry.packages.insert("log", vec!["app"]);
+        assert!(matches!(
+            block_on(Graph::new().expand_from_async("app", &registry, 3)),
+            Err(Error::CircularDependency)
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::{ArcSwap, Cache};
+
+use crate::Graph;
+
+/// Read-only graph published by a [`Publisher`]
+#[derive(Clone, Debug)]
+pub struct FrozenGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    graph: Graph<T>,
+}
+
+/// Publishes immutable versions of a graph to readers.
+///
+/// Writers build a new version off the current one and swap it in whole,
+/// while readers keep querying whichever version they hold.
+/// Neither obtaining the current version nor swapping it takes a lock,
+/// and a [`Reader`] caches it until a new version is published.
+#[derive(Debug)]
+pub struct Publisher<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    current: ArcSwap<FrozenGraph<T>>,
+    version: AtomicU64,
+    writer: Mutex<()>,
+}
+
+/// Caching handle to the current version of a [`Publisher`]
+pub struct Reader<'a, T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    cache: Cache<&'a ArcSwap<FrozenGraph<T>>, Arc<FrozenGraph<T>>>,
+}
+
+impl<T> Publisher<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new(graph: Graph<T>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(FrozenGraph::from(graph)),
+            version: AtomicU64::new(0),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Returns the current version
+    pub fn load(&self) -> Arc<FrozenGraph<T>> {
+        self.current.load_full()
+    }
+
+    /// Returns how many versions have been published since creation
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Replaces the current version with graph
+    pub fn publish(&self, graph: Graph<T>) {
+        let _writer = self.writer.lock().unwrap();
+        self.swap(graph);
+    }
+
+    /// Builds the next version from a copy of the current one,
+    /// publishing it only if f returns Ok.
+    ///
+    /// Concurrent updates are serialized, so no update is lost.
+    pub fn update<R, E, F>(&self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Graph<T>) -> Result<R, E>,
+    {
+        let _writer = self.writer.lock().unwrap();
+        let mut next = self.load().graph.clone();
+        let result = f(&mut next)?;
+        self.swap(next);
+
+        Ok(result)
+    }
+
+    pub fn reader(&self) -> Reader<'_, T> {
+        Reader {
+            cache: Cache::new(&self.current),
+        }
+    }
+
+    fn swap(&self, graph: Graph<T>) {
+        self.current.store(Arc::new(FrozenGraph::from(graph)));
+        self.version.fetch_add(1, Ordering::Release);
+    }
+}
+
+impl<T> Reader<'_, T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns the current version, only reloading it if a new version
+    /// was published since the last call
+    pub fn get(&mut self) -> &Arc<FrozenGraph<T>> {
+        self.cache.load()
+    }
+}
+
+impl<T> From<Graph<T>> for FrozenGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    fn from(mut graph: Graph<T>) -> Self {
+        // Frozen graphs never change
+        graph.unsubscribe_all();
+        Self { graph }
+    }
+}
+
+impl<T> std::ops::Deref for FrozenGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    type Target = Graph<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_publisher() {
+        let mut g = Graph::new();
+        g.depend("b", "a").unwrap();
+
+        let publisher = Publisher::new(g);
+        let mut reader = publisher.reader();
+        let v0 = publisher.load();
+
+        publisher
+            .update(|g| -> Result<bool, Error<&str>> {
+                g.depend("c", "b")?;
+                g.depend("d", "c")
+            })
+            .unwrap();
+
+        // Held versions never change
+        assert!(!v0.contains(&"c"));
+        assert!(reader.get().depends_on(&"d", &"a"));
+        assert_eq!(publisher.version(), 1);
+
+        publisher
+            .update(|g| g.depend("a", "d"))
+            .expect_err("failed updates are not published");
+        assert_eq!(publisher.version(), 1);
+
+        publisher.publish(Graph::new());
+        assert!(reader.get().nodes.is_empty());
+        assert_eq!(publisher.version(), 2);
+    }
+
+    #[test]
+    fn test_publisher_threads() {
+        let publisher = Publisher::new(Graph::new());
+
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let publisher = &publisher;
+                scope.spawn(move || {
+                    for i in 0..25 {
+                        publisher
+                            .update(|g| g.depend(t * 100 + i + 1, t * 100 + i))
+                            .unwrap();
+                    }
+                });
+            }
+
+            scope.spawn(|| {
+                let mut reader = publisher.reader();
+                while reader.get().nodes.len() < 104 {
+                    let g = reader.get().clone();
+                    crate::assert_no_dangling(&g);
+                }
+            });
+        });
+
+        assert_eq!(publisher.version(), 100);
+        assert_eq!(publisher.load().dependencies(&124).len(), 24);
+    }
+}
@@ -0,0 +1,148 @@
+//! Python module `soydepend`, a string-keyed graph for scripts generating DAGs
+//!
+//! Errors of the graph are raised as `ValueError` with their message.
+
+use std::collections::HashSet;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{Error, Graph};
+
+/// Graph of string nodes, exported to Python as `soydepend.Graph`
+#[pyclass(name = "Graph")]
+#[derive(Debug, Default)]
+pub struct PyGraph {
+    graph: Graph<String>,
+}
+
+#[pymethods]
+impl PyGraph {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a graph from JSON, see [`Graph::from_json_str`]
+    #[staticmethod]
+    pub fn from_json(json: &str) -> PyResult<Self> {
+        let graph =
+            Graph::from_json_str(json).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        Ok(Self { graph })
+    }
+
+    /// Returns the graph as JSON, see [`Graph::to_json`]
+    pub fn to_json(&self) -> String {
+        self.graph.to_json()
+    }
+
+    /// Returns whether the edge is new rather than already in the graph
+    pub fn depend(&mut self, dependent: String, dependency: String) -> PyResult<bool> {
+        self.graph.depend(dependent, dependency).map_err(raise)
+    }
+
+    pub fn undepend(&mut self, dependent: String, dependency: String) -> PyResult<()> {
+        self.graph.undepend(&dependent, &dependency).map_err(raise)
+    }
+
+    /// Removes node, failing if it has dependents, see [`Graph::remove`]
+    pub fn remove(&mut self, node: String) -> PyResult<()> {
+        self.graph.remove(&node).map_err(raise)
+    }
+
+    pub fn depends_on(&self, dependent: String, dependency: String) -> bool {
+        self.graph.depends_on(&dependent, &dependency)
+    }
+
+    /// Returns sorted deep dependencies of node
+    pub fn dependencies(&self, node: String) -> Vec<String> {
+        sorted(self.graph.dependencies(&node))
+    }
+
+    /// Returns sorted deep dependents of node
+    pub fn dependents(&self, node: String) -> Vec<String> {
+        sorted(self.graph.dependents(&node))
+    }
+
+    /// Returns [`Graph::layers`] as sorted lists
+    pub fn layers(&self) -> Vec<Vec<String>> {
+        self.graph.layers().into_iter().map(sorted).collect()
+    }
+
+    pub fn __contains__(&self, node: String) -> bool {
+        self.graph.contains(&node)
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.graph.nodes.len()
+    }
+}
+
+impl PyGraph {
+    /// Returns the wrapped graph
+    pub fn into_inner(self) -> Graph<String> {
+        self.graph
+    }
+}
+
+impl From<Graph<String>> for PyGraph {
+    fn from(graph: Graph<String>) -> Self {
+        Self { graph }
+    }
+}
+
+#[pymodule]
+fn soydepend(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGraph>()
+}
+
+fn raise(err: Error<String>) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn sorted(nodes: HashSet<String>) -> Vec<String> {
+    let mut nodes: Vec<String> = nodes.into_iter().collect();
+    nodes.sort();
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    #[test]
+    fn test_python_module() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "soydepend").unwrap();
+            soydepend(&module).unwrap();
+
+            let globals = PyDict::new(py);
+            globals.set_item("soydepend", module).unwrap();
+            let script = c"
+g = soydepend.Graph()
+assert g.depend('app', 'http')
+assert not g.depend('app', 'http')
+g.depend('http', 'tls')
+assert g.depends_on('app', 'tls')
+assert g.dependencies('app') == ['http', 'tls']
+assert g.dependents('tls') == ['app', 'http']
+assert g.layers() == [['tls'], ['http'], ['app']]
+assert 'http' in g and len(g) == 3
+
+try:
+    g.depend('tls', 'app')
+    raise AssertionError('cycle was accepted')
+except ValueError as err:
+    assert str(err) == 'circular dependency'
+
+g.undepend('app', 'http')
+g.remove('http')
+assert soydepend.Graph.from_json(g.to_json()).to_json() == g.to_json()
+";
+            py.run(script, Some(&globals), None).unwrap();
+        });
+    }
+}
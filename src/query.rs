@@ -0,0 +1,423 @@
+//! Query language selecting sets of nodes
+//!
+//! ```text
+//! deps(planet) & !deps(star)
+//! rdeps(x) | roots()
+//! deps(roots()) & "quoted name"
+//! deps(glob(libfoo-*)) & !glob("*-test")
+//! ```
+//!
+//! Bare or double-quoted names select single nodes, by their display form.
+//! Functions are `deps(e)` and `rdeps(e)`, deep dependencies and dependents
+//! of any node of e, `glob(pattern)`, nodes matching a pattern
+//! like [`Graph::find_nodes`], and `roots()`, `leaves()` and `all()`.
+//! `!` complements, `&` intersects and `|` unites, in that order of precedence,
+//! and parentheses group. Parentheses and functions nest at most 256 deep.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// Deepest nesting of parentheses and functions a query may have
+const MAX_DEPTH: usize = 256;
+
+/// Invalid query, or query naming a missing node, at byte offset
+#[derive(Debug, PartialEq, Eq)]
+pub struct QueryError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Parsed query, which may be evaluated against many graphs
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query(Expr);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Node { name: String, offset: usize },
+    Glob(Vec<char>),
+    Deps(Box<Expr>),
+    Rdeps(Box<Expr>),
+    Roots,
+    Leaves,
+    All,
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let mut parser = Parser {
+            tokens: lex(query)?,
+            position: 0,
+            end: query.len(),
+            depth: 0,
+        };
+
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(Self(expr)),
+            Some((offset, _)) => Err(error(*offset, "expected end of query")),
+        }
+    }
+
+    /// Returns nodes of graph selected by this query,
+    /// failing if it names nodes missing from graph
+    pub fn eval<T>(&self, graph: &Graph<T>) -> Result<HashSet<T>, QueryError>
+    where
+        T: Clone + Eq + Hash + Display,
+    {
+        let names: HashMap<String, &T> = graph.nodes.iter().map(|n| (n.to_string(), n)).collect();
+        eval(&self.0, graph, &names)
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Self::parse(query)
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + Hash + Display,
+{
+    /// Parses and evaluates query, see [`Query`]
+    pub fn query(&self, query: &str) -> Result<HashSet<T>, QueryError> {
+        Query::parse(query)?.eval(self)
+    }
+}
+
+fn eval<T>(
+    expr: &Expr,
+    graph: &Graph<T>,
+    names: &HashMap<String, &T>,
+) -> Result<HashSet<T>, QueryError>
+where
+    T: Clone + Eq + Hash + Display,
+{
+    let reach = |inner: &Expr, edges: &crate::Edges<T>| -> Result<HashSet<T>, QueryError> {
+        let mut reached = HashSet::new();
+        for node in eval(inner, graph, names)? {
+            reached.extend(crate::dig_deep(edges, &node));
+        }
+
+        Ok(reached)
+    };
+
+    Ok(match expr {
+        Expr::Node { name, offset } => {
+            let node = names
+                .get(name)
+                .ok_or_else(|| error(*offset, format!("no such node {name}")))?;
+
+            HashSet::from([(*node).clone()])
+        }
+        Expr::Glob(pattern) => names
+            .iter()
+            .filter(|(name, _)| crate::glob::matches(pattern, &name.chars().collect::<Vec<_>>()))
+            .map(|(_, node)| (*node).clone())
+            .collect(),
+        Expr::Deps(inner) => reach(inner, &graph.dependencies)?,
+        Expr::Rdeps(inner) => reach(inner, &graph.dependents)?,
+        Expr::Roots => graph
+            .nodes
+            .iter()
+            .filter(|n| graph.dependents.get(*n).is_none_or(|d| d.is_empty()))
+            .cloned()
+            .collect(),
+        Expr::Leaves => graph.leaves(),
+        Expr::All => graph.nodes.clone(),
+        Expr::Not(inner) => {
+            let excluded = eval(inner, graph, names)?;
+            graph.nodes.difference(&excluded).cloned().collect()
+        }
+        Expr::And(exprs) => {
+            let mut selected = eval(&exprs[0], graph, names)?;
+            for expr in &exprs[1..] {
+                let other = eval(expr, graph, names)?;
+                selected.retain(|node| other.contains(node));
+            }
+
+            selected
+        }
+        Expr::Or(exprs) => {
+            let mut selected = HashSet::new();
+            for expr in exprs {
+                selected.extend(eval(expr, graph, names)?);
+            }
+
+            selected
+        }
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Open,
+    Close,
+    Not,
+    And,
+    Or,
+}
+
+fn lex(query: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '!' => Token::Not,
+            '&' => Token::And,
+            '|' => Token::Or,
+            '"' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => name.push(escaped),
+                            None => return Err(error(offset, "unterminated name")),
+                        },
+                        Some((_, c)) => name.push(c),
+                        None => return Err(error(offset, "unterminated name")),
+                    }
+                }
+
+                Token::Name(name)
+            }
+            c => {
+                let mut name = String::from(c);
+                while let Some((_, c)) = chars.next_if(|(_, c)| !is_special(*c)) {
+                    name.push(c);
+                }
+
+                Token::Name(name)
+            }
+        };
+
+        tokens.push((offset, token));
+    }
+
+    Ok(tokens)
+}
+
+fn is_special(c: char) -> bool {
+    c.is_whitespace() || "()!&|\"".contains(c)
+}
+
+fn error(offset: usize, message: impl Into<String>) -> QueryError {
+    QueryError {
+        offset,
+        message: message.into(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+    /// Parentheses and functions around the current token
+    depth: usize,
+}
+
+impl Parser {
+    /// Parses a whole query, or one nested in parentheses or a function
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        if self.depth > MAX_DEPTH {
+            let offset = self.tokens.get(self.position).map_or(self.end, |(o, _)| *o);
+            return Err(error(offset, "query nested too deeply"));
+        }
+
+        self.depth += 1;
+        let mut exprs = vec![self.and()?];
+        while self.eat(&Token::Or) {
+            exprs.push(self.and()?);
+        }
+        self.depth -= 1;
+
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => Expr::Or(exprs),
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut exprs = vec![self.not()?];
+        while self.eat(&Token::And) {
+            exprs.push(self.not()?);
+        }
+
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => Expr::And(exprs),
+        })
+    }
+
+    /// Parses a primary expression after any number of `!`, which cancel out in pairs
+    fn not(&mut self) -> Result<Expr, QueryError> {
+        let mut negated = false;
+        while self.eat(&Token::Not) {
+            negated = !negated;
+        }
+
+        let expr = self.primary()?;
+        Ok(match negated {
+            true => Expr::Not(Box::new(expr)),
+            false => expr,
+        })
+    }
+
+    fn primary(&mut self) -> Result<Expr, QueryError> {
+        let Some((offset, token)) = self.tokens.get(self.position).cloned() else {
+            return Err(error(self.end, "unexpected end of query"));
+        };
+
+        self.position += 1;
+
+        match token {
+            Token::Open => {
+                let expr = self.or()?;
+                self.expect_close()?;
+                Ok(expr)
+            }
+            Token::Name(name) if self.eat(&Token::Open) => {
+                let expr = match name.as_str() {
+                    "deps" => Expr::Deps(Box::new(self.or()?)),
+                    "rdeps" => Expr::Rdeps(Box::new(self.or()?)),
+                    "glob" => match self.tokens.get(self.position).cloned() {
+                        Some((_, Token::Name(pattern))) => {
+                            self.position += 1;
+                            Expr::Glob(pattern.chars().collect())
+                        }
+                        Some((offset, _)) => return Err(error(offset, "expected a pattern")),
+                        None => return Err(error(self.end, "expected a pattern")),
+                    },
+                    "roots" => Expr::Roots,
+                    "leaves" => Expr::Leaves,
+                    "all" => Expr::All,
+                    _ => return Err(error(offset, format!("unknown function {name}"))),
+                };
+
+                self.expect_close()?;
+                Ok(expr)
+            }
+            Token::Name(name) => Ok(Expr::Node { name, offset }),
+            _ => Err(error(offset, "expected a name, function or (")),
+        }
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        let found = self
+            .tokens
+            .get(self.position)
+            .is_some_and(|(_, t)| t == expected);
+        if found {
+            self.position += 1;
+        }
+
+        found
+    }
+
+    fn expect_close(&mut self) -> Result<(), QueryError> {
+        match self.tokens.get(self.position) {
+            Some((_, Token::Close)) => {
+                self.position += 1;
+                Ok(())
+            }
+            Some((offset, _)) => Err(error(*offset, "expected )")),
+            None => Err(error(self.end, "expected )")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph<&'static str> {
+        let mut g = Graph::new();
+        g.depend("earth", "sun").unwrap();
+        g.depend("moon", "earth").unwrap();
+        g.depend("sun", "star").unwrap();
+        g.depend("rover", "mars").unwrap();
+        g.depend("the moon base", "moon").unwrap();
+        g
+    }
+
+    fn query(query: &str) -> Vec<&'static str> {
+        let mut nodes: Vec<_> = graph().query(query).unwrap().into_iter().collect();
+        nodes.sort();
+        nodes
+    }
+
+    #[test]
+    fn test_query() {
+        assert_eq!(query("deps(moon) & !deps(sun)"), ["earth", "sun"]);
+        assert_eq!(
+            query("rdeps(earth) | roots()"),
+            ["moon", "rover", "the moon base"]
+        );
+        assert_eq!(query("leaves()"), ["mars", "star"]);
+        assert_eq!(query("deps(roots()) & rdeps(sun)"), ["earth", "moon"]);
+        assert_eq!(query("!(all())"), Vec::<&str>::new());
+        assert_eq!(query("rdeps(\"the moon base\" | mars)"), ["rover"]);
+        assert_eq!(query("sun|star&!sun"), ["star", "sun"]);
+        assert_eq!(query("!!mars"), ["mars"]);
+        assert_eq!(query("glob(m*)"), ["mars", "moon"]);
+        assert_eq!(query("rdeps(glob(\"s?n\")) & !glob(*moon*)"), ["earth"]);
+
+        let parsed: Query = "deps(moon)".parse().unwrap();
+        assert_eq!(parsed.eval(&graph()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let err = |query: &str| graph().query(query).unwrap_err();
+
+        assert_eq!(err("deps(pluto)"), error(5, "no such node pluto"));
+        assert_eq!(err("orbit(sun)"), error(0, "unknown function orbit"));
+        assert_eq!(err("deps(sun"), error(8, "expected )"));
+        assert_eq!(err("sun & "), error(6, "unexpected end of query"));
+        assert_eq!(err("sun moon"), error(4, "expected end of query"));
+        assert_eq!(err("(| sun)"), error(1, "expected a name, function or ("));
+        assert_eq!(err("glob(deps(sun))"), error(9, "expected )"));
+        assert_eq!(err("glob(!sun)"), error(5, "expected a pattern"));
+        assert_eq!(err("\"sun").to_string(), "unterminated name at byte 0");
+    }
+
+    #[test]
+    fn test_query_nesting() {
+        assert_eq!(query(&("!".repeat(200_000) + "mars")), ["mars"]);
+        assert_eq!(query(&("!".repeat(200_001) + "!mars")), ["mars"]);
+        assert_eq!(query(&vec!["mars"; 100_000].join(" | ")), ["mars"]);
+        assert_eq!(query(&vec!["!sun"; 10_000].join(" & ")).len(), 6);
+
+        let nested = |depth| "(".repeat(depth) + "moon" + &")".repeat(depth);
+        assert_eq!(query(&nested(MAX_DEPTH)), ["moon"]);
+
+        let err = graph().query(&nested(200_000)).unwrap_err();
+        assert_eq!(err, error(MAX_DEPTH + 1, "query nested too deeply"));
+
+        let deps = "deps(".repeat(200_000) + "moon";
+        let err = graph().query(&deps).unwrap_err();
+        assert_eq!(err, error(5 * (MAX_DEPTH + 1), "query nested too deeply"));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns at most limit nodes with their deep dependent counts,
+    /// most depended upon first. Ties are in no particular order.
+    pub fn rank_by_dependents(&self, limit: usize) -> Vec<(T, usize)> {
+        let mut ranking: Vec<(T, usize)> = self
+            .nodes
+            .iter()
+            .map(|node| (node.clone(), self.dependents(node).len()))
+            .collect();
+
+        ranking.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+        ranking.truncate(limit);
+
+        ranking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_by_dependents() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "db").unwrap();
+        g.depend("http", "libc").unwrap();
+        g.depend("db", "libc").unwrap();
+        g.depend("cli", "libc").unwrap();
+
+        assert_eq!(g.rank_by_dependents(1), vec![("libc", 4)]);
+
+        let ranking = g.rank_by_dependents(10);
+        assert_eq!(ranking.len(), 5);
+        assert_eq!(ranking[1].1, 1);
+        assert_eq!(ranking[2].1, 1);
+        assert!(ranking[3..].iter().all(|(_, count)| *count == 0));
+
+        assert!(Graph::<&str>::new().rank_by_dependents(3).is_empty());
+    }
+}
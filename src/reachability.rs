@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use crate::Graph;
+
+const BITS: usize = u64::BITS as usize;
+
+/// Transitive closure of a graph, indexing nodes with u32 ids and storing
+/// the deep dependencies of each node as a fixed-size bitset,
+/// see [`Graph::reachability`].
+///
+/// Answers [`Graph::depends_on`] with a bit test, at the cost of
+/// a bit for every pair of nodes. Later mutations to the graph are not seen.
+#[derive(Clone, Debug)]
+pub struct Reachability<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    nodes: Vec<T>,
+    ids: HashMap<T, u32>,
+    /// Rows of words per node, where bit i of a row is set if its node depends on node i
+    rows: Vec<u64>,
+    words: usize,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Computes the transitive closure of the graph as bitsets
+    pub fn reachability(&self) -> Reachability<T> {
+        let nodes: Vec<T> = self.layers().into_iter().flatten().collect();
+        let ids: HashMap<T, u32> = nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| (node.clone(), id as u32))
+            .collect();
+
+        let words = nodes.len().div_ceil(BITS);
+        let mut rows = vec![0; nodes.len() * words];
+
+        // Layers put dependencies before their dependents,
+        // so rows of dependencies are complete when they are merged
+        for (id, node) in nodes.iter().enumerate() {
+            let (done, rest) = rows.split_at_mut(id * words);
+            let row = &mut rest[..words];
+
+            for dependency in self.dependencies.get(node).into_iter().flatten() {
+                let dependency = ids[dependency] as usize;
+                let closure = &done[dependency * words..(dependency + 1) * words];
+                for (word, other) in row.iter_mut().zip(closure) {
+                    *word |= other;
+                }
+
+                row[dependency / BITS] |= 1 << (dependency % BITS);
+            }
+        }
+
+        Reachability {
+            nodes,
+            ids,
+            rows,
+            words,
+        }
+    }
+}
+
+impl<T> Reachability<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns the id of node, or None if it was not in the graph
+    pub fn id(&self, node: &T) -> Option<u32> {
+        self.ids.get(node).copied()
+    }
+
+    /// Returns the node with id
+    pub fn node(&self, id: u32) -> Option<&T> {
+        self.nodes.get(id as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Like [`Graph::depends_on`], with a bit test
+    pub fn depends_on(&self, dependent: &T, dependency: &T) -> bool {
+        match (self.id(dependent), self.id(dependency)) {
+            (Some(dependent), Some(dependency)) => self.depends_on_id(dependent, dependency),
+            _ => false,
+        }
+    }
+
+    /// Like [`Reachability::depends_on`], with node ids
+    pub fn depends_on_id(&self, dependent: u32, dependency: u32) -> bool {
+        let dependency = dependency as usize;
+        self.row(dependent)
+            .and_then(|row| row.get(dependency / BITS))
+            .is_some_and(|word| word & (1 << (dependency % BITS)) != 0)
+    }
+
+    /// Returns deep dependencies of node, in order of their ids
+    pub fn dependencies(&self, node: &T) -> impl Iterator<Item = &T> {
+        self.id(node)
+            .into_iter()
+            .flat_map(|id| self.dependency_ids(id))
+            .map(|id| &self.nodes[id as usize])
+    }
+
+    /// Returns ids of deep dependencies of the node with id, in increasing order
+    pub fn dependency_ids(&self, id: u32) -> impl Iterator<Item = u32> + '_ {
+        self.row(id)
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &word)| {
+                let mut word = word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
+
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some((i * BITS + bit) as u32)
+                })
+            })
+    }
+
+    fn row(&self, id: u32) -> Option<&[u64]> {
+        let start = id as usize * self.words;
+        self.rows.get(start..start + self.words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_reachability() {
+        let mut g = Graph::new();
+        for i in 1..200 {
+            g.depend(i, i / 2).unwrap();
+        }
+        g.depend(7, 150).unwrap();
+        g.add_node(500);
+
+        let r = g.reachability();
+        assert_eq!(r.len(), 201);
+
+        for node in [0, 1, 7, 75, 150, 199, 500] {
+            let deep: HashSet<_> = r.dependencies(&node).copied().collect();
+            assert_eq!(deep, g.dependencies(&node));
+            for other in [0, 3, 7, 150, 500] {
+                assert_eq!(r.depends_on(&node, &other), g.depends_on(&node, &other));
+            }
+        }
+
+        let id = r.id(&150).unwrap();
+        assert_eq!(r.node(id), Some(&150));
+        assert!(r.depends_on_id(r.id(&7).unwrap(), id));
+        assert!(r.dependency_ids(id).is_sorted());
+        assert!(!r.depends_on_id(id, 1000));
+        assert_eq!(r.dependencies(&1000).count(), 0);
+        assert!(Graph::<u8>::new().reachability().is_empty());
+    }
+}
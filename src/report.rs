@@ -0,0 +1,165 @@
+//! Human-readable reports summarizing a graph
+
+use std::fmt::{Display, Write};
+
+use crate::Graph;
+
+/// Number of nodes listed in rankings
+const TOP: usize = 10;
+
+/// Returns a Markdown summary of g: node and edge counts, depth stats,
+/// the most depended upon nodes, the deepest chain, diamonds,
+/// and orphans, i.e. nodes without any edges.
+///
+/// Lists are sorted by their display form, so reports of equal graphs are equal.
+pub fn markdown<T>(g: &Graph<T>) -> String
+where
+    T: Clone + Eq + std::hash::Hash + Display,
+{
+    let mut report = String::from("# Dependency report\n\n## Stats\n\n");
+
+    let edges: usize = g.dependencies.values().map(|d| d.len()).sum();
+    let depth = g.depth_stats();
+    let _ = writeln!(report, "- Nodes: {}", g.nodes.len());
+    let _ = writeln!(report, "- Edges: {edges}");
+    let _ = writeln!(report, "- Leaves: {}", g.leaves().len());
+    let _ = writeln!(report, "- Max depth: {}", depth.max);
+    let _ = writeln!(report, "- Mean depth: {:.2}", depth.mean);
+    let _ = writeln!(report, "- Median depth: {}", depth.median);
+
+    report.push_str("\n## Most depended upon\n\n");
+    let mut ranking: Vec<(String, usize)> = g
+        .rank_by_dependents(g.nodes.len())
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(node, count)| (node.to_string(), count))
+        .collect();
+    ranking.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+    ranking.truncate(TOP);
+
+    if ranking.is_empty() {
+        report.push_str("None\n");
+    } else {
+        report.push_str("| Node | Dependents |\n| --- | ---: |\n");
+        for (node, count) in ranking {
+            let _ = writeln!(report, "| `{node}` | {count} |");
+        }
+    }
+
+    report.push_str("\n## Deepest chain\n\n");
+    let chain: Vec<String> = g
+        .deepest_chain()
+        .iter()
+        .map(|node| format!("`{node}`"))
+        .collect();
+    match chain.len() {
+        0 => report.push_str("None\n"),
+        _ => {
+            let _ = writeln!(report, "{}", chain.join(" → "));
+        }
+    }
+
+    report.push_str("\n## Diamonds\n\n");
+    let mut diamonds: Vec<String> = g
+        .find_diamonds()
+        .into_iter()
+        .map(|(top, left, right, bottom)| {
+            let (left, right) = sorted(left.to_string(), right.to_string());
+            format!("- `{top}` → `{left}` / `{right}` → `{bottom}`")
+        })
+        .collect();
+    push_list(&mut report, &mut diamonds);
+
+    report.push_str("\n## Orphans\n\n");
+    let mut orphans: Vec<String> = g
+        .nodes
+        .iter()
+        .filter(|node| g.dependency_count(node) == 0 && g.dependent_count(node) == 0)
+        .map(|node| format!("- `{node}`"))
+        .collect();
+    push_list(&mut report, &mut orphans);
+
+    report
+}
+
+fn sorted(a: String, b: String) -> (String, String) {
+    match a <= b {
+        true => (a, b),
+        false => (b, a),
+    }
+}
+
+fn push_list(report: &mut String, items: &mut [String]) {
+    if items.is_empty() {
+        report.push_str("None\n");
+        return;
+    }
+
+    items.sort();
+    for item in items {
+        report.push_str(item);
+        report.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "db").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("db", "tls").unwrap();
+        g.insert_node(&"scratch");
+        // Reserved capacity does not make nodes depend on anything
+        g.reserve_dependencies("scratch", 4);
+        g.reserve_dependencies("tls", 4);
+
+        let expected = "\
+# Dependency report
+
+## Stats
+
+- Nodes: 5
+- Edges: 4
+- Leaves: 2
+- Max depth: 2
+- Mean depth: 0.80
+- Median depth: 1
+
+## Most depended upon
+
+| Node | Dependents |
+| --- | ---: |
+| `tls` | 3 |
+| `db` | 1 |
+| `http` | 1 |
+
+## Deepest chain
+
+`app` → `db` → `tls`
+
+## Diamonds
+
+- `app` → `db` / `http` → `tls`
+
+## Orphans
+
+- `scratch`
+";
+
+        let report = markdown(&g);
+        let (head, tail) = report.split_once("## Deepest chain").unwrap();
+        let (_, tail) = tail.split_once("## Diamonds").unwrap();
+        assert!(expected.starts_with(head));
+        assert!(expected.ends_with(tail));
+        assert!(report.contains("→ `tls`\n"));
+
+        let empty = markdown(&Graph::<&str>::new());
+        assert!(empty.contains("- Nodes: 0"));
+        assert_eq!(empty.matches("None").count(), 4);
+    }
+}
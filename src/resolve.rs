@@ -0,0 +1,262 @@
+mod version;
+
+pub use version::{ParseError, Version, VersionReq};
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Error, Graph};
+
+/// Concrete version of a package, the node type of resolved graphs
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Package {
+    pub name: String,
+    pub version: Version,
+}
+
+/// Candidate versions of packages and their requirements,
+/// from which [`Index::resolve`] selects
+#[derive(Clone, Debug, Default)]
+pub struct Index {
+    packages: HashMap<String, BTreeMap<Version, Vec<(String, VersionReq)>>>,
+}
+
+#[derive(Debug)]
+pub enum ResolveError {
+    /// A requirement names a package missing from the index
+    NoSuchPackage(String),
+    /// No version of the package satisfies all of its requirements
+    Unsatisfiable {
+        name: String,
+        requirements: Vec<VersionReq>,
+    },
+    /// Selected versions depend on each other in a cycle
+    Graph(Error<Package>),
+}
+
+/// Partial solution, cloned at every choice so that it can be backtracked
+#[derive(Clone)]
+struct State<'a> {
+    selected: HashMap<&'a str, Version>,
+    requirements: HashMap<&'a str, Vec<&'a VersionReq>>,
+    pending: Vec<(&'a str, &'a VersionReq)>,
+}
+
+impl Index {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a candidate version of package with its requirements,
+    /// replacing the candidate if it already exists
+    pub fn add<I>(&mut self, name: &str, version: Version, requirements: I)
+    where
+        I: IntoIterator<Item = (String, VersionReq)>,
+    {
+        self.packages
+            .entry(name.to_string())
+            .or_default()
+            .insert(version, requirements.into_iter().collect());
+    }
+
+    /// Returns candidate versions of package in ascending order
+    pub fn versions(&self, name: &str) -> Vec<Version> {
+        self.packages
+            .get(name)
+            .map(|versions| versions.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Selects one version of every package required by roots,
+    /// preferring the highest versions, and returns the graph of
+    /// selected packages and their dependencies.
+    ///
+    /// Conflicts are resolved by backtracking, so pathological
+    /// indexes may take exponential time.
+    pub fn resolve(&self, roots: &[(String, VersionReq)]) -> Result<Graph<Package>, ResolveError> {
+        let state = State {
+            selected: HashMap::new(),
+            requirements: HashMap::new(),
+            pending: roots.iter().rev().map(|(n, r)| (n.as_str(), r)).collect(),
+        };
+
+        let state = self.solve(state)?;
+
+        let mut graph = Graph::new();
+        for (name, version) in &state.selected {
+            let package = Package::new(name, *version);
+            graph.insert_node(&package);
+
+            for (dependency, _) in &self.packages[*name][version] {
+                let selected = state.selected[dependency.as_str()];
+                graph
+                    .depend(package.clone(), Package::new(dependency, selected))
+                    .map_err(ResolveError::Graph)?;
+            }
+        }
+
+        Ok(graph)
+    }
+
+    fn solve<'a>(&'a self, mut state: State<'a>) -> Result<State<'a>, ResolveError> {
+        while let Some((name, requirement)) = state.pending.pop() {
+            let requirements = state.requirements.entry(name).or_default();
+            requirements.push(requirement);
+            let requirements = requirements.clone();
+
+            let unsatisfiable = || ResolveError::Unsatisfiable {
+                name: name.to_string(),
+                requirements: requirements.iter().map(|r| (*r).clone()).collect(),
+            };
+
+            if let Some(selected) = state.selected.get(name) {
+                match requirement.matches(selected) {
+                    true => continue,
+                    false => return Err(unsatisfiable()),
+                }
+            }
+
+            let versions = self
+                .packages
+                .get(name)
+                .ok_or_else(|| ResolveError::NoSuchPackage(name.to_string()))?;
+
+            let mut failure = unsatisfiable();
+            let candidates = versions
+                .iter()
+                .rev()
+                .filter(|(version, _)| requirements.iter().all(|r| r.matches(version)));
+
+            for (version, dependencies) in candidates {
+                let mut next = state.clone();
+                next.selected.insert(name, *version);
+                next.pending
+                    .extend(dependencies.iter().rev().map(|(n, r)| (n.as_str(), r)));
+
+                match self.solve(next) {
+                    Ok(solved) => return Ok(solved),
+                    Err(err) => failure = err,
+                }
+            }
+
+            return Err(failure);
+        }
+
+        Ok(state)
+    }
+}
+
+impl Package {
+    pub fn new(name: &str, version: Version) -> Self {
+        Self {
+            name: name.to_string(),
+            version,
+        }
+    }
+}
+
+impl std::fmt::Display for Package {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+impl From<Error<Package>> for ResolveError {
+    fn from(err: Error<Package>) -> Self {
+        Self::Graph(err)
+    }
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSuchPackage(name) => write!(f, "no such package: {name}"),
+            Self::Unsatisfiable { name, requirements } => {
+                write!(f, "no version of {name} satisfies")?;
+                for (i, requirement) in requirements.iter().enumerate() {
+                    match i {
+                        0 => write!(f, " {requirement}")?,
+                        _ => write!(f, " and {requirement}")?,
+                    }
+                }
+
+                Ok(())
+            }
+            Self::Graph(err) => write!(f, "graph error: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(name: &str, req: &str) -> (String, VersionReq) {
+        (name.to_string(), req.parse().unwrap())
+    }
+
+    fn v(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    fn index() -> Index {
+        let mut index = Index::new();
+        index.add("app", v("1.0.0"), [req("web", "^2"), req("log", "^0.4")]);
+        index.add("web", v("2.0.0"), [req("log", "^0.4")]);
+        index.add("web", v("2.1.0"), [req("log", "^0.5")]);
+        index.add("log", v("0.4.1"), []);
+        index.add("log", v("0.4.8"), []);
+        index.add("log", v("0.5.0"), []);
+
+        index
+    }
+
+    #[test]
+    fn test_resolve() {
+        let index = index();
+        assert_eq!(
+            index.versions("log"),
+            vec![v("0.4.1"), v("0.4.8"), v("0.5.0")]
+        );
+
+        // web 2.1.0 conflicts with app over log, so web backtracks to 2.0.0
+        let g = index.resolve(&[req("app", "*")]).unwrap();
+        let app = Package::new("app", v("1.0.0"));
+        let web = Package::new("web", v("2.0.0"));
+        let log = Package::new("log", v("0.4.8"));
+
+        assert_eq!(g.nodes.len(), 3);
+        assert!(g.depends_on_directly(&app, &web));
+        assert!(g.depends_on_directly(&app, &log));
+        assert!(g.depends_on_directly(&web, &log));
+        assert_eq!(log.to_string(), "log@0.4.8");
+
+        let g = index.resolve(&[req("web", "^2")]).unwrap();
+        assert!(g.contains(&Package::new("log", v("0.5.0"))));
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let mut index = index();
+
+        let err = index
+            .resolve(&[req("app", "*"), req("log", "^0.5")])
+            .expect_err("conflicting log requirements");
+        assert!(matches!(err, ResolveError::Unsatisfiable { ref name, .. } if name == "log"));
+
+        let err = index.resolve(&[req("db", "*")]).expect_err("no db");
+        assert_eq!(err.to_string(), "no such package: db");
+
+        let err = index.resolve(&[req("app", "^2")]).expect_err("no app 2");
+        assert_eq!(
+            err.to_string(),
+            "no version of app satisfies >=2.0.0, <3.0.0"
+        );
+
+        index.add("log", v("0.4.9"), [req("app", "*")]);
+        let err = index.resolve(&[req("app", "*")]).expect_err("cycle");
+        assert!(matches!(
+            err,
+            ResolveError::Graph(Error::CircularDependency)
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+/// Semantic version without pre-release or build metadata
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+/// Cargo-style version requirement, e.g. `^1.2`, `~0.3.1`, `>=1, <2` or `*`.
+///
+/// Comma-separated comparators must all match.
+/// Bare versions are caret requirements, as in Cargo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionReq {
+    comparators: Vec<(Op, Version)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+}
+
+/// Error parsing a [`Version`] or [`VersionReq`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError(String);
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl VersionReq {
+    /// Requirement matching every version
+    pub const STAR: Self = Self {
+        comparators: Vec::new(),
+    };
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|(op, bound)| match op {
+            Op::Less => version < bound,
+            Op::LessEq => version <= bound,
+            Op::Greater => version > bound,
+            Op::GreaterEq => version >= bound,
+        })
+    }
+}
+
+/// Parses up to 3 dot-separated numbers, returning how many were present
+fn parse_partial(s: &str) -> Result<(Version, usize), ParseError> {
+    let invalid = || ParseError(format!("bad version: {s}"));
+
+    let mut parts = [0; 3];
+    let mut len = 0;
+
+    for part in s.trim().split('.') {
+        if len == 3 {
+            return Err(invalid());
+        }
+
+        parts[len] = part.parse().map_err(|_| invalid())?;
+        len += 1;
+    }
+
+    Ok((Version::new(parts[0], parts[1], parts[2]), len))
+}
+
+/// Returns the smallest version above everything starting with the first len parts
+fn bump(v: Version, len: usize) -> Version {
+    match len {
+        1 => Version::new(v.major + 1, 0, 0),
+        2 => Version::new(v.major, v.minor + 1, 0),
+        _ => Version::new(v.major, v.minor, v.patch + 1),
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_partial(s)? {
+            (version, 3) => Ok(version),
+            _ => Err(ParseError(format!("bad version: {s}"))),
+        }
+    }
+}
+
+impl std::str::FromStr for VersionReq {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut comparators = Vec::new();
+
+        for part in s.split(',').map(str::trim) {
+            if part == "*" {
+                continue;
+            }
+
+            let (op, rest) = [">=", "<=", ">", "<", "=", "^", "~"]
+                .into_iter()
+                .find_map(|op| part.strip_prefix(op).map(|rest| (op, rest)))
+                .unwrap_or(("^", part));
+
+            let (v, len) = parse_partial(rest)?;
+            match op {
+                ">=" => comparators.push((Op::GreaterEq, v)),
+                "<=" => comparators.push((Op::LessEq, v)),
+                ">" => comparators.push((Op::Greater, v)),
+                "<" => comparators.push((Op::Less, v)),
+                "=" => {
+                    comparators.push((Op::GreaterEq, v));
+                    comparators.push((Op::Less, bump(v, len)));
+                }
+                "~" => {
+                    comparators.push((Op::GreaterEq, v));
+                    comparators.push((Op::Less, bump(v, len.min(2))));
+                }
+                _ => {
+                    // Up to and including the first non-zero part is fixed
+                    let fixed = match (v.major, v.minor) {
+                        (0, 0) => len,
+                        (0, _) => 2,
+                        _ => 1,
+                    };
+
+                    comparators.push((Op::GreaterEq, v));
+                    comparators.push((Op::Less, bump(v, fixed.min(len))));
+                }
+            }
+        }
+
+        Ok(Self { comparators })
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl std::fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.comparators.is_empty() {
+            return write!(f, "*");
+        }
+
+        for (i, (op, version)) in self.comparators.iter().enumerate() {
+            let op = match op {
+                Op::Less => "<",
+                Op::LessEq => "<=",
+                Op::Greater => ">",
+                Op::GreaterEq => ">=",
+            };
+
+            match i {
+                0 => write!(f, "{op}{version}")?,
+                _ => write!(f, ", {op}{version}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(req: &str, version: &str) -> bool {
+        let req: VersionReq = req.parse().unwrap();
+        req.matches(&version.parse().unwrap())
+    }
+
+    #[test]
+    fn test_version_req() {
+        assert!(matches("1.2.3", "1.9.0"));
+        assert!(!matches("1.2.3", "2.0.0"));
+        assert!(!matches("^1.2.3", "1.2.2"));
+        assert!(matches("^0.2.3", "0.2.9"));
+        assert!(!matches("^0.2.3", "0.3.0"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+        assert!(matches("^0", "0.9.9"));
+        assert!(matches("~1.2.3", "1.2.9"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches("~1", "1.9.0"));
+        assert!(matches("=1.2", "1.2.7"));
+        assert!(!matches("=1.2.3", "1.2.4"));
+        assert!(matches(">=1, <2", "1.5.0"));
+        assert!(!matches(">=1, <2", "2.0.0"));
+        assert!(matches("*", "0.0.1"));
+        assert!(matches(">1.0.0", "1.0.1"));
+        assert!(!matches("<=1.0.0", "1.0.1"));
+
+        assert_eq!(
+            "^1.2".parse::<VersionReq>().unwrap().to_string(),
+            ">=1.2.0, <2.0.0"
+        );
+        assert_eq!(VersionReq::STAR.to_string(), "*");
+
+        "1.2".parse::<Version>().expect_err("partial version");
+        "1.x".parse::<VersionReq>().expect_err("not a number");
+        "1.2.3.4".parse::<VersionReq>().expect_err("too many parts");
+    }
+}
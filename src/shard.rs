@@ -0,0 +1,15 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Number of shards used by sharded graph layouts
+pub(crate) const SHARDS: usize = 32;
+
+/// Returns which of the shards key belongs to
+pub(crate) fn index<K>(key: &K, shards: usize) -> usize
+where
+    K: Hash + ?Sized,
+{
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish() as usize % shards
+}
@@ -0,0 +1,411 @@
+//! Generators for property tests over graphs
+//!
+//! [`Arbitrary`] mirrors the trait of the same name in quickcheck:
+//! values are generated from a seeded [`Gen`], and failing values shrink
+//! toward smaller ones. Generated graphs are always acyclic,
+//! and shrinking only ever drops nodes and edges, which keeps them so.
+//! [`check`] runs a property over many generated values.
+//!
+//! [`random_dag`] and [`random_shaped_dag`] generate larger graphs
+//! of chosen shapes, e.g. for benchmarks.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// Seeded source of randomness, with a size bounding generated values.
+///
+/// Uses SplitMix64, so the same seed always yields the same values.
+#[derive(Clone, Debug)]
+pub struct Gen {
+    state: u64,
+    size: usize,
+}
+
+impl Gen {
+    pub fn new(seed: u64, size: usize) -> Self {
+        Self { state: seed, size }
+    }
+
+    /// Returns the bound on generated values, e.g. the node count of graphs
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: usize) {
+        self.size = size;
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number below n, or 0 if n is 0
+    pub fn below(&mut self, n: usize) -> usize {
+        match n {
+            0 => 0,
+            n => (self.next_u64() % n as u64) as usize,
+        }
+    }
+
+    /// Returns true with probability p
+    pub fn chance(&mut self, p: f64) -> bool {
+        // 53 random bits fill the mantissa of a float in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        match items.len() {
+            0 => None,
+            len => items.get(self.below(len)),
+        }
+    }
+}
+
+/// Values that can be generated randomly and shrunk when they fail a property
+pub trait Arbitrary: Sized {
+    fn arbitrary(g: &mut Gen) -> Self;
+
+    /// Returns smaller values to try in place of this failing one
+    fn shrink(&self) -> Vec<Self> {
+        Vec::new()
+    }
+}
+
+macro_rules! arbitrary_uint {
+    ($($t:ty),*) => {$(
+        impl Arbitrary for $t {
+            fn arbitrary(g: &mut Gen) -> Self {
+                g.below(g.size() * 4 + 1) as $t
+            }
+
+            fn shrink(&self) -> Vec<Self> {
+                let mut smaller = vec![0, self / 2, self.saturating_sub(1)];
+                smaller.dedup();
+                smaller.retain(|n| n < self);
+                smaller
+            }
+        }
+    )*};
+}
+
+arbitrary_uint!(u8, u16, u32, u64, usize);
+
+impl Arbitrary for String {
+    fn arbitrary(g: &mut Gen) -> Self {
+        (0..g.below(g.size() + 1))
+            .map(|_| (b'a' + g.below(26) as u8) as char)
+            .collect()
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let chars: Vec<char> = self.chars().collect();
+        let mut smaller = vec![
+            String::new(),
+            chars[..chars.len() / 2].iter().collect(),
+            chars[..chars.len().saturating_sub(1)].iter().collect(),
+        ];
+        smaller.dedup();
+        smaller.retain(|s| s.len() < self.len());
+        smaller
+    }
+}
+
+/// Graphs of up to size nodes, where each node may depend on those generated before it
+impl<T> Arbitrary for Graph<T>
+where
+    T: Arbitrary + Clone + Eq + Hash,
+{
+    fn arbitrary(g: &mut Gen) -> Self {
+        let count = g.below(g.size() + 1);
+        let mut nodes: Vec<T> = Vec::with_capacity(count);
+
+        // Values may collide, so give up on a few rather than loop forever
+        for _ in 0..count * 4 {
+            if nodes.len() == count {
+                break;
+            }
+
+            let node = T::arbitrary(g);
+            if !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+
+        let mut graph = Graph::new();
+        for (i, node) in nodes.iter().enumerate() {
+            graph.add_node(node.clone());
+            for dependency in &nodes[..i] {
+                if g.chance(0.25) {
+                    graph
+                        .depend(node.clone(), dependency.clone())
+                        .unwrap_or_else(|err| {
+                            panic!(
+                                "edges to earlier nodes cannot form cycles: {}",
+                                err.map(drop)
+                            )
+                        });
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let edges: Vec<(&T, &T)> = self.edges().collect();
+        let without = |skip_node: Option<&T>, skip_edge: Option<usize>| {
+            let mut graph = Graph::new();
+            for node in self.nodes().filter(|n| Some(*n) != skip_node) {
+                graph.add_node(node.clone());
+            }
+
+            for (i, (dependent, dependency)) in edges.iter().enumerate() {
+                if Some(i) == skip_edge
+                    || skip_node.is_some_and(|n| n == *dependent || n == *dependency)
+                {
+                    continue;
+                }
+
+                // Subgraphs of acyclic graphs are acyclic
+                graph
+                    .depend((*dependent).clone(), (*dependency).clone())
+                    .unwrap_or_else(|err| {
+                        panic!("subgraph edges cannot form cycles: {}", err.map(drop))
+                    });
+            }
+
+            graph
+        };
+
+        self.nodes()
+            .map(|node| without(Some(node), None))
+            .chain((0..edges.len()).map(|i| without(None, Some(i))))
+            .collect()
+    }
+}
+
+/// Shapes of graphs from [`random_shaped_dag`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    /// Each node depends on any node numbered below it with the edge probability
+    Random,
+    /// Each node depends on the one right below it,
+    /// plus shortcuts further down with the edge probability
+    Chain,
+    /// Node 0 depends directly on every other node, which depend
+    /// on nodes numbered below them with the edge probability
+    FanOut,
+    /// Nodes split into this many layers of consecutive numbers, each node depending
+    /// on one node of the layer below, plus others there with the edge probability
+    Layered(usize),
+}
+
+/// Returns a graph of nodes `0..nodes`, where each node depends on any node
+/// numbered below it with probability edge_probability.
+///
+/// The same arguments always yield the same graph.
+pub fn random_dag(nodes: usize, edge_probability: f64, seed: u64) -> Graph<usize> {
+    random_shaped_dag(Shape::Random, nodes, edge_probability, seed)
+}
+
+/// Like [`random_dag`], but with graphs of shape
+pub fn random_shaped_dag(
+    shape: Shape,
+    nodes: usize,
+    edge_probability: f64,
+    seed: u64,
+) -> Graph<usize> {
+    let mut g = Gen::new(seed, nodes);
+    let mut graph = Graph::new();
+    for node in 0..nodes {
+        graph.add_node(node);
+    }
+
+    // Edges only point to lower numbers, or away from node 0 in fan-outs,
+    // so they cannot form cycles
+    let mut depend = |dependent, dependency| {
+        graph
+            .depend(dependent, dependency)
+            .expect("edges cannot form cycles");
+    };
+
+    match shape {
+        Shape::Random => {
+            for dependent in 1..nodes {
+                for dependency in 0..dependent {
+                    if g.chance(edge_probability) {
+                        depend(dependent, dependency);
+                    }
+                }
+            }
+        }
+        Shape::Chain => {
+            for dependent in 1..nodes {
+                depend(dependent, dependent - 1);
+                for dependency in 0..dependent - 1 {
+                    if g.chance(edge_probability) {
+                        depend(dependent, dependency);
+                    }
+                }
+            }
+        }
+        Shape::FanOut => {
+            for dependent in 1..nodes {
+                depend(0, dependent);
+                for dependency in 1..dependent {
+                    if g.chance(edge_probability) {
+                        depend(dependent, dependency);
+                    }
+                }
+            }
+        }
+        Shape::Layered(layers) => {
+            let layers = layers.clamp(1, nodes.max(1));
+            let start = |layer: usize| layer * nodes / layers;
+
+            for layer in 1..layers {
+                let below = start(layer - 1)..start(layer);
+                for dependent in start(layer)..start(layer + 1) {
+                    depend(dependent, below.start + g.below(below.len()));
+                    for dependency in below.clone() {
+                        if g.chance(edge_probability) {
+                            depend(dependent, dependency);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+/// Checks property against values generated from seed,
+/// growing their size with each case.
+///
+/// # Panics
+///
+/// Panics with the smallest failing value found by shrinking,
+/// if property fails for any of them.
+pub fn check<A, F>(cases: usize, seed: u64, property: F)
+where
+    A: Arbitrary + Debug,
+    F: Fn(&A) -> bool,
+{
+    let mut g = Gen::new(seed, 0);
+    for case in 0..cases {
+        g.set_size(1 + case * 32 / cases.max(1));
+
+        let mut value = A::arbitrary(&mut g);
+        if property(&value) {
+            continue;
+        }
+
+        while let Some(smaller) = value.shrink().into_iter().find(|v| !property(v)) {
+            value = smaller;
+        }
+
+        panic!("property failed in case {case} with seed {seed}: {value:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen() {
+        let mut a = Gen::new(7, 10);
+        let mut b = Gen::new(7, 10);
+        let values: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        assert_eq!(values, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(values[0], Gen::new(8, 10).next_u64());
+
+        assert!((0..100).all(|_| a.below(3) < 3));
+        assert_eq!(a.below(0), 0);
+        assert!(!a.chance(0.0));
+        assert!(a.chance(1.0));
+        assert_eq!(a.choose::<u8>(&[]), None);
+    }
+
+    #[test]
+    fn test_arbitrary_graph() {
+        let mut g = Gen::new(42, 20);
+        for _ in 0..50 {
+            let graph = Graph::<u32>::arbitrary(&mut g);
+            assert!(graph.nodes().count() <= 20);
+
+            // Acyclic graphs layer every node
+            let layered: usize = graph.layers().iter().map(|l| l.len()).sum();
+            assert_eq!(layered, graph.nodes().count());
+
+            for smaller in graph.shrink() {
+                let nodes = smaller.nodes().count();
+                let edges = smaller.edges().count();
+                assert!(nodes + edges < graph.nodes().count() + graph.edges().count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_dag() {
+        let g = random_dag(50, 0.1, 3);
+        assert_eq!(g.nodes().count(), 50);
+        assert_eq!(g.to_json(), random_dag(50, 0.1, 3).to_json());
+        assert_ne!(g.to_json(), random_dag(50, 0.1, 4).to_json());
+
+        assert_eq!(random_dag(10, 0.0, 1).edges().count(), 0);
+        assert_eq!(random_dag(10, 1.0, 1).edges().count(), 45);
+        assert_eq!(random_dag(0, 0.5, 1).nodes().count(), 0);
+
+        let chain = random_shaped_dag(Shape::Chain, 20, 0.0, 1);
+        assert_eq!(chain.layers().len(), 20);
+        assert_eq!(chain.deepest_chain().len(), 20);
+
+        let fan_out = random_shaped_dag(Shape::FanOut, 20, 0.2, 1);
+        assert!((1..20).all(|n| fan_out.depends_on_directly(&0, &n)));
+
+        for shape in [Shape::Layered(4), Shape::Layered(30), Shape::Layered(0)] {
+            let layered = random_shaped_dag(shape, 20, 0.3, 1);
+            let Shape::Layered(layers) = shape else {
+                unreachable!()
+            };
+
+            assert_eq!(layered.layers().len(), layers.clamp(1, 20));
+            assert_eq!(layered.nodes().count(), 20);
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        check::<Graph<String>, _>(100, 1, |g| g.layers().len() <= g.nodes().count());
+
+        let last_failure = std::cell::Cell::new((0, 0));
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            check::<Graph<u8>, _>(100, 1, |g| {
+                let failed = g.edges().count() >= 2;
+                if failed {
+                    last_failure.set((g.nodes().count(), g.edges().count()));
+                }
+
+                !failed
+            });
+        }))
+        .unwrap_err();
+
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("property failed in case"), "{message}");
+
+        // Shrinking drops every node and edge not needed to fail,
+        // leaving two edges that may or may not share a node
+        let (nodes, edges) = last_failure.get();
+        assert_eq!(edges, 2);
+        assert!((3..=4).contains(&nodes), "{nodes} nodes");
+    }
+}
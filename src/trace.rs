@@ -0,0 +1,147 @@
+//! Spans around expensive graph operations, emitted with the `tracing` crate
+//! behind the `tracing` feature.
+//!
+//! Instrumented operations (deep digs, cycle checks, layering and bulk loads)
+//! open a debug span named after them, recording their counts as fields once
+//! they finish, and rejected cycles are logged as debug events.
+//! Without the feature, spans and events compile to nothing.
+
+/// Enters a debug span for operation name with the given empty fields,
+/// exited when dropped
+macro_rules! span {
+    ($name:literal $(, $field:ident)*) => {{
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!($name $(, $field = tracing::field::Empty)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        let span = $crate::trace::Span;
+        span
+    }};
+}
+
+/// Logs a debug event
+macro_rules! event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub(crate) use {event, span};
+
+/// Span without the `tracing` feature, recording nothing
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    #[inline(always)]
+    pub(crate) fn record(&self, _field: &str, _value: usize) {}
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    use crate::Graph;
+
+    /// Spans and events seen by a subscriber
+    #[derive(Default)]
+    struct Collector {
+        spans: Mutex<Vec<Seen>>,
+        entered: Mutex<Vec<u64>>,
+        events: Mutex<Vec<String>>,
+    }
+
+    struct Seen {
+        name: &'static str,
+        /// Spans entered around this one when it was opened
+        depth: usize,
+        fields: Vec<(String, u64)>,
+    }
+
+    struct Fields(Vec<(String, u64)>);
+
+    impl Visit for Fields {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.0.push((field.name().to_string(), value));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push((format!("{field}={value:?}"), 0));
+        }
+    }
+
+    impl tracing::Subscriber for &'static Collector {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let depth = self.entered.lock().unwrap().len();
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(Seen {
+                name: span.metadata().name(),
+                depth,
+                fields: Vec::new(),
+            });
+
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut fields = Fields(Vec::new());
+            values.record(&mut fields);
+
+            let mut spans = self.spans.lock().unwrap();
+            spans[span.into_u64() as usize - 1].fields.extend(fields.0);
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(Vec::new());
+            event.record(&mut fields);
+            let message = fields.0.into_iter().map(|(field, _)| field).collect();
+            self.events.lock().unwrap().push(message);
+        }
+
+        fn enter(&self, span: &Id) {
+            self.entered.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.entered.lock().unwrap().pop();
+        }
+    }
+
+    #[test]
+    fn test_spans() {
+        let collector: &'static Collector = Box::leak(Box::default());
+
+        tracing::subscriber::with_default(collector, || {
+            let mut g = Graph::new();
+            g.depend("b", "a").unwrap();
+            g.depend("c", "b").unwrap();
+            g.depend("a", "c").unwrap_err();
+            g.dependencies(&"c");
+        });
+
+        let spans = collector.spans.lock().unwrap();
+        let named = |name| spans.iter().filter(move |span| span.name == name);
+
+        // One cycle check per edge, each digging once
+        assert_eq!(named("cycle_check").count(), 3);
+        let digs: Vec<_> = named("dig_deep").collect();
+        assert_eq!(digs.len(), 4);
+        assert_eq!(digs[0].depth, 1);
+        assert_eq!(digs[3].depth, 0);
+        assert_eq!(digs[3].fields, [("nodes".to_string(), 2)]);
+
+        let events = collector.events.lock().unwrap();
+        assert_eq!(*events, ["message=rejected circular dependency"]);
+    }
+}
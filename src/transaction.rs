@@ -0,0 +1,246 @@
+use crate::{EdgeKind, Error, Graph};
+
+/// Mutations staged by a [`Transaction`], replayed on commit
+#[derive(Clone, Debug)]
+enum Op<T> {
+    AddNode(T),
+    Depend(T, T),
+    DependKind(T, T, EdgeKind),
+    Undepend(T, T),
+    Remove(T),
+    RemoveForce(T),
+    RemoveAutoremove(T),
+}
+
+/// Staged mutations of a graph, see [`Graph::transaction`].
+///
+/// Queries on the transaction see its staged mutations.
+pub struct Transaction<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    staged: Graph<T>,
+    ops: Vec<Op<T>>,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Runs f with a transaction, applying all of its mutations to the graph
+    /// if f returns Ok, or none of them if f returns Err.
+    ///
+    /// Only the mutations offered by [`Transaction`] can be staged; everything
+    /// else on the graph is read-only while the transaction runs.
+    /// If replaying the staged mutations fails, the graph is left
+    /// as it was before the transaction and the error returned.
+    ///
+    /// Listeners are only notified of mutations once they are committed.
+    pub fn transaction<R, E, F>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Transaction<T>) -> Result<R, E>,
+        E: From<Error<T>>,
+    {
+        let staged = self.clone();
+        let mut tx = Transaction {
+            staged,
+            ops: Vec::new(),
+        };
+
+        let result = f(&mut tx)?;
+
+        // Replay on a detached copy first, so that listeners never see
+        // mutations of a commit failing midway
+        let mut detached = self.clone();
+        for op in tx.ops.iter().cloned() {
+            op.apply(&mut detached)?;
+        }
+
+        for op in tx.ops {
+            if op.apply(self).is_err() {
+                unreachable!("replay failed on the graph but not on its copy");
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl<T> Op<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Replays the staged mutation on graph
+    fn apply(self, graph: &mut Graph<T>) -> Result<(), Error<T>> {
+        match self {
+            Op::AddNode(node) => graph.add_node(node),
+            Op::Depend(dependent, dependency) => {
+                graph.depend(dependent, dependency)?;
+            }
+            Op::DependKind(dependent, dependency, kind) => {
+                graph.depend_kind(dependent, dependency, kind)?
+            }
+            Op::Undepend(dependent, dependency) => graph.undepend(&dependent, &dependency)?,
+            Op::Remove(target) => graph.remove(&target)?,
+            Op::RemoveForce(target) => graph.remove_force(&target),
+            Op::RemoveAutoremove(target) => graph.remove_autoremove(&target),
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Transaction<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn add_node(&mut self, node: T) {
+        self.staged.add_node(node.clone());
+        self.ops.push(Op::AddNode(node));
+    }
+
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
+        let new = self.staged.depend(dependent.clone(), dependency.clone())?;
+        self.ops.push(Op::Depend(dependent, dependency));
+
+        Ok(new)
+    }
+
+    pub fn depend_kind(
+        &mut self,
+        dependent: T,
+        dependency: T,
+        kind: EdgeKind,
+    ) -> Result<(), Error<T>> {
+        self.staged
+            .depend_kind(dependent.clone(), dependency.clone(), kind)?;
+        self.ops.push(Op::DependKind(dependent, dependency, kind));
+
+        Ok(())
+    }
+
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
+        self.staged.undepend(dependent, dependency)?;
+        self.ops
+            .push(Op::Undepend(dependent.clone(), dependency.clone()));
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, target: &T) -> Result<(), Error<T>> {
+        self.staged.remove(target)?;
+        self.ops.push(Op::Remove(target.clone()));
+
+        Ok(())
+    }
+
+    pub fn remove_force(&mut self, target: &T) {
+        self.staged.remove_force(target);
+        self.ops.push(Op::RemoveForce(target.clone()));
+    }
+
+    pub fn remove_autoremove(&mut self, target: &T) {
+        self.staged.remove_autoremove(target);
+        self.ops.push(Op::RemoveAutoremove(target.clone()));
+    }
+}
+
+impl<T> std::ops::Deref for Transaction<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    type Target = Graph<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.staged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::{assert_no_dangling, Error, Graph, Listener};
+
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    impl Listener<&'static str> for Counter {
+        fn on_edge_added(&self, _: &&'static str, _: &&'static str) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_transaction() {
+        let counter = Arc::new(Counter::default());
+        let mut g = Graph::new();
+        g.subscribe(counter.clone());
+        g.depend("b", "a").unwrap();
+
+        // Later edge fails cycle check, rolling back the earlier ones
+        let result = g.transaction(|tx| {
+            tx.depend("c", "b")?;
+            tx.depend("d", "c")?;
+            assert!(tx.depends_on(&"d", &"a"));
+
+            tx.depend("a", "d")
+        });
+
+        assert!(matches!(result, Err(Error::CircularDependency)));
+        assert!(!g.contains(&"c"));
+        assert!(!g.contains(&"d"));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert_no_dangling(&g);
+
+        let removed = g
+            .transaction(|tx| -> Result<_, Error<&str>> {
+                tx.depend("c", "b")?;
+                tx.depend("d", "c")?;
+                tx.undepend(&"d", &"c")?;
+                tx.remove(&"d")?;
+
+                Ok(tx.contains(&"d"))
+            })
+            .unwrap();
+
+        assert!(!removed);
+        assert!(g.depends_on(&"c", &"a"));
+        assert!(!g.contains(&"d"));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 3);
+        assert_no_dangling(&g);
+
+        g.transaction(|tx| -> Result<(), Error<&str>> {
+            tx.remove_force(&"a");
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(g.nodes.is_empty());
+    }
+
+    #[test]
+    fn test_transaction_replay_error() {
+        let counter = Arc::new(Counter::default());
+        let mut g = Graph::new();
+        g.subscribe(counter.clone());
+        g.depend("b", "a").unwrap();
+
+        // Replay fails midway, after the first edge was already replayed
+        let result = g.transaction(|tx| -> Result<(), Error<&str>> {
+            tx.depend("c", "b")?;
+            tx.ops.push(super::Op::Undepend("x", "y"));
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        assert!(!g.contains(&"c"));
+        assert_eq!(g.nodes.len(), 2);
+        assert_no_dangling(&g);
+
+        // Listeners survive the rollback, never having seen the edge
+        g.depend("c", "b").unwrap();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::iter::keep_all;
+use crate::{Direction, Graph};
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns dependencies of node at most max_depth edges away,
+    /// so 1 gives direct dependencies only
+    pub fn dependencies_within(&self, node: &T, max_depth: usize) -> HashSet<T> {
+        self.dependencies_within_filtered(node, max_depth, keep_all)
+    }
+
+    /// Like [`Graph::dependencies_within`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependencies_within_filtered<F>(&self, node: &T, max_depth: usize, keep: F) -> HashSet<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.depths_from(node, Direction::Dependencies, Some(max_depth), keep)
+            .into_keys()
+            .collect()
+    }
+
+    /// Returns dependents of node at most max_depth edges away,
+    /// so 1 gives direct dependents only
+    pub fn dependents_within(&self, node: &T, max_depth: usize) -> HashSet<T> {
+        self.dependents_within_filtered(node, max_depth, keep_all)
+    }
+
+    /// Like [`Graph::dependents_within`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependents_within_filtered<F>(&self, node: &T, max_depth: usize, keep: F) -> HashSet<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.depths_from(node, Direction::Dependents, Some(max_depth), keep)
+            .into_keys()
+            .collect()
+    }
+
+    /// Returns deep dependencies of node, each with its least number of edges away from node
+    pub fn dependencies_with_depth(&self, node: &T) -> HashMap<T, usize> {
+        self.dependencies_with_depth_filtered(node, keep_all)
+    }
+
+    /// Like [`Graph::dependencies_with_depth`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependencies_with_depth_filtered<F>(&self, node: &T, keep: F) -> HashMap<T, usize>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.depths_from(node, Direction::Dependencies, None, keep)
+    }
+
+    /// Returns deep dependents of node, each with its least number of edges away from node
+    pub fn dependents_with_depth(&self, node: &T) -> HashMap<T, usize> {
+        self.dependents_with_depth_filtered(node, keep_all)
+    }
+
+    /// Like [`Graph::dependents_with_depth`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependents_with_depth_filtered<F>(&self, node: &T, keep: F) -> HashMap<T, usize>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.depths_from(node, Direction::Dependents, None, keep)
+    }
+
+    /// Returns deep dependencies of node, following only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependencies_filtered<F>(&self, node: &T, keep: F) -> HashSet<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.reachable(node, Direction::Dependencies, keep)
+    }
+
+    /// Returns deep dependents of node, following only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependents_filtered<F>(&self, node: &T, keep: F) -> HashSet<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.reachable(node, Direction::Dependents, keep)
+    }
+
+    /// Like [`Graph::depends_on`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn depends_on_filtered<F>(&self, dependent: &T, dependency: &T, keep: F) -> bool
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.bfs_filtered(dependent, Direction::Dependencies, keep)
+            .skip(1)
+            .any(|(n, _)| n == dependency)
+    }
+
+    /// Returns deep dependencies of node in build order,
+    /// each after all of its own dependencies
+    pub fn dependencies_sorted(&self, node: &T) -> Vec<T> {
+        self.dependencies_sorted_filtered(node, keep_all)
+    }
+
+    /// Like [`Graph::dependencies_sorted`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependencies_sorted_filtered<F>(&self, node: &T, keep: F) -> Vec<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut order: Vec<T> = self.dfs_post_filtered(node, keep).cloned().collect();
+        order.pop();
+        order
+    }
+
+    /// Returns deep dependents of node in rebuild order,
+    /// each after all of its dependencies among them
+    pub fn dependents_sorted(&self, node: &T) -> Vec<T> {
+        self.dependents_sorted_filtered(node, keep_all)
+    }
+
+    /// Like [`Graph::dependents_sorted`], but follows only edges for which
+    /// keep(dependent, dependency) holds
+    pub fn dependents_sorted_filtered<F>(&self, node: &T, keep: F) -> Vec<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut order: Vec<T> = self
+            .postorder(node, Direction::Dependents, keep)
+            .cloned()
+            .collect();
+        order.pop();
+        order.reverse();
+        order
+    }
+
+    fn reachable<F>(&self, node: &T, direction: Direction, keep: F) -> HashSet<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.bfs_filtered(node, direction, keep)
+            .skip(1)
+            .map(|(n, _)| n.clone())
+            .collect()
+    }
+
+    /// Returns nodes reachable from node in direction over edges kept by keep,
+    /// with their least number of edges away from node, stopping after max edges if set
+    fn depths_from<F>(
+        &self,
+        node: &T,
+        direction: Direction,
+        max: Option<usize>,
+        keep: F,
+    ) -> HashMap<T, usize>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.bfs_filtered(node, direction, keep)
+            .skip(1)
+            .take_while(|(_, depth)| max.is_none_or(|max| *depth <= max))
+            .map(|(n, depth)| (n.clone(), depth))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_graph, EdgeKind};
+
+    #[test]
+    fn test_filtered() {
+        let mut g = test_graph();
+        g.depend_kind("app", "mock", EdgeKind::Dev).unwrap();
+        g.depend_kind("http", "log", EdgeKind::Dev).unwrap();
+
+        let runtime =
+            |a: &&'static str, b: &&'static str| g.kinds(a, b).contains(&EdgeKind::Runtime);
+        assert_eq!(
+            g.dependencies_filtered(&"http", runtime),
+            HashSet::from(["tls", "libc"])
+        );
+        assert_eq!(
+            g.dependents_filtered(&"log", runtime),
+            HashSet::from(["app"])
+        );
+
+        // Skipping nodes by skipping the edges into them
+        assert_eq!(
+            g.dependencies_filtered(&"app", |_, b| *b != "http"),
+            HashSet::from(["log", "mock", "libc"])
+        );
+        assert_eq!(
+            g.dependencies_filtered(&"app", |_, _| true),
+            g.dependencies(&"app")
+        );
+
+        // Every query has a filtered twin
+        assert!(g.depends_on(&"http", &"log"));
+        assert!(!g.depends_on_filtered(&"http", &"log", runtime));
+        assert!(g.depends_on_filtered(&"app", &"libc", runtime));
+        assert_eq!(
+            g.dependencies_within_filtered(&"app", 1, runtime),
+            HashSet::from(["http", "log"])
+        );
+        assert_eq!(
+            g.dependents_within_filtered(&"libc", 2, runtime),
+            HashSet::from(["tls", "log", "http", "app"])
+        );
+        assert_eq!(
+            g.dependencies_with_depth_filtered(&"http", runtime),
+            HashMap::from([("tls", 1), ("libc", 2)])
+        );
+        assert_eq!(
+            g.dependents_with_depth_filtered(&"log", runtime),
+            HashMap::from([("app", 1)])
+        );
+        assert_eq!(
+            g.dependencies_sorted_filtered(&"http", runtime),
+            ["libc", "tls"]
+        );
+        assert_eq!(g.dependents_sorted_filtered(&"log", runtime), ["app"]);
+    }
+
+    #[test]
+    fn test_within() {
+        let g = test_graph();
+
+        assert!(g.dependencies_within(&"app", 0).is_empty());
+        assert_eq!(
+            g.dependencies_within(&"app", 1),
+            HashSet::from(["http", "log"])
+        );
+        assert_eq!(
+            g.dependencies_within(&"app", 2),
+            HashSet::from(["http", "log", "tls", "libc"])
+        );
+        assert_eq!(g.dependencies_within(&"app", 9), g.dependencies(&"app"));
+
+        assert_eq!(
+            g.dependents_within(&"libc", 1),
+            HashSet::from(["tls", "log"])
+        );
+        assert_eq!(
+            g.dependents_within(&"libc", 2),
+            HashSet::from(["tls", "log", "http", "app"])
+        );
+        assert!(g.dependents_within(&"missing", 3).is_empty());
+    }
+
+    #[test]
+    fn test_with_depth() {
+        let mut g = test_graph();
+        g.depend("app", "libc").unwrap();
+
+        // Shortest, not first or longest, paths count
+        assert_eq!(
+            g.dependencies_with_depth(&"app"),
+            HashMap::from([("http", 1), ("log", 1), ("libc", 1), ("tls", 2)])
+        );
+        assert_eq!(
+            g.dependents_with_depth(&"tls"),
+            HashMap::from([("http", 1), ("app", 2)])
+        );
+        assert!(g.dependencies_with_depth(&"libc").is_empty());
+    }
+
+    /// Asserts that order holds exactly the nodes, each after those it depends on
+    fn assert_build_order(g: &Graph<&'static str>, order: &[&'static str], nodes: HashSet<&str>) {
+        assert_eq!(order.len(), nodes.len(), "{order:?}");
+        assert_eq!(order.iter().copied().collect::<HashSet<_>>(), nodes);
+
+        for (i, node) in order.iter().enumerate() {
+            for later in &order[i + 1..] {
+                assert!(!g.depends_on(node, later), "{node} before {later}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dependencies_sorted() {
+        let mut g = test_graph();
+        g.depend("app", "libc").unwrap();
+        g.depend("http", "log").unwrap();
+
+        let order = g.dependencies_sorted(&"app");
+        assert_build_order(&g, &order, g.dependencies(&"app"));
+        assert_eq!(order.first(), Some(&"libc"));
+
+        assert_eq!(g.dependencies_sorted(&"tls"), ["libc"]);
+        assert!(g.dependencies_sorted(&"libc").is_empty());
+
+        let mut dense = Graph::new();
+        for i in 0..40u32 {
+            for j in (0..i).filter(|j| (i * 7 + j * 3) % 4 == 0) {
+                dense.depend(i, j).unwrap();
+            }
+        }
+
+        for node in 0..40 {
+            let order = dense.dependencies_sorted(&node);
+            assert_eq!(order.len(), dense.dependencies(&node).len());
+            for (i, a) in order.iter().enumerate() {
+                assert!(order[i + 1..].iter().all(|b| !dense.depends_on(a, b)));
+            }
+
+            let order = dense.dependents_sorted(&node);
+            assert_eq!(order.len(), dense.dependents(&node).len());
+            for (i, a) in order.iter().enumerate() {
+                assert!(order[i + 1..].iter().all(|b| !dense.depends_on(a, b)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_dependents_sorted() {
+        let mut g = test_graph();
+        g.depend("app", "libc").unwrap();
+        g.depend("http", "log").unwrap();
+
+        let order = g.dependents_sorted(&"libc");
+        assert_build_order(&g, &order, g.dependents(&"libc"));
+        assert_eq!(order.last(), Some(&"app"));
+
+        assert_eq!(g.dependents_sorted(&"http"), ["app"]);
+        assert!(g.dependents_sorted(&"app").is_empty());
+    }
+}
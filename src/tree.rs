@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::io::IsTerminal;
+
+use crate::{Error, Graph};
+
+const BOLD_CYAN: &str = "\x1b[1;36m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash + Display,
+{
+    /// Returns the dependency tree of node like `cargo tree` does,
+    /// with dependencies sorted by their display form.
+    ///
+    /// Subtrees already shown are only shown once, with later
+    /// occurrences marked `(*)`.
+    pub fn tree_string(&self, node: &T) -> Result<String, Error<T>> {
+        self.render_tree(node, false)
+    }
+
+    /// Like [`Graph::tree_string`], but styled with ANSI escapes: roots are
+    /// bold cyan, leaves green, dirty nodes yellow, and repeated subtrees dim
+    pub fn tree_string_colored(&self, node: &T) -> Result<String, Error<T>> {
+        self.render_tree(node, true)
+    }
+
+    /// Prints the tree of node to stdout, colored if stdout is a terminal
+    /// and the `NO_COLOR` environment variable is unset
+    pub fn print_tree(&self, node: &T) -> Result<(), Error<T>> {
+        let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        print!("{}", self.render_tree(node, color)?);
+
+        Ok(())
+    }
+
+    fn render_tree(&self, node: &T, color: bool) -> Result<String, Error<T>> {
+        if !self.contains(node) {
+            return Err(Error::NoSuchNode);
+        }
+
+        let mut tree = self.paint(node, node.to_string(), color);
+        tree.push('\n');
+
+        let mut shown = HashSet::from([node]);
+        self.push_subtree(&mut tree, node, "", &mut shown, color);
+
+        Ok(tree)
+    }
+
+    fn paint(&self, node: &T, name: String, color: bool) -> String {
+        if !color {
+            return name;
+        }
+
+        let style = if self.dirty.contains(node) {
+            YELLOW
+        } else if !self.dependents.contains_key(node) {
+            BOLD_CYAN
+        } else if self.dependency_count(node) == 0 {
+            GREEN
+        } else {
+            return name;
+        };
+
+        format!("{style}{name}{RESET}")
+    }
+
+    fn push_subtree<'a>(
+        &'a self,
+        tree: &mut String,
+        node: &T,
+        prefix: &str,
+        shown: &mut HashSet<&'a T>,
+        color: bool,
+    ) {
+        let mut dependencies: Vec<(String, &T)> = self
+            .dependencies
+            .get(node)
+            .into_iter()
+            .flatten()
+            .map(|dependency| (dependency.to_string(), dependency))
+            .collect();
+        dependencies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let count = dependencies.len();
+        for (i, (name, dependency)) in dependencies.into_iter().enumerate() {
+            let last = i + 1 == count;
+            let (branch, indent) = match last {
+                true => ("└── ", "    "),
+                false => ("├── ", "│   "),
+            };
+
+            tree.push_str(prefix);
+            tree.push_str(branch);
+            tree.push_str(&self.paint(dependency, name, color));
+
+            let expanded = self.dependency_count(dependency) != 0;
+            if !shown.insert(dependency) && expanded {
+                match color {
+                    true => tree.push_str(&format!(" {DIM}(*){RESET}\n")),
+                    false => tree.push_str(" (*)\n"),
+                }
+                continue;
+            }
+
+            tree.push('\n');
+            let prefix = format!("{prefix}{indent}");
+            self.push_subtree(tree, dependency, &prefix, shown, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_string() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("app", "db").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("db", "tls").unwrap();
+        g.depend("tls", "libc").unwrap();
+        g.depend("db", "libc").unwrap();
+
+        assert_eq!(
+            g.tree_string(&"app").unwrap(),
+            "\
+app
+├── db
+│   ├── libc
+│   └── tls
+│       └── libc
+└── http
+    └── tls (*)
+"
+        );
+
+        assert_eq!(g.tree_string(&"libc").unwrap(), "libc\n");
+
+        // Reserved capacity does not make leaves look expanded
+        g.reserve_dependencies("libc", 4);
+        g.dirty.insert("db");
+        assert_eq!(
+            g.tree_string_colored(&"app").unwrap(),
+            "\
+\x1b[1;36mapp\x1b[0m
+├── \x1b[33mdb\x1b[0m
+│   ├── \x1b[32mlibc\x1b[0m
+│   └── tls
+│       └── \x1b[32mlibc\x1b[0m
+└── http
+    └── tls \x1b[2m(*)\x1b[0m
+"
+        );
+        g.tree_string(&"nope").expect_err("no such node");
+    }
+}
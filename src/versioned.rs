@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use crate::{Diff, Error, PersistentGraph};
+
+/// Graph wrapper keeping named snapshots of past states, e.g. one per deploy.
+///
+/// Snapshots are [`PersistentGraph`] clones sharing structure with
+/// the current graph, so taking one is cheap however large the graph is.
+#[derive(Clone, Debug, Default)]
+pub struct VersionedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    current: PersistentGraph<T>,
+    snapshots: BTreeMap<String, PersistentGraph<T>>,
+}
+
+impl<T> VersionedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    pub fn new(graph: PersistentGraph<T>) -> Self {
+        Self {
+            current: graph,
+            snapshots: BTreeMap::new(),
+        }
+    }
+
+    /// Adds dependency edge to the current graph,
+    /// returning whether the edge is new rather than already in the graph
+    pub fn depend(&mut self, dependent: T, dependency: T) -> Result<bool, Error<T>> {
+        let (current, new) = self.current.depend(dependent, dependency)?;
+        self.current = current;
+
+        Ok(new)
+    }
+
+    pub fn undepend(&mut self, dependent: &T, dependency: &T) -> Result<(), Error<T>> {
+        self.current = self.current.undepend(dependent, dependency)?;
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, target: &T) -> Result<(), Error<T>> {
+        self.current = self.current.remove(target)?;
+
+        Ok(())
+    }
+
+    /// Saves the current graph as name, returning whether it replaced
+    /// an older snapshot of the same name
+    pub fn snapshot(&mut self, name: impl Into<String>) -> bool {
+        self.snapshots
+            .insert(name.into(), self.current.clone())
+            .is_some()
+    }
+
+    /// Rolls the current graph back (or forward) to snapshot name,
+    /// returning false if there is no such snapshot
+    pub fn restore(&mut self, name: &str) -> bool {
+        let Some(snapshot) = self.snapshots.get(name) else {
+            return false;
+        };
+
+        self.current = snapshot.clone();
+        true
+    }
+
+    /// Returns names of snapshots, sorted
+    pub fn list_snapshots(&self) -> Vec<&str> {
+        self.snapshots.keys().map(String::as_str).collect()
+    }
+
+    pub fn get_snapshot(&self, name: &str) -> Option<&PersistentGraph<T>> {
+        self.snapshots.get(name)
+    }
+
+    /// Drops snapshot name, returning whether there was one
+    pub fn delete_snapshot(&mut self, name: &str) -> bool {
+        self.snapshots.remove(name).is_some()
+    }
+
+    /// Returns changes made since snapshot name, if there is one
+    pub fn diff_since(&self, name: &str) -> Option<Diff<T>> {
+        let snapshot = self.snapshots.get(name)?;
+
+        Some(snapshot.to_graph().diff(&self.current.to_graph()))
+    }
+
+    /// Returns the current graph
+    pub fn into_inner(self) -> PersistentGraph<T> {
+        self.current
+    }
+}
+
+impl<T> std::ops::Deref for VersionedGraph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    type Target = PersistentGraph<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_snapshots() {
+        let mut g = VersionedGraph::default();
+        assert!(g.depend("app", "http").unwrap());
+        g.depend("app", "xml").unwrap();
+        assert!(!g.depend("app", "xml").unwrap());
+        assert!(!g.snapshot("v1"));
+
+        g.undepend(&"app", &"xml").unwrap();
+        g.remove(&"xml").unwrap();
+        g.depend("http", "tls").unwrap();
+        assert!(!g.snapshot("v2"));
+        g.depend("app", "json").unwrap();
+
+        assert_eq!(g.list_snapshots(), ["v1", "v2"]);
+        assert!(g.get_snapshot("v1").unwrap().contains(&"xml"));
+
+        let diff = g.diff_since("v1").unwrap();
+        assert_eq!(diff.removed_nodes, HashSet::from(["xml"]));
+        assert_eq!(
+            diff.added_edges,
+            HashSet::from([("http", "tls"), ("app", "json")])
+        );
+        assert!(g.diff_since("v3").is_none());
+
+        assert!(g.restore("v1"));
+        assert!(g.contains(&"xml"));
+        assert!(!g.contains(&"tls"));
+
+        // Snapshots are unaffected by later changes
+        g.depend("xml", "sax").unwrap();
+        assert!(!g.get_snapshot("v1").unwrap().contains(&"sax"));
+
+        assert!(g.restore("v2"));
+        assert!(g.depends_on(&"app", &"tls"));
+        assert!(!g.restore("v3"));
+
+        assert!(g.snapshot("v1"));
+        assert!(g.delete_snapshot("v2"));
+        assert!(!g.delete_snapshot("v2"));
+        assert_eq!(g.list_snapshots(), ["v1"]);
+        assert!(g.into_inner().contains(&"tls"));
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::iter::{keep_all, Step};
+use crate::{Direction, Graph};
+
+/// What a depth-first walk does after a [`Visitor`] discovers a node
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Control {
+    /// Go on into the node's neighbors
+    #[default]
+    Continue,
+    /// Leave the node's neighbors unvisited, unless reached from elsewhere
+    SkipSubtree,
+    /// End the walk without further calls to the visitor
+    Stop,
+}
+
+/// Callbacks for [`Graph::visit`], for writing custom algorithms over the graph.
+///
+/// Both methods do nothing by default.
+pub trait Visitor<T> {
+    /// Called when the walk first reaches node
+    fn discover(&mut self, _node: &T) -> Control {
+        Control::Continue
+    }
+
+    /// Called once all neighbors reachable from node are finished,
+    /// or right after discovery if its subtree is skipped
+    fn finish(&mut self, _node: &T) {}
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Walks depth-first from start in direction, calling visitor as nodes are
+    /// discovered and finished. Every reachable node is visited at most once.
+    ///
+    /// Returns false if the visitor stopped the walk with [`Control::Stop`].
+    pub fn visit<V>(&self, start: &T, direction: Direction, visitor: &mut V) -> bool
+    where
+        V: Visitor<T> + ?Sized,
+    {
+        self.visit_filtered(start, direction, keep_all, visitor)
+    }
+
+    /// Like [`Graph::visit`], but follows only edges for which
+    /// keep(dependent, dependency) holds, whichever the direction
+    pub fn visit_filtered<F, V>(
+        &self,
+        start: &T,
+        direction: Direction,
+        keep: F,
+        visitor: &mut V,
+    ) -> bool
+    where
+        F: Fn(&T, &T) -> bool,
+        V: Visitor<T> + ?Sized,
+    {
+        let step = Step::new(self, direction, keep);
+        let mut visited = HashSet::new();
+        let mut stack: Vec<(&T, bool)> = self
+            .nodes
+            .get(start)
+            .map(|n| (n, false))
+            .into_iter()
+            .collect();
+
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                visitor.finish(node);
+                continue;
+            }
+
+            if !visited.insert(node) {
+                continue;
+            }
+
+            match visitor.discover(node) {
+                Control::Stop => return false,
+                Control::SkipSubtree => {
+                    visitor.finish(node);
+                    continue;
+                }
+                Control::Continue => {}
+            }
+
+            stack.push((node, true));
+
+            let next = step.next(node);
+            stack.extend(next.filter(|n| !visited.contains(n)).map(|n| (n, false)));
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_graph;
+
+    #[derive(Default)]
+    struct Recorder {
+        skip: Option<&'static str>,
+        stop: Option<&'static str>,
+        discovered: Vec<&'static str>,
+        finished: Vec<&'static str>,
+    }
+
+    impl Visitor<&'static str> for Recorder {
+        fn discover(&mut self, node: &&'static str) -> Control {
+            self.discovered.push(node);
+            if self.stop == Some(node) {
+                Control::Stop
+            } else if self.skip == Some(node) {
+                Control::SkipSubtree
+            } else {
+                Control::Continue
+            }
+        }
+
+        fn finish(&mut self, node: &&'static str) {
+            self.finished.push(node);
+        }
+    }
+
+    #[test]
+    fn test_visit() {
+        let g = test_graph();
+
+        let mut all = Recorder::default();
+        assert!(g.visit(&"app", Direction::Dependencies, &mut all));
+        assert_eq!(all.discovered.len(), 5);
+        assert_eq!(all.discovered[0], "app");
+        assert_eq!(all.finished.len(), 5);
+        assert_eq!(all.finished[0], "libc");
+        assert_eq!(all.finished[4], "app");
+
+        // Finished nodes come after everything they depend on
+        for (i, node) in all.finished.iter().enumerate() {
+            assert!(all.finished[i + 1..]
+                .iter()
+                .all(|later| !g.depends_on(node, later)));
+        }
+
+        // libc is still reached through log
+        let mut skip = Recorder {
+            skip: Some("http"),
+            ..Default::default()
+        };
+        assert!(g.visit(&"app", Direction::Dependencies, &mut skip));
+        assert!(!skip.discovered.contains(&"tls"));
+        assert!(skip.discovered.contains(&"libc"));
+        assert_eq!(skip.finished.len(), 4);
+
+        let mut stop = Recorder {
+            stop: Some("tls"),
+            ..Default::default()
+        };
+        assert!(!g.visit(&"libc", Direction::Dependents, &mut stop));
+        assert_eq!(stop.discovered.last(), Some(&"tls"));
+        assert!(!stop.finished.contains(&"tls"));
+        assert!(!stop.finished.contains(&"libc"));
+
+        let mut runtime = Recorder::default();
+        assert!(g.visit_filtered(
+            &"app",
+            Direction::Dependencies,
+            |a, _| *a != "http",
+            &mut runtime
+        ));
+        assert_eq!(runtime.discovered.len(), 4);
+        assert!(!runtime.discovered.contains(&"tls"));
+
+        let mut none = Recorder::default();
+        assert!(g.visit(&"missing", Direction::Dependencies, &mut none));
+        assert!(none.discovered.is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+//! String-keyed graph for JavaScript, exchanging graphs as JSON
+//!
+//! Everything crossing the boundary is a string, a bool, or a JSON string,
+//! and errors are messages, so that wrappers stay plain to export.
+
+use std::collections::HashSet;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::Graph;
+
+/// Graph of string nodes for web UIs
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct WasmGraph {
+    graph: Graph<String>,
+}
+
+#[wasm_bindgen]
+impl WasmGraph {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a graph from JSON, see [`Graph::from_json_str`]
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<WasmGraph, String> {
+        let graph = Graph::from_json_str(json).map_err(|err| err.to_string())?;
+        Ok(Self { graph })
+    }
+
+    /// Returns the graph as JSON, see [`Graph::to_json`]
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        self.graph.to_json()
+    }
+
+    pub fn depend(&mut self, dependent: &str, dependency: &str) -> Result<bool, String> {
+        self.graph
+            .depend(dependent.to_string(), dependency.to_string())
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn undepend(&mut self, dependent: &str, dependency: &str) -> Result<(), String> {
+        self.graph
+            .undepend(&dependent.to_string(), &dependency.to_string())
+            .map_err(|err| err.to_string())
+    }
+
+    /// Removes node, failing if it has dependents, see [`Graph::remove`]
+    pub fn remove(&mut self, node: &str) -> Result<(), String> {
+        self.graph
+            .remove(&node.to_string())
+            .map_err(|err| err.to_string())
+    }
+
+    #[wasm_bindgen(js_name = dependsOn)]
+    pub fn depends_on(&self, dependent: &str, dependency: &str) -> bool {
+        self.graph
+            .depends_on(&dependent.to_string(), &dependency.to_string())
+    }
+
+    /// Returns deep dependencies of node as a sorted JSON array
+    pub fn dependencies(&self, node: &str) -> String {
+        array(&self.graph.dependencies(&node.to_string()))
+    }
+
+    /// Returns deep dependents of node as a sorted JSON array
+    pub fn dependents(&self, node: &str) -> String {
+        array(&self.graph.dependents(&node.to_string()))
+    }
+
+    /// Returns [`Graph::layers`] as a JSON array of sorted arrays
+    pub fn layers(&self) -> String {
+        let layers: Vec<String> = self.graph.layers().iter().map(array).collect();
+        format!("[{}]", layers.join(","))
+    }
+}
+
+impl WasmGraph {
+    /// Returns the wrapped graph
+    pub fn into_inner(self) -> Graph<String> {
+        self.graph
+    }
+}
+
+impl From<Graph<String>> for WasmGraph {
+    fn from(graph: Graph<String>) -> Self {
+        Self { graph }
+    }
+}
+
+fn array(nodes: &HashSet<String>) -> String {
+    let mut nodes: Vec<&String> = nodes.iter().collect();
+    nodes.sort();
+
+    let mut json = String::from("[");
+    for (i, node) in nodes.into_iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+
+        crate::json::push_string(&mut json, node);
+    }
+    json.push(']');
+
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_graph() {
+        let mut g = WasmGraph::from_json(r#"{"app": ["http"], "http": ["tls"]}"#).unwrap();
+        g.depend("app", "db").unwrap();
+        assert_eq!(g.depend("tls", "app").unwrap_err(), "circular dependency");
+
+        assert!(g.depends_on("app", "tls"));
+        assert_eq!(g.dependencies("app"), r#"["db","http","tls"]"#);
+        assert_eq!(g.dependents("tls"), r#"["app","http"]"#);
+        assert_eq!(g.layers(), r#"[["db","tls"],["http"],["app"]]"#);
+
+        assert_eq!(g.remove("http").unwrap_err(), "dependencies exist");
+        g.undepend("app", "http").unwrap();
+        g.remove("http").unwrap();
+        assert_eq!(g.to_json(), r#"{"app":["db"],"db":[],"tls":[]}"#);
+
+        let err = WasmGraph::from_json("[]").unwrap_err();
+        assert_eq!(err, "line 1: expected an object of nodes");
+        assert_eq!(WasmGraph::new().to_json(), "{}");
+    }
+}
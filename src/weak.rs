@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+
+use crate::{insert_to_deps, rm_from_deps, Error, Graph};
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Adds a weak dependency edge, which queries report like any other edge,
+    /// but which does not keep dependency from being removed.
+    ///
+    /// Removing dependency drops its weak edges and leaves weak dependents in place.
+    /// Depending weakly on an existing strong dependency has no effect,
+    /// while [`Graph::depend`] makes an existing weak edge strong.
+    pub fn depend_weak(&mut self, dependent: T, dependency: T) -> Result<(), Error<T>> {
+        let weak = self.is_weak(&dependent, &dependency);
+        let suggested = crate::edges_contain(&self.suggested, &dependency, &dependent);
+
+        // depend strengthens existing edges, so weak ones are restored
+        let new = self.depend(dependent.clone(), dependency.clone())?;
+
+        if suggested {
+            insert_to_deps(&mut self.suggested, dependency.clone(), dependent.clone());
+        }
+
+        if new || weak {
+            insert_to_deps(&mut self.weak, dependency, dependent);
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether dependent depends directly and weakly on dependency
+    pub fn is_weak(&self, dependent: &T, dependency: &T) -> bool {
+        crate::edges_contain(&self.weak, dependency, dependent)
+    }
+
+    /// Returns direct dependents of node whose edges are not weak
+    pub fn strong_dependents(&self, node: &T) -> HashSet<T> {
+        self.strong_dependents_iter(node).cloned().collect()
+    }
+
+    pub(crate) fn strong_dependents_iter<'a>(&'a self, node: &'a T) -> impl Iterator<Item = &'a T> {
+        self.dependents
+            .get(node)
+            .into_iter()
+            .flatten()
+            .filter(move |dependent| !self.is_weak(dependent, node))
+    }
+
+    /// Makes a weak edge strong, if it is weak
+    pub(crate) fn strengthen(&mut self, dependent: &T, dependency: &T) {
+        rm_from_deps(&mut self.weak, dependency, dependent);
+        rm_from_deps(&mut self.suggested, dependency, dependent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_no_dangling;
+
+    #[test]
+    fn test_weak_edges() {
+        let mut g = Graph::new();
+        g.depend("editor", "core").unwrap();
+        g.depend("spellcheck", "dictionary").unwrap();
+        g.depend_weak("editor", "spellcheck").unwrap();
+
+        assert!(g.is_weak(&"editor", &"spellcheck"));
+        assert!(!g.is_weak(&"editor", &"core"));
+        assert!(g.depends_on(&"editor", &"dictionary"));
+        assert!(!g.is_dependend(&"spellcheck"));
+        assert!(g.strong_dependents(&"spellcheck").is_empty());
+        assert_eq!(
+            g.removal_plan(&"spellcheck").unwrap(),
+            vec!["spellcheck", "dictionary"]
+        );
+
+        // Weak edges are dropped along with their dependency
+        g.remove(&"spellcheck").unwrap();
+        assert!(g.contains(&"editor"));
+        assert_eq!(g.dependencies(&"editor"), HashSet::from(["core"]));
+        assert!(g.weak.is_empty());
+        assert_no_dangling(&g);
+
+        // remove_force keeps weak dependents
+        g.depend_weak("editor", "spellcheck").unwrap();
+        g.depend("spellcheck", "dictionary").unwrap();
+        g.depend("grammar", "dictionary").unwrap();
+        g.remove_force(&"dictionary");
+        assert!(g.contains(&"editor"));
+        assert!(!g.contains(&"spellcheck"));
+        assert!(!g.contains(&"grammar"));
+        assert!(g.weak.is_empty());
+
+        // remove_autoremove drops dependencies only held by weak edges
+        g.depend("plugin", "core").unwrap();
+        g.depend_weak("editor", "plugin").unwrap();
+        g.depend("app", "editor").unwrap();
+        g.remove_autoremove(&"app");
+        assert!(g.nodes.is_empty());
+        assert_no_dangling(&g);
+    }
+
+    #[test]
+    fn test_weak_edges_strengthen() {
+        let mut g = Graph::new();
+        g.depend("a", "b").unwrap();
+        g.depend_weak("a", "b").unwrap();
+        assert!(!g.is_weak(&"a", &"b"));
+
+        g.depend_weak("c", "d").unwrap();
+        g.depend_weak("c", "d").unwrap();
+        assert!(g.is_weak(&"c", &"d"));
+        g.depend("c", "d").unwrap();
+        assert!(!g.is_weak(&"c", &"d"));
+        assert!(g.is_dependend(&"d"));
+
+        g.depend_weak("e", "f").unwrap();
+        g.undepend(&"e", &"f").unwrap();
+        assert!(g.weak.is_empty());
+
+        g.depend_weak("d", "c")
+            .expect_err("weak edges still form cycles");
+    }
+}
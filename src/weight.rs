@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::{Error, Graph};
+
+/// Weights of nodes and edges, e.g. durations for critical path analysis
+#[derive(Clone, Debug)]
+pub(crate) struct Weights<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    pub(crate) nodes: HashMap<T, u64>,
+    /// Keyed by dependent then dependency
+    pub(crate) edges: HashMap<T, HashMap<T, u64>>,
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Sets weight of node, which is 0 unless set
+    pub fn set_weight(&mut self, node: &T, weight: u64) -> Result<(), Error<T>> {
+        if !self.contains(node) {
+            return Err(Error::NoSuchNode);
+        }
+
+        self.touch_node(node);
+        self.weights.nodes.insert(node.clone(), weight);
+
+        Ok(())
+    }
+
+    /// Sets weight of the edge, which is 0 unless set
+    pub fn set_edge_weight(
+        &mut self,
+        dependent: &T,
+        dependency: &T,
+        weight: u64,
+    ) -> Result<(), Error<T>> {
+        if !self.depends_on_directly(dependent, dependency) {
+            return Err(Error::NoSuchDirectDependency);
+        }
+
+        self.touch_pair(dependent, dependency);
+        self.weights
+            .edges
+            .entry(dependent.clone())
+            .or_default()
+            .insert(dependency.clone(), weight);
+
+        Ok(())
+    }
+
+    pub fn weight(&self, node: &T) -> u64 {
+        self.weights.nodes.get(node).copied().unwrap_or(0)
+    }
+
+    pub fn edge_weight(&self, dependent: &T, dependency: &T) -> u64 {
+        self.weights
+            .edges
+            .get(dependent)
+            .and_then(|weights| weights.get(dependency))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the chain with the greatest total weight of its nodes and edges,
+    /// from its deepest dependency to its final dependent, along with that total.
+    ///
+    /// This is the chain that gates the whole graph when independent nodes
+    /// run in parallel and weights are durations.
+    pub fn critical_path(&self) -> (Vec<T>, u64) {
+        let mut costs: HashMap<T, (u64, Option<T>)> = HashMap::new();
+
+        for layer in self.layers() {
+            for node in layer {
+                let slowest = self
+                    .dependencies
+                    .get(&node)
+                    .into_iter()
+                    .flatten()
+                    .map(|dependency| {
+                        let cost = costs[dependency].0 + self.edge_weight(&node, dependency);
+                        (cost, dependency)
+                    })
+                    .max_by_key(|(cost, _)| *cost);
+
+                let cost = match slowest {
+                    Some((cost, dependency)) => (cost, Some(dependency.clone())),
+                    None => (0, None),
+                };
+
+                costs.insert(node.clone(), (cost.0 + self.weight(&node), cost.1));
+            }
+        }
+
+        let Some((last, (total, _))) = costs.iter().max_by_key(|(_, (cost, _))| *cost) else {
+            return (Vec::new(), 0);
+        };
+
+        let mut path = vec![last.clone()];
+        while let Some((_, Some(previous))) = costs.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+
+        path.reverse();
+
+        (path, *total)
+    }
+
+    /// Drops weight of a removed edge
+    pub(crate) fn forget_edge_weight(&mut self, dependent: &T, dependency: &T) {
+        let Some(weights) = self.weights.edges.get_mut(dependent) else {
+            return;
+        };
+
+        weights.remove(dependency);
+        if weights.is_empty() {
+            self.weights.edges.remove(dependent);
+        }
+    }
+
+    /// Drops weight of a removed node
+    pub(crate) fn forget_weight(&mut self, node: &T) {
+        self.weights.nodes.remove(node);
+    }
+}
+
+impl<T> Default for Weights<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    fn default() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Weights<T>
+where
+    T: Eq + std::hash::Hash,
+{
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.edges.shrink_to_fit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_critical_path() {
+        let mut g = Graph::new();
+        assert_eq!(g.critical_path(), (vec![], 0));
+
+        g.depend("link", "compile-a").unwrap();
+        g.depend("link", "compile-b").unwrap();
+        g.depend("compile-a", "codegen").unwrap();
+        g.depend("package", "link").unwrap();
+        g.depend("docs", "codegen").unwrap();
+
+        for (node, weight) in [
+            ("codegen", 2),
+            ("compile-a", 5),
+            ("compile-b", 9),
+            ("link", 3),
+            ("package", 1),
+            ("docs", 4),
+        ] {
+            g.set_weight(&node, weight).unwrap();
+        }
+
+        assert_eq!(
+            g.critical_path(),
+            (vec!["compile-b", "link", "package"], 13)
+        );
+
+        // Edge weights count towards the chain
+        g.set_edge_weight(&"compile-a", &"codegen", 3).unwrap();
+        assert_eq!(g.edge_weight(&"compile-a", &"codegen"), 3);
+        assert_eq!(
+            g.critical_path(),
+            (vec!["codegen", "compile-a", "link", "package"], 14)
+        );
+
+        g.set_weight(&"sun", 1).expect_err("no such node");
+        g.set_edge_weight(&"codegen", &"link", 1)
+            .expect_err("no such edge");
+
+        // Weights go away with their nodes and edges
+        g.undepend(&"compile-a", &"codegen").unwrap();
+        assert_eq!(g.edge_weight(&"compile-a", &"codegen"), 0);
+        g.remove_force(&"compile-b");
+        assert_eq!(g.weight(&"compile-b"), 0);
+        assert!(g.weights.edges.is_empty());
+        assert_eq!(g.weights.nodes.len(), 3);
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::Graph;
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + std::hash::Hash,
+{
+    /// Returns a shortest chain explaining why dependent depends on dependency,
+    /// from dependent to dependency, or None if it does not
+    pub fn why(&self, dependent: &T, dependency: &T) -> Option<Vec<T>> {
+        let mut via: HashMap<&T, &T> = HashMap::new();
+        let mut q = VecDeque::from([dependent]);
+
+        while let Some(current) = q.pop_front() {
+            for next in self.dependencies.get(current).into_iter().flatten() {
+                if next == dependent || via.contains_key(next) {
+                    continue;
+                }
+
+                via.insert(next, current);
+                if next != dependency {
+                    q.push_back(next);
+                    continue;
+                }
+
+                let mut chain = vec![next.clone()];
+                let mut node = next;
+                while let Some(previous) = via.get(node) {
+                    chain.push((*previous).clone());
+                    node = previous;
+                }
+
+                chain.reverse();
+                return Some(chain);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_why() {
+        let mut g = Graph::new();
+        g.depend("app", "http").unwrap();
+        g.depend("http", "tls").unwrap();
+        g.depend("tls", "libc").unwrap();
+        g.depend("app", "db").unwrap();
+        g.depend("db", "libc").unwrap();
+
+        assert_eq!(g.why(&"app", &"libc").unwrap().len(), 3);
+        assert_eq!(g.why(&"http", &"libc"), Some(vec!["http", "tls", "libc"]));
+        assert_eq!(g.why(&"app", &"db"), Some(vec!["app", "db"]));
+        assert_eq!(g.why(&"libc", &"app"), None);
+        assert_eq!(g.why(&"app", &"app"), None);
+        assert_eq!(g.why(&"nope", &"app"), None);
+    }
+}